@@ -2,19 +2,21 @@ use std::{
     convert::TryInto,
     ffi, fs,
     io::{self, Read, Seek, Write},
-    path, result,
-    str::FromStr,
+    path,
     sync::atomic::{AtomicU64, Ordering},
     thread, time,
 };
 
 use rand::{rngs::StdRng, Rng, SeedableRng};
-use regex::Regex;
 use structopt::StructOpt;
 #[macro_use]
 extern crate lazy_static;
 
-use rdms::{err_at, Error, Result};
+use rdms::{
+    err_at,
+    util::{self, SizeArg},
+    Error, Result,
+};
 
 mod plot;
 mod stats;
@@ -212,7 +214,7 @@ static R_TOTAL: AtomicU64 = AtomicU64::new(0);
 fn main() {
     let opt = Opt::from_args();
 
-    for bsize in opt.clone().block_size.get_blocks() {
+    for bsize in get_blocks(&opt.block_size) {
         // io: append data
         let mut threads = vec![];
         let start_time = time::SystemTime::now();
@@ -443,36 +445,7 @@ fn reader_thread(id: isize, mut ctxt: Context) -> Result<Stats> {
     Ok(ss)
 }
 
-fn humanize(bytes: usize) -> String {
-    if bytes < 1024 {
-        format!("{}B", bytes)
-    } else if bytes < (1024 * 1024) {
-        format!("{}KB", bytes / 1024)
-    } else if bytes < (1024 * 1024 * 1024) {
-        format!("{}MB", bytes / (1024 * 1024))
-    } else if bytes < (1024 * 1024 * 1024 * 1024) {
-        format!("{}GB", bytes / (1024 * 1024 * 1024))
-    } else {
-        format!("{}TB", bytes / (1024 * 1024 * 1024 * 1024))
-    }
-}
-
-#[derive(Debug, Clone)]
-enum SizeArg {
-    None,
-    Range(Option<isize>, Option<isize>),
-    List(Vec<isize>),
-}
-
 lazy_static! {
-    static ref ARG_RE1: Regex = {
-        let patt = r"^([0-9]+[kKmMgG]?)(\.\.[0-9]+[kKmMgG]?)?$";
-        Regex::new(patt).unwrap()
-    };
-    static ref ARG_RE2: Regex = {
-        let patt = r"^([0-9]+[kKmMgG]?)(,[0-9]+[kKmMgG]?)*$";
-        Regex::new(patt).unwrap()
-    };
     static ref BLOCK_SIZES: [isize; 9] = [
         128,
         256,
@@ -494,87 +467,10 @@ lazy_static! {
     ];
 }
 
-impl FromStr for SizeArg {
-    type Err = String;
-
-    fn from_str(s: &str) -> result::Result<SizeArg, Self::Err> {
-        //println!("re1 {}", s);
-        match ARG_RE1.captures(s) {
-            None => (),
-            Some(captrs) => {
-                let x = captrs.get(1).map(|m| SizeArg::to_isize(m.as_str()));
-                let y = captrs.get(2).map(|m| {
-                    let s = m.as_str().chars().skip(2).collect::<String>();
-                    SizeArg::to_isize(s.as_str())
-                });
-                // println!("re1 {}, {:?} {:?}", s, x, y);
-                return Ok(SizeArg::Range(x.transpose()?, y.transpose()?));
-            }
-        };
-        //println!("re2 {}", s);
-        match ARG_RE2.captures(s) {
-            None => Ok(SizeArg::None),
-            Some(captrs) => {
-                let sizes = captrs
-                    .get(0)
-                    .unwrap()
-                    .as_str()
-                    .split(',')
-                    .map(|s| SizeArg::to_isize(s).unwrap())
-                    .collect::<Vec<isize>>();
-                return Ok(SizeArg::List(sizes));
-            }
-        }
-    }
+fn humanize(bytes: usize) -> String {
+    util::humanize_bytes(bytes)
 }
 
-impl SizeArg {
-    fn to_isize(s: &str) -> result::Result<isize, String> {
-        let chs: Vec<char> = s.chars().collect();
-        let (s, amp) = match chs[chs.len() - 1] {
-            'k' | 'K' => {
-                let s: String = chs[..(chs.len() - 1)].iter().collect();
-                (s, 1024)
-            }
-            'm' | 'M' => {
-                let s: String = chs[..(chs.len() - 1)].iter().collect();
-                (s, 1024 * 1024)
-            }
-            'g' | 'G' => {
-                let s: String = chs[..(chs.len() - 1)].iter().collect();
-                (s, 1024 * 1024 * 1024)
-            }
-            't' | 'T' => {
-                let s: String = chs[..(chs.len() - 1)].iter().collect();
-                (s, 1024 * 1024 * 1024 * 1024)
-            }
-            _ => {
-                let s: String = chs[..chs.len()].iter().collect();
-                (s, 1)
-            }
-        };
-        // println!("{}", s);
-        match s.parse::<isize>() {
-            Err(err) => Err(format!("parse: {:?}", err)),
-            Ok(n) => Ok(n * amp),
-        }
-    }
-
-    fn get_blocks(self) -> Vec<isize> {
-        let (from, till) = match self {
-            SizeArg::None => return vec![],
-            SizeArg::List(sizes) => return sizes,
-            SizeArg::Range(None, None) => return vec![],
-            SizeArg::Range(Some(x), None) => return vec![x],
-            SizeArg::Range(None, Some(_)) => unreachable!(),
-            SizeArg::Range(Some(x), Some(y)) => (x, y),
-        };
-        BLOCK_SIZES
-            .clone()
-            .iter()
-            .skip_while(|x| **x < from)
-            .take_while(|x| **x <= till)
-            .map(|x| *x)
-            .collect()
-    }
+fn get_blocks(arg: &SizeArg) -> Vec<isize> {
+    arg.to_sizes(&*BLOCK_SIZES)
 }
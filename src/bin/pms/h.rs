@@ -41,7 +41,8 @@ where
                             self.repos.push(repo);
                         }
                     }
-                    match files::walk(&scan_dir, self.clone(), check_dir_entry) {
+                    let opts = self.to_walk_opts()?;
+                    match files::walk(&scan_dir, &opts, self.clone(), check_dir_entry) {
                         Ok(ws) => break Ok(ws),
                         Err(err) => println!("skip scan_dir {:?}, {}", scan_dir, err),
                     };
@@ -51,6 +52,15 @@ where
         }
     }
 
+    fn to_walk_opts(&self) -> Result<files::WalkOpts> {
+        let mut opts = files::WalkOpts::new().add_exclude(r"/\.git$")?;
+        for dir in self.h.to_exclude_dirs().into_iter() {
+            let patt = format!("^{}(/|$)", regex::escape(&dir.to_string_lossy()));
+            opts = opts.add_exclude(&patt)?;
+        }
+        Ok(opts)
+    }
+
     pub fn into_repositories(self) -> Result<Vec<repo::Repo>> {
         let index = self.clone().into_trie();
 
@@ -90,12 +100,8 @@ fn check_dir_entry<H>(
 where
     H: Handler,
 {
-    if let Some(".git") = entry.file_name().to_str() {
-        Ok(files::WalkRes::SkipDir)
-    } else {
-        if let Ok(repo) = repo::Repo::from_entry(parent, entry) {
-            walk_state.repos.push(repo);
-        }
-        Ok(files::WalkRes::Ok)
+    if let Ok(repo) = repo::Repo::from_entry(parent, entry) {
+        walk_state.repos.push(repo);
     }
+    Ok(files::WalkRes::Ok)
 }
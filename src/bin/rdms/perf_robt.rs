@@ -116,7 +116,7 @@ struct InitialConfig {
 impl Default for InitialConfig {
     fn default() -> InitialConfig {
         InitialConfig {
-            name: "rdms-robt-perf".to_string(),
+            name: "rdms-perf".to_string(),
             dir: "".to_string(),
             z_blocksize: 4096,
             m_blocksize: 4096,
@@ -142,13 +142,13 @@ struct Incremental {
 impl Default for Incremental {
     fn default() -> Incremental {
         Incremental {
-            name: "rdms-robt-perf-incr1".to_string(),
+            name: "rdms-perf-incr1".to_string(),
             sets: 1_000_000,
             ins: 1_000_000,
             rems: 100_000,
             dels: 0,
             compact: true,
-            compact_name: "rdms-robt-perf-compact1".to_string(),
+            compact_name: "rdms-perf-compact1".to_string(),
         }
     }
 }
@@ -161,6 +161,7 @@ struct Load {
     iter_versions: bool,
     reverse: bool,
     reverse_versions: bool,
+    readahead: usize,
     readers: usize,
     validate: bool,
 }
@@ -174,6 +175,7 @@ impl Default for Load {
             iter_versions: false,
             reverse: true,
             reverse_versions: false,
+            readahead: 0,
             readers: 1,
             validate: true,
         }
@@ -314,6 +316,21 @@ where
         };
         println!("rdms: load-spawn iter took {:?} for {} items", elapsed, n);
     }
+    if p.load.iter && p.load.readahead > 0 {
+        let (elapsed, n) = {
+            let mut index = index.try_clone().unwrap();
+            index.set_readahead(p.load.readahead);
+            let start = time::Instant::now();
+            let n: usize =
+                index.iter(..).unwrap().map(|_: Result<dbs::Entry<K, V>>| 1_usize).sum();
+            assert!(n == index.len(), "{} != {}", n, index.len());
+            (start.elapsed(), n)
+        };
+        println!(
+            "rdms: load-spawn iter with readahead:{} took {:?} for {} items",
+            p.load.readahead, elapsed, n
+        );
+    }
     if p.load.iter_versions {
         let (elapsed, n) = {
             let start = time::Instant::now();
@@ -365,7 +382,7 @@ where
     rand::distributions::Standard: rand::distributions::Distribution<K>,
     rand::distributions::Standard: rand::distributions::Distribution<V>,
 {
-    let appmd = "rdms-robt-perf-initial".as_bytes().to_vec();
+    let appmd = "rdms-perf-initial".as_bytes().to_vec();
     let p_init = p.initial.clone();
     let mdb =
         llrb::load_index(seed, p_init.sets, p_init.ins, p_init.rems, p_init.dels, None);
@@ -409,7 +426,7 @@ where
         let mut config = config.clone();
         config.name = p_incr.name.clone();
 
-        let appmd = format!("rdms-robt-perf-incremental-{}", i).as_bytes().to_vec();
+        let appmd = format!("rdms-perf-incremental-{}", i).as_bytes().to_vec();
         let seqno = Some(index.to_seqno());
 
         let mdb = llrb::load_index(
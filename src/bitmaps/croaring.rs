@@ -14,6 +14,7 @@ use crate::{dbs::Bloom, hash::CityHasher, Error, Result};
 
 // TODO: right now we are using cityhash-rs, make hasher generic.
 
+#[derive(Clone)]
 pub struct CRoaring {
     bitmap: Bitmap,
 }
@@ -0,0 +1,165 @@
+//! Module `croaring64` implement the [Bloom] trait for 64-bit digests using
+//! a two-level [roaring bitmap][roaring-bitmap].
+//!
+//! [Bloom]: crate::dbs::Bloom
+//! [roaring-bitmap]: https://roaringbitmap.org
+
+use croaring::bitmap::Bitmap;
+
+use std::{
+    collections::BTreeMap,
+    convert::{TryFrom, TryInto},
+    hash::{Hash, Hasher},
+};
+
+use crate::{dbs::Bloom, hash::CityHasher, Error, Result};
+
+/// Roaring bitmap over 64-bit digests, unlike [CRoaring][super::CRoaring]
+/// digests are not folded into 32-bits. Digests are partitioned on their
+/// high 32-bits, and low 32-bits of each partition is kept in a 32-bit
+/// roaring bitmap.
+#[derive(Clone)]
+pub struct CRoaring64 {
+    bitmaps: BTreeMap<u32, Bitmap>,
+}
+
+impl Default for CRoaring64 {
+    fn default() -> CRoaring64 {
+        CRoaring64::new()
+    }
+}
+
+impl CRoaring64 {
+    pub fn new() -> CRoaring64 {
+        CRoaring64 { bitmaps: BTreeMap::new() }
+    }
+
+    #[inline]
+    fn to_digest<Q: ?Sized + Hash>(element: &Q) -> u64 {
+        let mut hasher = CityHasher::default();
+        element.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl Bloom for CRoaring64 {
+    #[inline]
+    fn len(&self) -> Result<usize> {
+        let n: u64 = self.bitmaps.values().map(|b| b.cardinality()).sum();
+        err_at!(FailConvert, n.try_into())
+    }
+
+    #[inline]
+    fn add_key<Q: ?Sized + Hash>(&mut self, element: &Q) {
+        self.add_digest64(Self::to_digest(element))
+    }
+
+    #[inline]
+    fn add_keys<Q: Hash>(&mut self, keys: &[Q]) {
+        for key in keys.iter() {
+            self.add_key(key)
+        }
+    }
+
+    #[inline]
+    fn add_digest32(&mut self, digest: u32) {
+        self.add_digest64(u64::from(digest))
+    }
+
+    #[inline]
+    fn add_digests32(&mut self, digests: &[u32]) {
+        for digest in digests.iter() {
+            self.add_digest32(*digest)
+        }
+    }
+
+    #[inline]
+    fn add_digest64(&mut self, digest: u64) {
+        let (high, low) = ((digest >> 32) as u32, (digest & 0xFFFFFFFF) as u32);
+        self.bitmaps.entry(high).or_insert_with(Bitmap::create).add(low)
+    }
+
+    #[inline]
+    fn add_digests64(&mut self, digests: &[u64]) {
+        for digest in digests.iter() {
+            self.add_digest64(*digest)
+        }
+    }
+
+    #[inline]
+    fn build(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn contains<Q: ?Sized + Hash>(&self, element: &Q) -> bool {
+        let digest = Self::to_digest(element);
+        let (high, low) = ((digest >> 32) as u32, (digest & 0xFFFFFFFF) as u32);
+        match self.bitmaps.get(&high) {
+            Some(bitmap) => bitmap.contains(low),
+            None => false,
+        }
+    }
+
+    // serialized as, 4-byte count of partitions, followed by, for each
+    // partition, 4-byte high-word, 4-byte length and the serialized bitmap.
+    fn to_bytes(&self) -> Result<Vec<u8>> {
+        let n = err_at!(FailConvert, u32::try_from(self.bitmaps.len()))?;
+        let mut buf = n.to_be_bytes().to_vec();
+        for (high, bitmap) in self.bitmaps.iter() {
+            let data = bitmap.serialize();
+            let len = err_at!(FailConvert, u32::try_from(data.len()))?;
+            buf.extend_from_slice(&high.to_be_bytes());
+            buf.extend_from_slice(&len.to_be_bytes());
+            buf.extend_from_slice(&data);
+        }
+        Ok(buf)
+    }
+
+    fn from_bytes(buf: &[u8]) -> Result<(CRoaring64, usize)> {
+        let read_u32 = |off: usize| -> Result<u32> {
+            match buf.get(off..off + 4) {
+                Some(data) => Ok(u32::from_be_bytes(data.try_into().unwrap())),
+                None => err_at!(InvalidInput, msg: "croaring64 truncated at {}", off),
+            }
+        };
+
+        let mut bitmaps = BTreeMap::new();
+        let (n, mut off) = (read_u32(0)?, 4);
+        for _ in 0..n {
+            let high = read_u32(off)?;
+            let len = err_at!(FailConvert, usize::try_from(read_u32(off + 4)?))?;
+            off += 8;
+            let data = match buf.get(off..off + len) {
+                Some(data) => data,
+                None => err_at!(InvalidInput, msg: "croaring64 truncated at {}", off)?,
+            };
+            bitmaps.insert(high, Bitmap::deserialize(data));
+            off += len;
+        }
+
+        Ok((CRoaring64 { bitmaps }, off))
+    }
+
+    fn or(&self, other: &CRoaring64) -> Result<CRoaring64> {
+        let mut bitmaps = BTreeMap::new();
+        for (high, bitmap) in self.bitmaps.iter() {
+            let bitmap = match other.bitmaps.get(high) {
+                Some(other) => bitmap.or(other),
+                None => bitmap.clone(),
+            };
+            bitmaps.insert(*high, bitmap);
+        }
+        for (high, bitmap) in other.bitmaps.iter() {
+            if !bitmaps.contains_key(high) {
+                bitmaps.insert(*high, bitmap.clone());
+            }
+        }
+
+        Ok(CRoaring64 { bitmaps })
+    }
+}
+
+#[cfg(test)]
+#[path = "croaring64_test.rs"]
+mod croaring64_test;
@@ -0,0 +1,85 @@
+use rand::{prelude::random, rngs::StdRng, Rng, SeedableRng};
+
+use crate::{bitmaps::CRoaring, dbs::Bloom};
+
+use super::*;
+
+#[test]
+fn test_croaring64_bitmap() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_croaring64_bitmap seed:{}", seed);
+
+    let keys: Vec<u64> = (0..100_000).map(|_| rng.gen::<u64>()).collect();
+
+    let filter = {
+        let mut filter = CRoaring64::new();
+        filter.add_keys(&keys);
+        filter.build().expect("fail building croaring64 filter");
+        filter
+    };
+
+    let mut digests: Vec<u64> = keys.iter().map(CRoaring64::to_digest).collect();
+    digests.sort_unstable();
+    digests.dedup();
+    assert_eq!(filter.len(), Ok(digests.len()));
+
+    for key in keys.iter() {
+        assert!(filter.contains(key), "key {} not present", key);
+    }
+
+    let filter = {
+        let val = filter.to_bytes().unwrap();
+        let (filter, n) = CRoaring64::from_bytes(&val).unwrap();
+        assert_eq!(n, val.len(), "{} {}", n, val.len());
+        filter
+    };
+    for key in keys.iter() {
+        assert!(filter.contains(key), "key {} not present", key);
+    }
+
+    let (a, b) = keys.split_at(keys.len() / 2);
+    let (mut fa, mut fb) = (CRoaring64::new(), CRoaring64::new());
+    fa.add_keys(a);
+    fb.add_keys(b);
+    let filter = fa.or(&fb).unwrap();
+    assert_eq!(filter.len(), Ok(digests.len()));
+    for key in keys.iter() {
+        assert!(filter.contains(key), "key {} not present", key);
+    }
+}
+
+#[test]
+fn test_croaring64_false_positive() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_croaring64_false_positive seed:{}", seed);
+
+    let keys: Vec<u64> = (0..1_000_000).map(|_| rng.gen::<u64>()).collect();
+
+    let mut filter32 = CRoaring::new();
+    let mut filter64 = CRoaring64::new();
+    for key in keys.iter() {
+        filter32.add_key(key);
+        filter64.add_key(key);
+    }
+
+    let (mut fp32, mut fp64) = (0, 0);
+    let mut keys = keys;
+    keys.sort_unstable();
+    for _ in 0..1_000_000 {
+        let key = rng.gen::<u64>();
+        if keys.binary_search(&key).is_ok() {
+            continue;
+        }
+        if filter32.contains(&key) {
+            fp32 += 1;
+        }
+        if filter64.contains(&key) {
+            fp64 += 1;
+        }
+    }
+    println!("test_croaring64_false_positive fp32:{} fp64:{}", fp32, fp64);
+
+    assert!(fp64 <= fp32, "fp32:{} fp64:{}", fp32, fp64);
+}
@@ -1,12 +1,14 @@
 //! Module implement adaptors for popular bitmap filters.
 
 mod croaring;
+mod croaring64;
 mod fuse16;
 mod fuse8;
 mod nobitmap;
 mod xor8;
 
 pub use self::croaring::CRoaring;
+pub use self::croaring64::CRoaring64;
 pub use nobitmap::NoBitmap;
 // Re-imported from xorfilter package.
 pub use xorfilter::Fuse16;
@@ -4,6 +4,14 @@ use std::hash::Hash;
 
 use crate::{dbs, Result};
 
+/// Dummy bitmap index, to be used when index does not need a bloom filter.
+///
+/// * [contains][dbs::Bloom::contains] always return `true`, that is, every
+///   key is treated as a possible member, and lookups shall never be
+///   short-circuited.
+/// * Serializes to an empty byte-array, which can be re-opened back as NoBitmap.
+///   In debug builds, de-serializing a non-empty byte-array shall panic, this
+///   can catch an index built with a real bitmap and opened as NoBitmap.
 #[derive(Default, Clone)]
 pub struct NoBitmap;
 
@@ -59,7 +67,8 @@ impl dbs::Bloom for NoBitmap {
     }
 
     #[inline]
-    fn from_bytes(_buf: &[u8]) -> Result<(NoBitmap, usize)> {
+    fn from_bytes(buf: &[u8]) -> Result<(NoBitmap, usize)> {
+        debug_assert!(buf.is_empty(), "NoBitmap from {} bytes of bitmap", buf.len());
         Ok((NoBitmap, 0))
     }
 
@@ -68,3 +77,7 @@ impl dbs::Bloom for NoBitmap {
         Ok(NoBitmap)
     }
 }
+
+#[cfg(test)]
+#[path = "nobitmap_test.rs"]
+mod nobitmap_test;
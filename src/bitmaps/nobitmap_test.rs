@@ -0,0 +1,73 @@
+use rand::{prelude::random, rngs::StdRng, Rng, SeedableRng};
+
+use std::fs;
+
+use crate::{dbs::Bloom, llrb, robt};
+
+use super::*;
+
+#[test]
+fn test_nobitmap() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_nobitmap seed:{}", seed);
+
+    let mut filter = NoBitmap;
+    let keys: Vec<u64> = (0..1000).map(|_| rng.gen::<u64>()).collect();
+    for key in keys.iter().take(500) {
+        filter.add_key(key);
+    }
+    filter.build().unwrap();
+
+    for key in keys.iter() {
+        assert!(filter.contains(key), "key {}", key);
+    }
+    assert_eq!(filter.len().unwrap(), 0);
+
+    let data = filter.to_bytes().unwrap();
+    assert!(data.is_empty());
+    let (filter, n) = NoBitmap::from_bytes(&data).unwrap();
+    assert_eq!(n, 0);
+    for key in keys.iter() {
+        assert!(filter.contains(key), "key {}", key);
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic]
+fn test_nobitmap_from_bitmap() {
+    let mut filter = xorfilter::Xor8::<xorfilter::BuildHasherDefault>::new();
+    filter.add_key(&10_u64);
+    filter.build().unwrap();
+
+    let data = <xorfilter::Xor8 as Bloom>::to_bytes(&filter).unwrap();
+    NoBitmap::from_bytes(&data).ok();
+}
+
+#[test]
+fn test_nobitmap_robt() {
+    let seed: u64 = random();
+    println!("test_nobitmap_robt seed:{}", seed);
+
+    let dir = std::env::temp_dir().join("test_nobitmap_robt");
+    fs::remove_dir_all(&dir).ok();
+    let config = robt::Config::new(dir.as_os_str(), "nobitmap");
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 10_000, 10_000, 1_000, 1_000, None);
+    {
+        let mut build = robt::Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter().unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap().close().unwrap();
+    }
+
+    let mut index =
+        robt::Index::<u64, u64, NoBitmap>::open(dir.as_os_str(), "nobitmap").unwrap();
+    for entry in mdb.iter().unwrap() {
+        assert!(index.as_bitmap().contains(entry.as_key()));
+        let e = index.get(entry.as_key()).unwrap();
+        assert_eq!(e.key, entry.key);
+    }
+
+    index.purge().unwrap();
+}
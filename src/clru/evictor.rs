@@ -10,7 +10,6 @@ use std::{
 
 use crate::{
     clru::{self, Access, Config},
-    dbs::{self, Footprint},
     Result,
 };
 
@@ -19,13 +18,13 @@ use crate::{
 /// * Node is marked as deleted.
 /// * Node is older than configured elapsed time, optional.
 /// * Number of nodes in the access list exceed the count-limit, optional.
-/// * Cummulative size of values held in cache exceeds size-limit, optional.
+/// * Cummulative footprint of values held in cache exceeds weight-limit, optional.
 pub struct Evictor<K, V, H>
 where
     K: fmt::Debug,
 {
-    max_size: Option<usize>,
-    cur_size: Option<Arc<AtomicUsize>>,
+    max_weight: Option<usize>,
+    cur_weight: Option<Arc<AtomicUsize>>,
     max_count: usize,
     cur_count: Arc<AtomicUsize>,
     max_old: Option<time::Duration>,
@@ -59,8 +58,8 @@ where
         map: cmap::Map<K, Arc<clru::Value<K, V>>, H>,
     ) -> Self {
         Evictor {
-            max_size: config.max_size,
-            cur_size: config.cur_size.clone(),
+            max_weight: config.max_weight,
+            cur_weight: config.cur_weight.clone(),
             max_count: config.max_count,
             cur_count: Arc::clone(&config.cur_count),
             max_old: config.max_old.map(time::Duration::from_secs),
@@ -80,7 +79,7 @@ where
 impl<K, V, H> Evictor<K, V, H>
 where
     K: Clone + PartialEq + Hash + fmt::Debug,
-    V: Clone + dbs::Footprint,
+    V: Clone,
     H: BuildHasher,
 {
     pub fn run(mut self) -> Result<Self> {
@@ -154,9 +153,9 @@ where
             Access::N { deleted, born, .. } => match deleted.load(SeqCst) {
                 true => Evict::Deleted,
                 false => {
-                    let cur_size = self.cur_size.as_ref().map(|x| x.load(SeqCst));
-                    let mut evicta = match cur_size {
-                        Some(cur_size) if cur_size > self.max_size.unwrap() => true,
+                    let cur_weight = self.cur_weight.as_ref().map(|x| x.load(SeqCst));
+                    let mut evicta = match cur_weight {
+                        Some(cur_weight) if cur_weight > self.max_weight.unwrap() => true,
                         Some(_) | None => false,
                     };
 
@@ -234,8 +233,8 @@ where
                                 // IMPORTANT: delete_next() and map.remove() have
                                 // synchronisation problem. Sequence is important.
                                 let key = behind.next_key();
-                                let (remok, size) = match self.map.remove(key) {
-                                    Some(value) => (1, value.footprint()?),
+                                let (remok, weight) = match self.map.remove(key) {
+                                    Some(value) => (1, value.weight),
                                     None => (0, 0),
                                 };
 
@@ -248,12 +247,8 @@ where
                                 //);
                                 garbage_access.push(next_access);
 
-                                if let Some(x) = self.cur_size.as_ref() {
-                                    if size < 0 {
-                                        x.fetch_sub(size.abs() as usize, SeqCst);
-                                    } else {
-                                        x.fetch_add(size as usize, SeqCst);
-                                    }
+                                if let Some(x) = self.cur_weight.as_ref() {
+                                    x.fetch_sub(weight, SeqCst);
                                 }
                                 self.cur_count.fetch_sub(remok, SeqCst);
                                 //println!(
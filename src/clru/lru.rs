@@ -1,25 +1,30 @@
 use std::{
     borrow::Borrow,
+    collections::HashMap,
     fmt,
     hash::{BuildHasher, Hash},
+    mem,
     sync::{
         atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering::SeqCst},
-        Arc,
+        Arc, Condvar, Mutex,
     },
     thread, time,
 };
 
 use crate::{
     clru::{self, Access, Evictor},
-    dbs, Result,
+    Error, Result,
 };
 
 pub struct Config {
     pub thread_pool_size: usize,
-    pub max_size: Option<usize>,
+    /// Cap the cache by summed weight of cached values, in bytes, instead of
+    /// entry count. Values are weighed using the weigher supplied to
+    /// [Lru::with_weigher].
+    pub max_weight: Option<usize>,
     pub max_count: usize,
     pub max_old: Option<u64>, // in seconds.
-    pub(crate) cur_size: Option<Arc<AtomicUsize>>,
+    pub(crate) cur_weight: Option<Arc<AtomicUsize>>,
     pub(crate) cur_count: Arc<AtomicUsize>,
 }
 
@@ -27,17 +32,20 @@ impl Config {
     pub fn new(thread_pool_size: usize, max_count: usize) -> Config {
         Config {
             thread_pool_size,
-            max_size: None,
+            max_weight: None,
             max_count,
             max_old: None,
-            cur_size: None,
+            cur_weight: None,
             cur_count: Arc::new(AtomicUsize::new(0)),
         }
     }
 
-    pub fn set_max_size(&mut self, max_size: usize) -> &mut Self {
-        self.max_size = Some(max_size);
-        self.cur_size = Some(Arc::new(AtomicUsize::new(0)));
+    /// Evict entries until the summed weight of cached values falls within
+    /// `max_weight` bytes. Useful when values are of varying size, cache shall
+    /// be created using [Lru::with_weigher].
+    pub fn set_max_weight(&mut self, max_weight: usize) -> &mut Self {
+        self.max_weight = Some(max_weight);
+        self.cur_weight = Some(Arc::new(AtomicUsize::new(0)));
         self
     }
 
@@ -47,12 +55,16 @@ impl Config {
     }
 }
 
+// weigh a value, in bytes, for caches capped by max_weight.
+type Weigher<V> = Arc<dyn Fn(&V) -> Result<usize> + Send + Sync>;
+
 pub struct Lru<K, V, H = cmap::DefaultHasher>
 where
     K: fmt::Debug,
 {
-    max_size: Option<usize>,
-    cur_size: Option<Arc<AtomicUsize>>,
+    max_weight: Option<usize>,
+    cur_weight: Option<Arc<AtomicUsize>>,
+    weigher: Option<Weigher<V>>,
     max_count: usize,
     cur_count: Arc<AtomicUsize>,
     max_old: Option<time::Duration>,
@@ -62,6 +74,8 @@ where
     access_tail: Arc<Access<K>>,
     evictor: Option<thread::JoinHandle<Result<Evictor<K, V, H>>>>,
     close: Arc<AtomicBool>,
+    // keys whose value is being computed, refer to get_or_insert_with.
+    inflight: Arc<Mutex<HashMap<K, Arc<InFlight<V>>>>>,
 
     n_gets: Arc<AtomicUsize>,
     n_sets: Arc<AtomicUsize>,
@@ -73,8 +87,9 @@ where
 {
     fn clone(&self) -> Self {
         Lru {
-            max_size: self.max_size,
-            cur_size: self.cur_size.as_ref().map(Arc::clone),
+            max_weight: self.max_weight,
+            cur_weight: self.cur_weight.as_ref().map(Arc::clone),
+            weigher: self.weigher.as_ref().map(Arc::clone),
             max_count: self.max_count,
             cur_count: Arc::clone(&self.cur_count),
             max_old: self.max_old,
@@ -84,6 +99,7 @@ where
             access_tail: Arc::clone(&self.access_tail),
             evictor: None,
             close: Arc::clone(&self.close),
+            inflight: Arc::clone(&self.inflight),
 
             n_gets: Arc::clone(&self.n_gets),
             n_sets: Arc::clone(&self.n_sets),
@@ -94,7 +110,7 @@ where
 impl<K, V> Lru<K, V, cmap::DefaultHasher>
 where
     K: 'static + Send + Sync + Clone + PartialEq + Hash + fmt::Debug,
-    V: 'static + Send + Sync + Clone + dbs::Footprint,
+    V: 'static + Send + Sync + Clone,
 {
     pub fn from_config(config: Config) -> Self {
         Lru::with_hash(cmap::DefaultHasher::default(), config)
@@ -104,11 +120,14 @@ where
 impl<K, V, H> Lru<K, V, H>
 where
     K: 'static + Send + Sync + Clone + PartialEq + Hash + fmt::Debug,
-    V: 'static + Send + Sync + Clone + dbs::Footprint,
+    V: 'static + Send + Sync + Clone,
     H: 'static + Send + Sync + Clone + BuildHasher,
 {
+    /// Create a cache capped by entry count, and optionally by age. Caches
+    /// configured with [Config::set_max_weight] shall be created using
+    /// [Lru::with_weigher], else [Lru::set] fails.
     pub fn with_hash(hash_builder: H, mut config: Config) -> Lru<K, V, H> {
-        config.cur_size = config.max_size.map(|_| Arc::new(AtomicUsize::new(0)));
+        config.cur_weight = config.max_weight.map(|_| Arc::new(AtomicUsize::new(0)));
         config.cur_count = Arc::new(AtomicUsize::new(0));
 
         let (access_head, access_tail) = Access::new_list();
@@ -128,8 +147,9 @@ where
         };
 
         Lru {
-            max_size: config.max_size,
-            cur_size: config.cur_size,
+            max_weight: config.max_weight,
+            cur_weight: config.cur_weight,
+            weigher: None,
             max_count: config.max_count,
             cur_count: Arc::clone(&config.cur_count),
             max_old: config.max_old.map(time::Duration::from_secs),
@@ -139,12 +159,25 @@ where
             access_tail,
             evictor,
             close,
+            inflight: Arc::new(Mutex::new(HashMap::new())),
 
             n_gets: Arc::new(AtomicUsize::new(0)),
             n_sets: Arc::new(AtomicUsize::new(0)),
         }
     }
 
+    /// Create a cache that can be capped by [Config::set_max_weight], `weigher`
+    /// shall return the weight of a value in bytes. Use [clru::footprint] for
+    /// values implementing [crate::dbs::Footprint].
+    pub fn with_weigher<F>(hash_builder: H, config: Config, weigher: F) -> Lru<K, V, H>
+    where
+        F: 'static + Fn(&V) -> Result<usize> + Send + Sync,
+    {
+        let mut lru = Lru::with_hash(hash_builder, config);
+        lru.weigher = Some(Arc::new(weigher));
+        lru
+    }
+
     pub fn close(mut self) -> Result<Option<Stats>> {
         let _access_head = loop {
             if Arc::get_mut(&mut self.access_head).is_some() {
@@ -220,7 +253,9 @@ where
         }
     }
 
-    pub fn set(&mut self, key: K, value: V) -> Option<V>
+    /// Add `value` to cache, return the old value if `key` was already cached.
+    /// For caches capped by weight, fails if `value` cannot be weighed.
+    pub fn set(&mut self, key: K, value: V) -> Result<Option<V>>
     where
         K: Clone + PartialEq + Hash,
         V: Clone,
@@ -228,15 +263,30 @@ where
     {
         self.n_sets.fetch_add(1, SeqCst);
 
+        let weight = match (self.cur_weight.as_ref(), self.weigher.as_ref()) {
+            (Some(_), Some(weigher)) => {
+                weigher(&value)? + mem::size_of::<clru::Value<K, V>>()
+            }
+            (Some(_), None) => err_at!(InvalidInput, msg: "max_weight without weigher")?,
+            (None, _) => 0,
+        };
+
         let new_ptr = Box::leak(self.access_head.new(&key));
 
-        let value = Arc::new(clru::Value { value, access: AtomicPtr::new(new_ptr) });
+        let value =
+            Arc::new(clru::Value { value, access: AtomicPtr::new(new_ptr), weight });
+        if let Some(cur_weight) = self.cur_weight.as_ref() {
+            cur_weight.fetch_add(weight, SeqCst);
+        }
 
         let res = match self.map.set(key, value).as_ref().map(|x| x.as_ref()) {
-            Some(clru::Value { access, value }) => {
-                let access = unsafe { access.load(SeqCst).as_ref().unwrap() };
+            Some(old) => {
+                if let Some(cur_weight) = self.cur_weight.as_ref() {
+                    cur_weight.fetch_sub(old.weight, SeqCst);
+                }
+                let access = unsafe { old.access.load(SeqCst).as_ref().unwrap() };
                 access.delete();
-                Some(value.clone())
+                Some(old.value.clone())
             }
             None => {
                 self.cur_count.fetch_add(1, SeqCst);
@@ -246,7 +296,57 @@ where
 
         self.access_head.append(unsafe { Box::from_raw(new_ptr) });
 
-        res
+        Ok(res)
+    }
+
+    /// Return the cached value for `key`, on cache miss compute the value
+    /// using `f` and add it to the cache. Under concurrent misses for the
+    /// same key, across this instance and its clones, only one caller runs
+    /// `f` while others wait for its result. If that caller panics, one of
+    /// the waiting callers shall compute the value.
+    pub fn get_or_insert_with<F>(&mut self, key: K, f: F) -> Result<V>
+    where
+        K: Clone + Eq + Hash,
+        V: Clone,
+        H: BuildHasher,
+        F: FnOnce() -> V,
+    {
+        let inflight = loop {
+            if let Some(value) = self.get(&key) {
+                return Ok(value);
+            }
+
+            let inflight = {
+                let mut inflights = err_at!(Fatal, self.inflight.lock())?;
+                // computed value is cached before its in-flight entry is removed.
+                if let Some(value) = self.get(&key) {
+                    return Ok(value);
+                }
+                match inflights.get(&key) {
+                    Some(inflight) => Arc::clone(inflight),
+                    None => {
+                        let inflight = Arc::new(InFlight::new());
+                        inflights.insert(key.clone(), Arc::clone(&inflight));
+                        break inflight;
+                    }
+                }
+            };
+
+            if let Some(value) = inflight.wait()? {
+                return Ok(value);
+            }
+        };
+
+        let _leader = Leader {
+            key: &key,
+            inflight: &inflight,
+            inflights: Arc::clone(&self.inflight),
+        };
+        let value = f();
+        self.set(key.clone(), value.clone())?;
+        inflight.done(Some(value.clone()))?;
+
+        Ok(value)
     }
 
     pub fn len(&self) -> usize {
@@ -256,6 +356,69 @@ where
     pub fn is_empty(&self) -> bool {
         self.map.len() == 0
     }
+
+    /// Return the summed weight of values held in cache, applicable only
+    /// if cache is configured with [Config::set_max_weight].
+    pub fn to_weight(&self) -> Option<usize> {
+        self.cur_weight.as_ref().map(|x| x.load(SeqCst))
+    }
+}
+
+// value being computed by one of the callers of get_or_insert_with.
+struct InFlight<V> {
+    // None while pending, Some(None) if computing caller failed.
+    value: Mutex<Option<Option<V>>>,
+    cond: Condvar,
+}
+
+impl<V> InFlight<V>
+where
+    V: Clone,
+{
+    fn new() -> Self {
+        InFlight { value: Mutex::new(None), cond: Condvar::new() }
+    }
+
+    fn wait(&self) -> Result<Option<V>> {
+        let mut value = err_at!(Fatal, self.value.lock())?;
+        while value.is_none() {
+            value = err_at!(Fatal, self.cond.wait(value))?;
+        }
+        Ok(value.clone().unwrap())
+    }
+
+    fn done(&self, val: Option<V>) -> Result<()> {
+        let mut value = err_at!(Fatal, self.value.lock())?;
+        if value.is_none() {
+            *value = Some(val);
+            self.cond.notify_all();
+        }
+        Ok(())
+    }
+}
+
+// remove the in-flight entry once the computing caller returns or panics.
+struct Leader<'a, K, V>
+where
+    K: Eq + Hash,
+    V: Clone,
+{
+    key: &'a K,
+    inflight: &'a InFlight<V>,
+    inflights: Arc<Mutex<HashMap<K, Arc<InFlight<V>>>>>,
+}
+
+impl<'a, K, V> Drop for Leader<'a, K, V>
+where
+    K: Eq + Hash,
+    V: Clone,
+{
+    fn drop(&mut self) {
+        if let Ok(mut inflights) = self.inflights.lock() {
+            inflights.remove(self.key);
+        }
+        self.inflight.done(None).ok();
+    }
 }
 
 enum AccessResult<V> {
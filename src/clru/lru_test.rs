@@ -3,7 +3,7 @@ use rand::{self, prelude::random, rngs::StdRng, Rng, SeedableRng};
 
 use std::{fmt, hash::Hash, thread};
 
-use crate::{clru, dbs, llrb};
+use crate::{clru, dbs, llrb, Error, Result};
 
 macro_rules! test_code {
     ($seed:expr, $keytype:ty) => {{
@@ -115,6 +115,48 @@ fn test_lru_u128() {
     test_code!(seed, u128);
 }
 
+#[test]
+fn test_lru_get_or_insert_with() {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering::SeqCst},
+        Arc, Barrier,
+    };
+
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let n_threads = [2, 4, 8, 16, 32][rng.gen::<usize>() % 5];
+    println!("test_lru_get_or_insert_with seed:{} n_threads:{}", seed, n_threads);
+
+    let lru: clru::Lru<u64, u128> = {
+        let config = clru::Config::new(n_threads + 1, 1000);
+        clru::Lru::from_config(config)
+    };
+    let n_calls = Arc::new(AtomicUsize::new(0));
+    let barrier = Arc::new(Barrier::new(n_threads));
+
+    let mut handles = vec![];
+    for _ in 0..n_threads {
+        let (mut lru, n_calls) = (lru.clone(), Arc::clone(&n_calls));
+        let barrier = Arc::clone(&barrier);
+        handles.push(thread::spawn(move || {
+            barrier.wait();
+            let f = || {
+                n_calls.fetch_add(1, SeqCst);
+                thread::sleep(std::time::Duration::from_millis(10));
+                0x1234_u128
+            };
+            lru.get_or_insert_with(42, f).unwrap()
+        }));
+    }
+    for handle in handles.into_iter() {
+        assert_eq!(handle.join().unwrap(), 0x1234);
+    }
+
+    assert_eq!(n_calls.load(SeqCst), 1);
+    assert_eq!(lru.get(&42), Some(0x1234));
+    lru.close().unwrap();
+}
+
 fn with_lru<K>(
     _thread_id: usize,
     seed: u64,
@@ -142,7 +184,7 @@ where
             None => {
                 // println!("thread-{} get-no key:{}", _thread_id, key);
                 let ref_value = index.get(&key).unwrap().to_value().unwrap();
-                lru.set(key, ref_value);
+                lru.set(key, ref_value).unwrap();
                 stats.misses += 1;
             }
         }
@@ -248,3 +290,102 @@ fn validate(
 
     assert_eq!(n_ops * n_threads, stats.n_evicted + stats.n_deleted + stats.n_access_gc)
 }
+
+#[test]
+fn test_lru_weight() {
+    use std::time;
+
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_lru_weight seed:{}", seed);
+
+    let n = 1000;
+    let max_weight = 64 * 1024;
+    let mut lru: clru::Lru<u64, Vec<u8>> = {
+        let mut config = clru::Config::new(2, n);
+        config.set_max_weight(max_weight);
+        clru::Lru::with_weigher(cmap::DefaultHasher::default(), config, clru::footprint)
+    };
+
+    let mut weight = 0;
+    for key in 0..(n as u64) {
+        let value = vec![0xAB_u8; rng.gen::<usize>() % 2048];
+        weight += value.len();
+        lru.set(key, value).unwrap();
+    }
+    assert!(weight > max_weight, "{} <= {}", weight, max_weight);
+
+    // eviction happens in background, wait for the cache to fit the budget.
+    let start = time::Instant::now();
+    while lru.to_weight().unwrap() > max_weight {
+        assert!(start.elapsed() < time::Duration::from_secs(10));
+        thread::sleep(time::Duration::from_millis(10));
+    }
+
+    let len = lru.len();
+    println!("test_lru_weight len:{} weight:{:?}", len, lru.to_weight());
+    assert!(len < n, "{} {}", len, n);
+
+    let footprint = (0..(n as u64))
+        .filter_map(|key| lru.get(&key))
+        .map(|value| dbs::Footprint::footprint(&value).unwrap() as usize)
+        .sum::<usize>();
+    assert!(footprint <= max_weight, "{} > {}", footprint, max_weight);
+
+    lru.close().unwrap();
+}
+
+#[test]
+fn test_lru_weigher() {
+    // values need not implement footprint, unless cache is capped by weight.
+    struct Blob(u64);
+
+    impl Clone for Blob {
+        fn clone(&self) -> Self {
+            Blob(self.0)
+        }
+    }
+
+    let mut lru: clru::Lru<u64, Blob> = clru::Lru::from_config(clru::Config::new(2, 10));
+    for key in 0..100_u64 {
+        lru.set(key, Blob(key)).unwrap();
+    }
+    assert_eq!(lru.to_weight(), None);
+    lru.close().unwrap();
+
+    // cache capped by weight, without a weigher.
+    let mut lru: clru::Lru<u64, Blob> = {
+        let mut config = clru::Config::new(2, 10);
+        config.set_max_weight(1024);
+        clru::Lru::from_config(config)
+    };
+    assert!(lru.set(1, Blob(1)).is_err());
+    assert_eq!(lru.len(), 0);
+    lru.close().unwrap();
+
+    // weigher errors are returned to the caller.
+    let mut lru: clru::Lru<u64, Blob> = {
+        let mut config = clru::Config::new(2, 10);
+        config.set_max_weight(1024);
+        let weigher = |value: &Blob| match value.0 {
+            0 => err_at!(InvalidInput, msg: "cannot weigh"),
+            n => Ok(n as usize),
+        };
+        clru::Lru::with_weigher(cmap::DefaultHasher::default(), config, weigher)
+    };
+    assert!(lru.set(0, Blob(0)).is_err());
+    assert_eq!(lru.to_weight(), Some(0));
+    lru.set(1, Blob(10)).unwrap();
+    let weight = lru.to_weight().unwrap();
+    assert!(weight >= 10, "{}", weight);
+    lru.close().unwrap();
+
+    // negative footprint is not counted as zero.
+    impl dbs::Footprint for Blob {
+        fn footprint(&self) -> Result<isize> {
+            Ok(-(self.0 as isize))
+        }
+    }
+    assert!(clru::footprint(&Blob(1)).is_err());
+    assert_eq!(clru::footprint(&Blob(0)).unwrap(), 0);
+}
@@ -1,8 +1,8 @@
 //! Module implement concurrent LRU cache.
 
-use std::{fmt, sync::atomic::AtomicPtr};
+use std::{convert::TryFrom, fmt, sync::atomic::AtomicPtr};
 
-use crate::{dbs, Result};
+use crate::{dbs, Error, Result};
 
 mod access;
 mod evictor;
@@ -19,6 +19,8 @@ where
 {
     value: V,
     access: AtomicPtr<Access<K>>,
+    // accounted against max_weight, zero if cache is not capped by weight.
+    weight: usize,
 }
 
 impl<K, V> dbs::Footprint for Value<K, V>
@@ -32,3 +34,13 @@ where
         Ok(size)
     }
 }
+
+/// Weigher for caches holding [Footprint][dbs::Footprint] values, can be
+/// supplied to [Lru::with_weigher]. Fails if `value`'s footprint is negative.
+pub fn footprint<V>(value: &V) -> Result<usize>
+where
+    V: dbs::Footprint,
+{
+    let n = value.footprint()?;
+    err_at!(FailConvert, usize::try_from(n), "negative footprint {}", n)
+}
@@ -1,11 +1,33 @@
+use cbordata::Cborize;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
 use std::{fmt, result};
 
-use crate::dba;
+use crate::{dba, util, Error, Result};
+
+const COMMIT_CONTENT_VER: u32 = 0x00010001;
+
+/// Digest algorithm used to compute object's [Oid].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DigestAlg {
+    /// SHA-1, 20 byte digest, compatible with git.
+    Sha1,
+    /// SHA-256, 32 byte digest.
+    Sha256,
+}
+
+impl Default for DigestAlg {
+    fn default() -> DigestAlg {
+        DigestAlg::Sha1
+    }
+}
 
 /// Type is object id, which is a hash digest of object's content.
-#[derive(Clone)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub enum Oid {
     Sha1 { hash: [u8; 20] },
+    Sha256 { hash: [u8; 32] },
 }
 
 impl Oid {
@@ -16,10 +38,50 @@ impl Oid {
         Oid::Sha1 { hash }
     }
 
+    /// Create a new Oid from sha256 digest.
+    pub fn from_sha256(digest: &[u8]) -> Oid {
+        let mut hash = [0; 32];
+        hash[..].copy_from_slice(digest);
+        Oid::Sha256 { hash }
+    }
+
+    /// Compute the digest for `data` using algorithm `alg`.
+    pub fn from_data(alg: DigestAlg, data: &[u8]) -> Oid {
+        match alg {
+            DigestAlg::Sha1 => Oid::from_sha1(&Sha1::digest(data)),
+            DigestAlg::Sha256 => Oid::from_sha256(&Sha256::digest(data)),
+        }
+    }
+
     /// Return the raw-bytes of sha1 hash.
     pub fn as_sha1(&self) -> Option<&[u8]> {
         match self {
             Oid::Sha1 { hash } => Some(hash),
+            Oid::Sha256 { .. } => None,
+        }
+    }
+
+    /// Return the raw-bytes of sha256 hash.
+    pub fn as_sha256(&self) -> Option<&[u8]> {
+        match self {
+            Oid::Sha1 { .. } => None,
+            Oid::Sha256 { hash } => Some(hash),
+        }
+    }
+
+    /// Return the raw-bytes of the hash.
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Oid::Sha1 { hash } => hash,
+            Oid::Sha256 { hash } => hash,
+        }
+    }
+
+    /// Return the digest algorithm used to compute this Oid.
+    pub fn to_alg(&self) -> DigestAlg {
+        match self {
+            Oid::Sha1 { .. } => DigestAlg::Sha1,
+            Oid::Sha256 { .. } => DigestAlg::Sha256,
         }
     }
 }
@@ -65,6 +127,65 @@ pub struct Edge {
 }
 
 impl Object {
+    /// Compute object's Oid using the digest algorithm `alg`. Digest is computed
+    /// over a git style header, `<type> <length>\0`, followed by the payload.
+    ///
+    /// * For blobs, payload is the blob's content.
+    /// * For trees, payload is the list of edges, each encoded as
+    ///   `<octal-mode> <name>\0<raw-oid>`.
+    /// * For commits, payload is the canonical cbor encoding of its tree's oid,
+    ///   parent oids, author and committer, refer [util::into_cbor_canonical].
+    ///
+    /// With [DigestAlg::Sha1], blobs and trees are addressed the same as git.
+    /// Commits are not, since they don't carry commit message.
+    pub fn oid_with(&self, alg: DigestAlg) -> Result<Oid> {
+        let (kind, payload) = match self {
+            Object::Blob { value, .. } => ("blob", value.to_vec()),
+            Object::Tree { edges, .. } => {
+                let mut payload = vec![];
+                for edge in edges.iter() {
+                    let mode = format!("{:o} {}\0", edge.file_mode, edge.name);
+                    payload.extend_from_slice(mode.as_bytes());
+                    payload.extend_from_slice(edge.obj_hash.as_bytes());
+                }
+                ("tree", payload)
+            }
+            Object::Commit { tree, parents, author, committer, .. } => {
+                let content = CommitContent {
+                    tree: tree.as_oid().as_bytes().to_vec(),
+                    parents: parents.iter().map(|p| p.as_bytes().to_vec()).collect(),
+                    author_name: author.name.clone(),
+                    author_email: author.email.clone(),
+                    author_timestamp: author.timestamp,
+                    committer_name: committer.name.clone(),
+                    committer_email: committer.email.clone(),
+                    committer_timestamp: committer.timestamp,
+                };
+                ("commit", util::into_cbor_canonical(content)?)
+            }
+            Object::Oid { hash } if hash.to_alg() == alg => return Ok(hash.clone()),
+            Object::Oid { .. } => {
+                err_at!(InvalidInput, msg: "can't compute {:?} oid for reference", alg)?
+            }
+        };
+
+        let mut data = format!("{} {}\0", kind, payload.len()).into_bytes();
+        data.extend_from_slice(&payload);
+
+        Ok(Oid::from_data(alg, &data))
+    }
+
+    /// Recompute object's Oid, using the same digest algorithm, and check it
+    /// with object's address.
+    pub fn verify(&self) -> Result<()> {
+        let hash = self.as_oid();
+        let oid = self.oid_with(hash.to_alg())?;
+        if &oid != hash {
+            err_at!(InvalidInput, msg: "oid mismatch {:?} != {:?}", oid, hash)?
+        }
+        Ok(())
+    }
+
     /// Return object's Oid, its hash-digest.
     pub fn as_oid(&self) -> &Oid {
         match self {
@@ -119,6 +240,23 @@ impl Object {
     }
 }
 
+// content of a commit object, that is hashed to compute its Oid.
+#[derive(Cborize)]
+struct CommitContent {
+    tree: Vec<u8>,
+    parents: Vec<Vec<u8>>,
+    author_name: String,
+    author_email: String,
+    author_timestamp: u64,
+    committer_name: String,
+    committer_email: String,
+    committer_timestamp: u64,
+}
+
+impl CommitContent {
+    const ID: u32 = COMMIT_CONTENT_VER;
+}
+
 /// Type define user-detail needed to create a commit object.
 #[derive(Clone)]
 pub struct User {
@@ -180,3 +318,7 @@ where
         write!(f, "Entry<{:?}>", self.key)
     }
 }
+
+#[cfg(test)]
+#[path = "entry_test.rs"]
+mod entry_test;
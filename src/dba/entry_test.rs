@@ -0,0 +1,84 @@
+use super::*;
+
+#[test]
+fn test_dba_oid_blob() {
+    let value = b"hello world\n".to_vec();
+    let git_oid = git2::Oid::hash_object(git2::ObjectType::Blob, &value).unwrap();
+    assert_eq!(git_oid.to_string(), "3b18e512dba79e4c8300dd08aeb37f8e728b8dad");
+
+    let obj = Object::Blob { hash: Oid::from_sha1(git_oid.as_bytes()), value };
+    obj.verify().unwrap();
+
+    let sha1 = obj.oid_with(DigestAlg::default()).unwrap();
+    assert_eq!(sha1.to_alg(), DigestAlg::Sha1);
+    assert_eq!(sha1.as_sha1(), Some(git_oid.as_bytes()));
+    assert_eq!(&sha1, obj.as_oid());
+
+    let sha256 = obj.oid_with(DigestAlg::Sha256).unwrap();
+    assert_eq!(sha256.to_alg(), DigestAlg::Sha256);
+    assert_eq!(sha256.as_bytes().len(), 32);
+    assert_eq!(sha256.as_sha1(), None);
+    assert_eq!(sha256, obj.oid_with(DigestAlg::Sha256).unwrap());
+
+    let obj = Object::Blob {
+        hash: sha256.clone(),
+        value: b"hello world\n".to_vec(),
+    };
+    obj.verify().unwrap();
+
+    // tampered content fails verification, with either digest.
+    for hash in [sha1, sha256].iter() {
+        let obj = Object::Blob { hash: hash.clone(), value: b"hello world".to_vec() };
+        assert!(obj.verify().is_err());
+    }
+}
+
+#[test]
+fn test_dba_oid_tree_commit() {
+    let tree = Object::Tree { hash: Oid::from_sha1(&[0; 20]), edges: vec![] };
+    let oid = tree.oid_with(DigestAlg::Sha1).unwrap();
+    assert_eq!(oid.as_bytes(), git2::Oid::from_str(EMPTY_TREE).unwrap().as_bytes());
+
+    let blob = Object::Blob {
+        hash: Oid::from_sha1(&[0; 20]),
+        value: b"rdms".to_vec(),
+    };
+    let user = User {
+        name: "rdms".to_string(),
+        email: "rdms@localhost".to_string(),
+        timestamp: 1_600_000_000,
+    };
+
+    for alg in [DigestAlg::Sha1, DigestAlg::Sha256].iter() {
+        let edges = vec![Edge {
+            file_mode: 0o100644,
+            obj_type: Type::Blob,
+            obj_hash: blob.oid_with(*alg).unwrap(),
+            name: "README".to_string(),
+        }];
+        let tree = Object::Tree { hash: Oid::from_sha1(&[0; 20]), edges };
+        let tree = Object::Tree {
+            hash: tree.oid_with(*alg).unwrap(),
+            edges: tree.iter_edges().unwrap().cloned().collect(),
+        };
+        tree.verify().unwrap();
+
+        let commit = Object::Commit {
+            hash: Oid::from_sha1(&[0; 20]),
+            tree: Box::new(tree.clone()),
+            parents: vec![],
+            author: user.clone(),
+            committer: user.clone(),
+        };
+        let oid = commit.oid_with(*alg).unwrap();
+        assert_eq!(oid.to_alg(), *alg);
+        assert_eq!(oid, commit.oid_with(*alg).unwrap());
+        assert_ne!(oid.as_bytes(), tree.as_oid().as_bytes());
+
+        let refr = Object::Oid { hash: oid.clone() };
+        assert_eq!(refr.oid_with(*alg).unwrap(), oid);
+        refr.verify().unwrap();
+    }
+}
+
+const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
@@ -67,12 +67,19 @@ impl<'a> TryFrom<git2::Object<'a>> for dba::Object {
     type Error = Error;
 
     fn try_from(val: git2::Object) -> Result<dba::Object> {
-        match val.kind() {
-            Some(git2::ObjectType::Blob) => Ok(val.into_blob().unwrap().into()),
-            Some(git2::ObjectType::Tree) => val.into_tree().unwrap().try_into(),
-            Some(git2::ObjectType::Commit) => val.into_commit().unwrap().try_into(),
-            _ => err_at!(FailGitapi, msg: "invalid object type"),
-        }
+        // commit objects are not addressed the same as git, hence only blobs and
+        // trees are verified.
+        let obj: dba::Object = match val.kind() {
+            Some(git2::ObjectType::Blob) => val.into_blob().unwrap().into(),
+            Some(git2::ObjectType::Tree) => val.into_tree().unwrap().try_into()?,
+            Some(git2::ObjectType::Commit) => {
+                return val.into_commit().unwrap().try_into()
+            }
+            _ => err_at!(FailGitapi, msg: "invalid object type")?,
+        };
+        obj.verify()?;
+
+        Ok(obj)
     }
 }
 
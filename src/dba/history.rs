@@ -0,0 +1,89 @@
+//! Module implement commit-history traversal over [dba::Object].
+//!
+//! Commit objects refer to their parents by [Oid], hence walking the history
+//! needs a way to fetch an object given its Oid, typically a lookup into the
+//! DBA store. Refer to [walk_history].
+
+use std::collections::{HashSet, VecDeque};
+
+use crate::{
+    dba::{self, Oid},
+    Error, Result,
+};
+
+/// Walk the commit-history starting from commit `start`, following parent
+/// edges breadth-first, that is, commits are yielded in the order of their
+/// distance from `start`. `fetch` shall return the object for an Oid, say,
+/// by looking up the DBA store. Each commit is yielded only once, even if it
+/// is reachable via more than one path, and cyclic parent edges, from a
+/// corrupted store, don't loop forever.
+///
+/// Iteration stops after the first error, say, if `fetch` fails or the
+/// fetched object is not a commit.
+pub fn walk_history<F>(start: Oid, fetch: F) -> History<F>
+where
+    F: FnMut(&Oid) -> Result<dba::Object>,
+{
+    let mut visited = HashSet::new();
+    visited.insert(start.clone());
+
+    let mut queue = VecDeque::new();
+    queue.push_back(start);
+
+    History { fetch, queue, visited }
+}
+
+/// Iterator over commit-history, refer to [walk_history].
+pub struct History<F>
+where
+    F: FnMut(&Oid) -> Result<dba::Object>,
+{
+    fetch: F,
+    queue: VecDeque<Oid>,
+    visited: HashSet<Oid>,
+}
+
+impl<F> History<F>
+where
+    F: FnMut(&Oid) -> Result<dba::Object>,
+{
+    fn fetch_commit(&mut self, hash: &Oid) -> Result<dba::Object> {
+        let obj = (self.fetch)(hash)?;
+        if obj.as_oid() != hash {
+            err_at!(InvalidInput, msg: "fetched {:?} for {:?}", obj.as_oid(), hash)?
+        }
+        match obj.iter_parents() {
+            Some(parents) => {
+                for parent in parents {
+                    if self.visited.insert(parent.clone()) {
+                        self.queue.push_back(parent.clone());
+                    }
+                }
+                Ok(obj)
+            }
+            None => err_at!(InvalidInput, msg: "object {:?} is not a commit", hash),
+        }
+    }
+}
+
+impl<F> Iterator for History<F>
+where
+    F: FnMut(&Oid) -> Result<dba::Object>,
+{
+    type Item = Result<dba::Object>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let hash = self.queue.pop_front()?;
+        match self.fetch_commit(&hash) {
+            Ok(obj) => Some(Ok(obj)),
+            Err(err) => {
+                self.queue.clear();
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "history_test.rs"]
+mod history_test;
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use super::*;
+
+fn new_commit(parents: Vec<Oid>, timestamp: u64) -> dba::Object {
+    let tree = dba::Object::Tree { hash: Oid::from_sha1(&[0; 20]), edges: vec![] };
+    let tree = dba::Object::Tree {
+        hash: tree.oid_with(dba::DigestAlg::Sha1).unwrap(),
+        edges: vec![],
+    };
+    let user = dba::User {
+        name: "rdms".to_string(),
+        email: "rdms@localhost".to_string(),
+        timestamp,
+    };
+    let commit = dba::Object::Commit {
+        hash: Oid::from_sha1(&[0; 20]),
+        tree: Box::new(tree.clone()),
+        parents: parents.clone(),
+        author: user.clone(),
+        committer: user.clone(),
+    };
+    dba::Object::Commit {
+        hash: commit.oid_with(dba::DigestAlg::Sha1).unwrap(),
+        tree: Box::new(tree),
+        parents,
+        author: user.clone(),
+        committer: user,
+    }
+}
+
+#[test]
+fn test_dba_walk_history() {
+    // c0 <- c1 <- c2 <- c4
+    //          \       /
+    //           <- c3 <
+    let c0 = new_commit(vec![], 1_600_000_000);
+    let c1 = new_commit(vec![c0.as_oid().clone()], 1_600_000_001);
+    let c2 = new_commit(vec![c1.as_oid().clone()], 1_600_000_002);
+    let c3 = new_commit(vec![c1.as_oid().clone()], 1_600_000_003);
+    let c4 = new_commit(vec![c2.as_oid().clone(), c3.as_oid().clone()], 1_600_000_004);
+
+    let mut store: HashMap<Oid, dba::Object> = HashMap::new();
+    for obj in [&c0, &c1, &c2, &c3, &c4].iter() {
+        store.insert(obj.as_oid().clone(), (*obj).clone());
+    }
+    let fetch = |hash: &Oid| match store.get(hash) {
+        Some(obj) => Ok(obj.clone()),
+        None => err_at!(InvalidInput, msg: "missing object {:?}", hash),
+    };
+
+    let hashes: Vec<Oid> = walk_history(c4.as_oid().clone(), fetch)
+        .map(|obj| obj.unwrap().as_oid().clone())
+        .collect();
+    let refs: Vec<Oid> =
+        [&c4, &c2, &c3, &c1, &c0].iter().map(|obj| obj.as_oid().clone()).collect();
+    assert_eq!(hashes, refs);
+
+    let hashes: Vec<Oid> = walk_history(c1.as_oid().clone(), fetch)
+        .map(|obj| obj.unwrap().as_oid().clone())
+        .collect();
+    assert_eq!(hashes, vec![c1.as_oid().clone(), c0.as_oid().clone()]);
+
+    // missing parent stops the walk with an error.
+    store.remove(c1.as_oid());
+    let fetch = |hash: &Oid| match store.get(hash) {
+        Some(obj) => Ok(obj.clone()),
+        None => err_at!(InvalidInput, msg: "missing object {:?}", hash),
+    };
+    let items: Vec<Result<dba::Object>> =
+        walk_history(c2.as_oid().clone(), fetch).collect();
+    assert_eq!(items.len(), 2);
+    assert!(items[0].is_ok());
+    assert!(items[1].is_err());
+}
+
+#[test]
+fn test_dba_walk_history_cycle() {
+    // corrupted store, where commits are not content addressed and refer to
+    // each other as parents.
+    let (a, b) = (Oid::from_sha1(&[1; 20]), Oid::from_sha1(&[2; 20]));
+    let mut store: HashMap<Oid, dba::Object> = HashMap::new();
+    for (hash, parent) in [(&a, &b), (&b, &a)].iter() {
+        let mut commit = new_commit(vec![(*parent).clone()], 1_600_000_000);
+        if let dba::Object::Commit { hash: h, .. } = &mut commit {
+            *h = (*hash).clone();
+        }
+        store.insert((*hash).clone(), commit);
+    }
+    let blob = dba::Object::Blob { hash: Oid::from_sha1(&[3; 20]), value: vec![] };
+    store.insert(blob.as_oid().clone(), blob.clone());
+
+    let fetch = |hash: &Oid| match store.get(hash) {
+        Some(obj) => Ok(obj.clone()),
+        None => err_at!(InvalidInput, msg: "missing object {:?}", hash),
+    };
+
+    let hashes: Vec<Oid> = walk_history(a.clone(), fetch)
+        .map(|obj| obj.unwrap().as_oid().clone())
+        .collect();
+    assert_eq!(hashes, vec![a.clone(), b.clone()]);
+
+    // history can only be walked over commits.
+    let mut iter = walk_history(blob.as_oid().clone(), fetch);
+    assert!(iter.next().unwrap().is_err());
+    assert!(iter.next().is_none());
+}
@@ -20,8 +20,13 @@
 //! _**`Oid` a.k.a Object-id**_
 //!
 //! Refer to [Oid] enumeration type. It is typically a hash digest value generated on
-//! the object's content.
+//! the object's content. Digest algorithm is selectable, refer to [DigestAlg], and
+//! defaults to SHA1 for compatibility with git.
 //!
+//! _**History**_
+//!
+//! Commit objects refer to their parent commits by Oid, use [walk_history] to
+//! traverse the commit-history, `git log` style, starting from a commit.
 //!
 //! _**`AsKey`**_
 //!
@@ -39,9 +44,11 @@ use crate::Result;
 
 mod entry;
 mod git;
+mod history;
 mod types;
 
-pub use entry::{Edge, Entry, Object, Oid, Type, User};
+pub use entry::{DigestAlg, Edge, Entry, Object, Oid, Type, User};
+pub use history::{walk_history, History};
 pub use types::Str;
 
 /// AsKey trait can be implemented by any type, that can then be used as key to
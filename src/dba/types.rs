@@ -1,6 +1,11 @@
-//! Module implement [dba::AsKey] trait for [String], [path::Path] [path::PathBuf].
+//! Module implement [dba::AsKey] trait for [str], [String], [path::Path],
+//! [path::PathBuf], `Vec<String>` and tuples of upto 4 keys.
 //!
 //! And custom types that are handy to use as DBA store keys. `Str`.
+//!
+//! String keys are split on `/`, where empty components, from leading, trailing
+//! or repeated separators, are ignored. That is, `"/a//b/"` and `"a/b"` map to
+//! the same key path `["a", "b"]`.
 
 use std::path;
 
@@ -56,12 +61,69 @@ impl dba::AsKey for Str {
     }
 }
 
+impl dba::AsKey for str {
+    fn to_key_path(&self) -> Result<Vec<String>> {
+        let parts = self.split('/').filter(|c| !c.is_empty());
+        Ok(parts.map(ToString::to_string).collect())
+    }
+}
+
 impl dba::AsKey for String {
     fn to_key_path(&self) -> Result<Vec<String>> {
-        Ok(self.split('/').map(ToString::to_string).collect())
+        self.as_str().to_key_path()
+    }
+}
+
+impl<'a, T> dba::AsKey for &'a T
+where
+    T: dba::AsKey + ?Sized,
+{
+    fn to_key_path(&self) -> Result<Vec<String>> {
+        (**self).to_key_path()
+    }
+}
+
+/// Each item is a path component, empty items are ignored. Items containing
+/// the `/` separator are invalid.
+impl dba::AsKey for Vec<String> {
+    fn to_key_path(&self) -> Result<Vec<String>> {
+        let mut items = vec![];
+        for c in self.iter().filter(|c| !c.is_empty()) {
+            match c.contains('/') {
+                true => err_at!(InvalidInput, msg: "key component {:?} is invalid", c)?,
+                false => items.push(c.to_string()),
+            }
+        }
+        Ok(items)
     }
 }
 
+macro_rules! impl_as_key_tuples {
+    ($(($($name:ident: $type:ident),+)),*) => (
+        $(
+            /// Key path for tuple is the concatenation of its items' key path.
+            impl<$($type),+> dba::AsKey for ($($type,)+)
+            where
+                $($type: dba::AsKey),+
+            {
+                fn to_key_path(&self) -> Result<Vec<String>> {
+                    let ($($name,)+) = self;
+                    let mut items = vec![];
+                    $(items.extend($name.to_key_path()?);)+
+                    Ok(items)
+                }
+            }
+        )*
+    );
+}
+
+impl_as_key_tuples![
+    (a: A),
+    (a: A, b: B),
+    (a: A, b: B, c: C),
+    (a: A, b: B, c: C, d: D)
+];
+
 impl dba::AsKey for path::Path {
     fn to_key_path(&self) -> Result<Vec<String>> {
         let mut items = vec![];
@@ -93,3 +155,7 @@ impl dba::AsKey for path::PathBuf {
         Ok(items)
     }
 }
+
+#[cfg(test)]
+#[path = "types_test.rs"]
+mod types_test;
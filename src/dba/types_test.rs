@@ -0,0 +1,78 @@
+use super::*;
+
+use crate::dba::AsKey;
+
+fn to_strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_dba_as_key_str() {
+    let testcases = [
+        ("", vec![]),
+        ("/", vec![]),
+        ("a", vec!["a"]),
+        ("a/b/c", vec!["a", "b", "c"]),
+        ("/a/b/c", vec!["a", "b", "c"]),
+        ("a/b/c/", vec!["a", "b", "c"]),
+        ("//a//b///c//", vec!["a", "b", "c"]),
+    ];
+
+    for (key, refp) in testcases.iter() {
+        let refp = to_strings(refp);
+        assert_eq!(key.to_key_path().unwrap(), refp, "{:?}", key);
+        assert_eq!((*key).to_key_path().unwrap(), refp, "{:?}", key);
+        assert_eq!(key.to_string().to_key_path().unwrap(), refp, "{:?}", key);
+    }
+}
+
+#[test]
+fn test_dba_as_key_str_type() {
+    let key = Str::from("hello-world");
+    assert_eq!(key.to_key_path().unwrap(), to_strings(&["h", "e", "l", "hello-world"]));
+
+    let key = Str::from(("hello-world", 4));
+    let refp = to_strings(&["h", "e", "l", "l", "hello-world"]);
+    assert_eq!(key.to_key_path().unwrap(), refp);
+
+    assert!(Str::from("").to_key_path().unwrap().is_empty());
+}
+
+#[test]
+fn test_dba_as_key_path() {
+    let key = path::PathBuf::from("a/b/c");
+    assert_eq!(key.to_key_path().unwrap(), to_strings(&["a", "b", "c"]));
+    assert_eq!(key.as_path().to_key_path().unwrap(), to_strings(&["a", "b", "c"]));
+
+    assert!(path::PathBuf::from("/a/b").to_key_path().is_err());
+    assert!(path::PathBuf::from("a/../b").to_key_path().is_err());
+}
+
+#[test]
+fn test_dba_as_key_vec() {
+    let key = to_strings(&["a", "", "b c", "d"]);
+    assert_eq!(key.to_key_path().unwrap(), to_strings(&["a", "b c", "d"]));
+
+    let key: Vec<String> = vec![];
+    assert!(key.to_key_path().unwrap().is_empty());
+
+    let key = to_strings(&["a", "b/c"]);
+    assert!(key.to_key_path().is_err());
+}
+
+#[test]
+fn test_dba_as_key_tuple() {
+    assert_eq!(("a/b",).to_key_path().unwrap(), to_strings(&["a", "b"]));
+
+    let key = ("users", "/alice/".to_string());
+    assert_eq!(key.to_key_path().unwrap(), to_strings(&["users", "alice"]));
+
+    let key = ("a", "b/c", to_strings(&["d"]));
+    assert_eq!(key.to_key_path().unwrap(), to_strings(&["a", "b", "c", "d"]));
+
+    let key = ("a", "", "b", path::PathBuf::from("c/d"));
+    assert_eq!(key.to_key_path().unwrap(), to_strings(&["a", "b", "c", "d"]));
+
+    let key = ("a", to_strings(&["b/c"]));
+    assert!(key.to_key_path().is_err());
+}
@@ -0,0 +1,116 @@
+use cbordata::{self as cbor, Cbor, FromCbor, IntoCbor};
+
+use std::{convert::TryFrom, ops::Deref, sync::Arc};
+
+use crate::{dbs, Error, Result};
+
+/// Binary value type, backed by reference counted byte-slice. Unlike [Vec<u8>]
+/// and [dbs::Binary] cloning a Bytes value is cheap, clones share the same
+/// underlying bytes.
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct Bytes(Arc<[u8]>);
+
+impl Default for Bytes {
+    fn default() -> Bytes {
+        Bytes(Arc::from(Vec::default()))
+    }
+}
+
+impl From<Vec<u8>> for Bytes {
+    fn from(val: Vec<u8>) -> Bytes {
+        Bytes(Arc::from(val))
+    }
+}
+
+impl From<&[u8]> for Bytes {
+    fn from(val: &[u8]) -> Bytes {
+        Bytes(Arc::from(val))
+    }
+}
+
+impl From<Bytes> for Vec<u8> {
+    fn from(val: Bytes) -> Vec<u8> {
+        val.0.to_vec()
+    }
+}
+
+impl Deref for Bytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for Bytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Bytes {
+    /// Return true if both values share the same underlying bytes.
+    pub fn ptr_eq(&self, other: &Bytes) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl ToString for Bytes {
+    fn to_string(&self) -> String {
+        String::from_utf8_lossy(&self.0).to_string()
+    }
+}
+
+impl IntoCbor for Bytes {
+    fn into_cbor(self) -> cbor::Result<Cbor> {
+        self.0.to_vec().into_cbor()
+    }
+}
+
+impl FromCbor for Bytes {
+    fn from_cbor(val: Cbor) -> cbor::Result<Bytes> {
+        Ok(Bytes::from(Vec::<u8>::from_cbor(val)?))
+    }
+}
+
+impl dbs::Diff for Bytes {
+    type Delta = Self;
+
+    fn diff(&self, old: &Self) -> Self::Delta {
+        old.clone()
+    }
+
+    fn merge(&self, delta: &Self::Delta) -> Self {
+        delta.clone()
+    }
+}
+
+impl dbs::Footprint for Bytes {
+    fn footprint(&self) -> Result<isize> {
+        use std::mem::size_of;
+        let size = size_of::<Bytes>() + self.0.len();
+        err_at!(FailConvert, isize::try_from(size))
+    }
+}
+
+impl<'a> arbitrary::Arbitrary<'a> for Bytes {
+    fn arbitrary(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        let size = u.arbitrary::<usize>()? % 1024;
+        let val = match u.arbitrary::<u64>()? {
+            0 => Bytes::default(),
+            val => format!("{:0width$}", val, width = size).as_bytes().into(),
+        };
+        Ok(val)
+    }
+}
+
+impl rand::distributions::Distribution<Bytes> for rand::distributions::Standard {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Bytes {
+        let (val, size) = (rng.gen::<u64>(), rng.gen::<usize>() % 1024);
+        format!("{:0width$}", val, width = size).as_bytes().into()
+    }
+}
+
+#[cfg(test)]
+#[path = "bytes_test.rs"]
+mod bytes_test;
@@ -0,0 +1,66 @@
+use rand::{prelude::random, rngs::StdRng, Rng, SeedableRng};
+
+use std::mem::size_of;
+
+use super::*;
+use crate::{dbs::Footprint, util};
+
+#[test]
+fn test_bytes_footprint() {
+    let seed: u64 = random();
+    println!("test_bytes_footprint seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    assert_eq!(Bytes::default().footprint().unwrap() as usize, size_of::<Bytes>());
+
+    for _i in 0..1000 {
+        let val: Vec<u8> = (0..(rng.gen::<usize>() % 4096)).map(|_| rng.gen()).collect();
+        let n = val.len();
+        let bytes = Bytes::from(val);
+        assert_eq!(bytes.footprint().unwrap() as usize, size_of::<Bytes>() + n);
+    }
+}
+
+#[test]
+fn test_bytes_clone() {
+    let val = Bytes::from(b"hello world".to_vec());
+    let clone = val.clone();
+
+    assert!(val.ptr_eq(&clone));
+    assert_eq!(val.as_ptr(), clone.as_ptr());
+    assert_eq!(val, clone);
+
+    let copy = Bytes::from(val.as_ref());
+    assert!(!val.ptr_eq(&copy));
+    assert_eq!(val, copy);
+}
+
+#[test]
+fn test_bytes_cbor() {
+    let seed: u64 = random();
+    println!("test_bytes_cbor seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for _i in 0..1000 {
+        let val: Bytes = rng.gen();
+        let data = util::into_cbor_bytes(val.clone()).unwrap();
+        let (out, n) = util::from_cbor_bytes::<Bytes>(&data).unwrap();
+        assert_eq!(n, data.len());
+        assert_eq!(out, val);
+    }
+
+    let data = util::into_cbor_bytes(Bytes::default()).unwrap();
+    assert_eq!(util::from_cbor_bytes::<Bytes>(&data).unwrap().0, Bytes::default());
+}
+
+#[test]
+fn test_bytes_diff() {
+    use crate::dbs::Diff;
+
+    let old = Bytes::from(b"old".to_vec());
+    let new = Bytes::from(b"new".to_vec());
+
+    let delta = new.diff(&old);
+    assert!(delta.ptr_eq(&old));
+    assert_eq!(new.merge(&delta), old);
+}
@@ -0,0 +1,89 @@
+use cbordata::{self as cbor, Cbor, FromCbor, IntoCbor};
+
+use std::{convert::TryFrom, ops::Deref};
+
+use crate::{dbs, Error, Result};
+
+/// Numeric value type for counters and gauges. Unlike native integer types,
+/// whose delta is the older value itself, delta for Counter is the arithmetic
+/// difference between two consecutive versions, which keeps version chains
+/// compact. Arithmetic wraps around on overflow.
+#[derive(Clone, Copy, Default, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct Counter(pub i64);
+
+impl From<i64> for Counter {
+    fn from(val: i64) -> Counter {
+        Counter(val)
+    }
+}
+
+impl From<Counter> for i64 {
+    fn from(val: Counter) -> i64 {
+        val.0
+    }
+}
+
+impl Deref for Counter {
+    type Target = i64;
+
+    fn deref(&self) -> &i64 {
+        &self.0
+    }
+}
+
+impl ToString for Counter {
+    fn to_string(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+impl IntoCbor for Counter {
+    fn into_cbor(self) -> cbor::Result<Cbor> {
+        self.0.into_cbor()
+    }
+}
+
+impl FromCbor for Counter {
+    fn from_cbor(val: Cbor) -> cbor::Result<Counter> {
+        Ok(Counter(i64::from_cbor(val)?))
+    }
+}
+
+impl dbs::Diff for Counter {
+    type Delta = Self;
+
+    fn diff(&self, old: &Self) -> Self::Delta {
+        Counter(self.0.wrapping_sub(old.0))
+    }
+
+    fn merge(&self, delta: &Self::Delta) -> Self {
+        Counter(self.0.wrapping_sub(delta.0))
+    }
+
+    fn reverse_merge(&self, delta: &Self::Delta) -> Option<Self> {
+        Some(Counter(self.0.wrapping_add(delta.0)))
+    }
+}
+
+impl dbs::Footprint for Counter {
+    fn footprint(&self) -> Result<isize> {
+        use std::mem::size_of;
+        err_at!(FailConvert, isize::try_from(size_of::<Counter>()))
+    }
+}
+
+impl<'a> arbitrary::Arbitrary<'a> for Counter {
+    fn arbitrary(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(Counter(u.arbitrary::<i64>()?))
+    }
+}
+
+impl rand::distributions::Distribution<Counter> for rand::distributions::Standard {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Counter {
+        Counter(rng.gen::<i64>())
+    }
+}
+
+#[cfg(test)]
+#[path = "counter_test.rs"]
+mod counter_test;
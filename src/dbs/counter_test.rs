@@ -0,0 +1,57 @@
+use rand::{prelude::random, rngs::StdRng, Rng, SeedableRng};
+
+use super::*;
+use crate::dbs::Diff;
+
+#[test]
+fn test_counter_diff() {
+    let ver1 = Counter(10);
+    let ver2 = Counter(13);
+
+    let delta = ver2.diff(&ver1);
+    assert_eq!(delta, Counter(3));
+    assert_eq!(ver2.merge(&delta), ver1);
+    assert_eq!(ver1.reverse_merge(&delta), Some(ver2));
+
+    let (ver1, ver2) = (Counter(i64::MAX), Counter(i64::MIN));
+    let delta = ver2.diff(&ver1);
+    assert_eq!(ver2.merge(&delta), ver1);
+    assert_eq!(ver1.reverse_merge(&delta), Some(ver2));
+}
+
+#[test]
+fn test_counter_chain() {
+    let seed: u64 = random();
+    println!("test_counter_chain seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut versions = vec![Counter(rng.gen::<i32>() as i64)];
+    for _ in 0..1000 {
+        let incr = rng.gen::<i16>() as i64;
+        versions.push(Counter(versions.last().unwrap().0 + incr));
+    }
+
+    // newest version along with deltas, newest to oldest.
+    let latest = *versions.last().unwrap();
+    let deltas: Vec<Counter> =
+        versions.windows(2).rev().map(|w| w[1].diff(&w[0])).collect();
+
+    let mut value = latest;
+    for (i, delta) in deltas.iter().enumerate() {
+        value = value.merge(delta);
+        assert_eq!(value, versions[versions.len() - i - 2], "seed:{} i:{}", seed, i);
+    }
+
+    let mut value = versions[0];
+    for (i, delta) in deltas.iter().rev().enumerate() {
+        value = value.reverse_merge(delta).unwrap();
+        assert_eq!(value, versions[i + 1], "seed:{} i:{}", seed, i);
+    }
+}
+
+#[test]
+fn test_counter_cbor() {
+    let val = Counter(-1234);
+    let cbor_val = val.into_cbor().unwrap();
+    assert_eq!(Counter::from_cbor(cbor_val).unwrap(), val);
+}
@@ -24,7 +24,13 @@ const NDIFF_VER: u32 = 0x00070001;
 /// ```notest
 /// D = C - P (diff operation)
 /// P = C - D (merge operation, to get old value)
+/// C = P + D (reverse_merge operation, to get new value)
 /// ```
+///
+/// Indexes persist the latest value along with deltas of older versions, hence
+/// [Diff::merge] is sufficient to walk backward from the latest version. Tools
+/// that walk forward from the oldest version can use [Diff::reverse_merge], if
+/// the value type's delta carries enough information to do so.
 pub trait Diff: Sized + Clone + From<<Self as Diff>::Delta> {
     type Delta: Clone + From<Self>;
 
@@ -35,6 +41,14 @@ pub trait Diff: Sized + Clone + From<<Self as Diff>::Delta> {
     /// Merge delta with newer version to return older version of the value.
     /// `Old = New - Delta`.
     fn merge(&self, delta: &Self::Delta) -> Self;
+
+    /// Merge delta with older version to return newer version of the value.
+    /// `New = Old + Delta`. Deltas that only preserve the older value cannot
+    /// compute the newer version, for such types, which is the default, return
+    /// None.
+    fn reverse_merge(&self, _delta: &Self::Delta) -> Option<Self> {
+        None
+    }
 }
 
 /// Associated type for value-type that don't implement [Diff] trait, i.e
@@ -313,6 +313,20 @@ where
         }
     }
 
+    /// Return a reference to the latest version of value, without cloning.
+    /// Return None if entry is marked as deleted.
+    ///
+    /// Entries returned by disk indexes, like [robt][crate::robt], are fully
+    /// fetched from value-log and decoded into owned values, the value-log
+    /// read buffer is dropped before the entry is returned. Hence the
+    /// reference is only bound to the lifetime of this entry.
+    pub fn value_ref(&self) -> Option<&V> {
+        match &self.value {
+            Value::U { value, .. } => Some(value),
+            Value::D { .. } => None,
+        }
+    }
+
     /// Return a reference to key
     pub fn as_key(&self) -> &K {
         &self.key
@@ -332,6 +346,19 @@ where
         self.value.is_deleted()
     }
 
+    /// Return the seqno of the newest version, whether it is an update or a
+    /// delete. Same as [Entry::to_seqno].
+    pub fn latest_version_seqno(&self) -> u64 {
+        self.value.to_seqno()
+    }
+
+    /// Return whether entry is marked as deleted and its newest version is
+    /// older than `seqno`, that is, the entry is eligible for
+    /// tombstone-compaction with `Cutoff::Tombstone(Bound::Excluded(seqno))`.
+    pub fn is_tombstone_older_than(&self, seqno: u64) -> bool {
+        self.is_deleted() && self.latest_version_seqno() < seqno
+    }
+
     /// Return a list of all the versions of values, `values[0]` hold the oldest
     /// version `values[n-1]` hold the latest version.
     pub fn to_values(&self) -> Vec<Value<V>>
@@ -362,6 +389,64 @@ where
         values
     }
 
+    /// Return the value of the oldest version, None if the oldest version is
+    /// marked as deleted.
+    pub fn oldest_value(&self) -> Option<V>
+    where
+        D: Clone,
+    {
+        let mut val: Option<V> = self.to_value();
+        for d in self.deltas.iter().rev() {
+            val = match (val, d) {
+                (Some(v), Delta::U { delta, .. }) => Some(v.merge(delta)),
+                (None, Delta::U { delta, .. }) => Some(delta.clone().into()),
+                (_, Delta::D { .. }) => None,
+            };
+        }
+        val
+    }
+
+    /// Same as [Entry::to_values], but versions are reconstructed walking
+    /// forward from the oldest version using [Diff::reverse_merge]. If the
+    /// value type does not support reverse merge, or if a version cannot be
+    /// derived from its older version, like an upsert following a delete,
+    /// fall back to walking backward from the latest version.
+    pub fn to_values_forward(&self) -> Vec<Value<V>>
+    where
+        D: Clone,
+    {
+        let n = self.deltas.len();
+        let mut values = Vec::with_capacity(n + 1);
+        let mut val: Option<V> = self.oldest_value();
+
+        for (i, d) in self.deltas.iter().enumerate() {
+            let seqno = d.to_seqno();
+            values.push(match val.clone() {
+                Some(value) => Value::U { value, seqno },
+                None => Value::D { seqno },
+            });
+
+            let next_deleted = match self.deltas.get(i + 1) {
+                Some(Delta::D { .. }) => true,
+                Some(Delta::U { .. }) => false,
+                None => self.value.is_deleted(),
+            };
+            val = match (val, d, next_deleted) {
+                (_, _, true) => None,
+                (Some(v), Delta::U { delta, .. }, false) => {
+                    match v.reverse_merge(delta) {
+                        Some(v) => Some(v),
+                        None => return self.to_values(),
+                    }
+                }
+                (_, _, false) => return self.to_values(),
+            };
+        }
+        values.push(self.value.clone());
+
+        values
+    }
+
     /// Check whether all version of `other` is present in `self`.
     pub fn contains(&self, other: &Self) -> bool
     where
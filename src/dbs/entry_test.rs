@@ -288,3 +288,125 @@ fn test_entry_compact_tombstone() {
         }
     }
 }
+
+// value type whose delta is the difference between two versions, so that
+// newer versions can be reconstructed from older versions.
+#[derive(Clone, Debug, PartialEq)]
+struct Acc(i64);
+
+#[derive(Clone, Debug, PartialEq)]
+struct AccDelta(i64);
+
+impl From<Acc> for AccDelta {
+    fn from(val: Acc) -> AccDelta {
+        AccDelta(val.0)
+    }
+}
+
+impl From<AccDelta> for Acc {
+    fn from(val: AccDelta) -> Acc {
+        Acc(val.0)
+    }
+}
+
+impl Diff for Acc {
+    type Delta = AccDelta;
+
+    fn diff(&self, old: &Acc) -> AccDelta {
+        AccDelta(self.0 - old.0)
+    }
+
+    fn merge(&self, delta: &AccDelta) -> Acc {
+        Acc(self.0 - delta.0)
+    }
+
+    fn reverse_merge(&self, delta: &AccDelta) -> Option<Acc> {
+        Some(Acc(self.0 + delta.0))
+    }
+}
+
+#[test]
+fn test_entry_reverse_merge() {
+    let seed: u64 = random();
+    println!("test_entry_reverse_merge {}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for _i in 0..100 {
+        let n = rng.gen::<usize>() % 32;
+        let with_deletes = rng.gen::<bool>();
+
+        let value = Acc(rng.gen::<i32>() as i64);
+        let mut entry: Entry<u8, Acc> = Entry::new(10, value.clone(), 1);
+        let mut refvs = vec![Value::U { value, seqno: 1 }];
+        for seqno in 2..(n as u64 + 2) {
+            if with_deletes && rng.gen::<u8>() % 4 == 0 {
+                entry = entry.delete(seqno);
+                refvs.push(Value::D { seqno });
+            } else {
+                let value = Acc(rng.gen::<i32>() as i64);
+                entry = entry.insert(value.clone(), seqno);
+                refvs.push(Value::U { value, seqno });
+            }
+        }
+
+        assert_eq!(entry.oldest_value(), refvs[0].to_value());
+        assert_eq!(entry.to_values(), refvs);
+        assert_eq!(entry.to_values_forward(), refvs);
+
+        // reconstruct each intermediate version from its older version.
+        let mut val = entry.oldest_value();
+        for (i, d) in entry.deltas.iter().enumerate() {
+            match (val.as_ref(), d, &refvs[i + 1]) {
+                (Some(v), Delta::U { delta, .. }, Value::U { value, .. }) => {
+                    assert_eq!(v.reverse_merge(delta).as_ref(), Some(value));
+                }
+                // newer version is a delete.
+                (_, _, Value::D { .. }) => assert!(with_deletes),
+                // upsert following a delete cannot be derived.
+                (None, Delta::D { .. }, Value::U { .. }) => assert!(with_deletes),
+                (v, d, refv) => panic!("{:?} {:?} {:?}", v, d, refv),
+            }
+            val = refvs[i + 1].to_value();
+        }
+    }
+
+    // value types that cannot walk forward fall back to walking backward.
+    let mut entry: Entry<u8, u64> = Entry::new(10, 200, 1);
+    entry = entry.insert(300, 2);
+    entry = entry.delete(3);
+    entry = entry.insert(400, 4);
+    assert_eq!(entry.oldest_value(), Some(200));
+    assert_eq!(200_u64.reverse_merge(&200), None);
+    assert_eq!(entry.to_values_forward(), entry.to_values());
+}
+
+#[test]
+fn test_entry_tombstone_older_than() {
+    // live entry, with older deletes.
+    let live: Entry<u8, u64> = Entry::new(10, 200, 1).delete(2).insert(300, 5);
+    assert_eq!(live.latest_version_seqno(), 5);
+    assert!(!live.is_tombstone_older_than(4));
+    assert!(!live.is_tombstone_older_than(5));
+    assert!(!live.is_tombstone_older_than(100));
+
+    // recently deleted entry.
+    let recent: Entry<u8, u64> = Entry::new(10, 200, 1).insert(300, 2).delete(9);
+    assert_eq!(recent.latest_version_seqno(), 9);
+    assert!(!recent.is_tombstone_older_than(8));
+    assert!(!recent.is_tombstone_older_than(9));
+    assert!(recent.is_tombstone_older_than(10));
+
+    // deleted long ago.
+    let old: Entry<u8, u64> = Entry::new_delete(10, 3);
+    assert_eq!(old.latest_version_seqno(), 3);
+    assert!(!old.is_tombstone_older_than(0));
+    assert!(old.is_tombstone_older_than(4));
+    assert!(old.is_tombstone_older_than(100));
+
+    for entry in [live, recent, old].iter() {
+        let seqno = entry.latest_version_seqno() + 1;
+        let cutoff = Cutoff::Tombstone(Bound::Excluded(seqno));
+        let purged = entry.compact(cutoff).is_none();
+        assert_eq!(purged, entry.is_tombstone_older_than(seqno));
+    }
+}
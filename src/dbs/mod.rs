@@ -5,22 +5,28 @@ use std::{borrow::Borrow, hash::Hash};
 use crate::Result;
 
 // trait-defs: Diff, Footprint, Bloom Replay, WalWriter,
-// type-defs : Cutoff, Delta, NoDiff, Entry, Binary, Value, Wr, Write
+// type-defs : Counter, Cutoff, Delta, NoDiff, Entry, Binary, Bytes, Seqno, Value, Wr, Write
 
 mod binary;
+mod bytes;
 mod compact;
+mod counter;
 mod delta;
 mod diff;
 mod entry;
+mod seqno;
 mod types;
 mod value;
 mod wop;
 
 pub use binary::Binary;
+pub use bytes::Bytes;
 pub use compact::Cutoff;
+pub use counter::Counter;
 pub use delta::Delta;
 pub use diff::{Diff, NoDiff};
 pub use entry::Entry;
+pub use seqno::Seqno;
 pub use value::Value;
 pub use wop::{Wr, Write};
 
@@ -90,7 +96,8 @@ pub trait Bloom: Sized {
 /// Trait define methods to integrate index with Wal (Write-Ahead-Log).
 ///
 /// After writing into the `Wal`, write operation shall be applied on
-/// the `Index`.
+/// the `Index`, along with the [Seqno] assigned by the `Wal`. Seqno can
+/// be supplied either as [Seqno] or as plain `u64`.
 pub trait WalWriter<K, V>
 where
     K: Clone + Ord,
@@ -103,7 +110,7 @@ where
         &mut self,
         key: K,
         value: V,
-        index: u64,
+        seqno: impl Into<Seqno>,
     ) -> Result<Option<Entry<K, V, <V as Diff>::Delta>>>;
 
     /// Set {key, value} in index if an older entry exists with the
@@ -116,7 +123,7 @@ where
         key: K,
         value: V,
         cas: u64,
-        index: u64,
+        seqno: impl Into<Seqno>,
     ) -> Result<Option<Entry<K, V, <V as Diff>::Delta>>>;
 
     /// Delete key from index. Return old entry if present.
@@ -130,7 +137,7 @@ where
     fn delete_index<Q>(
         &mut self,
         key: &Q,
-        index: u64,
+        seqno: impl Into<Seqno>,
     ) -> Result<Option<Entry<K, V, <V as Diff>::Delta>>>
     where
         K: Borrow<Q>,
@@ -147,13 +154,19 @@ where
     V: Clone + Diff,
 {
     /// Replay set operation from wal-file onto index.
-    fn set_index(&mut self, key: K, value: V, index: u64) -> Result<()>;
+    fn set_index(&mut self, key: K, value: V, seqno: impl Into<Seqno>) -> Result<()>;
 
     /// Replay set-cas operation from wal-file onto index.
-    fn set_cas_index(&mut self, key: K, value: V, cas: u64, index: u64) -> Result<()>;
+    fn set_cas_index(
+        &mut self,
+        key: K,
+        value: V,
+        cas: u64,
+        seqno: impl Into<Seqno>,
+    ) -> Result<()>;
 
     /// Replay delete operation from wal-file onto index.
-    fn delete_index(&mut self, key: K, index: u64) -> Result<()>;
+    fn delete_index(&mut self, key: K, seqno: impl Into<Seqno>) -> Result<()>;
 }
 
 /// Trait to serialize an implementing type to JSON encoded string.
@@ -162,6 +175,32 @@ pub trait ToJson {
     fn to_json(&self) -> String;
 }
 
+/// Trait to deserialize an implementing type from JSON encoded string, inverse
+/// of [ToJson].
+pub trait FromJson: Sized {
+    /// Call this method to parse the type from JSON encoded string.
+    fn from_json(text: &str) -> Result<Self>;
+}
+
+/// Trait to scan an index snapshot, optionally partitioned into shards, so
+/// that the snapshot can be committed into another index.
+pub trait CommitIterator<K, V>
+where
+    V: Diff,
+{
+    type Iter: Iterator<Item = Entry<K, V>>;
+
+    /// Scan all entries from a read snapshot of the index. If `versions` is
+    /// true, older versions of each entry are included.
+    fn scan(&self, versions: bool) -> Result<Self::Iter>;
+
+    /// Split a read snapshot of the index into `n_shards` partitions, of
+    /// roughly equal number of entries, and return an iterator for each
+    /// partition in key order. May return fewer than `n_shards` iterators
+    /// when the index is too small to partition.
+    fn scans(&self, n_shards: usize, versions: bool) -> Result<Vec<Self::Iter>>;
+}
+
 // TODO: check whether this can be removed in future.
 // Trait to create new memory based index instances using pre-defined set of
 // configuration. This is needed for multi-level index.
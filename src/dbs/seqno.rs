@@ -0,0 +1,57 @@
+use std::fmt;
+
+/// Sequence number, monotonically increasing number assigned to every
+/// mutation on an index.
+///
+/// Wraps a `u64`, so that seqno are not confused with other `u64` values, like
+/// `cas`. Arithmetic on seqno is saturating, use [From] to convert from and to
+/// `u64`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Seqno(pub u64);
+
+impl From<u64> for Seqno {
+    fn from(seqno: u64) -> Seqno {
+        Seqno(seqno)
+    }
+}
+
+impl From<Seqno> for u64 {
+    fn from(seqno: Seqno) -> u64 {
+        seqno.0
+    }
+}
+
+impl fmt::Display for Seqno {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Seqno {
+    /// Return the next seqno, saturates at `u64::MAX`.
+    #[inline]
+    pub fn next(&self) -> Seqno {
+        Seqno(self.0.saturating_add(1))
+    }
+
+    /// Return seqno incremented by `n`, saturates at `u64::MAX`.
+    #[inline]
+    pub fn saturating_add(&self, n: u64) -> Seqno {
+        Seqno(self.0.saturating_add(n))
+    }
+
+    /// Return seqno decremented by `n`, saturates at ZERO.
+    #[inline]
+    pub fn saturating_sub(&self, n: u64) -> Seqno {
+        Seqno(self.0.saturating_sub(n))
+    }
+
+    #[inline]
+    pub fn to_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+#[path = "seqno_test.rs"]
+mod seqno_test;
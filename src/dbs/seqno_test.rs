@@ -0,0 +1,124 @@
+use rand::{prelude::random, rngs::StdRng, Rng, SeedableRng};
+
+use std::borrow::Borrow;
+
+use super::*;
+use crate::{
+    dbs::{Entry, Replay, WalWriter},
+    Result,
+};
+
+#[test]
+fn test_seqno_ordering() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_seqno_ordering {}", seed);
+
+    for _i in 0..1000 {
+        let (a, b) = (rng.gen::<u64>(), rng.gen::<u64>());
+        assert_eq!(Seqno(a).cmp(&Seqno(b)), a.cmp(&b));
+        assert_eq!(Seqno::from(a) == Seqno::from(b), a == b);
+        assert_eq!(u64::from(Seqno(a)), a);
+        assert_eq!(Seqno(a).to_u64(), a);
+        assert_eq!(Seqno(a).to_string(), a.to_string());
+    }
+
+    let mut seqnos: Vec<Seqno> = (0..100).rev().map(Seqno::from).collect();
+    seqnos.sort();
+    assert!(seqnos.windows(2).all(|w| w[0] < w[1] && w[0].next() == w[1]));
+    assert_eq!(Seqno::default(), Seqno(0));
+}
+
+#[test]
+fn test_seqno_saturating() {
+    let max = Seqno(u64::MAX);
+    assert_eq!(max.next(), max);
+    assert_eq!(max.saturating_add(10), max);
+    assert_eq!(Seqno(u64::MAX - 1).next(), max);
+    assert_eq!(Seqno(10).saturating_add(5), Seqno(15));
+
+    assert_eq!(Seqno(0).saturating_sub(1), Seqno(0));
+    assert_eq!(Seqno(10).saturating_sub(5), Seqno(5));
+}
+
+#[test]
+fn test_seqno_wal_traits() {
+    #[derive(Default)]
+    struct Ops(Vec<(u64, u64)>);
+
+    impl WalWriter<u64, u64> for Ops {
+        fn set_index(
+            &mut self,
+            key: u64,
+            _value: u64,
+            seqno: impl Into<Seqno>,
+        ) -> Result<Option<Entry<u64, u64>>> {
+            self.0.push((key, seqno.into().to_u64()));
+            Ok(None)
+        }
+
+        fn set_cas_index(
+            &mut self,
+            key: u64,
+            _value: u64,
+            _cas: u64,
+            seqno: impl Into<Seqno>,
+        ) -> Result<Option<Entry<u64, u64>>> {
+            self.0.push((key, seqno.into().to_u64()));
+            Ok(None)
+        }
+
+        fn delete_index<Q>(
+            &mut self,
+            key: &Q,
+            seqno: impl Into<Seqno>,
+        ) -> Result<Option<Entry<u64, u64>>>
+        where
+            u64: Borrow<Q>,
+            Q: ToOwned<Owned = u64> + Ord + ?Sized,
+        {
+            self.0.push((key.to_owned(), seqno.into().to_u64()));
+            Ok(None)
+        }
+    }
+
+    impl Replay<u64, u64> for Ops {
+        fn set_index(
+            &mut self,
+            key: u64,
+            _value: u64,
+            seqno: impl Into<Seqno>,
+        ) -> Result<()> {
+            self.0.push((key, seqno.into().to_u64()));
+            Ok(())
+        }
+
+        fn set_cas_index(
+            &mut self,
+            key: u64,
+            _value: u64,
+            _cas: u64,
+            seqno: impl Into<Seqno>,
+        ) -> Result<()> {
+            self.0.push((key, seqno.into().to_u64()));
+            Ok(())
+        }
+
+        fn delete_index(&mut self, key: u64, seqno: impl Into<Seqno>) -> Result<()> {
+            self.0.push((key, seqno.into().to_u64()));
+            Ok(())
+        }
+    }
+
+    // seqno as plain u64 and as Seqno.
+    let mut ops = Ops::default();
+    WalWriter::set_index(&mut ops, 10, 100, 1_u64).unwrap();
+    WalWriter::set_cas_index(&mut ops, 10, 101, 1, Seqno(2)).unwrap();
+    WalWriter::delete_index(&mut ops, &10, 3_u64).unwrap();
+    Replay::set_index(&mut ops, 20, 200, Seqno(4)).unwrap();
+    Replay::set_cas_index(&mut ops, 20, 201, 4, 5_u64).unwrap();
+    Replay::delete_index(&mut ops, 20, 6_u64).unwrap();
+
+    let refs = vec![(10, 1), (10, 2), (10, 3), (20, 4), (20, 5), (20, 6)];
+    assert_eq!(ops.0, refs);
+}
@@ -1,9 +1,10 @@
-//! Implement Diff, Footprint traits for native types and std-types.
+//! Implement Diff, Footprint, ToJson, FromJson, KeyPath traits for native types and
+//! std-types.
 
 use std::convert::TryFrom;
 
 use crate::{
-    dbs::{Diff, Footprint},
+    dbs::{Binary, Bytes, Diff, Footprint, FromJson, KeyPath, ToJson},
     Error, Result,
 };
 
@@ -72,6 +73,147 @@ impl Footprint for String {
     }
 }
 
+macro_rules! impl_to_json_basic_types {
+    ($($type:ty),*) => (
+        $(
+            impl ToJson for $type {
+                fn to_json(&self) -> String {
+                    self.to_string()
+                }
+            }
+        )*
+    );
+}
+
+impl_to_json_basic_types![
+    bool, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+];
+
+impl ToJson for String {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+/// Binary data is not natively JSON, encoded as base64 string.
+impl ToJson for Vec<u8> {
+    fn to_json(&self) -> String {
+        format!("{:?}", base64::encode(self))
+    }
+}
+
+/// Binary data is not natively JSON, encoded as base64 string.
+impl ToJson for Binary {
+    fn to_json(&self) -> String {
+        self.val.to_json()
+    }
+}
+
+/// Binary data is not natively JSON, encoded as base64 string.
+impl ToJson for Bytes {
+    fn to_json(&self) -> String {
+        format!("{:?}", base64::encode(self))
+    }
+}
+
+macro_rules! impl_from_json_basic_types {
+    ($($type:ty),*) => (
+        $(
+            impl FromJson for $type {
+                fn from_json(text: &str) -> Result<Self> {
+                    err_at!(InvalidFormat, text.trim().parse::<$type>(), "{:?}", text)
+                }
+            }
+        )*
+    );
+}
+
+impl_from_json_basic_types![
+    bool, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+];
+
+impl FromJson for String {
+    fn from_json(text: &str) -> Result<Self> {
+        err_at!(InvalidFormat, serde_json::from_str(text))
+    }
+}
+
+impl FromJson for Vec<u8> {
+    fn from_json(text: &str) -> Result<Self> {
+        let text: String = err_at!(InvalidFormat, serde_json::from_str(text))?;
+        err_at!(InvalidFormat, base64::decode(&text))
+    }
+}
+
+impl FromJson for Binary {
+    fn from_json(text: &str) -> Result<Self> {
+        Ok(Binary { val: Vec::<u8>::from_json(text)? })
+    }
+}
+
+impl FromJson for Bytes {
+    fn from_json(text: &str) -> Result<Self> {
+        Ok(Vec::<u8>::from_json(text)?.into())
+    }
+}
+
+/// Split on `/`, empty components from leading, trailing or repeated
+/// separators are ignored.
+impl KeyPath for str {
+    fn key_path(&self) -> Vec<String> {
+        let parts = self.split('/').filter(|c| !c.is_empty());
+        parts.map(ToString::to_string).collect()
+    }
+}
+
+impl KeyPath for String {
+    fn key_path(&self) -> Vec<String> {
+        self.as_str().key_path()
+    }
+}
+
+impl<'a, T> KeyPath for &'a T
+where
+    T: KeyPath + ?Sized,
+{
+    fn key_path(&self) -> Vec<String> {
+        (**self).key_path()
+    }
+}
+
+/// Each item is a path component, empty items are ignored.
+impl KeyPath for Vec<String> {
+    fn key_path(&self) -> Vec<String> {
+        self.iter().filter(|c| !c.is_empty()).cloned().collect()
+    }
+}
+
+macro_rules! impl_key_path_tuples {
+    ($(($($name:ident: $type:ident),+)),*) => (
+        $(
+            /// Key path for tuple is the concatenation of its items' key path.
+            impl<$($type),+> KeyPath for ($($type,)+)
+            where
+                $($type: KeyPath),+
+            {
+                fn key_path(&self) -> Vec<String> {
+                    let ($($name,)+) = self;
+                    let mut items = vec![];
+                    $(items.extend($name.key_path());)+
+                    items
+                }
+            }
+        )*
+    );
+}
+
+impl_key_path_tuples![
+    (a: A),
+    (a: A, b: B),
+    (a: A, b: B, c: C),
+    (a: A, b: B, c: C, d: D)
+];
+
 #[cfg(test)]
 #[path = "types_test.rs"]
 mod types_test;
@@ -61,3 +61,33 @@ test_footprint_basic_types![
     (f64, test_footprint_f64, 8),
     (char, test_footprint_char, 4)
 ];
+
+#[test]
+fn test_key_path() {
+    let to_strings =
+        |items: &[&str]| -> Vec<String> { items.iter().map(|s| s.to_string()).collect() };
+
+    let testcases = [
+        ("", vec![]),
+        ("/", vec![]),
+        ("a", vec!["a"]),
+        ("a/b/c", vec!["a", "b", "c"]),
+        ("/a/b/c", vec!["a", "b", "c"]),
+        ("a/b/c/", vec!["a", "b", "c"]),
+        ("//a//b///c//", vec!["a", "b", "c"]),
+    ];
+    for (key, refp) in testcases.iter() {
+        let refp = to_strings(refp);
+        assert_eq!(key.key_path(), refp, "{:?}", key);
+        assert_eq!(key.to_string().key_path(), refp, "{:?}", key);
+    }
+
+    let key = to_strings(&["a", "", "b/c"]);
+    assert_eq!(key.key_path(), to_strings(&["a", "b/c"]));
+
+    assert_eq!(("a/b",).key_path(), to_strings(&["a", "b"]));
+    let key = ("users", "/alice/".to_string());
+    assert_eq!(key.key_path(), to_strings(&["users", "alice"]));
+    let key = ("a", "", "b/c", to_strings(&["d"]));
+    assert_eq!(key.key_path(), to_strings(&["a", "b", "c", "d"]));
+}
@@ -93,4 +93,7 @@ where
     /// Mutation sequence number for this write-operation.
     pub seqno: u64,
     pub old_entry: Option<dbs::Entry<K, V>>,
+    /// Change in index footprint caused by this write-operation, that is, new
+    /// footprint minus old footprint.
+    pub footprint: isize,
 }
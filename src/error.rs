@@ -5,6 +5,8 @@
 
 use std::{error, fmt, result};
 
+use crate::Result;
+
 /// Error enumerates over all possible errors cases in `rdms` package.
 #[derive(Clone)]
 pub enum Error {
@@ -67,6 +69,11 @@ pub enum Error {
     DiffSizeExceeded(String, String),
     /// Return list of files that needs to be purged.
     PurgeFile(String, String),
+    /// Operation was cancelled by application, like a long running build.
+    Cancelled(String, String),
+    /// Input to an index build has two entries with the same key, in strict
+    /// mode, message carries the key.
+    DuplicateKey(String, String),
 
     #[doc(hidden)]
     // internal error, given key is less than the entire data set.
@@ -131,6 +138,73 @@ macro_rules! err_at {
     }};
 }
 
+/// Extension trait to enrich errors with the location, in file, where the
+/// error happened. Useful to localize corruption while decoding persisted data.
+pub trait ResultExt<T> {
+    /// On error, add file-position `fpos` to the error message.
+    fn at_fpos(self, fpos: u64) -> Result<T>;
+
+    /// On error, add file `name` to the error message.
+    fn at_file<N: fmt::Debug>(self, name: N) -> Result<T>;
+}
+
+impl<T> ResultExt<T> for Result<T> {
+    fn at_fpos(self, fpos: u64) -> Result<T> {
+        self.map_err(|err| err.with_context(format!("at fpos:{}", fpos)))
+    }
+
+    fn at_file<N: fmt::Debug>(self, name: N) -> Result<T> {
+        self.map_err(|err| err.with_context(format!("in file:{:?}", name)))
+    }
+}
+
+macro_rules! with_context {
+    ($err:expr, $ctx:expr, $($v:ident),+) => {
+        match $err {
+            $(Error::$v(p, m) => Error::$v(p, format!("{} {}", m, $ctx)),)+
+        }
+    };
+}
+
+impl Error {
+    fn with_context(self, ctx: String) -> Error {
+        with_context!(
+            self,
+            ctx,
+            Fatal,
+            NotImplemented,
+            UnInitialized,
+            TimeFail,
+            IPCFail,
+            ThreadFail,
+            SystemFail,
+            Timeout,
+            InvalidInput,
+            InvalidFormat,
+            APIMisuse,
+            FailCbor,
+            InvalidFile,
+            FailConvert,
+            IOError,
+            FailGitapi,
+            NotFound,
+            EmptyIndex,
+            InvalidCAS,
+            KeySizeExceeded,
+            ValueSizeExceeded,
+            DiffSizeExceeded,
+            PurgeFile,
+            Cancelled,
+            DuplicateKey,
+            __LessThan,
+            __ZBlockOverflow,
+            __MBlockOverflow,
+            __MBlockExhausted,
+            __ZBlockExhausted
+        )
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
         match self {
@@ -156,6 +230,8 @@ impl fmt::Display for Error {
             Error::ValueSizeExceeded(p, m) => write!(f, "{} ValueSizeExceeded:{}", p, m),
             Error::DiffSizeExceeded(p, m) => write!(f, "{} DiffSizeExceeded:{}", p, m),
             Error::PurgeFile(p, m) => write!(f, "{} PurgeFile:{}", p, m),
+            Error::Cancelled(p, m) => write!(f, "{} Cancelled:{}", p, m),
+            Error::DuplicateKey(p, m) => write!(f, "{} DuplicateKey:{}", p, m),
             Error::FailCbor(p, m) => write!(f, "{} FailCbor:{}", p, m),
             Error::__LessThan(p, m) => write!(f, "{} __LessThan:{}", p, m),
             Error::__ZBlockOverflow(p, m) => write!(f, "{} __ZBlockOverflow:{}", p, m),
@@ -1,6 +1,12 @@
 use cbordata::FromCbor;
 
-use std::{borrow::Borrow, convert::TryFrom, fmt, ops::RangeBounds};
+use std::{
+    borrow::Borrow,
+    collections::BTreeMap,
+    convert::TryFrom,
+    fmt,
+    ops::{Bound, RangeBounds},
+};
 
 use crate::{bitmaps::NoBitmap, dbs, llrb, robt, Error, Result};
 
@@ -316,7 +322,7 @@ where
     {
         match self {
             Index::Llrb { store } => store.get(key),
-            Index::Robt { store } => store.get(key),
+            Index::Robt { store } => store.get_unhashed(key, false),
         }
     }
 
@@ -327,7 +333,24 @@ where
     {
         match self {
             Index::Llrb { store } => store.get_versions(key),
-            Index::Robt { store } => store.get_versions(key),
+            Index::Robt { store } => store.get_unhashed(key, true),
+        }
+    }
+
+    /// Return whether `key` is present in the index, including deleted keys
+    /// whose tombstone is present. Disk index avoids reading the value-log.
+    pub fn contains<Q: ?Sized>(&mut self, key: &Q) -> Result<bool>
+    where
+        K: Borrow<Q>,
+        Q: Ord,
+    {
+        match self {
+            Index::Llrb { store } => match store.get(key) {
+                Ok(_) => Ok(true),
+                Err(Error::NotFound(_, _)) => Ok(false),
+                Err(err) => Err(err),
+            },
+            Index::Robt { store } => store.contains_unhashed(key),
         }
     }
 
@@ -456,3 +479,161 @@ where
     Llrb { iter: llrb::Reverse<K, V, R, Q> },
     Robt { iter: robt::Iter<'a, K, V> },
 }
+
+impl<'a, K, V> Iterator for Iter<'a, K, V>
+where
+    K: Clone + Ord + FromCbor,
+    V: dbs::Diff + FromCbor,
+    <V as dbs::Diff>::Delta: FromCbor,
+{
+    type Item = Result<dbs::Entry<K, V>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Iter::Llrb { iter } => iter.next().map(Ok),
+            Iter::Robt { iter } => iter.next(),
+        }
+    }
+}
+
+impl<'a, K, V, R, Q> Iterator for Range<'a, K, V, R, Q>
+where
+    K: Clone + Ord + Borrow<Q> + FromCbor,
+    V: dbs::Diff + FromCbor,
+    <V as dbs::Diff>::Delta: FromCbor,
+    R: RangeBounds<Q>,
+    Q: ?Sized + Ord,
+{
+    type Item = Result<dbs::Entry<K, V>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Range::Llrb { iter } => iter.next().map(Ok),
+            Range::Robt { iter } => iter.next(),
+        }
+    }
+}
+
+impl<'a, K, V, R, Q> Iterator for Reverse<'a, K, V, R, Q>
+where
+    K: Clone + Ord + Borrow<Q> + FromCbor,
+    V: dbs::Diff + FromCbor,
+    <V as dbs::Diff>::Delta: FromCbor,
+    R: RangeBounds<Q>,
+    Q: ?Sized + Ord,
+{
+    type Item = Result<dbs::Entry<K, V>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Reverse::Llrb { iter } => iter.next().map(Ok),
+            Reverse::Robt { iter } => iter.next(),
+        }
+    }
+}
+
+/// Check that `index` agrees with the `reference` map, a shared utility to
+/// test index implementations. Every key in `reference` shall be returned by
+/// `get` with the same value, and `iter`, `range` and `reverse` shall yield
+/// the same (key, value) pairs as `reference`, in the same order. Entries
+/// marked as deleted in `index` are skipped, as they are absent in
+/// `reference`. Ranges are derived from the keys in `reference`.
+///
+/// Return [Error::Fatal] describing the first mismatch.
+pub fn validate_against<K, V, B>(
+    index: &mut Index<K, V, B>,
+    reference: &BTreeMap<K, V>,
+) -> Result<()>
+where
+    K: Clone + Ord + fmt::Debug + FromCbor,
+    V: dbs::Diff + FromCbor + PartialEq + fmt::Debug,
+    <V as dbs::Diff>::Delta: FromCbor,
+    B: dbs::Bloom,
+{
+    for (key, value) in reference.iter() {
+        let entry = match index.get(key) {
+            Ok(entry) => entry,
+            Err(Error::NotFound(_, _)) => {
+                err_at!(Fatal, msg: "validate_against, get {:?} missing", key)?
+            }
+            Err(err) => return Err(err),
+        };
+        match entry.value_ref() {
+            Some(val) if val == value => (),
+            val => err_at!(
+                Fatal,
+                msg: "validate_against, get {:?} {:?} != {:?}", key, val, value
+            )?,
+        }
+    }
+
+    validate_iter("iter", index.iter()?, reference.iter())?;
+
+    let keys: Vec<&K> = reference.keys().collect();
+    let (lo, hi) = match keys.len() {
+        0 => return Ok(()),
+        n => (keys[n / 4].clone(), keys[(n * 3) / 4].clone()),
+    };
+    let ranges = vec![
+        (Bound::Included(lo.clone()), Bound::Excluded(hi.clone())),
+        (Bound::Excluded(lo.clone()), Bound::Included(hi.clone())),
+        (Bound::Unbounded, Bound::Included(lo)),
+        (Bound::Excluded(hi), Bound::Unbounded),
+    ];
+    for r in ranges.into_iter() {
+        let name = format!("range {:?}", r);
+        validate_iter(&name, index.range(r.clone())?, reference.range(r.clone()))?;
+        let name = format!("reverse {:?}", r);
+        validate_iter(&name, index.reverse(r.clone())?, reference.range(r).rev())?;
+    }
+
+    Ok(())
+}
+
+fn validate_iter<'a, K, V, I, R>(name: &str, iter: I, mut refs: R) -> Result<()>
+where
+    K: 'a + PartialEq + fmt::Debug,
+    V: 'a + dbs::Diff + PartialEq + fmt::Debug,
+    I: Iterator<Item = Result<dbs::Entry<K, V>>>,
+    R: Iterator<Item = (&'a K, &'a V)>,
+{
+    let mut n = 0;
+    for entry in iter {
+        let entry = entry?;
+        if entry.is_deleted() {
+            continue;
+        }
+        match refs.next() {
+            Some((key, value)) if entry.as_key() == key => match entry.value_ref() {
+                Some(val) if val == value => (),
+                val => err_at!(
+                    Fatal,
+                    msg: "validate_against, {} at {} key {:?} {:?} != {:?}",
+                    name, n, key, val, value
+                )?,
+            },
+            Some((key, _)) => err_at!(
+                Fatal,
+                msg: "validate_against, {} at {} key {:?} != {:?}",
+                name, n, entry.as_key(), key
+            )?,
+            None => err_at!(
+                Fatal,
+                msg: "validate_against, {} at {} unexpected key {:?}",
+                name, n, entry.as_key()
+            )?,
+        }
+        n += 1;
+    }
+
+    match refs.next() {
+        Some((key, _)) => {
+            err_at!(Fatal, msg: "validate_against, {} at {} missing key {:?}", name, n, key)
+        }
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+#[path = "index_test.rs"]
+mod index_test;
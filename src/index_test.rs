@@ -0,0 +1,66 @@
+use rand::{prelude::random, rngs::StdRng, Rng, SeedableRng};
+
+use super::*;
+
+#[test]
+fn test_validate_against() {
+    let seed: u64 = random();
+    println!("test_validate_against seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let store = llrb::Index::<u16, u64>::new("test_validate_against", false);
+    let mut reference: BTreeMap<u16, u64> = BTreeMap::new();
+    for _ in 0..100_000 {
+        let key: u16 = rng.gen();
+        match rng.gen::<u8>() % 4 {
+            0 => {
+                store.delete(&key).unwrap();
+                reference.remove(&key);
+            }
+            _ => {
+                let value: u64 = rng.gen();
+                store.set(key, value).unwrap();
+                reference.insert(key, value);
+            }
+        }
+    }
+    println!("test_validate_against keys:{}", reference.len());
+
+    let mut index: Index<u16, u64> = Index::from_llrb(store.clone());
+    validate_against(&mut index, &reference).unwrap();
+
+    // same validation against a disk index built from the llrb.
+    let dir = std::env::temp_dir().join("test_validate_against");
+    std::fs::remove_dir_all(&dir).ok();
+    let config = robt::Config::new(dir.as_os_str(), "validate-against");
+    let mut build = robt::Builder::<u16, u64>::initial(config, vec![]).unwrap();
+    let iter = store.iter_versions().unwrap().map(Ok);
+    let disk = build.build_index(iter, NoBitmap, None).unwrap();
+    let mut disk: Index<u16, u64> = Index::from_robt(disk);
+    validate_against(&mut disk, &reference).unwrap();
+    disk.purge().unwrap();
+
+    // mismatches are reported.
+    let key = *reference.keys().nth(rng.gen::<usize>() % reference.len()).unwrap();
+    let mut refs = reference.clone();
+    refs.insert(key, refs[&key].wrapping_add(1));
+    match validate_against(&mut index, &refs) {
+        Err(Error::Fatal(_, msg)) => println!("test_validate_against {}", msg),
+        res => panic!("unexpected {:?}", res),
+    }
+
+    let mut refs = reference.clone();
+    refs.remove(&key);
+    match validate_against(&mut index, &refs) {
+        Err(Error::Fatal(_, msg)) => println!("test_validate_against {}", msg),
+        res => panic!("unexpected {:?}", res),
+    }
+
+    let missing = (0..=u16::MAX).find(|k| !reference.contains_key(k)).unwrap();
+    let mut refs = reference;
+    refs.insert(missing, 0);
+    match validate_against(&mut index, &refs) {
+        Err(Error::Fatal(_, msg)) => println!("test_validate_against {}", msg),
+        res => panic!("unexpected {:?}", res),
+    }
+}
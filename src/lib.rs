@@ -43,6 +43,7 @@
 //! And there _will_ be situations where a full table scan is required,
 //! while allowing background read/write operations. Piece-wise scanning
 //! can help in those situations, provided the index is configured for LSM.
+//! Refer to [scans::PacedScan] for pacing such scans.
 //!
 //! **Compaction**
 //!
@@ -108,14 +109,15 @@ pub mod git;
 pub mod hash;
 mod index;
 pub mod parsec;
+pub mod scans;
 // pub mod web; TODO: incomplete
 pub mod mq;
 pub mod trie;
 pub mod wral;
 pub mod zimf;
 
-pub use error::Error;
-pub use index::Index;
+pub use error::{Error, ResultExt};
+pub use index::{validate_against, Index};
 
 // core modules
 // pub mod core;
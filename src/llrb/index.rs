@@ -32,9 +32,14 @@
 use std::{
     borrow::Borrow,
     cmp::{self, Ordering},
+    convert::TryFrom,
     fmt, marker,
     ops::{Bound, RangeBounds},
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering::SeqCst},
+        Arc, Mutex,
+    },
+    time,
 };
 
 use crate::{
@@ -76,6 +81,12 @@ where
 
     mu: Arc<Mutex<u32>>,
     inner: Arc<Spinlock<Arc<Inner<K, V>>>>,
+
+    // active reader snapshots, maintained lock-free.
+    n_snapshots: Arc<AtomicUsize>,
+    n_writes: Arc<AtomicUsize>,
+    // maximum time, in nanoseconds, writer waited on readers.
+    max_wait: Arc<AtomicU64>,
 }
 
 impl<K, V> Index<K, V>
@@ -98,6 +109,10 @@ where
 
             mu: Arc::new(Mutex::new(0)),
             inner: Arc::new(Spinlock::new(Arc::new(inner))),
+
+            n_snapshots: Arc::new(AtomicUsize::new(0)),
+            n_writes: Arc::new(AtomicUsize::new(0)),
+            max_wait: Arc::new(AtomicU64::new(0)),
         }
     }
 
@@ -182,6 +197,9 @@ where
         stats.n_deleted = inner.n_deleted;
         stats.tree_footprint = inner.tree_footprint;
         stats.spin_stats = self.inner.as_ref().to_stats()?;
+        stats.n_snapshots = self.n_snapshots.load(SeqCst);
+        stats.n_writes = self.n_writes.load(SeqCst);
+        stats.max_writer_wait = time::Duration::from_nanos(self.max_wait.load(SeqCst));
         // blacks and depths are available only from validate call.
         Ok(stats)
     }
@@ -204,12 +222,13 @@ where
         let _w = self.mu.lock();
 
         let inner = Arc::clone(&self.inner.read());
+        let fp = inner.tree_footprint;
         let op = (key, value, None, None);
         let (inner, old_entry) = inner.set(op)?.into_root();
-        let seqno = inner.seqno;
-        *self.inner.write() = Arc::new(inner);
+        let (seqno, footprint) = (inner.seqno, inner.tree_footprint - fp);
+        self.publish(inner);
 
-        Ok(dbs::Wr { seqno, old_entry })
+        Ok(dbs::Wr { seqno, old_entry, footprint })
     }
 
     /// Set `key`, `value` into index. If already an entry in present with
@@ -219,12 +238,13 @@ where
         let _w = self.mu.lock();
 
         let inner = Arc::clone(&self.inner.read());
+        let fp = inner.tree_footprint;
         let op = (key, value, Some(cas), None);
         let (inner, old_entry) = inner.set(op)?.into_root();
-        let seqno = inner.seqno;
-        *self.inner.write() = Arc::new(inner);
+        let (seqno, footprint) = (inner.seqno, inner.tree_footprint - fp);
+        self.publish(inner);
 
-        Ok(dbs::Wr { seqno, old_entry })
+        Ok(dbs::Wr { seqno, old_entry, footprint })
     }
 
     /// Insert `key`, `value` into index. Non destructive version of
@@ -234,12 +254,13 @@ where
         let _w = self.mu.lock();
 
         let inner = Arc::clone(&self.inner.read());
+        let fp = inner.tree_footprint;
         let op = (key, value, None, None);
         let (inner, old_entry) = inner.insert(op)?.into_root();
-        let seqno = inner.seqno;
-        *self.inner.write() = Arc::new(inner);
+        let (seqno, footprint) = (inner.seqno, inner.tree_footprint - fp);
+        self.publish(inner);
 
-        Ok(dbs::Wr { seqno, old_entry })
+        Ok(dbs::Wr { seqno, old_entry, footprint })
     }
 
     /// Insert `key`, `value` into index. Non destructive version of
@@ -251,12 +272,13 @@ where
         let _w = self.mu.lock();
 
         let inner = Arc::clone(&self.inner.read());
+        let fp = inner.tree_footprint;
         let op = (key, value, Some(cas), None);
         let (inner, old_entry) = inner.insert(op)?.into_root();
-        let seqno = inner.seqno;
-        *self.inner.write() = Arc::new(inner);
+        let (seqno, footprint) = (inner.seqno, inner.tree_footprint - fp);
+        self.publish(inner);
 
-        Ok(dbs::Wr { seqno, old_entry })
+        Ok(dbs::Wr { seqno, old_entry, footprint })
     }
 
     /// Remove the entry, matching the key, from the index.
@@ -268,12 +290,13 @@ where
         let _w = self.mu.lock();
 
         let inner = Arc::clone(&self.inner.read());
+        let fp = inner.tree_footprint;
         let op = (key, None, None);
         let (inner, old_entry) = inner.remove(op)?.into_root();
-        let seqno = inner.seqno;
-        *self.inner.write() = Arc::new(inner);
+        let (seqno, footprint) = (inner.seqno, inner.tree_footprint - fp);
+        self.publish(inner);
 
-        Ok(dbs::Wr { seqno, old_entry })
+        Ok(dbs::Wr { seqno, old_entry, footprint })
     }
 
     /// Remove the entry, with matching key and matching entry's
@@ -286,12 +309,13 @@ where
         let _w = self.mu.lock();
 
         let inner = Arc::clone(&self.inner.read());
+        let fp = inner.tree_footprint;
         let op = (key, Some(cas), None);
         let (inner, old_entry) = inner.remove(op)?.into_root();
-        let seqno = inner.seqno;
-        *self.inner.write() = Arc::new(inner);
+        let (seqno, footprint) = (inner.seqno, inner.tree_footprint - fp);
+        self.publish(inner);
 
-        Ok(dbs::Wr { seqno, old_entry })
+        Ok(dbs::Wr { seqno, old_entry, footprint })
     }
 
     /// Non destructive version of remove method. Mark entry as deleted.
@@ -303,15 +327,20 @@ where
         let _w = self.mu.lock();
 
         let inner = Arc::clone(&self.inner.read());
+        let fp = inner.tree_footprint;
         let op = (key, None, None);
         let (inner, old_entry) = inner.delete(op)?.into_root();
-        let seqno = inner.seqno;
-        *self.inner.write() = Arc::new(inner);
+        let (seqno, footprint) = (inner.seqno, inner.tree_footprint - fp);
+        self.publish(inner);
 
-        Ok(dbs::Wr { seqno, old_entry })
+        Ok(dbs::Wr { seqno, old_entry, footprint })
     }
 
-    /// Non destructive version of remove method. Mark entry as deleted.
+    /// Compare-and-delete, non destructive version of remove_cas method. Mark
+    /// entry as deleted only if entry's sequence-number matches `cas`. If
+    /// index don't have an entry with `key`, `cas` must be ZERO. On mismatch,
+    /// index is left untouched and [Error::InvalidCAS] is returned, else the
+    /// deleted entry is returned as `old_entry`.
     pub fn delete_cas<Q>(&self, key: &Q, cas: u64) -> Result<dbs::Wr<K, V>>
     where
         K: Borrow<Q>,
@@ -320,12 +349,13 @@ where
         let _w = self.mu.lock();
 
         let inner = Arc::clone(&self.inner.read());
+        let fp = inner.tree_footprint;
         let op = (key, Some(cas), None);
         let (inner, old_entry) = inner.delete(op)?.into_root();
-        let seqno = inner.seqno;
-        *self.inner.write() = Arc::new(inner);
+        let (seqno, footprint) = (inner.seqno, inner.tree_footprint - fp);
+        self.publish(inner);
 
-        Ok(dbs::Wr { seqno, old_entry })
+        Ok(dbs::Wr { seqno, old_entry, footprint })
     }
 
     /// Apply op on top of this index. For more detail refer to [dbs::Write] type.
@@ -333,6 +363,7 @@ where
         let _w = self.mu.lock();
 
         let inner = Arc::clone(&self.inner.read());
+        let fp = inner.tree_footprint;
         let (inner, old_entry) = match op {
             dbs::Write::Set { key, value, cas, seqno } => {
                 inner.set((key, value, cas, seqno))?.into_root()
@@ -348,10 +379,10 @@ where
             }
         };
 
-        let seqno = inner.seqno;
-        *self.inner.write() = Arc::new(inner);
+        let (seqno, footprint) = (inner.seqno, inner.tree_footprint - fp);
+        self.publish(inner);
 
-        Ok(dbs::Wr { seqno, old_entry })
+        Ok(dbs::Wr { seqno, old_entry, footprint })
     }
 
     /// Commit a latest batch of mutations into this snapshot, there by creating a
@@ -362,7 +393,10 @@ where
         K: PartialEq,
         I: Iterator<Item = dbs::Entry<K, V>>,
     {
+        let start = time::Instant::now();
         let mut inner = self.inner.write();
+        self.account_write(start);
+
         let (new_inner, n) = {
             let (ir, n) = inner.commit(iter, versions)?;
             let (new_inner, _) = ir.into_root();
@@ -372,6 +406,35 @@ where
 
         Ok(n)
     }
+
+    /// Purge versions older than `seqno` from every entry in this index, the
+    /// latest version of an entry is retained even if it is older than
+    /// `seqno`. Return the number of versions purged, footprint reclaimed is
+    /// accounted in [Index::footprint]. Refer to `lsm-compaction` in package
+    /// documentation.
+    pub fn purge_older(&self, seqno: u64) -> Result<usize> {
+        let _w = self.mu.lock();
+
+        let inner = Arc::clone(&self.inner.read());
+        let (inner, n) = inner.purge_older(seqno)?;
+        self.publish(inner);
+
+        Ok(n)
+    }
+
+    // publish a new snapshot for readers.
+    fn publish(&self, inner: Inner<K, V>) {
+        let start = time::Instant::now();
+        let mut w = self.inner.write();
+        self.account_write(start);
+        *w = Arc::new(inner);
+    }
+
+    fn account_write(&self, start: time::Instant) {
+        let wait = u64::try_from(start.elapsed().as_nanos()).unwrap_or(u64::MAX);
+        self.max_wait.fetch_max(wait, SeqCst);
+        self.n_writes.fetch_add(1, SeqCst);
+    }
 }
 
 impl<K, V> Index<K, V>
@@ -386,6 +449,7 @@ where
         K: Clone + Borrow<Q>,
         Q: Ord + ?Sized,
     {
+        let _snap = SnapGuard::new(&self.n_snapshots);
         let inner = Arc::clone(&self.inner.read());
         inner.get(key, false /*versions*/)
     }
@@ -397,6 +461,7 @@ where
         K: Clone + Borrow<Q>,
         Q: Ord + ?Sized,
     {
+        let _snap = SnapGuard::new(&self.n_snapshots);
         let inner = Arc::clone(&self.inner.read());
         inner.get(key, true /*versions*/)
     }
@@ -404,13 +469,17 @@ where
     /// For full table scan, without older versions.
     pub fn iter(&self) -> Result<Iter<K, V>> {
         let inner = Arc::clone(&self.inner.read());
-        Ok(inner.iter(false /*versions*/))
+        let mut iter = inner.iter(false /*versions*/);
+        iter.snap = Some(SnapGuard::new(&self.n_snapshots));
+        Ok(iter)
     }
 
     /// For full table scan, with older versions.
     pub fn iter_versions(&self) -> Result<Iter<K, V>> {
         let inner = Arc::clone(&self.inner.read());
-        Ok(inner.iter(true /*versions*/))
+        let mut iter = inner.iter(true /*versions*/);
+        iter.snap = Some(SnapGuard::new(&self.n_snapshots));
+        Ok(iter)
     }
 
     /// Iterate over entries within the specifed `range`, without older versions.
@@ -421,7 +490,9 @@ where
         Q: ?Sized + Ord,
     {
         let inner = Arc::clone(&self.inner.read());
-        Ok(inner.range(range, false /*versions*/))
+        let mut iter = inner.range(range, false /*versions*/);
+        iter.iter.snap = Some(SnapGuard::new(&self.n_snapshots));
+        Ok(iter)
     }
 
     /// Iterate over entries within the specifed `range`, with older versions.
@@ -432,7 +503,9 @@ where
         Q: ?Sized + Ord,
     {
         let inner = Arc::clone(&self.inner.read());
-        Ok(inner.range(range, true /*versions*/))
+        let mut iter = inner.range(range, true /*versions*/);
+        iter.iter.snap = Some(SnapGuard::new(&self.n_snapshots));
+        Ok(iter)
     }
 
     /// Reverse iterate over entries withing specified `range`, without older
@@ -445,7 +518,9 @@ where
         Q: Ord + ?Sized,
     {
         let inner = Arc::clone(&self.inner.read());
-        Ok(inner.reverse(range, false /*versions*/))
+        let mut iter = inner.reverse(range, false /*versions*/);
+        iter.iter.snap = Some(SnapGuard::new(&self.n_snapshots));
+        Ok(iter)
     }
 
     /// Reverse iterate over entries withing specified `range`, with older versions.
@@ -458,7 +533,9 @@ where
         Q: Ord + ?Sized,
     {
         let inner = Arc::clone(&self.inner.read());
-        Ok(inner.reverse(range, true /*versions*/))
+        let mut iter = inner.reverse(range, true /*versions*/);
+        iter.iter.snap = Some(SnapGuard::new(&self.n_snapshots));
+        Ok(iter)
     }
 
     /// Validate Index tree with following rules:
@@ -478,6 +555,40 @@ where
     }
 }
 
+impl<K, V> dbs::CommitIterator<K, V> for Index<K, V>
+where
+    K: Clone + Ord,
+    V: dbs::Diff,
+{
+    type Iter = Range<K, V, (Bound<K>, Bound<K>), K>;
+
+    fn scan(&self, versions: bool) -> Result<Self::Iter> {
+        let inner = Arc::clone(&self.inner.read());
+        let mut iter = inner.range((Bound::Unbounded, Bound::Unbounded), versions);
+        iter.iter.snap = Some(SnapGuard::new(&self.n_snapshots));
+        Ok(iter)
+    }
+
+    fn scans(&self, n_shards: usize, versions: bool) -> Result<Vec<Self::Iter>> {
+        let inner = Arc::clone(&self.inner.read());
+
+        let mut iters = vec![];
+        let mut low = Bound::Unbounded;
+        for key in inner.split_keys(n_shards).into_iter() {
+            let range = (low, Bound::Excluded(key.clone()));
+            let mut iter = inner.range(range, versions);
+            iter.iter.snap = Some(SnapGuard::new(&self.n_snapshots));
+            iters.push(iter);
+            low = Bound::Included(key);
+        }
+        let mut iter = inner.range((low, Bound::Unbounded), versions);
+        iter.iter.snap = Some(SnapGuard::new(&self.n_snapshots));
+        iters.push(iter);
+
+        Ok(iters)
+    }
+}
+
 #[derive(Clone)]
 struct Inner<K, V>
 where
@@ -666,6 +777,21 @@ where
         Ok(Ir::Root { inner, old })
     }
 
+    fn purge_older(&self, seqno: u64) -> Result<(Inner<K, V>, usize)> {
+        let (root, n, footprint) = purge_older(self.root.as_ref(), seqno)?;
+
+        let inner = Inner {
+            root,
+            seqno: self.seqno,
+
+            n_count: self.n_count,
+            n_deleted: self.n_deleted,
+            tree_footprint: self.tree_footprint + footprint,
+        };
+
+        Ok((inner, n))
+    }
+
     fn commit<I>(&self, iter: I, versions: bool) -> Result<(Ir<K, V>, usize)>
     where
         K: PartialEq,
@@ -763,7 +889,7 @@ where
                 let (_, value, _, seqno) = op;
                 let (oldfp, old) = (node.footprint()?, node.entry.clone());
                 node.set(value, seqno);
-                let footprint = oldfp - node.footprint()?;
+                let footprint = node.footprint()? - oldfp;
                 (node, Some(old), footprint)
             }
             Ordering::Equal => {
@@ -810,7 +936,7 @@ where
                 let (_, value, _, seqno) = op;
                 let (oldfp, old) = (node.footprint()?, node.entry.clone());
                 node.insert(value, seqno);
-                let footprint = oldfp - node.footprint()?;
+                let footprint = node.footprint()? - oldfp;
                 (node, Some(old), footprint)
             }
             Ordering::Equal => {
@@ -867,7 +993,7 @@ where
                 }
 
                 if !node.as_key().borrow().lt(key) && node.right.is_none() {
-                    (None, Some(node.entry.clone()), node.footprint()?)
+                    (None, Some(node.entry.clone()), -node.footprint()?)
                 } else {
                     node = match node.as_right_ref() {
                         r @ Some(_)
@@ -880,7 +1006,7 @@ where
 
                     if !node.as_key().borrow().lt(key) {
                         let [right, sub_node] = self.do_remove_min(node.as_right_ref());
-                        let footprint = node.footprint()?;
+                        let footprint = -node.footprint()?;
                         node.right = right.map(Arc::new);
                         let mut sub_node = match sub_node {
                             Some(sub_node) => sub_node,
@@ -946,7 +1072,7 @@ where
             Ordering::Equal if cas_ok => {
                 let (oldfp, old) = (node.footprint()?, node.entry.clone());
                 node.delete(seqno);
-                let footprint = oldfp - node.footprint()?;
+                let footprint = node.footprint()? - oldfp;
                 (walkuprot_23(node), Some(old), footprint)
             }
             Ordering::Equal => {
@@ -1015,7 +1141,7 @@ where
             Ordering::Equal if versions => {
                 let (oldfp, old) = (node.footprint()?, node.entry.clone());
                 node.commit(entry)?;
-                let footprint = oldfp - node.footprint()?;
+                let footprint = node.footprint()? - oldfp;
                 (node, Some(old), footprint)
             }
             Ordering::Equal => {
@@ -1024,7 +1150,7 @@ where
                     dbs::Value::U { value, seqno } => node.set(value, seqno),
                     dbs::Value::D { seqno } => node.delete(seqno),
                 }
-                let footprint = oldfp - node.footprint()?;
+                let footprint = node.footprint()? - oldfp;
                 (node, Some(old), footprint)
             }
         };
@@ -1052,7 +1178,7 @@ where
         let mut paths = Vec::default();
         build_iter(IFlag::Left, root, &mut paths);
 
-        Iter { paths, frwrd: true, versions }
+        Iter { paths, frwrd: true, versions, snap: None }
     }
 
     fn range<R, Q>(&self, range: R, versions: bool) -> Range<K, V, R, Q>
@@ -1069,7 +1195,7 @@ where
             Bound::Included(low) => find_start(root, low, true, &mut paths),
             Bound::Excluded(low) => find_start(root, low, false, &mut paths),
         };
-        let iter = Iter { paths, frwrd: true, versions };
+        let iter = Iter { paths, frwrd: true, versions, snap: None };
 
         Range { range, iter, fin: false, high: marker::PhantomData }
     }
@@ -1088,7 +1214,7 @@ where
             Bound::Included(high) => find_end(root, high, true, &mut paths),
             Bound::Excluded(high) => find_end(root, high, false, &mut paths),
         };
-        let iter = Iter { paths, frwrd: false, versions };
+        let iter = Iter { paths, frwrd: false, versions, snap: None };
 
         Reverse { range, iter, fin: false, low: marker::PhantomData }
     }
@@ -1115,6 +1241,33 @@ where
 
         Ok(())
     }
+
+    // pick upto `n-1` keys that split the tree into `n` partitions. Since the
+    // tree is balanced, nodes from the top few levels are spread evenly across
+    // the key space, walk level by level until there are enough of them.
+    fn split_keys(&self, n: usize) -> Vec<K>
+    where
+        K: Clone,
+    {
+        let n = cmp::max(n, 1);
+        let root = self.root.as_ref().map(Borrow::borrow);
+
+        let mut keys = vec![];
+        for depth in 1..=MAX_TREE_DEPTH {
+            let mut level_keys = vec![];
+            collect_keys(root, depth, &mut level_keys);
+            let done = level_keys.len() == keys.len();
+            keys = level_keys;
+            if done || keys.len() >= (n - 1) {
+                break;
+            }
+        }
+
+        match keys.len() {
+            m if m < n => keys,
+            m => (1..n).map(|i| keys[((i * (m + 1)) / n) - 1].clone()).collect(),
+        }
+    }
 }
 
 #[inline]
@@ -1317,6 +1470,61 @@ where
     }
 }
 
+// purge versions older than `seqno` from every node under `node`, sub-trees
+// that are not affected are shared with the older snapshot. Return the new
+// sub-tree, number of versions purged and the change in footprint.
+#[allow(clippy::type_complexity)]
+fn purge_older<K, V>(
+    node: Option<&Arc<Node<K, V>>>,
+    seqno: u64,
+) -> Result<(Option<Arc<Node<K, V>>>, usize, isize)>
+where
+    K: Clone + dbs::Footprint,
+    V: dbs::Diff + dbs::Footprint,
+    <V as dbs::Diff>::Delta: dbs::Footprint,
+{
+    let node = match node {
+        Some(node) => node,
+        None => return Ok((None, 0, 0)),
+    };
+
+    let (left, n_left, fp_left) = purge_older(node.left.as_ref(), seqno)?;
+    let (right, n_right, fp_right) = purge_older(node.right.as_ref(), seqno)?;
+    let n = node.entry.deltas.iter().filter(|d| d.to_seqno() < seqno).count();
+
+    if (n + n_left + n_right) == 0 {
+        return Ok((Some(Arc::clone(node)), 0, 0));
+    }
+
+    let mut node = node.as_ref().clone();
+    node.left = left;
+    node.right = right;
+
+    let mut footprint = fp_left + fp_right;
+    if n > 0 {
+        let oldfp = node.footprint()?;
+        let mut entry = node.entry.as_ref().clone();
+        entry.deltas.retain(|d| d.to_seqno() >= seqno);
+        node.entry = Arc::new(entry);
+        footprint += node.footprint()? - oldfp;
+    }
+
+    Ok((Some(Arc::new(node)), n + n_left + n_right, footprint))
+}
+
+// in-order walk collecting keys from nodes upto `depth` levels from `node`.
+fn collect_keys<K, V>(node: Option<&Node<K, V>>, depth: usize, keys: &mut Vec<K>)
+where
+    K: Clone,
+    V: dbs::Diff,
+{
+    if let (Some(node), true) = (node, depth > 0) {
+        collect_keys(node.left.as_deref(), depth - 1, keys);
+        keys.push(node.entry.as_key().clone());
+        collect_keys(node.right.as_deref(), depth - 1, keys);
+    }
+}
+
 fn validate_tree<K, V>(
     node: Option<&Node<K, V>>,
     fromred: bool,
@@ -1373,6 +1581,25 @@ where
     Ok((lb, n_deleted, lc + rc + 1))
 }
 
+// Guard type, to count active reader snapshots, count is decremented when
+// the guard is dropped.
+struct SnapGuard {
+    n_snapshots: Arc<AtomicUsize>,
+}
+
+impl SnapGuard {
+    fn new(n_snapshots: &Arc<AtomicUsize>) -> SnapGuard {
+        n_snapshots.fetch_add(1, SeqCst);
+        SnapGuard { n_snapshots: Arc::clone(n_snapshots) }
+    }
+}
+
+impl Drop for SnapGuard {
+    fn drop(&mut self) {
+        self.n_snapshots.fetch_sub(1, SeqCst);
+    }
+}
+
 // Iterator type, to do full table scan.
 //
 // A full table scan using this type is optimal when used with concurrent
@@ -1384,6 +1611,7 @@ where
     paths: Vec<Fragment<K, V>>,
     frwrd: bool,
     versions: bool,
+    snap: Option<SnapGuard>,
 }
 
 impl<K, V> Iterator for Iter<K, V>
@@ -5,6 +5,7 @@ use std::{
     collections::BTreeMap,
     convert::TryFrom,
     ops::{Add, Bound, Div, Mul, Rem, Sub},
+    sync::Barrier,
     thread,
 };
 
@@ -15,7 +16,7 @@ use super::*;
 // +set +set_cas +insert +insert_cas +delete +delete_cas +remove +remove_cas
 // +commit +set_seqno write
 // +get +iter +range +reverse +validate
-// footprint +deleted_count is_empty +is_spin +len to_name +to_seqno to_stats
+// +footprint +deleted_count is_empty +is_spin +len to_name +to_seqno to_stats
 
 #[test]
 fn test_llrb() {
@@ -87,6 +88,262 @@ fn test_llrb_commit() {
     test_commit_with_key::<u64>("test_commit_u64", seed, 1_000_000);
 }
 
+#[test]
+fn test_llrb_snapshot_stats() {
+    let seed: u64 = random();
+    println!("test_llrb_snapshot_stats seed:{}", seed);
+
+    let n_readers = 16;
+    // (sets, ins, rems, dels)
+    let load_ops = (1000, 1000, 100, 100);
+    let index = random_load_index("test_snapshot", seed, 0, u64::MAX, load_ops);
+
+    let (n_writes, n) = {
+        let stats = index.to_stats().unwrap();
+        assert_eq!(stats.n_snapshots, 0);
+        assert!(stats.n_writes > 0);
+        (stats.n_writes, index.len())
+    };
+
+    let acquired = Arc::new(Barrier::new(n_readers + 1));
+    let release = Arc::new(Barrier::new(n_readers + 1));
+    let mut handles = vec![];
+    for _ in 0..n_readers {
+        let (index, acquired, release) =
+            (index.clone(), Arc::clone(&acquired), Arc::clone(&release));
+        handles.push(thread::spawn(move || {
+            let iter = index.iter().unwrap();
+            acquired.wait();
+            release.wait();
+            iter.count()
+        }));
+    }
+
+    acquired.wait();
+    assert_eq!(index.to_stats().unwrap().n_snapshots, n_readers);
+    // writers shall not be blocked by active snapshots.
+    index.set(u64::MAX, 0).unwrap();
+    release.wait();
+
+    for handle in handles.into_iter() {
+        assert_eq!(handle.join().unwrap(), n);
+    }
+
+    let stats = index.to_stats().unwrap();
+    assert_eq!(stats.n_snapshots, 0);
+    assert_eq!(stats.n_writes, n_writes + 1);
+
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_llrb_footprint_delta() {
+    use std::mem::size_of;
+
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_llrb_footprint_delta seed:{}", seed);
+
+    let index: Index<u64, u64> = Index::new("test_footprint_delta", false);
+    let mut footprint: isize = 0;
+    for _i in 0..10_000 {
+        let (key, value) = (rng.gen::<u64>() % 1000, rng.gen::<u64>());
+        let wr = match rng.gen::<u8>() % 4 {
+            0 => index.set(key, value).unwrap(),
+            1 => index.insert(key, value).unwrap(),
+            2 => index.delete(&key).unwrap(),
+            _ => index.remove(&key).unwrap(),
+        };
+        footprint += wr.footprint;
+    }
+    assert_eq!(footprint, index.footprint().unwrap());
+
+    let overhead = size_of::<Node<u64, u64>>() as isize;
+    let full: isize =
+        index.iter_versions().unwrap().map(|e| overhead + e.footprint().unwrap()).sum();
+    assert_eq!(footprint, full);
+
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_llrb_delete_cas() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_llrb_delete_cas seed:{}", seed);
+
+    let (n_threads, key) = (8, 10_u64);
+    let index: Index<u64, u64> = Index::new("test_delete_cas", rng.gen::<bool>());
+    index.set(key, 100).unwrap();
+
+    // concurrent updates shall make the cas stale.
+    let stale = index.get(&key).unwrap().to_seqno();
+    let mut handles = vec![];
+    for id in 0..n_threads {
+        let index = index.clone();
+        handles.push(thread::spawn(move || {
+            for i in 0..100 {
+                index.set(key, (id * 1000) + i).unwrap();
+            }
+        }));
+    }
+    for handle in handles.into_iter() {
+        handle.join().unwrap();
+    }
+
+    match index.delete_cas(&key, stale) {
+        Err(Error::InvalidCAS(_, _)) => (),
+        res => panic!("expected InvalidCAS, got {:?}", res.map(|wr| wr.seqno)),
+    }
+    let entry = index.get(&key).unwrap();
+    assert!(!entry.is_deleted());
+
+    // cas for missing key must be ZERO.
+    assert!(index.delete_cas(&(key + 1), entry.to_seqno()).is_err());
+
+    // fresh cas shall delete, returning the removed entry.
+    let wr = index.delete_cas(&key, entry.to_seqno()).unwrap();
+    let old_entry = wr.old_entry.unwrap();
+    assert_eq!(old_entry.to_seqno(), entry.to_seqno());
+    assert_eq!(old_entry.to_value(), entry.to_value());
+    assert!(index.get(&key).unwrap().is_deleted());
+    assert_eq!(index.get(&key).unwrap().to_seqno(), wr.seqno);
+
+    // racing compare-and-delete with the same cas, exactly one shall win.
+    index.set(key, 200).unwrap();
+    let cas = index.get(&key).unwrap().to_seqno();
+    let barrier = Arc::new(Barrier::new(n_threads as usize));
+    let mut handles = vec![];
+    for _ in 0..n_threads {
+        let (index, barrier) = (index.clone(), Arc::clone(&barrier));
+        handles.push(thread::spawn(move || {
+            barrier.wait();
+            match index.delete_cas(&key, cas) {
+                Ok(_) => true,
+                Err(Error::InvalidCAS(_, _)) => false,
+                Err(err) => panic!("{}", err),
+            }
+        }));
+    }
+    let wins = handles.into_iter().map(|h| h.join().unwrap());
+    assert_eq!(wins.filter(|won| *won).count(), 1);
+
+    index.validate().unwrap();
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_llrb_purge_older() {
+    use std::mem::size_of;
+
+    let seed: u64 = random();
+    println!("test_llrb_purge_older seed:{}", seed);
+
+    let index = load_index::<u16, u64>(seed, 20_000, 20_000, 2_000, 2_000, None);
+    let cutoff = index.to_seqno() / 2;
+
+    let old: Vec<dbs::Entry<u16, u64>> = index.iter_versions().unwrap().collect();
+    let n_versions: usize = old.iter().map(|e| e.deltas.len()).sum();
+    let (n_count, fp) = (index.len(), index.footprint().unwrap());
+
+    let n = index.purge_older(cutoff).unwrap();
+    assert!(n > 0, "{}", n);
+
+    let new: Vec<dbs::Entry<u16, u64>> = index.iter_versions().unwrap().collect();
+    assert_eq!(new.len(), old.len());
+    assert_eq!(index.len(), n_count);
+
+    let mut n_kept = 0;
+    for (a, b) in old.into_iter().zip(new.into_iter()) {
+        assert_eq!(a.key, b.key);
+        assert_eq!(a.value, b.value);
+        assert!(b.deltas.iter().all(|d| d.to_seqno() >= cutoff));
+        let deltas: Vec<dbs::Delta<_>> =
+            a.deltas.into_iter().filter(|d| d.to_seqno() >= cutoff).collect();
+        assert_eq!(deltas, b.deltas);
+        n_kept += b.deltas.len();
+    }
+    assert_eq!(n, n_versions - n_kept);
+
+    let footprint = index.footprint().unwrap();
+    assert!(footprint < fp, "{} {}", footprint, fp);
+    let overhead = size_of::<Node<u16, u64>>() as isize;
+    let full: isize =
+        index.iter_versions().unwrap().map(|e| overhead + e.footprint().unwrap()).sum();
+    assert_eq!(footprint, full);
+
+    // purging again is a no-op.
+    assert_eq!(index.purge_older(cutoff).unwrap(), 0);
+    index.validate().unwrap();
+
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_llrb_range_versions() {
+    let seed: u64 = random();
+    println!("test_llrb_range_versions seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // expected version chain for each key, oldest version first.
+    let mut chains: BTreeMap<u16, Vec<dbs::Value<u64>>> = BTreeMap::new();
+    let index = Index::<u16, u64>::new("test_llrb_range_versions", false);
+    for _ in 0..20_000 {
+        let key = rng.gen::<u16>() % 4096;
+        let latest = chains.get(&key).and_then(|c| c.last());
+        let live = matches!(latest, Some(dbs::Value::U { .. }));
+        match rng.gen::<u8>() % 4 {
+            0 if live => {
+                let seqno = index.delete(&key).unwrap().seqno;
+                chains.get_mut(&key).unwrap().push(dbs::Value::D { seqno });
+            }
+            _ => {
+                let value: u64 = rng.gen();
+                let seqno = index.set(key, value).unwrap().seqno;
+                let chain = chains.entry(key).or_insert_with(Vec::new);
+                chain.push(dbs::Value::U { value, seqno });
+            }
+        }
+    }
+
+    fn check(entry: dbs::Entry<u16, u64>, item: (&u16, &Vec<dbs::Value<u64>>)) {
+        assert_eq!(entry.key, *item.0);
+        assert_eq!(entry.to_values(), *item.1, "key {}", item.0);
+    }
+
+    for _ in 0..100 {
+        let (a, b) = (rng.gen::<u16>() % 4096, rng.gen::<u16>() % 4096);
+        let r = match rng.gen::<u8>() % 3 {
+            0 => (Bound::Included(a.min(b)), Bound::Excluded(a.max(b))),
+            1 => (Bound::Excluded(a.min(b)), Bound::Included(a.max(b))),
+            _ => (Bound::Included(a.min(b)), Bound::Unbounded),
+        };
+
+        let iter = index.range_versions(r).unwrap();
+        let mut n = 0;
+        for (entry, item) in iter.zip(chains.range(r)) {
+            check(entry, item);
+            n += 1;
+        }
+        assert_eq!(n, chains.range(r).count());
+
+        let iter = index.reverse_versions(r).unwrap();
+        let mut n = 0;
+        for (entry, item) in iter.zip(chains.range(r).rev()) {
+            check(entry, item);
+            n += 1;
+        }
+        assert_eq!(n, chains.range(r).count());
+
+        // latest-value iteration drops the older versions.
+        for entry in index.range(r).unwrap() {
+            assert!(entry.deltas.is_empty());
+        }
+    }
+
+    index.validate().unwrap();
+}
+
 fn test_commit_with_key<K>(prefix: &str, seed: u64, key_max: K)
 where
     K: Ord + Copy + Clone + Rem<Output = K> + fmt::Debug + fmt::Display + dbs::Footprint,
@@ -245,7 +502,8 @@ where
         let (_seqno, _cas) = match op {
             Op::Set(key, val) => {
                 let key = key_for_thread(key, key_max, n_threads, id);
-                let dbs::Wr { seqno, old_entry } = index.set(key.clone(), val).unwrap();
+                let dbs::Wr { seqno, old_entry, .. } =
+                    index.set(key.clone(), val).unwrap();
                 let e = dbs::Entry::new(key.clone(), val, seqno);
                 compare_old_entry(old_entry, btmap.insert(key, e));
                 counts[0] += 1;
@@ -255,7 +513,7 @@ where
                 let key = key_for_thread(key, key_max, n_threads, id);
                 let cas = index.get_versions(&key).map(|e| e.to_seqno()).unwrap_or(0);
                 let (seqno, cas) = match index.set_cas(key.clone(), val, cas) {
-                    Ok(dbs::Wr { seqno, old_entry }) => {
+                    Ok(dbs::Wr { seqno, old_entry, .. }) => {
                         let e = dbs::Entry::new(key.clone(), val, seqno);
                         compare_old_entry(old_entry, btmap.insert(key, e));
                         (seqno, cas)
@@ -270,7 +528,7 @@ where
             }
             Op::Insert(key, val) => {
                 let key = key_for_thread(key, key_max, n_threads, id);
-                let dbs::Wr { seqno, old_entry } =
+                let dbs::Wr { seqno, old_entry, .. } =
                     index.insert(key.clone(), val).unwrap();
                 let e = btmap
                     .get(&key)
@@ -285,7 +543,7 @@ where
                 let key = key_for_thread(key, key_max, n_threads, id);
                 let cas = index.get_versions(&key).map(|e| e.to_seqno()).unwrap_or(0);
                 match index.insert_cas(key.clone(), val, cas) {
-                    Ok(dbs::Wr { seqno, old_entry }) => {
+                    Ok(dbs::Wr { seqno, old_entry, .. }) => {
                         let e = btmap
                             .get(&key.clone())
                             .cloned()
@@ -302,7 +560,7 @@ where
             }
             Op::Remove(key) => {
                 let key = key_for_thread(key, key_max, n_threads, id);
-                let dbs::Wr { seqno, old_entry } = index.remove(&key).unwrap();
+                let dbs::Wr { seqno, old_entry, .. } = index.remove(&key).unwrap();
                 compare_old_entry(old_entry, btmap.remove(&key));
                 counts[4] += 1;
                 (seqno, 0)
@@ -312,7 +570,7 @@ where
                 let key = key_for_thread(key, key_max, n_threads, id);
                 let cas = index.get_versions(&key).map(|e| e.to_seqno()).unwrap_or(0);
                 match index.remove_cas(&key, cas) {
-                    Ok(dbs::Wr { seqno, old_entry }) => {
+                    Ok(dbs::Wr { seqno, old_entry, .. }) => {
                         compare_old_entry(old_entry, btmap.remove(&key));
                         (seqno, cas)
                     }
@@ -325,7 +583,7 @@ where
             }
             Op::Delete(key) => {
                 let key = key_for_thread(key, key_max, n_threads, id);
-                let dbs::Wr { seqno, old_entry } = index.delete(&key).unwrap();
+                let dbs::Wr { seqno, old_entry, .. } = index.delete(&key).unwrap();
                 let e = btmap
                     .get(&key)
                     .cloned()
@@ -339,7 +597,7 @@ where
                 let key = key_for_thread(key, key_max, n_threads, id);
                 let cas = index.get_versions(&key).map(|e| e.to_seqno()).unwrap_or(0);
                 let (seqno, cas) = match index.delete_cas(&key, cas) {
-                    Ok(dbs::Wr { seqno, old_entry }) => {
+                    Ok(dbs::Wr { seqno, old_entry, .. }) => {
                         let e = btmap
                             .get(&key)
                             .cloned()
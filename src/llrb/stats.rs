@@ -1,4 +1,4 @@
-use std::{fmt, result};
+use std::{fmt, result, time};
 
 #[allow(unused_imports)]
 use crate::llrb::Index;
@@ -13,6 +13,12 @@ pub struct Stats {
     pub n_deleted: usize,
     pub tree_footprint: isize,
     pub spin_stats: spinlock::Stats,
+    /// Number of active reader snapshots, point-lookups and iterators.
+    pub n_snapshots: usize,
+    /// Number of write operations published to readers.
+    pub n_writes: usize,
+    /// Maximum time a writer waited to publish a new snapshot.
+    pub max_writer_wait: time::Duration,
     pub blacks: Option<usize>,
     pub depths: Option<Depth>,
 }
@@ -27,6 +33,9 @@ impl Stats {
             n_deleted: Default::default(),
             tree_footprint: Default::default(),
             spin_stats: Default::default(),
+            n_snapshots: Default::default(),
+            n_writes: Default::default(),
+            max_writer_wait: Default::default(),
             blacks: None,
             depths: None,
         }
@@ -46,6 +55,11 @@ impl fmt::Display for Stats {
         )?;
         writeln!(f, "llrb = {{ tree_footprint={} }}", self.tree_footprint)?;
         writeln!(f, "llrb.spin_stats = {}", self.spin_stats)?;
+        writeln!(
+            f,
+            "llrb = {{ n_snapshots={}, n_writes={}, max_writer_wait={:?} }}",
+            self.n_snapshots, self.n_writes, self.max_writer_wait
+        )?;
         writeln!(f, "llrb.depths = {}", d)
     }
 }
@@ -61,6 +75,7 @@ impl dbs::ToJson for Stats {
                 r#""n_deleted": {}, "#,
                 r#""tree_footprint": {}, "#,
                 r#""node_size": {}, "spin_stats": {}, "#,
+                r#""n_snapshots": {}, "n_writes": {}, "max_writer_wait": {}, "#,
                 r#""blacks": {}, "depths": {} }} }}"#,
             ),
             self.name,
@@ -69,6 +84,9 @@ impl dbs::ToJson for Stats {
             self.tree_footprint,
             self.node_size,
             spin_stats,
+            self.n_snapshots,
+            self.n_writes,
+            self.max_writer_wait.as_nanos(),
             self.blacks.as_ref().map_or(null.clone(), |x| format!("{}", x)),
             self.depths.as_ref().map_or(null, |x| x.to_json()),
         )
@@ -0,0 +1,101 @@
+use rayon::prelude::*;
+
+use std::{sync::mpsc, thread};
+
+use crate::{mq, Error, Result};
+
+pub struct FlatMap<Q, R, F>
+where
+    Q: 'static + Sync + Send,
+    R: 'static + Sync + Send,
+    F: 'static + Sync + Send + Fn(Q) -> Vec<R>,
+{
+    name: String,
+    chan_size: usize,
+
+    input: Option<mpsc::Receiver<Q>>,
+    flat_map: Option<F>,
+    handle: Option<thread::JoinHandle<Result<()>>>,
+}
+
+impl<Q, R, F> FlatMap<Q, R, F>
+where
+    Q: 'static + Sync + Send,
+    R: 'static + Sync + Send,
+    F: 'static + Sync + Send + Fn(Q) -> Vec<R>,
+{
+    pub fn new(name: String, input: mpsc::Receiver<Q>, flat_map: F) -> Self {
+        FlatMap {
+            name,
+            chan_size: mq::DEFAULT_CHAN_SIZE,
+
+            input: Some(input),
+            flat_map: Some(flat_map),
+            handle: None,
+        }
+    }
+
+    pub fn set_chan_size(&mut self, chan_size: usize) -> &mut Self {
+        self.chan_size = chan_size;
+        self
+    }
+
+    pub fn spawn(&mut self) -> mpsc::Receiver<R> {
+        let (name, chan_size) = (self.name.clone(), self.chan_size);
+        let (tx, output) = mpsc::sync_channel(self.chan_size);
+
+        let input = self.input.take().unwrap();
+        let flat_map = self.flat_map.take().unwrap();
+
+        self.handle =
+            Some(thread::spawn(move || action(name, chan_size, input, tx, flat_map)));
+
+        output
+    }
+
+    pub fn close_wait(self) -> Result<()> {
+        match self.handle {
+            Some(handle) => match handle.join() {
+                Ok(res) => res,
+                Err(_) => {
+                    err_at!(ThreadFail, msg: "thread fail FlatMap<{:?}>", self.name)
+                }
+            },
+            None => Ok(()),
+        }
+    }
+}
+
+fn action<Q, R, F>(
+    name: String,
+    chan_size: usize,
+    input: mpsc::Receiver<Q>,
+    tx: mpsc::SyncSender<R>,
+    flat_map: F,
+) -> Result<()>
+where
+    R: 'static + Sync + Send,
+    Q: 'static + Sync + Send,
+    F: 'static + Sync + Send + Fn(Q) -> Vec<R>,
+{
+    loop {
+        match mq::get_messages(&input, chan_size) {
+            Ok(qmsgs) => {
+                // indexed collect preserves the input order, outputs from the
+                // same input are emitted back to back.
+                let rmsgs: Vec<Vec<R>> = qmsgs.into_par_iter().map(&flat_map).collect();
+                for rmsg in rmsgs.into_iter().flatten() {
+                    err_at!(IPCFail, tx.send(rmsg), "thread FlatMap<{:?}", name)?
+                }
+            }
+            Err(mpsc::TryRecvError::Disconnected) => break,
+            _ => unreachable!(),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "flat_map_test.rs"]
+mod flat_map_test;
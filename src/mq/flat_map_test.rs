@@ -0,0 +1,42 @@
+use rand::{prelude::random, rngs::StdRng, Rng, SeedableRng};
+
+use super::*;
+
+#[test]
+fn test_mq_flat_map() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_mq_flat_map {}", seed);
+
+    let n_sentences = [0, 1, 10, 1_000, 10_000][rng.gen::<usize>() % 5];
+    let sentences: Vec<String> = (0..n_sentences)
+        .map(|i| {
+            let n_words = rng.gen::<usize>() % 10;
+            let words: Vec<String> =
+                (0..n_words).map(|j| format!("w{}-{}", i, j)).collect();
+            words.join(" ")
+        })
+        .collect();
+
+    let (tx, input) = mpsc::sync_channel(mq::DEFAULT_CHAN_SIZE);
+    let mut stage = FlatMap::new("split-words".to_string(), input, |s: String| {
+        s.split_whitespace().map(|w| w.to_string()).collect::<Vec<String>>()
+    });
+    let output = stage.set_chan_size(rng.gen::<usize>() % 100 + 1).spawn();
+
+    let reader = thread::spawn(move || output.iter().collect::<Vec<String>>());
+    for sentence in sentences.clone().into_iter() {
+        tx.send(sentence).unwrap();
+    }
+    std::mem::drop(tx);
+
+    let words = reader.join().unwrap();
+    stage.close_wait().unwrap();
+
+    let refs: Vec<String> = sentences
+        .iter()
+        .flat_map(|s| s.split_whitespace().map(|w| w.to_string()))
+        .collect();
+    println!("test_mq_flat_map words:{}", refs.len());
+    assert_eq!(words, refs);
+}
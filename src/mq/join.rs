@@ -0,0 +1,169 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    sync::mpsc,
+    thread,
+};
+
+use crate::{mq, Error, Result};
+
+/// Join two input streams on key, pairing a message from the first stream with
+/// a message from the second stream sharing the same key. Messages are paired
+/// in the order they are received, and messages that could not be paired when
+/// both the streams are disconnected are dropped.
+pub struct Join<A, B, K, FA, FB>
+where
+    A: 'static + Send,
+    B: 'static + Send,
+    K: 'static + Eq + Hash,
+    FA: 'static + Send + Fn(&A) -> K,
+    FB: 'static + Send + Fn(&B) -> K,
+{
+    name: String,
+    chan_size: usize,
+
+    inputs: Option<(mpsc::Receiver<A>, mpsc::Receiver<B>)>,
+    keys: Option<(FA, FB)>,
+    handles: Vec<thread::JoinHandle<Result<()>>>,
+}
+
+// message from either of the input streams.
+enum Side<A, B> {
+    A(A),
+    B(B),
+}
+
+impl<A, B, K, FA, FB> Join<A, B, K, FA, FB>
+where
+    A: 'static + Send,
+    B: 'static + Send,
+    K: 'static + Eq + Hash,
+    FA: 'static + Send + Fn(&A) -> K,
+    FB: 'static + Send + Fn(&B) -> K,
+{
+    pub fn new(
+        name: String,
+        input_a: mpsc::Receiver<A>,
+        input_b: mpsc::Receiver<B>,
+        key_a: FA,
+        key_b: FB,
+    ) -> Self {
+        Join {
+            name,
+            chan_size: mq::DEFAULT_CHAN_SIZE,
+
+            inputs: Some((input_a, input_b)),
+            keys: Some((key_a, key_b)),
+            handles: vec![],
+        }
+    }
+
+    pub fn set_chan_size(&mut self, chan_size: usize) -> &mut Self {
+        self.chan_size = chan_size;
+        self
+    }
+
+    pub fn spawn(&mut self) -> mpsc::Receiver<(A, B)> {
+        let name = self.name.clone();
+        let (tx, output) = mpsc::sync_channel(self.chan_size);
+        let (stx, srx) = mpsc::sync_channel(self.chan_size);
+
+        let (input_a, input_b) = self.inputs.take().unwrap();
+        let keys = self.keys.take().unwrap();
+
+        // funnel both the input streams into a single channel.
+        let (n1, n2) = (name.clone(), name.clone());
+        let atx = stx.clone();
+        self.handles.push(thread::spawn(move || {
+            for qmsg in input_a.iter() {
+                err_at!(IPCFail, atx.send(Side::A(qmsg)), "thread Join<{:?}>", n1)?
+            }
+            Ok(())
+        }));
+        self.handles.push(thread::spawn(move || {
+            for qmsg in input_b.iter() {
+                err_at!(IPCFail, stx.send(Side::B(qmsg)), "thread Join<{:?}>", n2)?
+            }
+            Ok(())
+        }));
+        self.handles.push(thread::spawn(move || action(name, srx, tx, keys)));
+
+        output
+    }
+
+    pub fn close_wait(self) -> Result<()> {
+        let mut res = Ok(());
+        for handle in self.handles.into_iter() {
+            res = match (res, handle.join()) {
+                (Ok(()), Ok(res)) => res,
+                (Ok(()), Err(_)) => {
+                    err_at!(ThreadFail, msg: "thread fail Join<{:?}>", self.name)
+                }
+                (res, _) => res,
+            };
+        }
+        res
+    }
+}
+
+fn action<A, B, K, FA, FB>(
+    name: String,
+    input: mpsc::Receiver<Side<A, B>>,
+    tx: mpsc::SyncSender<(A, B)>,
+    keys: (FA, FB),
+) -> Result<()>
+where
+    A: 'static + Send,
+    B: 'static + Send,
+    K: 'static + Eq + Hash,
+    FA: 'static + Send + Fn(&A) -> K,
+    FB: 'static + Send + Fn(&B) -> K,
+{
+    let (key_a, key_b) = keys;
+    let mut pending_a: HashMap<K, VecDeque<A>> = HashMap::new();
+    let mut pending_b: HashMap<K, VecDeque<B>> = HashMap::new();
+
+    for side in input.iter() {
+        let pair = match side {
+            Side::A(a) => {
+                let key = key_a(&a);
+                match pop_pending(&mut pending_b, &key) {
+                    Some(b) => (a, b),
+                    None => {
+                        pending_a.entry(key).or_insert_with(VecDeque::new).push_back(a);
+                        continue;
+                    }
+                }
+            }
+            Side::B(b) => {
+                let key = key_b(&b);
+                match pop_pending(&mut pending_a, &key) {
+                    Some(a) => (a, b),
+                    None => {
+                        pending_b.entry(key).or_insert_with(VecDeque::new).push_back(b);
+                        continue;
+                    }
+                }
+            }
+        };
+        err_at!(IPCFail, tx.send(pair), "thread Join<{:?}>", name)?
+    }
+
+    Ok(())
+}
+
+fn pop_pending<K, Q>(pending: &mut HashMap<K, VecDeque<Q>>, key: &K) -> Option<Q>
+where
+    K: Eq + Hash,
+{
+    let queue = pending.get_mut(key)?;
+    let qmsg = queue.pop_front();
+    if queue.is_empty() {
+        pending.remove(key);
+    }
+    qmsg
+}
+
+#[cfg(test)]
+#[path = "join_test.rs"]
+mod join_test;
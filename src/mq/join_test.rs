@@ -0,0 +1,50 @@
+use rand::{prelude::random, rngs::StdRng, Rng, SeedableRng};
+
+use super::*;
+
+#[test]
+fn test_mq_join() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_mq_join {}", seed);
+
+    let n_keys = [0, 1, 10, 1_000, 10_000][rng.gen::<usize>() % 5];
+    let keys_a: Vec<u64> = (0..n_keys).collect();
+    let mut keys_b: Vec<u64> = (0..n_keys).filter(|key| key % 3 != 0).collect();
+    keys_b.reverse();
+
+    let (tx_a, input_a) = mpsc::sync_channel(mq::DEFAULT_CHAN_SIZE);
+    let (tx_b, input_b) = mpsc::sync_channel(mq::DEFAULT_CHAN_SIZE);
+    let mut stage = Join::new(
+        "join".to_string(),
+        input_a,
+        input_b,
+        |a: &(u64, String)| a.0,
+        |b: &u64| *b,
+    );
+    let output = stage.set_chan_size(rng.gen::<usize>() % 100 + 1).spawn();
+
+    let reader =
+        thread::spawn(move || output.iter().collect::<Vec<((u64, String), u64)>>());
+    let writer = thread::spawn(move || {
+        for key in keys_b.into_iter() {
+            tx_b.send(key).unwrap();
+        }
+    });
+    for key in keys_a.into_iter() {
+        tx_a.send((key, key.to_string())).unwrap();
+    }
+    std::mem::drop(tx_a);
+    writer.join().unwrap();
+
+    let mut pairs = reader.join().unwrap();
+    stage.close_wait().unwrap();
+
+    pairs.sort();
+    let refs: Vec<((u64, String), u64)> = (0..n_keys)
+        .filter(|key| key % 3 != 0)
+        .map(|key| ((key, key.to_string()), key))
+        .collect();
+    println!("test_mq_join pairs:{}", pairs.len());
+    assert_eq!(pairs, refs);
+}
@@ -0,0 +1,116 @@
+use std::{sync::mpsc, thread};
+
+use crate::{mq, Error, Result};
+
+/// Merge two input streams, each ordered by seqno, into a single output
+/// stream ordered by seqno. `seqno` shall return the sequence number of
+/// a message. Inverse of [Split][mq::split::Split], when split outputs are
+/// partitioned, say using [Filter][mq::filter::Filter].
+pub struct Merge<Q, F>
+where
+    Q: 'static + Send,
+    F: 'static + Send + Fn(&Q) -> u64,
+{
+    name: String,
+    chan_size: usize,
+
+    inputs: Option<(mpsc::Receiver<Q>, mpsc::Receiver<Q>)>,
+    seqno: Option<F>,
+    handle: Option<thread::JoinHandle<Result<()>>>,
+}
+
+impl<Q, F> Merge<Q, F>
+where
+    Q: 'static + Send,
+    F: 'static + Send + Fn(&Q) -> u64,
+{
+    pub fn new(
+        name: String,
+        input_a: mpsc::Receiver<Q>,
+        input_b: mpsc::Receiver<Q>,
+        seqno: F,
+    ) -> Self {
+        Merge {
+            name,
+            chan_size: mq::DEFAULT_CHAN_SIZE,
+
+            inputs: Some((input_a, input_b)),
+            seqno: Some(seqno),
+            handle: None,
+        }
+    }
+
+    pub fn set_chan_size(&mut self, chan_size: usize) -> &mut Self {
+        self.chan_size = chan_size;
+        self
+    }
+
+    pub fn spawn(&mut self) -> mpsc::Receiver<Q> {
+        let name = self.name.clone();
+        let (tx, output) = mpsc::sync_channel(self.chan_size);
+
+        let (inputs, seqno) = (self.inputs.take().unwrap(), self.seqno.take().unwrap());
+
+        self.handle = Some(thread::spawn(move || action(name, inputs, tx, seqno)));
+
+        output
+    }
+
+    pub fn close_wait(self) -> Result<()> {
+        match self.handle {
+            Some(handle) => match handle.join() {
+                Ok(res) => res,
+                Err(_) => {
+                    err_at!(ThreadFail, msg: "thread fail Merge<{:?}>", self.name)
+                }
+            },
+            None => Ok(()),
+        }
+    }
+}
+
+fn action<Q, F>(
+    name: String,
+    inputs: (mpsc::Receiver<Q>, mpsc::Receiver<Q>),
+    tx: mpsc::SyncSender<Q>,
+    seqno: F,
+) -> Result<()>
+where
+    Q: 'static + Send,
+    F: 'static + Send + Fn(&Q) -> u64,
+{
+    let (a, b) = inputs;
+
+    // head of each stream, None once the stream is disconnected.
+    let (mut qa, mut qb) = (a.recv().ok(), b.recv().ok());
+    loop {
+        let qmsg = match (qa.take(), qb.take()) {
+            (Some(x), Some(y)) if seqno(&x) <= seqno(&y) => {
+                qa = a.recv().ok();
+                qb = Some(y);
+                x
+            }
+            (Some(x), Some(y)) => {
+                qa = Some(x);
+                qb = b.recv().ok();
+                y
+            }
+            (Some(x), None) => {
+                qa = a.recv().ok();
+                x
+            }
+            (None, Some(y)) => {
+                qb = b.recv().ok();
+                y
+            }
+            (None, None) => break,
+        };
+        err_at!(IPCFail, tx.send(qmsg), "thread Merge<{:?}>", name)?
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "merge_test.rs"]
+mod merge_test;
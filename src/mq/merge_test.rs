@@ -0,0 +1,43 @@
+use rand::{prelude::random, rngs::StdRng, Rng, SeedableRng};
+
+use super::*;
+
+use crate::mq::{filter::Filter, split::Split};
+
+#[test]
+fn test_mq_merge() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_mq_merge {}", seed);
+
+    let n_msgs = [0, 1, 10, 1_000, 10_000][rng.gen::<usize>() % 5];
+    let msgs: Vec<(u64, u64)> = (0..n_msgs).map(|seqno| (seqno, rng.gen())).collect();
+
+    let (tx, input) = mpsc::sync_channel(mq::DEFAULT_CHAN_SIZE);
+    let mut split = Split::new("split".to_string(), input, 2);
+    let mut outputs = split.set_chan_size(rng.gen::<usize>() % 100 + 1).spawn();
+
+    let (output_b, output_a) = (outputs.pop().unwrap(), outputs.pop().unwrap());
+    let mut even =
+        Filter::new("even".to_string(), output_a, |m: &(u64, u64)| m.0 % 2 == 0);
+    let mut odd = Filter::new("odd".to_string(), output_b, |m: &(u64, u64)| m.0 % 2 == 1);
+    let (input_a, input_b) = (even.spawn(), odd.spawn());
+
+    let mut stage = Merge::new("merge".to_string(), input_a, input_b, |m| m.0);
+    let output = stage.set_chan_size(rng.gen::<usize>() % 100 + 1).spawn();
+
+    let reader = thread::spawn(move || output.iter().collect::<Vec<(u64, u64)>>());
+    for msg in msgs.clone().into_iter() {
+        tx.send(msg).unwrap();
+    }
+    std::mem::drop(tx);
+
+    let outs = reader.join().unwrap();
+    split.close_wait().unwrap();
+    even.close_wait().unwrap();
+    odd.close_wait().unwrap();
+    stage.close_wait().unwrap();
+
+    println!("test_mq_merge msgs:{}", outs.len());
+    assert_eq!(outs, msgs);
+}
@@ -2,7 +2,10 @@ use std::{result, sync::mpsc};
 
 pub mod filter;
 pub mod filter_map;
+pub mod flat_map;
+pub mod join;
 pub mod map;
+pub mod merge;
 pub mod reduce;
 pub mod sink;
 pub mod source;
@@ -30,6 +33,10 @@ where
                 break Ok(qmsgs);
             }
             Err(mpsc::TryRecvError::Empty) => break Ok(qmsgs),
+            // return pending messages before reporting disconnect.
+            Err(mpsc::TryRecvError::Disconnected) if !qmsgs.is_empty() => {
+                break Ok(qmsgs)
+            }
             Err(err @ mpsc::TryRecvError::Disconnected) => break Err(err),
         }
     }
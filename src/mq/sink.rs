@@ -1,6 +1,8 @@
-use std::{sync::mpsc, thread};
+use cbordata::{FromCbor, IntoCbor};
 
-use crate::{mq, Error, Result};
+use std::{hash::Hash, sync::mpsc, thread};
+
+use crate::{dbs, mq, robt, Error, Result};
 
 pub struct Sink<Q, F>
 where
@@ -112,3 +114,89 @@ where
     callb(Q::finish())?;
     res
 }
+
+/// Terminal stage, that feeds sorted entries from `input` into a robt
+/// [Builder][robt::Builder], refer to [robt_builder].
+pub struct RobtSink<K, V, B>
+where
+    K: IntoCbor,
+    V: dbs::Diff + IntoCbor,
+    <V as dbs::Diff>::Delta: IntoCbor,
+{
+    name: String,
+    input: mpsc::Receiver<dbs::Entry<K, V>>,
+    builder: robt::Builder<K, V>,
+    bitmap: B,
+    seqno: Option<u64>,
+}
+
+/// Create a sink that build a robt index from entries received on `input`.
+///
+/// Entries shall be received in the builder's sort order, as per its
+/// comparator or `Ord`, ascending, or descending if configured with
+/// [robt::Config::set_descending], else the build fails with `InvalidInput`.
+/// Consecutive entries with same key are handled as per
+/// [robt::Config::set_strict_keys]. Since [robt::Builder] is not `Send`, the
+/// index is built in the calling thread by [RobtSink::close_wait], which
+/// drains `input` until all upstream stages have closed. Hence call
+/// `close_wait` on this sink before calling `close_wait` on the upstream
+/// stages.
+pub fn robt_builder<K, V, B>(
+    name: String,
+    input: mpsc::Receiver<dbs::Entry<K, V>>,
+    builder: robt::Builder<K, V>,
+    bitmap: B,
+) -> RobtSink<K, V, B>
+where
+    K: IntoCbor,
+    V: dbs::Diff + IntoCbor,
+    <V as dbs::Diff>::Delta: IntoCbor,
+{
+    RobtSink { name, input, builder, bitmap, seqno: None }
+}
+
+impl<K, V, B> RobtSink<K, V, B>
+where
+    K: Clone + Ord + Hash + IntoCbor + FromCbor,
+    V: dbs::Diff + IntoCbor + FromCbor,
+    <V as dbs::Diff>::Delta: IntoCbor + FromCbor,
+    B: Clone + dbs::Bloom,
+{
+    /// Seqno to persist with the index, refer to [robt::Builder::build_index].
+    pub fn set_seqno(&mut self, seqno: u64) -> &mut Self {
+        self.seqno = Some(seqno);
+        self
+    }
+
+    /// Build the index from entries received on input, and return the index
+    /// once the input is closed.
+    pub fn close_wait(self) -> Result<robt::Index<K, V, B>> {
+        let RobtSink { name, input, mut builder, bitmap, seqno } = self;
+
+        let cmp = builder.to_comparator();
+        let descending = builder.as_config().descending;
+        let mut prev: Option<K> = None;
+        let iter = input.into_iter().map(|entry| -> Result<dbs::Entry<K, V>> {
+            let ord = |prev: &K| match &cmp {
+                Some(cmp) => cmp(prev, entry.as_key()),
+                None => prev.cmp(entry.as_key()),
+            };
+            // duplicate keys are left to the builder, refer to strict_keys.
+            let ord = |prev: &K| match descending {
+                true => ord(prev).reverse(),
+                false => ord(prev),
+            };
+            if matches!(&prev, Some(prev) if ord(prev).is_gt()) {
+                err_at!(InvalidInput, msg: "RobtSink<{:?}> key out of order", name)?
+            }
+            prev = Some(entry.to_key());
+            Ok(entry)
+        });
+
+        builder.build_index(iter, bitmap, seqno)
+    }
+}
+
+#[cfg(test)]
+#[path = "sink_test.rs"]
+mod sink_test;
@@ -0,0 +1,180 @@
+use rand::{prelude::random, rngs::StdRng, Rng, SeedableRng};
+
+use std::fs;
+
+use super::*;
+
+use crate::{bitmaps::NoBitmap, mq::map::Map, mq::source::Source};
+
+type GenFn = fn() -> Result<Option<u64>>;
+
+#[test]
+fn test_mq_sink_robt_builder() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_mq_sink_robt_builder {}", seed);
+
+    let dir = std::env::temp_dir().join("test_mq_sink_robt_builder");
+    fs::remove_dir_all(&dir).ok();
+    let config = robt::Config::new(dir.as_os_str(), "mq-sink");
+
+    let n_keys = [1, 10, 1_000, 10_000][rng.gen::<usize>() % 4];
+    let keys: Vec<u64> = (0..n_keys).map(|i| i * 2).collect();
+
+    let mut source =
+        Source::<u64, _, GenFn>::from_iter("src".to_string(), keys.clone().into_iter());
+    let output = source.set_chan_size(rng.gen::<usize>() % 100 + 1).spawn();
+    let mut map = Map::new("map".to_string(), output, |key: u64| {
+        dbs::Entry::<u64, u64>::new(key, key + 100, key + 1)
+    });
+    let output = map.set_chan_size(rng.gen::<usize>() % 100 + 1).spawn();
+
+    let builder = robt::Builder::<u64, u64>::initial(config.clone(), vec![]).unwrap();
+    let sink = robt_builder("robt".to_string(), output, builder, NoBitmap);
+    sink.close_wait().unwrap().close().unwrap();
+
+    source.close_wait().unwrap();
+    map.close_wait().unwrap();
+
+    let mut index =
+        robt::Index::<u64, u64, NoBitmap>::open(dir.as_os_str(), "mq-sink").unwrap();
+    println!("test_mq_sink_robt_builder n_keys:{}", n_keys);
+    assert_eq!(index.len(), keys.len());
+
+    let entries: Vec<dbs::Entry<u64, u64>> =
+        index.iter(..).unwrap().map(|e| e.unwrap()).collect();
+    assert_eq!(entries.len(), keys.len());
+    for (entry, key) in entries.into_iter().zip(keys.into_iter()) {
+        assert_eq!(entry.to_key(), key);
+        assert_eq!(entry.to_value(), Some(key + 100));
+        assert_eq!(entry.to_seqno(), key + 1);
+    }
+    index.purge().unwrap();
+
+    // out of order keys shall fail the build.
+    let keys: Vec<u64> = vec![10, 20, 15, 30];
+    let mut source =
+        Source::<u64, _, GenFn>::from_iter("src".to_string(), keys.into_iter());
+    let output = source.spawn();
+    let mut map = Map::new("map".to_string(), output, |key: u64| {
+        dbs::Entry::<u64, u64>::new(key, key, 1)
+    });
+    let output = map.spawn();
+
+    let builder = robt::Builder::<u64, u64>::initial(config, vec![]).unwrap();
+    let sink = robt_builder("robt".to_string(), output, builder, NoBitmap);
+    match sink.close_wait() {
+        Err(Error::InvalidInput(_, _)) => (),
+        Err(err) => panic!("unexpected err {}", err),
+        Ok(_) => panic!("expected out of order error"),
+    }
+
+    // upstream stages may fail to send, after the sink has quit.
+    source.close_wait().ok();
+    map.close_wait().ok();
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_mq_sink_robt_descending() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_mq_sink_robt_descending {}", seed);
+
+    let dir = std::env::temp_dir().join("test_mq_sink_robt_descending");
+    fs::remove_dir_all(&dir).ok();
+    let mut config = robt::Config::new(dir.as_os_str(), "mq-sink-desc");
+    config.set_descending(true);
+
+    let n_keys = [1, 10, 1_000, 10_000][rng.gen::<usize>() % 4];
+    let keys: Vec<u64> = (0..n_keys).rev().map(|i| i * 2).collect();
+
+    let mut source =
+        Source::<u64, _, GenFn>::from_iter("src".to_string(), keys.clone().into_iter());
+    let output = source.set_chan_size(rng.gen::<usize>() % 100 + 1).spawn();
+    let mut map = Map::new("map".to_string(), output, |key: u64| {
+        dbs::Entry::<u64, u64>::new(key, key + 100, key + 1)
+    });
+    let output = map.set_chan_size(rng.gen::<usize>() % 100 + 1).spawn();
+
+    let builder = robt::Builder::<u64, u64>::initial(config.clone(), vec![]).unwrap();
+    let sink = robt_builder("robt".to_string(), output, builder, NoBitmap);
+    sink.close_wait().unwrap().close().unwrap();
+
+    source.close_wait().unwrap();
+    map.close_wait().unwrap();
+
+    let mut index =
+        robt::Index::<u64, u64, NoBitmap>::open(dir.as_os_str(), "mq-sink-desc").unwrap();
+    println!("test_mq_sink_robt_descending n_keys:{}", n_keys);
+    assert_eq!(index.len(), keys.len());
+
+    let entries: Vec<dbs::Entry<u64, u64>> =
+        index.reverse(..).unwrap().map(|e| e.unwrap()).collect();
+    assert_eq!(entries.len(), keys.len());
+    for (entry, key) in entries.into_iter().zip(keys.into_iter()) {
+        assert_eq!(entry.to_key(), key);
+        assert_eq!(entry.to_value(), Some(key + 100));
+    }
+    index.purge().unwrap();
+
+    // ascending keys are out of order for a descending build.
+    let keys: Vec<u64> = vec![30, 20, 25, 10];
+    let mut source =
+        Source::<u64, _, GenFn>::from_iter("src".to_string(), keys.into_iter());
+    let output = source.spawn();
+    let mut map = Map::new("map".to_string(), output, |key: u64| {
+        dbs::Entry::<u64, u64>::new(key, key, 1)
+    });
+    let output = map.spawn();
+
+    let builder = robt::Builder::<u64, u64>::initial(config, vec![]).unwrap();
+    let sink = robt_builder("robt".to_string(), output, builder, NoBitmap);
+    match sink.close_wait() {
+        Err(Error::InvalidInput(_, _)) => (),
+        Err(err) => panic!("unexpected err {}", err),
+        Ok(_) => panic!("expected out of order error"),
+    }
+
+    source.close_wait().ok();
+    map.close_wait().ok();
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_mq_sink_robt_strict_keys() {
+    let dir = std::env::temp_dir().join("test_mq_sink_robt_strict_keys");
+    fs::remove_dir_all(&dir).ok();
+
+    let keys: Vec<u64> = vec![10, 20, 20, 30];
+    for strict in [true, false].iter() {
+        let mut config = robt::Config::new(dir.as_os_str(), "mq-sink-strict");
+        config.set_strict_keys(*strict);
+
+        let mut source = Source::<u64, _, GenFn>::from_iter(
+            "src".to_string(),
+            keys.clone().into_iter(),
+        );
+        let output = source.spawn();
+        let mut map = Map::new("map".to_string(), output, |key: u64| {
+            dbs::Entry::<u64, u64>::new(key, key, 1)
+        });
+        let output = map.spawn();
+
+        let builder = robt::Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let sink = robt_builder("robt".to_string(), output, builder, NoBitmap);
+        match sink.close_wait() {
+            Err(Error::DuplicateKey(_, _)) if *strict => (),
+            Ok(index) if !*strict => index.purge().unwrap(),
+            Err(err) => panic!("unexpected err {}", err),
+            Ok(_) => panic!("expected duplicate key error"),
+        }
+
+        source.close_wait().ok();
+        map.close_wait().ok();
+    }
+
+    fs::remove_dir_all(&dir).ok();
+}
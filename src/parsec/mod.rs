@@ -18,7 +18,7 @@ impl fmt::Display for Position {
 }
 
 pub use lex::Lex;
-pub use parse::{Parsec, S};
+pub use parse::{Packrat, Parsec, S};
 
 /// Trait implemented by lexer types.
 pub trait Lexer {
@@ -2,6 +2,7 @@ use regex::{Regex, RegexSet};
 
 use std::{
     cell::RefCell,
+    collections::HashMap,
     rc::{Rc, Weak},
 };
 
@@ -213,6 +214,58 @@ where
     P: Parser,
 {
     pub fn parse<L>(&self, lex: &mut L) -> Result<Option<Node>>
+    where
+        L: Lexer + Clone,
+    {
+        self.do_parse(lex, None)
+    }
+
+    /// Same as [parse][Parsec::parse], but memoize the outcome of every
+    /// (parser, cursor) attempt in `cache`, so that each parser is attempted
+    /// only once at a given position. Avoids exponential backtracking with
+    /// deeply nested alternatives, at the cost of memory. `cache` is reset
+    /// on every call.
+    pub fn parse_packrat<L>(
+        &self,
+        lex: &mut L,
+        cache: &mut Packrat,
+    ) -> Result<Option<Node>>
+    where
+        L: Lexer + Clone,
+    {
+        cache.reset();
+        self.do_parse(lex, Some(cache))
+    }
+
+    fn do_parse<L>(&self, lex: &mut L, memo: Option<&mut Packrat>) -> Result<Option<Node>>
+    where
+        L: Lexer + Clone,
+    {
+        let memo = match memo {
+            Some(memo) => memo,
+            None => return self.parse_once(lex, None),
+        };
+
+        let key = (self as *const Self as usize, lex.to_cursor());
+        if let Some((res, cursor)) = memo.cache.get(&key) {
+            memo.n_hits += 1;
+            let res = res.clone();
+            lex.move_cursor(cursor - key.1);
+            return res;
+        }
+
+        memo.n_calls += 1;
+        let res = self.parse_once(lex, Some(&mut *memo));
+        memo.cache.insert(key, (res.clone(), lex.to_cursor()));
+
+        res
+    }
+
+    fn parse_once<L>(
+        &self,
+        lex: &mut L,
+        mut memo: Option<&mut Packrat>,
+    ) -> Result<Option<Node>>
     where
         L: Lexer + Clone,
     {
@@ -256,20 +309,22 @@ where
                 }
                 res => res,
             }?,
-            Parsec::P { name, parser } => match parser.parse(lex)? {
-                Some(mut node) => {
-                    node.set_name(name.as_str());
-                    Some(node)
+            Parsec::P { name, parser } => {
+                match parser.do_parse(lex, memo.as_deref_mut())? {
+                    Some(mut node) => {
+                        node.set_name(name.as_str());
+                        Some(node)
+                    }
+                    None => None,
                 }
-                None => None,
-            },
+            }
             Parsec::And { name, parsers } => {
                 let mut children = vec![];
                 let mut iter = parsers.iter();
                 loop {
                     saved_lex = lex.save();
                     match iter.next() {
-                        Some(parser) => match parser.parse(lex) {
+                        Some(parser) => match parser.do_parse(lex, memo.as_deref_mut()) {
                             Ok(Some(node)) => children.push(node),
                             Ok(None) => {
                                 lex.restore(saved_lex);
@@ -296,7 +351,7 @@ where
             {
                 let re = RegexSet::new(parsers.iter().map(|p| p.to_pattern())).unwrap();
                 match re.matches(lex.as_str()).iter().next() {
-                    Some(n) => match parsers[n].parse(lex) {
+                    Some(n) => match parsers[n].do_parse(lex, memo.as_deref_mut()) {
                         Ok(Some(node)) => Some(node),
                         Ok(None) => None,
                         Err(err) => return Err(err),
@@ -317,7 +372,7 @@ where
                             #[cfg(feature = "debug")]
                             println!("Parsec::Or trying {}", parser.to_name());
 
-                            match parser.parse(lex) {
+                            match parser.do_parse(lex, memo.as_deref_mut()) {
                                 Ok(Some(node)) => break Some(node),
                                 Ok(None) => {
                                     lex.restore(saved_lex.clone());
@@ -344,7 +399,7 @@ where
                 node
             }
             Parsec::Maybe { parser } => {
-                let child = match parser.parse(lex) {
+                let child = match parser.do_parse(lex, memo.as_deref_mut()) {
                     Ok(Some(node)) => Some(Box::new(node)),
                     Ok(None) => None,
                     Err(_) => {
@@ -360,7 +415,7 @@ where
 
                 loop {
                     saved_lex = lex.save();
-                    match parser.parse(lex) {
+                    match parser.do_parse(lex, memo.as_deref_mut()) {
                         Ok(Some(node)) => children.push(node),
                         Ok(None) => {
                             lex.restore(saved_lex);
@@ -381,7 +436,7 @@ where
 
                 loop {
                     saved_lex = lex.save();
-                    match parser.parse(lex) {
+                    match parser.do_parse(lex, memo.as_deref_mut()) {
                         Ok(Some(node)) => children.push(node),
                         Ok(None) if children.is_empty() => {
                             lex.restore(saved_lex.clone());
@@ -420,7 +475,7 @@ where
             }
             Parsec::Ref { parser } => {
                 let parser = parser.borrow().upgrade().unwrap();
-                parser.parse(lex)?
+                parser.do_parse(lex, memo.as_deref_mut())?
             }
         };
 
@@ -527,6 +582,39 @@ where
     }
 }
 
+/// Packrat cache for [Parsec::parse_packrat], memoizing the outcome of
+/// parsers keyed on (parser-id, cursor).
+#[derive(Default)]
+pub struct Packrat {
+    cache: HashMap<(usize, usize), (Result<Option<Node>>, usize)>,
+    n_calls: usize,
+    n_hits: usize,
+}
+
+impl Packrat {
+    pub fn new() -> Packrat {
+        Packrat::default()
+    }
+
+    /// Return the number of parse attempts, excluding the ones answered from
+    /// cache, in the last top-level parse.
+    pub fn to_calls(&self) -> usize {
+        self.n_calls
+    }
+
+    /// Return the number of parse attempts answered from cache, in the last
+    /// top-level parse.
+    pub fn to_hits(&self) -> usize {
+        self.n_hits
+    }
+
+    fn reset(&mut self) {
+        self.cache.clear();
+        self.n_calls = 0;
+        self.n_hits = 0;
+    }
+}
+
 pub struct S<L> {
     lex: L,
     root: Node,
@@ -537,3 +625,7 @@ impl<L> S<L> {
         (self.lex, self.root)
     }
 }
+
+#[cfg(test)]
+#[path = "parse_test.rs"]
+mod parse_test;
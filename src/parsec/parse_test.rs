@@ -0,0 +1,70 @@
+use std::rc::Rc;
+
+use crate::parsec::{Lex, Lexer, Node, Packrat, Parser};
+use crate::{and, atom, or};
+
+use super::*;
+
+struct NoExt;
+
+impl Parser for NoExt {
+    fn to_name(&self) -> String {
+        "NO_EXT".to_string()
+    }
+
+    fn parse<L>(&self, _lex: &mut L) -> Result<Option<Node>>
+    where
+        L: Lexer,
+    {
+        Ok(None)
+    }
+}
+
+// S := "a" S "b" | "a" S "c" | "a", parsing "a{n}c{n-1}" without memoization
+// re-parses S at every position twice for every level of nesting.
+fn pathological() -> Rc<Parsec<NoExt>> {
+    let s_ref = Parsec::<NoExt>::new_ref().unwrap();
+    let s = or!(
+        "S",
+        and!("ASB", atom!("a"), s_ref.clone(), atom!("b")),
+        and!("ASC", atom!("a"), s_ref.clone(), atom!("c")),
+        atom!("a")
+    );
+    s_ref.update_ref(Rc::clone(&s));
+    s
+}
+
+fn text(n: usize) -> String {
+    "a".repeat(n) + &"c".repeat(n - 1)
+}
+
+#[test]
+fn test_parsec_packrat() {
+    let parser = pathological();
+    let mut cache = Packrat::new();
+
+    for n in 1..12 {
+        let mut lex = Lex::new(text(n));
+        let refn = parser.parse(&mut lex).unwrap().unwrap();
+        assert_eq!(lex.to_cursor(), text(n).len());
+
+        let mut lex = Lex::new(text(n));
+        let node = parser.parse_packrat(&mut lex, &mut cache).unwrap().unwrap();
+        assert_eq!(lex.to_cursor(), text(n).len());
+        assert_eq!(node.to_string(), refn.to_string());
+        assert_eq!(node.to_string(), text(n));
+    }
+
+    let mut calls = vec![];
+    for n in [50, 100] {
+        let mut lex = Lex::new(text(n));
+        let node = parser.parse_packrat(&mut lex, &mut cache).unwrap().unwrap();
+        assert_eq!(node.to_string(), text(n));
+        println!("test_parsec_packrat n:{} calls:{}", n, cache.to_calls());
+        assert!(cache.to_hits() > 0);
+        assert!(cache.to_calls() <= 16 * text(n).len(), "{}", cache.to_calls());
+        calls.push(cache.to_calls());
+    }
+    // cache is reset across top-level calls, and calls grow linearly.
+    assert!(calls[1] <= (2 * calls[0]) + 16, "{:?}", calls);
+}
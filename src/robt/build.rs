@@ -1,10 +1,15 @@
 use cbordata::{self as cbor, Cbor, IntoCbor};
 
-use std::{cell::RefCell, convert::TryFrom, marker, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    convert::TryFrom,
+    marker,
+    rc::Rc,
+};
 
 use crate::{
     dbs,
-    robt::{self, Config, Entry, Flusher},
+    robt::{self, Codec, Config, Entry, Flusher},
     util, Error, Result,
 };
 
@@ -20,15 +25,48 @@ macro_rules! iter_result {
     }};
 }
 
+/// Shape of the btree, number of m-blocks and number of entries in them, at each
+/// level. Level-0 is the m-blocks just above the leaf nodes.
+#[derive(Default)]
+pub struct Shape {
+    levels: Vec<(u64, u64)>,
+}
+
+impl Shape {
+    fn add_block(&mut self, level: usize, n: u64) {
+        if self.levels.len() <= level {
+            self.levels.resize(level + 1, (0, 0));
+        }
+        self.levels[level].0 += 1;
+        self.levels[level].1 += n;
+    }
+
+    /// Return the depth of the tree, including the leaf nodes.
+    pub fn to_depth(&self) -> u64 {
+        match self.levels.iter().filter(|(n, _)| *n > 0).count() {
+            0 => 0,
+            n => (n as u64) + 1,
+        }
+    }
+
+    /// Return the total number of m-blocks and total entries in them.
+    pub fn to_mblocks(&self) -> (u64, u64) {
+        self.levels.iter().fold((0, 0), |(a, b), (x, y)| (a + x, b + y))
+    }
+}
+
 pub struct BuildMM<K, V, I>
 where
     V: dbs::Diff,
     I: Iterator<Item = Result<Entry<K, V>>>,
 {
     m_blocksize: usize,
+    codec: Codec,
     iflush: Rc<RefCell<Flusher>>,
     iter: Box<BuildIter<K, V, I>>,
-    entry: Option<(K, u64)>,
+    entry: Option<(K, u64, u64)>,
+    level: usize,
+    shape: Rc<RefCell<Shape>>,
 
     _val: marker::PhantomData<V>,
 }
@@ -42,12 +80,17 @@ where
         config: &Config,
         iflush: Rc<RefCell<Flusher>>,
         iter: BuildIter<K, V, I>,
+        level: usize,
+        shape: Rc<RefCell<Shape>>,
     ) -> Self {
         BuildMM {
             m_blocksize: config.m_blocksize,
+            codec: config.block_compression,
             iflush,
             iter: Box::new(iter),
             entry: None,
+            level,
+            shape,
 
             _val: marker::PhantomData,
         }
@@ -61,7 +104,7 @@ where
     <V as dbs::Diff>::Delta: IntoCbor,
     I: Iterator<Item = Result<Entry<K, V>>>,
 {
-    type Item = Result<(K, u64)>;
+    type Item = Result<(K, u64, u64)>;
 
     fn next(&mut self) -> Option<Self::Item> {
         let mut mblock = Vec::with_capacity(self.m_blocksize);
@@ -69,7 +112,7 @@ where
 
         let mut first_key: Option<K> = None;
         let mut curr_fpos = None;
-        let mut n = 0;
+        let (mut n, mut n_entries, mut count) = (0, 0, 0);
 
         iter_result!(Cbor::Major4(cbor::Info::Indefinite, vec![]).encode(&mut mblock));
 
@@ -79,20 +122,22 @@ where
                 entry.unwrap_or_else(|| self.iter.next())
             };
             match entry {
-                Some(Ok((key, fpos))) => {
+                Some(Ok((key, fpos, cnt))) => {
                     curr_fpos = Some(fpos);
                     n += 1;
 
                     first_key.get_or_insert_with(|| key.clone());
                     let ibytes = {
-                        let e = robt::Entry::<K, V>::new_mm(key.clone(), fpos);
+                        let e = robt::Entry::<K, V>::new_mm(key.clone(), fpos, cnt);
                         iter_result!(util::into_cbor_bytes(e))
                     };
                     if (mblock.len() + ibytes.len()) > block_size {
-                        self.entry = Some((key, fpos));
+                        self.entry = Some((key, fpos, cnt));
                         break;
                     }
                     mblock.extend_from_slice(&ibytes);
+                    n_entries += 1;
+                    count += cnt;
                 }
                 Some(Err(err)) => return Some(Err(err)),
                 None if first_key.is_some() => break,
@@ -107,10 +152,12 @@ where
 
         if n > 1 {
             curr_fpos = Some(self.iflush.borrow().to_fpos().unwrap_or(0));
+            let mblock = iter_result!(self.codec.encode(mblock));
             iter_result!(self.iflush.borrow_mut().flush(mblock));
+            self.shape.borrow_mut().add_block(self.level, n_entries);
         }
 
-        Some(Ok((first_key.unwrap(), curr_fpos.unwrap())))
+        Some(Ok((first_key.unwrap(), curr_fpos.unwrap(), count)))
     }
 }
 
@@ -120,9 +167,11 @@ where
     I: Iterator<Item = Result<Entry<K, V>>>,
 {
     m_blocksize: usize,
+    codec: Codec,
     iflush: Rc<RefCell<Flusher>>,
     iter: BuildZZ<K, V, I>,
-    entry: Option<(K, u64)>,
+    entry: Option<(K, u64, u64)>,
+    shape: Rc<RefCell<Shape>>,
 
     _val: marker::PhantomData<V>,
 }
@@ -136,12 +185,15 @@ where
         config: &Config,
         iflush: Rc<RefCell<Flusher>>,
         iter: BuildZZ<K, V, I>,
+        shape: Rc<RefCell<Shape>>,
     ) -> Self {
         BuildMZ {
             m_blocksize: config.m_blocksize,
+            codec: config.block_compression,
             iflush,
             iter,
             entry: None,
+            shape,
 
             _val: marker::PhantomData,
         }
@@ -155,13 +207,14 @@ where
     <V as dbs::Diff>::Delta: IntoCbor,
     I: Iterator<Item = Result<Entry<K, V>>>,
 {
-    type Item = Result<(K, u64)>;
+    type Item = Result<(K, u64, u64)>;
 
     fn next(&mut self) -> Option<Self::Item> {
         let mut mblock = Vec::with_capacity(self.m_blocksize);
         let block_size = self.m_blocksize.saturating_sub(1);
 
         let mut first_key: Option<K> = None;
+        let (mut n, mut count) = (0, 0);
 
         iter_result!(Cbor::Major4(cbor::Info::Indefinite, vec![]).encode(&mut mblock));
 
@@ -171,17 +224,19 @@ where
                 entry.unwrap_or_else(|| self.iter.next())
             };
             match entry {
-                Some(Ok((key, fpos))) => {
+                Some(Ok((key, fpos, cnt))) => {
                     first_key.get_or_insert_with(|| key.clone());
                     let ibytes = {
-                        let e = robt::Entry::<K, V>::new_mz(key.clone(), fpos);
+                        let e = robt::Entry::<K, V>::new_mz(key.clone(), fpos, cnt);
                         iter_result!(util::into_cbor_bytes(e))
                     };
                     if (mblock.len() + ibytes.len()) > block_size {
-                        self.entry = Some((key, fpos));
+                        self.entry = Some((key, fpos, cnt));
                         break;
                     }
                     mblock.extend_from_slice(&ibytes);
+                    n += 1;
+                    count += cnt;
                 }
                 Some(Err(err)) => return Some(Err(err)),
                 None if first_key.is_some() => break,
@@ -196,8 +251,11 @@ where
 
         let fpos = self.iflush.borrow().to_fpos().unwrap_or(0);
 
+        let mblock = iter_result!(self.codec.encode(mblock));
         iter_result!(self.iflush.borrow_mut().flush(mblock));
-        Some(Ok((first_key.unwrap(), fpos)))
+        self.shape.borrow_mut().add_block(0, n);
+
+        Some(Ok((first_key.unwrap(), fpos, count)))
     }
 }
 
@@ -208,12 +266,20 @@ where
 {
     z_blocksize: usize,
     v_blocksize: usize,
+    codec: Codec,
     value_in_vlog: bool,
+    value_compress_threshold: usize,
+    inline_value_max: usize,
     delta_ok: bool,
+    strict_keys: bool,
     iflush: Rc<RefCell<Flusher>>,
     vflush: Rc<RefCell<Flusher>>,
     entry: Option<Result<Entry<K, V>>>,
+    // serialized key of the previous entry, in strict mode.
+    prev_key: Option<Vec<u8>>,
     iter: Rc<RefCell<I>>,
+    // value-log bytes, from older value-log, referenced by this build.
+    n_rbytes: Rc<Cell<u64>>,
 
     _key: marker::PhantomData<K>,
     _val: marker::PhantomData<V>,
@@ -229,16 +295,23 @@ where
         iflush: Rc<RefCell<Flusher>>,
         vflush: Rc<RefCell<Flusher>>,
         iter: Rc<RefCell<I>>,
+        n_rbytes: Rc<Cell<u64>>,
     ) -> Self {
         BuildZZ {
             z_blocksize: config.z_blocksize,
             v_blocksize: config.v_blocksize,
+            codec: config.block_compression,
             value_in_vlog: config.value_in_vlog,
+            value_compress_threshold: config.value_compress_threshold,
+            inline_value_max: config.inline_value_max,
             delta_ok: config.delta_ok,
+            strict_keys: config.strict_keys,
             iflush,
             vflush,
             entry: None,
+            prev_key: None,
             iter,
+            n_rbytes,
 
             _key: marker::PhantomData,
             _val: marker::PhantomData,
@@ -253,7 +326,7 @@ where
     <V as dbs::Diff>::Delta: IntoCbor,
     I: Iterator<Item = Result<Entry<K, V>>>,
 {
-    type Item = Result<(K, u64)>;
+    type Item = Result<(K, u64, u64)>;
 
     fn next(&mut self) -> Option<Self::Item> {
         let mut zblock = Vec::with_capacity(self.z_blocksize);
@@ -261,6 +334,7 @@ where
         let block_size = self.z_blocksize.saturating_sub(1);
 
         let mut first_key: Option<K> = None;
+        let mut count = 0;
 
         iter_result!(Cbor::Major4(cbor::Info::Indefinite, vec![]).encode(&mut zblock));
 
@@ -270,7 +344,18 @@ where
         loop {
             let entry = match self.entry.take() {
                 Some(entry) => Some(entry),
-                None => iter.next(),
+                None => match iter.next() {
+                    // serialized keys are equal only if keys are equal.
+                    Some(Ok(entry)) if self.strict_keys => {
+                        let key = iter_result!(util::into_cbor_bytes(entry.to_key()));
+                        if self.prev_key.as_ref() == Some(&key) {
+                            return Some(err_at!(DuplicateKey, msg: "cbor {:?}", key));
+                        }
+                        self.prev_key = Some(key);
+                        Some(Ok(entry))
+                    }
+                    item => item,
+                },
             };
             match entry {
                 Some(Ok(mut entry)) => {
@@ -282,9 +367,12 @@ where
                         entry.drain_deltas()
                     }
                     first_key.get_or_insert_with(|| entry.as_key().clone());
-                    let (e, vbytes) = iter_result!(entry
-                        .clone()
-                        .into_reference(vfpos, self.value_in_vlog));
+                    let (e, vbytes) = iter_result!(entry.clone().into_reference(
+                        vfpos,
+                        self.value_in_vlog,
+                        self.value_compress_threshold,
+                        self.inline_value_max,
+                    ));
                     let ibytes = iter_result!(util::into_cbor_bytes(e));
 
                     if (zblock.len() + ibytes.len()) > block_size {
@@ -294,6 +382,8 @@ where
                     zblock.extend_from_slice(&ibytes);
                     vblock.extend_from_slice(&vbytes);
                     vfpos += u64::try_from(vbytes.len()).unwrap();
+                    count += 1;
+                    self.n_rbytes.set(self.n_rbytes.get() + entry.to_vlog_bytes());
                 }
                 Some(Err(err)) => return Some(Err(err)),
                 None if first_key.is_some() => break,
@@ -308,9 +398,10 @@ where
 
         let fpos = self.iflush.borrow().to_fpos().unwrap_or(0);
 
+        let zblock = iter_result!(self.codec.encode(zblock));
         iter_result!(self.vflush.borrow_mut().flush(vblock));
         iter_result!(self.iflush.borrow_mut().flush(zblock));
-        Some(Ok((first_key.unwrap(), fpos)))
+        Some(Ok((first_key.unwrap(), fpos, count)))
     }
 }
 
@@ -350,7 +441,7 @@ where
     <V as dbs::Diff>::Delta: IntoCbor,
     I: Iterator<Item = Result<Entry<K, V>>>,
 {
-    type Item = Result<(K, u64)>;
+    type Item = Result<(K, u64, u64)>;
 
     fn next(&mut self) -> Option<Self::Item> {
         match self {
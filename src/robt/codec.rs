@@ -0,0 +1,146 @@
+use cbordata::Cborize;
+
+use std::{
+    convert::TryFrom,
+    fs,
+    io::{self, Read, Seek},
+};
+
+use crate::{read_file, util, Error, Result, ResultExt};
+
+const CODEC_VER: u32 = 0x00010001;
+
+/// Compression codec for btree blocks.
+///
+/// Uncompressed blocks are persisted as fixed size blocks, refer to
+/// [Config::set_blocksize][crate::robt::Config::set_blocksize]. Compressed
+/// blocks are persisted as variable sized frames, that is, a 4-byte big-endian
+/// length, followed by compressed payload, followed by 1-byte trailer carrying
+/// the codec-id.
+///
+/// Only z-blocks (leaf nodes) and m-blocks (intermediate nodes) are compressed.
+/// Values and deltas in value-log are addressed by their file-position, hence
+/// value-log is always persisted uncompressed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Cborize)]
+pub enum Codec {
+    /// Blocks are persisted uncompressed.
+    None,
+    /// Blocks are compressed using [zstd][zstd], with compression `level`.
+    ///
+    /// [zstd]: https://facebook.github.io/zstd
+    Zstd { level: i32 },
+    /// Blocks are compressed using [lz4][lz4].
+    ///
+    /// [lz4]: https://lz4.github.io/lz4
+    Lz4,
+}
+
+impl Default for Codec {
+    fn default() -> Codec {
+        Codec::None
+    }
+}
+
+impl Codec {
+    const ID: u32 = CODEC_VER;
+
+    /// Return the codec-id, persisted in block trailer.
+    pub fn to_id(&self) -> u8 {
+        match self {
+            Codec::None => 0,
+            Codec::Zstd { .. } => 1,
+            Codec::Lz4 => 2,
+        }
+    }
+
+    pub fn is_none(&self) -> bool {
+        matches!(self, Codec::None)
+    }
+
+    // compress a fixed size block into a frame, for Codec::None block is returned
+    // as is.
+    pub(crate) fn encode(&self, block: Vec<u8>) -> Result<Vec<u8>> {
+        let data = match self {
+            Codec::None => return Ok(block),
+            Codec::Zstd { level } => {
+                err_at!(Fatal, zstd::bulk::compress(&block, *level))?
+            }
+            Codec::Lz4 => lz4_flex::compress_prepend_size(&block),
+        };
+
+        let len = err_at!(FailConvert, u32::try_from(data.len()))?;
+        let mut frame = Vec::with_capacity(data.len() + 5);
+        frame.extend_from_slice(&len.to_be_bytes());
+        frame.extend_from_slice(&data);
+        frame.push(self.to_id());
+
+        Ok(frame)
+    }
+
+    // read the block at `fpos`, decompressing them if required. Uncompressed
+    // blocks are always of `blocksize` length.
+    pub(crate) fn read_block(
+        &self,
+        fd: &mut fs::File,
+        fpos: u64,
+        blocksize: usize,
+    ) -> Result<Vec<u8>> {
+        let read = |fpos: u64, n: usize| {
+            let seek = io::SeekFrom::Start(fpos);
+            read_file!(fd, seek, n, "read block")
+        };
+        self.do_read_block(read, fpos, blocksize).at_fpos(fpos)
+    }
+
+    // same as read_block, but use positional reads that don't move the file
+    // cursor, hence `fd` can be shared with other handles cloned from it.
+    pub(crate) fn read_block_at(
+        &self,
+        fd: &fs::File,
+        fpos: u64,
+        blocksize: usize,
+    ) -> Result<Vec<u8>> {
+        let read = |fpos: u64, n: usize| util::files::read_at(fd, fpos, n);
+        self.do_read_block(read, fpos, blocksize).at_fpos(fpos)
+    }
+
+    fn do_read_block<F>(
+        &self,
+        mut read: F,
+        fpos: u64,
+        blocksize: usize,
+    ) -> Result<Vec<u8>>
+    where
+        F: FnMut(u64, usize) -> Result<Vec<u8>>,
+    {
+        if let Codec::None = self {
+            return read(fpos, blocksize);
+        }
+
+        let len = {
+            let data = read(fpos, 4)?;
+            u32::from_be_bytes([data[0], data[1], data[2], data[3]])
+        };
+        let mut data = {
+            let n = err_at!(FailConvert, usize::try_from(len))?;
+            read(fpos + 4, n + 1)?
+        };
+
+        match data.pop() {
+            Some(id) if id == self.to_id() => (),
+            id => err_at!(InvalidFile, msg: "codec {:?} mismatch {:?}", self, id)?,
+        }
+
+        let block = match self {
+            Codec::None => unreachable!(),
+            Codec::Zstd { .. } => {
+                err_at!(InvalidFile, zstd::bulk::decompress(&data, blocksize))?
+            }
+            Codec::Lz4 => {
+                err_at!(InvalidFile, lz4_flex::decompress_size_prepended(&data))?
+            }
+        };
+
+        Ok(block)
+    }
+}
@@ -2,7 +2,14 @@ use cbordata::Cborize;
 
 use std::{ffi, path};
 
-use crate::robt::files::{IndexFileName, VlogFileName};
+use crate::{
+    dbs,
+    robt::{
+        files::{validate_name, IndexFileName, VlogFileName},
+        Codec,
+    },
+    Result,
+};
 
 /// Default value for z-block-size, 4 * 1024 bytes.
 pub const ZBLOCKSIZE: usize = 4 * 1024; // 4KB leaf node
@@ -15,7 +22,27 @@ pub const VBLOCKSIZE: usize = 4 * 1024; // 4KB of blobs.
 /// index blocks.
 pub const FLUSH_QUEUE_SIZE: usize = 64;
 
-const STATS_VER: u32 = 0x000b0001;
+const STATS_VER: u32 = 0x000b0010;
+
+/// Policy to fsync index-file and value-log file while building an index.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BuildFsync {
+    /// Never fsync, build is faster but index is not crash durable. Useful
+    /// for throwaway indexes.
+    Never,
+    /// Fsync every time the specified number of bytes are flushed, and once
+    /// more at the end of build. Reduces the amount of dirty pages that are
+    /// lost on crash, for very large builds.
+    Periodic(u64),
+    /// Fsync once at the end of build.
+    Final,
+}
+
+impl Default for BuildFsync {
+    fn default() -> BuildFsync {
+        BuildFsync::Final
+    }
+}
 
 /// Compose a path to index file identified by unique `name` under `dir`.
 pub fn to_index_location(dir: &ffi::OsStr, name: &str) -> ffi::OsString {
@@ -72,6 +99,66 @@ pub struct Config {
     ///
     /// Default: [FLUSH_QUEUE_SIZE]
     pub flush_queue_size: usize,
+    /// Compress leaf-node and intermediate-node blocks using codec.
+    ///
+    /// Default: [Codec::None]
+    pub block_compression: Codec,
+    /// Build one bloom filter for each sub-tree under the root, instead of a
+    /// single bloom filter for the entire index.
+    ///
+    /// Default: false
+    pub bloom_shards: bool,
+    /// Load the bloom filter lazily, on the first `get` on an opened index,
+    /// instead of loading it while opening the index.
+    ///
+    /// Default: false
+    pub lazy_bitmap: bool,
+    /// Open the index even if its value-log file is missing, refer to
+    /// [Config::set_values_optional].
+    ///
+    /// Default: false
+    pub values_optional: bool,
+    /// Build a secondary bloom filter over key prefixes, alongside the main
+    /// bloom filter, refer to [Config::set_prefix_bloom].
+    ///
+    /// Default: false
+    pub prefix_bloom: bool,
+    /// Compress values, persisted in value-log, whose encoded size exceeds
+    /// this threshold, refer to [Config::set_value_compress_threshold]. ZERO
+    /// disables value compression.
+    ///
+    /// Default: 0
+    pub value_compress_threshold: usize,
+    /// Keep values, whose encoded size is at most this many bytes, inline in
+    /// the leaf node even when [Config::value_in_vlog] is true, refer to
+    /// [Config::set_inline_value_max]. ZERO spills all values to value-log.
+    ///
+    /// Default: 0
+    pub inline_value_max: usize,
+    /// Fsync policy while building the index.
+    ///
+    /// Default: [BuildFsync::Final]
+    pub build_fsync: BuildFsync,
+    /// Fail the build if input has two entries with the same key, refer to
+    /// [Config::set_strict_keys].
+    ///
+    /// Default: true
+    pub strict_keys: bool,
+    /// Number of m-block levels, below the root, to decode while opening the
+    /// index and keep resident, refer to [Config::set_pin_top_levels].
+    ///
+    /// Default: 0
+    pub pin_top_levels: usize,
+    /// Build the index in descending key order, refer to
+    /// [Config::set_descending].
+    ///
+    /// Default: false
+    pub descending: bool,
+    /// Directory in which value-log file is created, refer to
+    /// [Config::set_vlog_dir]. If None, value-log is created under `dir`.
+    ///
+    /// Default: None
+    pub vlog_dir: Option<ffi::OsString>,
     pub(crate) vlog_location: Option<ffi::OsString>,
 }
 
@@ -86,6 +173,19 @@ impl From<Stats> for Config {
             delta_ok: val.delta_ok,
             value_in_vlog: val.value_in_vlog,
             flush_queue_size: FLUSH_QUEUE_SIZE,
+            block_compression: val.block_compression,
+            bloom_shards: val.bloom_shards,
+            lazy_bitmap: val.lazy_bitmap,
+            values_optional: val.values_optional,
+            // prefix function is not persisted, refer to Builder::set_prefix_fn.
+            prefix_bloom: false,
+            value_compress_threshold: val.value_compress_threshold,
+            inline_value_max: val.inline_value_max,
+            build_fsync: BuildFsync::default(),
+            strict_keys: true,
+            pin_top_levels: val.pin_top_levels,
+            descending: val.descending,
+            vlog_dir: val.vlog_dir,
             vlog_location: val.vlog_location,
         }
     }
@@ -104,10 +204,30 @@ impl Config {
             delta_ok: true,
             value_in_vlog: false,
             flush_queue_size: FLUSH_QUEUE_SIZE,
+            block_compression: Codec::None,
+            bloom_shards: false,
+            lazy_bitmap: false,
+            values_optional: false,
+            prefix_bloom: false,
+            value_compress_threshold: 0,
+            inline_value_max: 0,
+            build_fsync: BuildFsync::default(),
+            strict_keys: true,
+            pin_top_levels: 0,
+            descending: false,
+            vlog_dir: None,
             vlog_location: None,
         }
     }
 
+    /// Set the name of the index, name is validated for file-name safety,
+    /// refer to [validate_name][crate::robt::validate_name].
+    pub fn set_name(&mut self, name: &str) -> Result<&mut Self> {
+        validate_name(name)?;
+        self.name = name.to_string();
+        Ok(self)
+    }
+
     /// Configure block size for leaf-node, intermediate-node, and value-log blocks
     pub fn set_blocksize(&mut self, z: usize, v: usize, m: usize) -> &mut Self {
         self.z_blocksize = z;
@@ -136,12 +256,131 @@ impl Config {
         self
     }
 
+    /// Create value-log file under `dir`, instead of the index directory. Useful
+    /// to place the index file on a fast device and the larger value-log on a
+    /// separate mount. The directory is persisted along with the index, and
+    /// can be overridden while opening the index, refer to
+    /// [Index::open_with_vlog_dir][crate::robt::Index::open_with_vlog_dir].
+    pub fn set_vlog_dir(&mut self, dir: &ffi::OsStr) -> &mut Self {
+        self.vlog_dir = Some(dir.to_os_string());
+        self
+    }
+
     /// Set flush queue size, increasing the queue size will improve batch
     /// flushing.
     pub fn set_flush_queue_size(&mut self, size: usize) -> &mut Self {
         self.flush_queue_size = size;
         self
     }
+
+    /// Compress leaf-node and intermediate-node blocks, using `codec`, before
+    /// flushing them to disk. Refer to [Codec] for details.
+    pub fn set_block_compression(&mut self, codec: Codec) -> &mut Self {
+        self.block_compression = codec;
+        self
+    }
+
+    /// Build one bloom filter for each sub-tree under the root block. Bloom
+    /// shards are loaded lazily, only when a key in its sub-tree is looked up,
+    /// this bounds the resident memory for bloom filters in large indexes.
+    pub fn set_bloom_shards(&mut self, bloom_shards: bool) -> &mut Self {
+        self.bloom_shards = bloom_shards;
+        self
+    }
+
+    /// Load the bloom filter lazily, only on the first `get` call on the
+    /// opened index, and cache it thereafter. Iterating or ranging over the
+    /// index shall not load the bloom filter. Useful for services that open
+    /// many indexes that are rarely looked up.
+    pub fn set_lazy_bitmap(&mut self, lazy_bitmap: bool) -> &mut Self {
+        self.lazy_bitmap = lazy_bitmap;
+        self
+    }
+
+    /// Open the index even if its value-log file is missing, say, when the
+    /// value-log is archived away and only key level operations, like
+    /// counting and iterating over keys, are needed. Operations that read
+    /// values or deltas from the missing value-log shall fail with
+    /// `IOError`, "vlog unavailable".
+    pub fn set_values_optional(&mut self, values_optional: bool) -> &mut Self {
+        self.values_optional = values_optional;
+        self
+    }
+
+    /// Build a secondary bloom filter over key prefixes, computed using the
+    /// function supplied via [Builder::set_prefix_fn][set_prefix_fn]. Use
+    /// [Index::may_contain_prefix][may_contain_prefix] to short-circuit range
+    /// scans over absent prefixes.
+    ///
+    /// [set_prefix_fn]: crate::robt::Builder::set_prefix_fn
+    /// [may_contain_prefix]: crate::robt::Index::may_contain_prefix
+    pub fn set_prefix_bloom(&mut self, prefix_bloom: bool) -> &mut Self {
+        self.prefix_bloom = prefix_bloom;
+        self
+    }
+
+    /// Compress values, persisted in value-log, whose encoded size exceeds
+    /// `bytes`. Compressing tiny values is counterproductive, hence only
+    /// large values are compressed, using [lz4][Codec::Lz4], and values that
+    /// don't shrink are persisted as is. Compressed values are flagged in
+    /// their value-log header and transparently decompressed while reading.
+    /// Applicable only when values are persisted in value-log, refer to
+    /// [Config::set_value_log]. Set `bytes` to ZERO to disable compression.
+    pub fn set_value_compress_threshold(&mut self, bytes: usize) -> &mut Self {
+        self.value_compress_threshold = bytes;
+        self
+    }
+
+    /// Persist values, whose encoded size is at most `bytes`, inline in the
+    /// leaf node and spill larger values to value-log. The choice is recorded
+    /// per entry, so lookups on small values don't pay for a value-log read.
+    /// Applicable only when values are persisted in value-log, refer to
+    /// [Config::set_value_log]. Set `bytes` to ZERO to spill all values.
+    pub fn set_inline_value_max(&mut self, bytes: usize) -> &mut Self {
+        self.inline_value_max = bytes;
+        self
+    }
+
+    /// Set the fsync policy for index-file and value-log file while building
+    /// the index. Refer to [BuildFsync] for details.
+    pub fn set_build_fsync(&mut self, policy: BuildFsync) -> &mut Self {
+        self.build_fsync = policy;
+        self
+    }
+
+    /// Builds assume entries in strictly increasing key order, in strict mode,
+    /// which is the default, build fails with `DuplicateKey` error if two
+    /// consecutive entries have the same key. Pass `strict` as false if the
+    /// input intentionally carries more than one entry for the same key,
+    /// like un-merged versions, in which case it is the caller's
+    /// responsibility to make sense of the built index.
+    pub fn set_strict_keys(&mut self, strict: bool) -> &mut Self {
+        self.strict_keys = strict;
+        self
+    }
+
+    /// Decode m-blocks from the top `levels` number of levels, below the
+    /// root block, while opening the index and keep them resident. Root block
+    /// is always resident. These blocks are touched on every lookup, pinning
+    /// them reduces a point lookup to one z-block read and, if values are in
+    /// value-log, one value-log read. Note that a level holds upto `fanout`
+    /// times more blocks than the level above it, refer to
+    /// [Stats::to_fanout].
+    pub fn set_pin_top_levels(&mut self, levels: usize) -> &mut Self {
+        self.pin_top_levels = levels;
+        self
+    }
+
+    /// Build the index in descending key order, for workloads that mostly
+    /// scan latest-first, like time series. Entries supplied to the builder
+    /// shall be sorted in descending order. Blocks are laid out in the input
+    /// order, hence [Index::reverse][crate::robt::Index::reverse] is the
+    /// optimized direction, while [Index::iter][crate::robt::Index::iter]
+    /// walks the blocks backwards to yield entries in ascending order.
+    pub fn set_descending(&mut self, descending: bool) -> &mut Self {
+        self.descending = descending;
+        self
+    }
 }
 
 impl Config {
@@ -149,11 +388,19 @@ impl Config {
         to_index_location(&self.dir, &self.name)
     }
 
+    /// Return the directory in which value-log file is created.
+    pub fn to_vlog_dir(&self) -> ffi::OsString {
+        match &self.vlog_dir {
+            Some(dir) => dir.clone(),
+            None => self.dir.clone(),
+        }
+    }
+
     pub fn to_vlog_location(&self) -> Option<ffi::OsString> {
         if self.value_in_vlog || self.delta_ok {
             let loc = match &self.vlog_location {
                 Some(loc) => loc.clone(),
-                None => to_vlog_location(&self.dir, &self.name),
+                None => to_vlog_location(&self.to_vlog_dir(), &self.name),
             };
             Some(loc)
         } else {
@@ -177,7 +424,31 @@ pub struct Stats {
     pub delta_ok: bool,
     /// Comes from [Config] type.
     pub value_in_vlog: bool,
+    /// Comes from [Config] type.
+    pub block_compression: Codec,
+    /// Comes from [Config] type.
+    pub bloom_shards: bool,
+    /// Comes from [Config] type.
+    pub lazy_bitmap: bool,
+    /// Comes from [Config] type.
+    pub values_optional: bool,
+    /// Comes from [Config] type.
+    pub prefix_bloom: bool,
+    /// Comes from [Config] type.
+    pub value_compress_threshold: usize,
+    /// Comes from [Config] type.
+    pub inline_value_max: usize,
+    /// Comes from [Config] type.
+    pub pin_top_levels: usize,
+    /// Comes from [Config] type.
+    pub descending: bool,
+    /// Identifies the custom key comparator, refer
+    /// [Builder::set_comparator][crate::robt::Builder::set_comparator]. Empty
+    /// if keys are ordered by [Ord].
+    pub comparator: String,
 
+    /// Comes from [Config] type.
+    pub vlog_dir: Option<ffi::OsString>,
     /// Optional value log file if either [Config::value_in_vlog] or [Config::delta_ok]
     /// is true.
     pub vlog_location: Option<ffi::OsString>,
@@ -190,15 +461,100 @@ pub struct Stats {
     pub seqno: u64,
     /// Older size of value-log file, applicable only in incremental build.
     pub n_abytes: u64,
+    /// Number of value-log bytes referenced by this index, that is, bytes appended
+    /// by this build and bytes, out of `n_abytes`, still referenced by this index.
+    pub n_vbytes: u64,
+    /// Number of value-log bytes, out of `n_abytes`, no longer referenced by this
+    /// index. Refer [Index::fragmentation][crate::robt::Index::fragmentation].
+    pub n_gbytes: u64,
+    /// Depth of the btree, including the root and leaf nodes.
+    pub tree_depth: u64,
+    /// Number of intermediate blocks, m-blocks, in the btree.
+    pub n_mblocks: u64,
+    /// Total number of entries across all m-blocks. Refer [Stats::to_fanout].
+    pub n_mentries: u64,
 
     /// Time taken to build this btree.
     pub build_time: u64,
     /// Timestamp when this index was built, from UNIX EPOCH, in secs, in UTC timezone.
     pub epoch: u64,
+    /// Incremented every time the bloom filter is re-built, refer
+    /// [Index::rebuild_bitmap][crate::robt::Index::rebuild_bitmap]. Zero for
+    /// freshly built index.
+    pub bitmap_version: u64,
 }
 
 impl Stats {
     const ID: u32 = STATS_VER;
+
+    /// Return the average fanout of intermediate blocks, m-blocks.
+    pub fn to_fanout(&self) -> f64 {
+        match self.n_mblocks {
+            0 => 0.0,
+            n => (self.n_mentries as f64) / (n as f64),
+        }
+    }
+}
+
+impl dbs::ToJson for Stats {
+    fn to_json(&self) -> String {
+        let null = "null".to_string();
+        let vlog_dir = self
+            .vlog_dir
+            .as_ref()
+            .map_or(null.clone(), |dir| format!("{:?}", dir.to_string_lossy()));
+        let vlog_location = self
+            .vlog_location
+            .as_ref()
+            .map_or(null, |loc| format!("{:?}", loc.to_string_lossy()));
+        format!(
+            concat!(
+                r#"{{ "robt": {{ "name": {:?}, "z_blocksize": {}, "#,
+                r#""m_blocksize": {}, "v_blocksize": {}, "delta_ok": {}, "#,
+                r#""value_in_vlog": {}, "block_compression": "{:?}", "#,
+                r#""bloom_shards": {}, "lazy_bitmap": {}, "values_optional": {}, "#,
+                r#""prefix_bloom": {}, "#,
+                r#""value_compress_threshold": {}, "inline_value_max": {}, "#,
+                r#""pin_top_levels": {}, "#,
+                r#""descending": {}, "#,
+                r#""comparator": {:?}, "#,
+                r#""vlog_dir": {}, "vlog_location": {}, "#,
+                r#""n_count": {}, "n_deleted": {}, "seqno": {}, "#,
+                r#""n_abytes": {}, "n_vbytes": {}, "n_gbytes": {}, "#,
+                r#""tree_depth": {}, "fanout": {:.2}, "#,
+                r#""build_time": {}, "epoch": {}, "bitmap_version": {} }} }}"#,
+            ),
+            self.name,
+            self.z_blocksize,
+            self.m_blocksize,
+            self.v_blocksize,
+            self.delta_ok,
+            self.value_in_vlog,
+            self.block_compression,
+            self.bloom_shards,
+            self.lazy_bitmap,
+            self.values_optional,
+            self.prefix_bloom,
+            self.value_compress_threshold,
+            self.inline_value_max,
+            self.pin_top_levels,
+            self.descending,
+            self.comparator,
+            vlog_dir,
+            vlog_location,
+            self.n_count,
+            self.n_deleted,
+            self.seqno,
+            self.n_abytes,
+            self.n_vbytes,
+            self.n_gbytes,
+            self.tree_depth,
+            self.to_fanout(),
+            self.build_time,
+            self.epoch,
+            self.bitmap_version,
+        )
+    }
 }
 
 impl From<Config> for Stats {
@@ -210,15 +566,32 @@ impl From<Config> for Stats {
             m_blocksize: config.m_blocksize,
             v_blocksize: config.v_blocksize,
             delta_ok: config.delta_ok,
+            vlog_dir: config.vlog_dir.clone(),
             vlog_location: config.to_vlog_location(),
             value_in_vlog: config.value_in_vlog,
+            block_compression: config.block_compression,
+            bloom_shards: config.bloom_shards,
+            lazy_bitmap: config.lazy_bitmap,
+            values_optional: config.values_optional,
+            prefix_bloom: config.prefix_bloom,
+            value_compress_threshold: config.value_compress_threshold,
+            inline_value_max: config.inline_value_max,
+            pin_top_levels: config.pin_top_levels,
+            descending: config.descending,
+            comparator: String::default(),
             // comes from index build
             n_count: u64::default(),
             n_deleted: usize::default(),
             seqno: u64::default(),
             n_abytes: u64::default(),
+            n_vbytes: u64::default(),
+            n_gbytes: u64::default(),
+            tree_depth: u64::default(),
+            n_mblocks: u64::default(),
+            n_mentries: u64::default(),
             build_time: u64::default(),
             epoch: u64::default(),
+            bitmap_version: u64::default(),
         }
     }
 }
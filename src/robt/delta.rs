@@ -0,0 +1,126 @@
+use cbordata::{FromCbor, IntoCbor};
+
+use std::{borrow::Borrow, hash::Hash, ops::Bound};
+
+use crate::{
+    dbs,
+    robt::{merge, scans::MergeScan, Config, Index},
+    Error, Result,
+};
+
+/// Read a base index along with the delta indexes built on top of it.
+///
+/// For append heavy workloads, like time-series ingestion, re-building the
+/// entire index for every batch is wasteful. Instead, each batch can be built
+/// as a small delta index, using [Builder::set_base][crate::robt::Builder::set_base],
+/// that holds only the new batch of entries and a reference to the base
+/// index. DeltaIndex merges deltas over the base while reading, entry from
+/// the newest index takes precedence. Periodically [compact][DeltaIndex::compact]
+/// the deltas into a new base index.
+pub struct DeltaIndex<K, V, B>
+where
+    K: FromCbor,
+    V: dbs::Diff + FromCbor,
+    <V as dbs::Diff>::Delta: FromCbor,
+    B: dbs::Bloom,
+{
+    base: Index<K, V, B>,
+    // ordered from oldest to newest delta.
+    deltas: Vec<Index<K, V, B>>,
+}
+
+impl<K, V, B> DeltaIndex<K, V, B>
+where
+    K: FromCbor,
+    V: dbs::Diff + FromCbor,
+    <V as dbs::Diff>::Delta: FromCbor,
+    B: dbs::Bloom,
+{
+    /// Compose `base` index with its `deltas`, deltas shall be ordered from
+    /// oldest to newest, and each delta shall be built over `base`.
+    pub fn new(base: Index<K, V, B>, deltas: Vec<Index<K, V, B>>) -> Result<Self> {
+        for delta in deltas.iter() {
+            match delta.to_base()? {
+                Some(r) if r.name == base.to_name() => {
+                    let (seqno, epoch) = (base.to_seqno(), base.to_stats().epoch);
+                    if r.seqno != seqno || r.epoch != epoch {
+                        err_at!(
+                            InvalidInput,
+                            msg: "delta {} over stale base {}", delta.to_name(), r.name
+                        )?
+                    }
+                }
+                Some(r) => err_at!(
+                    InvalidInput,
+                    msg: "delta {} over base {} not {}", delta.to_name(), r.name,
+                    base.to_name()
+                )?,
+                None => err_at!(InvalidInput, msg: "{} not a delta", delta.to_name())?,
+            }
+        }
+
+        Ok(DeltaIndex { base, deltas })
+    }
+
+    pub fn as_base(&self) -> &Index<K, V, B> {
+        &self.base
+    }
+
+    pub fn as_deltas(&self) -> &[Index<K, V, B>] {
+        &self.deltas
+    }
+
+    /// Return the latest version of entry for `key`, looked up from the
+    /// newest delta to the base index. Tombstone in a newer delta shadows
+    /// the older entries and is returned as is, refer to [Index::get].
+    pub fn get<Q>(&mut self, key: &Q) -> Result<dbs::Entry<K, V>>
+    where
+        K: Clone + Borrow<Q>,
+        Q: Ord + Hash + ?Sized + ToOwned<Owned = K>,
+    {
+        for delta in self.deltas.iter_mut().rev() {
+            match delta.get(key) {
+                Ok(entry) => return Ok(entry),
+                Err(Error::NotFound(_, _)) => (),
+                Err(err) => return Err(err),
+            }
+        }
+        self.base.get(key)
+    }
+
+    /// Iterate over all entries, latest version only, merged across base and
+    /// deltas.
+    pub fn iter(&mut self) -> Result<impl Iterator<Item = Result<dbs::Entry<K, V>>> + '_>
+    where
+        K: Clone + Ord,
+        <V as dbs::Diff>::Delta: From<V>,
+    {
+        let mut iters = vec![];
+        let r = (Bound::<K>::Unbounded, Bound::<K>::Unbounded);
+        iters.push(self.base.iter(r)?);
+        for delta in self.deltas.iter_mut() {
+            let r = (Bound::<K>::Unbounded, Bound::<K>::Unbounded);
+            iters.push(delta.iter(r)?);
+        }
+
+        Ok(MergeScan::new(iters).map(|item| item.map(|entry| entry.latest())))
+    }
+
+    /// Fold deltas into a new base index specified by [Config], older versions
+    /// are compacted as per `cutoff`. Refer to [merge] for details.
+    pub fn compact(self, config: Config, cutoff: dbs::Cutoff) -> Result<Index<K, V, B>>
+    where
+        K: Clone + Ord + Hash + IntoCbor,
+        V: IntoCbor,
+        <V as dbs::Diff>::Delta: IntoCbor + From<V>,
+        B: Clone,
+    {
+        let mut inputs = vec![self.base];
+        inputs.extend(self.deltas.into_iter());
+        merge(inputs, config, cutoff)
+    }
+}
+
+#[cfg(test)]
+#[path = "delta_test.rs"]
+mod delta_test;
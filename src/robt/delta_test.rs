@@ -0,0 +1,121 @@
+use rand::{prelude::random, rngs::StdRng, Rng, SeedableRng};
+
+use std::{collections::BTreeMap, fs};
+
+use super::*;
+use crate::{bitmaps::CRoaring, robt::Builder};
+
+#[test]
+fn test_robt_delta_index() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_delta_index {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_delta_index");
+    fs::remove_dir_all(&dir).ok();
+
+    let mut refs: BTreeMap<u64, (u64, Option<u64>)> = BTreeMap::new();
+    let mut seqno = 0;
+
+    let base = {
+        let entries: Vec<dbs::Entry<u64, u64>> = (0..10_000_u64)
+            .map(|key| {
+                seqno += 1;
+                let value = rng.gen::<u64>();
+                refs.insert(key, (seqno, Some(value)));
+                dbs::Entry::new(key, value, seqno)
+            })
+            .collect();
+        let config = Config::new(dir.as_os_str(), "delta-base");
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = entries.into_iter().map(Ok);
+        build.build_index(iter, CRoaring::new(), None).unwrap()
+    };
+    assert_eq!(base.to_base().unwrap(), None);
+
+    // each delta holds a small batch of updates, deletes and new keys.
+    let mut deltas = vec![];
+    for i in 0..3 {
+        let mut keys: Vec<u64> = (0..200).map(|_| rng.gen::<u64>() % 12_000).collect();
+        keys.sort_unstable();
+        keys.dedup();
+
+        let entries: Vec<dbs::Entry<u64, u64>> = keys
+            .iter()
+            .map(|key| {
+                seqno += 1;
+                match rng.gen::<u8>() % 5 {
+                    0 => {
+                        refs.insert(*key, (seqno, None));
+                        dbs::Entry::new_delete(*key, seqno)
+                    }
+                    _ => {
+                        let value = rng.gen::<u64>();
+                        refs.insert(*key, (seqno, Some(value)));
+                        dbs::Entry::new(*key, value, seqno)
+                    }
+                }
+            })
+            .collect();
+
+        let config = Config::new(dir.as_os_str(), &format!("delta-{}", i));
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        build.set_base(&base);
+        let iter = entries.into_iter().map(Ok);
+        let delta = build.build_index(iter, CRoaring::new(), None).unwrap();
+
+        let baseref = delta.to_base().unwrap().unwrap();
+        assert_eq!(baseref.name, base.to_name());
+        assert_eq!(baseref.seqno, base.to_seqno());
+        assert_eq!(delta.len(), keys.len());
+        let (a, b) = (delta.footprint().unwrap(), base.footprint().unwrap());
+        assert!(a < (b / 4), "delta:{} base:{}", a, b);
+        deltas.push(delta);
+    }
+
+    // base index is not a delta.
+    {
+        let other = Index::open(dir.as_os_str(), "delta-base").unwrap();
+        let base = Index::open(dir.as_os_str(), "delta-base").unwrap();
+        assert!(DeltaIndex::<u64, u64, CRoaring>::new(base, vec![other]).is_err());
+    }
+
+    let mut index = DeltaIndex::new(base, deltas).unwrap();
+    for (key, (seqno, value)) in refs.iter() {
+        let entry = index.get(key).unwrap();
+        assert_eq!(entry.to_seqno(), *seqno, "key {}", key);
+        assert_eq!(entry.to_value(), *value, "key {}", key);
+    }
+    match index.get(&20_000_u64) {
+        Err(Error::NotFound(_, _)) => (),
+        res => panic!("unexpected {:?}", res.map(|e| e.to_key())),
+    }
+
+    let items: Vec<(u64, (u64, Option<u64>))> = index
+        .iter()
+        .unwrap()
+        .map(|e| e.unwrap())
+        .map(|e| (e.to_key(), (e.to_seqno(), e.to_value())))
+        .collect();
+    let ref_items: Vec<(u64, (u64, Option<u64>))> =
+        refs.iter().map(|(k, v)| (*k, *v)).collect();
+    assert_eq!(items, ref_items);
+
+    // fold deltas into a fresh base.
+    let config = Config::new(dir.as_os_str(), "delta-compact");
+    let mut index = index.compact(config, dbs::Cutoff::new_mono()).unwrap();
+    assert_eq!(index.to_base().unwrap(), None);
+    let r = (Bound::<u64>::Unbounded, Bound::<u64>::Unbounded);
+    let items: Vec<(u64, (u64, Option<u64>))> = index
+        .iter(r)
+        .unwrap()
+        .map(|e| e.unwrap())
+        .map(|e| (e.to_key(), (e.to_seqno(), e.to_value())))
+        .collect();
+    let ref_items: Vec<(u64, (u64, Option<u64>))> =
+        ref_items.into_iter().filter(|(_, (_, v))| v.is_some()).collect();
+    assert_eq!(items, ref_items);
+
+    index.purge().unwrap();
+    fs::remove_dir_all(&dir).ok();
+}
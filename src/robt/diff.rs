@@ -0,0 +1,124 @@
+use cbordata::FromCbor;
+
+use std::cmp;
+
+use crate::{dbs, robt::Iter, Result};
+
+/// Key level change between an older and a newer version of an index, refer
+/// to [Index::diff][crate::robt::Index::diff].
+#[derive(Clone)]
+pub enum Change<K, V>
+where
+    V: dbs::Diff,
+{
+    /// Key is missing, or deleted, in older version and live in newer version.
+    Added(dbs::Entry<K, V>),
+    /// Key is live in both versions, with newer version carrying a new seqno.
+    Updated(dbs::Entry<K, V>),
+    /// Key is live in older version and missing, or deleted, in newer version.
+    Deleted(K),
+}
+
+impl<K, V> Change<K, V>
+where
+    V: dbs::Diff,
+{
+    pub fn as_key(&self) -> &K {
+        match self {
+            Change::Added(entry) | Change::Updated(entry) => entry.as_key(),
+            Change::Deleted(key) => key,
+        }
+    }
+}
+
+// merge-walk the latest version of entries from older and newer version of
+// the index, in key order.
+pub(crate) struct DiffIter<'a, K, V>
+where
+    V: dbs::Diff,
+{
+    old: Iter<'a, K, V>,
+    new: Iter<'a, K, V>,
+    old_head: Option<dbs::Entry<K, V>>,
+    new_head: Option<dbs::Entry<K, V>>,
+}
+
+impl<'a, K, V> DiffIter<'a, K, V>
+where
+    K: Clone + Ord + FromCbor,
+    V: dbs::Diff + FromCbor,
+    <V as dbs::Diff>::Delta: FromCbor,
+{
+    pub(crate) fn new(mut old: Iter<'a, K, V>, mut new: Iter<'a, K, V>) -> Result<Self> {
+        let old_head = old.next().transpose()?;
+        let new_head = new.next().transpose()?;
+        Ok(DiffIter { old, new, old_head, new_head })
+    }
+
+    fn next_old(&mut self) -> Result<dbs::Entry<K, V>> {
+        let entry = self.old_head.take().unwrap();
+        self.old_head = self.old.next().transpose()?;
+        Ok(entry)
+    }
+
+    fn next_new(&mut self) -> Result<dbs::Entry<K, V>> {
+        let entry = self.new_head.take().unwrap();
+        self.new_head = self.new.next().transpose()?;
+        Ok(entry)
+    }
+
+    fn do_next(&mut self) -> Result<Option<Change<K, V>>> {
+        loop {
+            let ord = match (&self.old_head, &self.new_head) {
+                (None, None) => return Ok(None),
+                (Some(_), None) => cmp::Ordering::Less,
+                (None, Some(_)) => cmp::Ordering::Greater,
+                (Some(old), Some(new)) => old.as_key().cmp(new.as_key()),
+            };
+
+            let change = match ord {
+                cmp::Ordering::Less => {
+                    let old = self.next_old()?;
+                    match old.is_deleted() {
+                        true => None,
+                        false => Some(Change::Deleted(old.to_key())),
+                    }
+                }
+                cmp::Ordering::Greater => {
+                    let new = self.next_new()?;
+                    match new.is_deleted() {
+                        true => None,
+                        false => Some(Change::Added(new)),
+                    }
+                }
+                cmp::Ordering::Equal => {
+                    let (old, new) = (self.next_old()?, self.next_new()?);
+                    match (old.is_deleted(), new.is_deleted()) {
+                        _ if old.to_seqno() == new.to_seqno() => None,
+                        (true, true) => None,
+                        (true, false) => Some(Change::Added(new)),
+                        (false, true) => Some(Change::Deleted(new.to_key())),
+                        (false, false) => Some(Change::Updated(new)),
+                    }
+                }
+            };
+
+            if let Some(change) = change {
+                break Ok(Some(change));
+            }
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for DiffIter<'a, K, V>
+where
+    K: Clone + Ord + FromCbor,
+    V: dbs::Diff + FromCbor,
+    <V as dbs::Diff>::Delta: FromCbor,
+{
+    type Item = Result<Change<K, V>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.do_next().transpose()
+    }
+}
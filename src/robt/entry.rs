@@ -3,8 +3,7 @@ use cbordata::{self as cbor, Cbor, Cborize, FromCbor, IntoCbor};
 use std::{
     borrow::Borrow,
     convert::{TryFrom, TryInto},
-    fmt,
-    io::{self, Read, Seek},
+    fmt, io,
 };
 
 use crate::{
@@ -13,7 +12,7 @@ use crate::{
     util, Error, Result,
 };
 
-const ENTRY_VER: u32 = 0x00130001;
+const ENTRY_VER: u32 = 0x00130002;
 
 #[derive(Clone, Debug, Eq, PartialEq, Cborize)]
 pub enum Entry<K, V, D = <V as dbs::Diff>::Delta>
@@ -23,10 +22,13 @@ where
     MM {
         key: K,
         fpos: u64,
+        // number of entries in leaf nodes under this child.
+        count: u64,
     },
     MZ {
         key: K,
         fpos: u64,
+        count: u64,
     },
     ZZ {
         key: K,
@@ -78,12 +80,12 @@ where
 {
     const ID: u32 = ENTRY_VER;
 
-    pub fn new_mm(key: K, fpos: u64) -> Self {
-        Entry::MM { key, fpos }
+    pub fn new_mm(key: K, fpos: u64, count: u64) -> Self {
+        Entry::MM { key, fpos, count }
     }
 
-    pub fn new_mz(key: K, fpos: u64) -> Self {
-        Entry::MZ { key, fpos }
+    pub fn new_mz(key: K, fpos: u64, count: u64) -> Self {
+        Entry::MZ { key, fpos, count }
     }
 
     pub fn drain_deltas(&mut self) {
@@ -94,14 +96,36 @@ where
             }
         }
     }
+
+    // return whether the value, or any of the deltas if `versions` is true,
+    // are persisted in value-log.
+    pub fn is_reference(&self, versions: bool) -> bool {
+        match self {
+            Entry::MM { .. } | Entry::MZ { .. } => false,
+            Entry::ZZ { value, deltas, .. } => {
+                let delta_ref = |d: &vlog::Delta<D>| matches!(d, vlog::Delta::R { .. });
+                matches!(value, vlog::Value::R { .. })
+                    || (versions && deltas.iter().any(delta_ref))
+            }
+        }
+    }
 }
 
 impl<K, V, D> Entry<K, V, D>
 where
     V: dbs::Diff<Delta = D>,
 {
-    // serialize into value-block and return the same.
-    pub fn into_reference(self, mut vfpos: u64, vlog: bool) -> Result<(Self, Vec<u8>)>
+    // serialize into value-block and return the same. Values larger than
+    // `threshold` are compressed, refer to Config::set_value_compress_threshold.
+    // Values no larger than `inline_max` are kept inline, refer to
+    // Config::set_inline_value_max.
+    pub fn into_reference(
+        self,
+        mut vfpos: u64,
+        vlog: bool,
+        threshold: usize,
+        inline_max: usize,
+    ) -> Result<(Self, Vec<u8>)>
     where
         V: IntoCbor,
         D: IntoCbor,
@@ -110,8 +134,18 @@ where
             Entry::MM { .. } => (self, vec![]),
             Entry::MZ { .. } => (self, vec![]),
             Entry::ZZ { key, value, deltas } => {
-                let (value, mut vblock) =
-                    if vlog { value.into_reference(vfpos)? } else { (value, vec![]) };
+                let (value, mut vblock) = match value {
+                    vlog::Value::N { value } if vlog && inline_max > 0 => {
+                        // encode once, and keep the value inline if it fits.
+                        let data = util::into_cbor_bytes(value.clone())?;
+                        match data.len() <= inline_max {
+                            true => (vlog::Value::N { value }, vec![]),
+                            false => vlog::Value::from_encoded(vfpos, data, threshold)?,
+                        }
+                    }
+                    value if vlog => value.into_reference(vfpos, threshold)?,
+                    value => (value, vec![]),
+                };
 
                 err_at!(
                     FailCbor,
@@ -140,6 +174,60 @@ where
         Ok((entry, data))
     }
 
+    // copy value and deltas referenced by this entry from value-log `f`, into
+    // a value-block relocated at `vfpos`, and return the same. Value-block is
+    // framed the same way as `into_reference`.
+    pub fn relocate<F>(self, f: &mut F, vfpos: u64) -> Result<(Self, Vec<u8>)>
+    where
+        F: io::Seek + io::Read,
+    {
+        let (entry, data) = match self {
+            Entry::MM { .. } => (self, vec![]),
+            Entry::MZ { .. } => (self, vec![]),
+            Entry::ZZ { key, value, deltas } => {
+                let mut vblock = vec![];
+                let value = match value {
+                    vlog::Value::R { fpos, length } => {
+                        let seek = io::SeekFrom::Start(fpos);
+                        let data = read_file!(f, seek, length, "relocating value")?;
+                        vblock.extend_from_slice(&data);
+                        vlog::Value::R { fpos: vfpos, length }
+                    }
+                    value => value,
+                };
+
+                err_at!(
+                    FailCbor,
+                    Cbor::Major4(cbor::Info::Indefinite, vec![]).encode(&mut vblock)
+                )?;
+
+                let mut drefs = vec![];
+                for delta in deltas.into_iter() {
+                    let delta = match delta {
+                        vlog::Delta::R { fpos, length } => {
+                            let n = err_at!(FailConvert, u64::try_from(vblock.len()))?;
+                            let seek = io::SeekFrom::Start(fpos);
+                            let data = read_file!(f, seek, length, "relocating delta")?;
+                            vblock.extend_from_slice(&data);
+                            vlog::Delta::R { fpos: vfpos + n, length }
+                        }
+                        delta => delta,
+                    };
+                    drefs.push(delta);
+                }
+
+                vblock
+                    .extend_from_slice(&util::into_cbor_bytes(cbor::SimpleValue::Break)?);
+
+                let entry = Entry::ZZ { key, value, deltas: drefs };
+
+                (entry, vblock)
+            }
+        };
+
+        Ok((entry, data))
+    }
+
     pub fn into_native<F>(self, f: &mut F, versions: bool) -> Result<Self>
     where
         V: FromCbor,
@@ -167,6 +255,42 @@ where
         Ok(entry)
     }
 
+    // same as into_native() with versions, except that deltas are read from
+    // value-log, newest first, only until the version as of `seqno` is reached,
+    // that is, until a delta with seqno less than or equal to `seqno`. Older
+    // deltas are discarded.
+    pub fn fetch_deltas_until<F>(self, f: &mut F, seqno: u64) -> Result<Self>
+    where
+        V: FromCbor,
+        D: FromCbor,
+        F: io::Seek + io::Read,
+    {
+        let entry = match self {
+            Entry::MM { .. } => self,
+            Entry::MZ { .. } => self,
+            Entry::ZZ { key, value, deltas } => {
+                let native_value = value.into_native(f)?;
+                let mut native_deltas = vec![];
+                if matches!(native_value.to_seqno(), Some(s) if s > seqno) {
+                    // deltas are ordered from oldest to newest.
+                    for delta in deltas.into_iter().rev() {
+                        let delta = delta.into_native(f)?;
+                        let done = matches!(delta.to_seqno(), Some(s) if s <= seqno);
+                        native_deltas.push(delta);
+                        if done {
+                            break;
+                        }
+                    }
+                    native_deltas.reverse();
+                }
+
+                Entry::ZZ { key, value: native_value, deltas: native_deltas }
+            }
+        };
+
+        Ok(entry)
+    }
+
     pub fn commit(self, new: Self) -> Self
     where
         K: Clone,
@@ -197,20 +321,18 @@ where
         V: fmt::Debug + FromCbor,
         <V as dbs::Diff>::Delta: fmt::Debug + FromCbor,
     {
-        let fd = &mut reader.index;
+        let (codec, fd) = (reader.codec, &mut reader.index);
         let entries = match self {
-            Entry::MM { key, fpos } => {
-                let off = io::SeekFrom::Start(*fpos);
-                let block = read_file!(fd, off, reader.m_blocksize, "read mm-block")?;
+            Entry::MM { key, fpos, count } => {
+                let block = codec.read_block(fd, *fpos, reader.m_blocksize)?;
                 let entries = util::from_cbor_bytes::<Vec<Entry<K, V, D>>>(&block)?.0;
-                println!("{}MM<{:?}@{},{}>", prefix, key, fpos, entries.len());
+                println!("{}MM<{:?}@{},{},{}>", prefix, key, fpos, entries.len(), count);
                 Some(entries)
             }
-            Entry::MZ { key, fpos } => {
-                let off = io::SeekFrom::Start(*fpos);
-                let block = read_file!(fd, off, reader.m_blocksize, "read mm-block")?;
+            Entry::MZ { key, fpos, count } => {
+                let block = codec.read_block(fd, *fpos, reader.z_blocksize)?;
                 let entries = util::from_cbor_bytes::<Vec<Entry<K, V, D>>>(&block)?.0;
-                println!("{}MZ<{:?}@{},{}>", prefix, key, fpos, entries.len());
+                println!("{}MZ<{:?}@{},{},{}>", prefix, key, fpos, entries.len(), count);
                 Some(entries)
             }
             Entry::ZZ { key, value, deltas } => {
@@ -283,6 +405,24 @@ where
         }
     }
 
+    /// Return the number of value-log bytes referenced by this entry, that is,
+    /// values and deltas that are already persisted in a value-log.
+    pub fn to_vlog_bytes(&self) -> u64 {
+        match self {
+            Entry::ZZ { value, deltas, .. } => {
+                let n = match value {
+                    vlog::Value::R { length, .. } => *length,
+                    vlog::Value::N { .. } => 0,
+                };
+                deltas.iter().fold(n, |n, delta| match delta {
+                    vlog::Delta::R { length, .. } => n + *length,
+                    vlog::Delta::N { .. } => n,
+                })
+            }
+            Entry::MZ { .. } | Entry::MM { .. } => 0,
+        }
+    }
+
     pub fn is_zblock(&self) -> bool {
         match self {
             Entry::MZ { .. } => false,
@@ -290,6 +430,24 @@ where
             Entry::ZZ { .. } => true,
         }
     }
+
+    /// Return the file-position of the child m-block, if this is an
+    /// intermediate entry pointing to an m-block.
+    pub fn to_mblock_fpos(&self) -> Option<u64> {
+        match self {
+            Entry::MM { fpos, .. } => Some(*fpos),
+            Entry::MZ { .. } | Entry::ZZ { .. } => None,
+        }
+    }
+
+    /// Return the number of leaf entries under this entry, one for a leaf
+    /// entry.
+    pub fn to_count(&self) -> u64 {
+        match self {
+            Entry::MM { count, .. } | Entry::MZ { count, .. } => *count,
+            Entry::ZZ { .. } => 1,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -25,8 +25,8 @@ fn test_robt_entry() {
         }
     }
     let zz = Entry::<u64, u64, u64>::from(dbnt.clone());
-    let mm = Entry::<u64, u64, u64>::new_mm(key, 100);
-    let mz = Entry::<u64, u64, u64>::new_mz(key, 200);
+    let mm = Entry::<u64, u64, u64>::new_mm(key, 100, 1000);
+    let mz = Entry::<u64, u64, u64>::new_mz(key, 200, 10);
 
     assert_eq!(dbnt, dbs::Entry::try_from(Entry::from(dbnt.clone())).unwrap());
     assert_eq!(zz.as_key(), &key);
@@ -41,17 +41,89 @@ fn test_robt_entry() {
     assert!(zz.is_zblock());
     assert!(!mz.is_zblock());
     assert!(!mm.is_zblock());
+    assert_eq!(zz.to_count(), 1);
+    assert_eq!(mz.to_count(), 10);
+    assert_eq!(mm.to_count(), 1000);
 
-    let res = mm.clone().into_reference(0, true).unwrap();
+    let res = mm.clone().into_reference(0, true, 0, 0).unwrap();
     assert_eq!(mm, res.0);
     assert!(res.1.is_empty());
-    let res = mz.clone().into_reference(0, true).unwrap();
+    let res = mz.clone().into_reference(0, true, 0, 0).unwrap();
     assert_eq!(mz, res.0);
     assert!(res.1.is_empty());
 
-    let (zz_ref, data) = zz.clone().into_reference(0, true).unwrap();
+    let (zz_ref, data) = zz.clone().into_reference(0, true, 0, 0).unwrap();
     assert_eq!(zz_ref.to_key(), key);
+    // value-block is framed by an indefinite array header and break byte.
+    assert_eq!(zz.to_vlog_bytes(), 0);
+    assert_eq!(zz_ref.to_vlog_bytes(), (data.len() as u64) - 2);
 
     let mut data = io::Cursor::new(data);
     assert_eq!(zz_ref.into_native(&mut data, true).unwrap(), zz);
 }
+
+#[test]
+fn test_robt_entry_fetch_deltas_until() {
+    use std::io::{Read, Seek};
+
+    // count the number of bytes read from value-log.
+    struct Counter {
+        inner: io::Cursor<Vec<u8>>,
+        n: usize,
+    }
+
+    impl io::Read for Counter {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.inner.read(buf)?;
+            self.n += n;
+            Ok(n)
+        }
+    }
+
+    impl io::Seek for Counter {
+        fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    let seed: u64 = random();
+    println!("test_robt_entry_fetch_deltas_until {}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let key = 10;
+
+    let n_versions = 100;
+    let mut dbnt = dbs::Entry::<u64, u64>::new(key, rng.gen(), 1);
+    for seqno in 2..=n_versions {
+        dbnt = match rng.gen::<u8>() % 4 {
+            0 => dbnt.delete(seqno),
+            _ => dbnt.insert(rng.gen(), seqno),
+        };
+    }
+    let (zz_ref, data) = Entry::<u64, u64, u64>::from(dbnt.clone())
+        .into_reference(0, true, 0, 0)
+        .unwrap();
+
+    let mut full = Counter { inner: io::Cursor::new(data.clone()), n: 0 };
+    let entry = zz_ref.clone().into_native(&mut full, true).unwrap();
+    assert_eq!(dbs::Entry::try_from(entry).unwrap(), dbnt);
+
+    for _i in 0..100 {
+        let seqno = rng.gen::<u64>() % (n_versions + 10);
+        let mut partial = Counter { inner: io::Cursor::new(data.clone()), n: 0 };
+        let entry = zz_ref.clone().fetch_deltas_until(&mut partial, seqno).unwrap();
+        let entry = dbs::Entry::try_from(entry).unwrap();
+
+        // version as of seqno is available from the partially fetched entry.
+        let (values, refs) = (entry.to_values(), dbnt.to_values());
+        assert_eq!(values[..], refs[(refs.len() - values.len())..]);
+        match refs.iter().rev().find(|v| v.to_seqno() <= seqno) {
+            Some(v) => assert_eq!(values.first(), Some(v)),
+            None => assert_eq!(values.len(), refs.len()),
+        }
+
+        // oldest version is at seqno 1.
+        if seqno > 1 {
+            assert!(partial.n < full.n, "{} {} {}", seqno, partial.n, full.n);
+        }
+    }
+}
@@ -2,6 +2,27 @@ use std::{convert::TryFrom, ffi, fmt, path, result};
 
 use crate::{Error, Result};
 
+/// Token used in composing index file name and value-log file name, reserved
+/// and not allowed within index name.
+pub const RESERVED_TOKEN: &str = "-robt-";
+
+/// Validate index `name`, that is used to compose index file name and
+/// value-log file name. Name shall not be empty, shall not be `.` or `..`,
+/// and shall not contain path separators, NUL character or [RESERVED_TOKEN].
+pub fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        err_at!(InvalidInput, msg: "empty index name")?
+    } else if name == "." || name == ".." {
+        err_at!(InvalidInput, msg: "index name {:?} is not a file name", name)?
+    } else if name.chars().any(|ch| path::is_separator(ch) || ch == '\0') {
+        err_at!(InvalidInput, msg: "index name {:?} has invalid character", name)?
+    } else if name.contains(RESERVED_TOKEN) {
+        err_at!(InvalidInput, msg: "index name {:?} has {:?}", name, RESERVED_TOKEN)?
+    }
+
+    Ok(())
+}
+
 /// An Index file is uniquely locatable by providing the `dir` and name.
 /// where `dir` is the directory in which the index file is located and `name`
 /// is the unique name for the index. `format!("{}-robt.indx", name)`
@@ -54,14 +75,56 @@ impl fmt::Display for IndexFileName {
 /// A Value log file is uniquely locatable by providing the `dir` and name.
 /// where `dir` is the directory in which the index/vlog file is located and
 /// `name` is the unique name for the index/vlog. `format!("{}-robt.vlog", name)`
+///
+/// Value-log files re-written for an index, refer to
+/// [Index::gc_vlog][crate::robt::Index::gc_vlog], are versioned as
+/// `format!("{}-robt-{}.vlog", name, version)`. Since index name cannot carry
+/// [RESERVED_TOKEN], versioned file names are unambiguous.
 #[derive(Clone)]
 pub struct VlogFileName(pub ffi::OsString);
 
 impl From<String> for VlogFileName {
     fn from(name: String) -> VlogFileName {
-        let file_name = format!("{}-robt.vlog", name);
+        VlogFileName::from_version(name, 0)
+    }
+}
+
+impl VlogFileName {
+    /// Compose versioned value-log file name, version ZERO is the same as
+    /// the un-versioned file name.
+    pub fn from_version(name: String, version: usize) -> VlogFileName {
+        let file_name = match version {
+            0 => format!("{}-robt.vlog", name),
+            version => format!("{}{}{}.vlog", name, RESERVED_TOKEN, version),
+        };
         VlogFileName(AsRef::<ffi::OsStr>::as_ref(&file_name).to_os_string())
     }
+
+    /// Return the index name and version of this value-log file name,
+    /// un-versioned file name is treated as version ZERO.
+    pub fn to_version(&self) -> Result<(String, usize)> {
+        let ffpp = path::Path::new(&self.0);
+
+        let fname = || -> Option<(String, usize)> {
+            let fname = ffpp.file_name()?;
+            let stem = fname.to_str()?.strip_suffix(".vlog")?;
+            match stem.strip_suffix("-robt") {
+                Some(name) => Some((name.to_string(), 0)),
+                None => {
+                    let (name, version) = stem.rsplit_once(RESERVED_TOKEN)?;
+                    match version.chars().all(|ch| ch.is_ascii_digit()) {
+                        true => Some((name.to_string(), version.parse().ok()?)),
+                        false => None,
+                    }
+                }
+            }
+        }();
+
+        match fname {
+            Some((name, version)) if !name.is_empty() => Ok((name, version)),
+            _ => err_at!(InvalidFile, msg: "{:?}", ffpp),
+        }
+    }
 }
 
 impl From<VlogFileName> for ffi::OsString {
@@ -74,21 +137,7 @@ impl TryFrom<VlogFileName> for String {
     type Error = Error;
 
     fn try_from(fname: VlogFileName) -> Result<String> {
-        let ffpp = path::Path::new(&fname.0);
-
-        let fname = || -> Option<&str> {
-            let fname = ffpp.file_name()?;
-            if fname.to_str()?.ends_with("-robt.vlog") {
-                Some(path::Path::new(fname).file_stem()?.to_str()?)
-            } else {
-                None
-            }
-        }();
-
-        match fname {
-            Some(fname) => Ok(fname.strip_suffix("-robt").unwrap().to_string()),
-            None => err_at!(InvalidFile, msg: "{:?}", ffpp),
-        }
+        Ok(fname.to_version()?.0)
     }
 }
 
@@ -16,7 +16,58 @@ fn test_robt_vlog_file() {
     let name = "test-vlog-file".to_string();
     let out = AsRef::<ffi::OsStr>::as_ref("test-vlog-file-robt.vlog").to_os_string();
 
-    let vlog_file = VlogFileName::from(name);
+    let vlog_file = VlogFileName::from(name.clone());
     assert_eq!(vlog_file.0, out);
+    assert_eq!(vlog_file.to_version().unwrap(), (name.clone(), 0));
     assert_eq!(ffi::OsString::from(vlog_file), out);
+
+    let out = AsRef::<ffi::OsStr>::as_ref("test-vlog-file-robt-12.vlog").to_os_string();
+    let vlog_file = VlogFileName::from_version(name.clone(), 12);
+    assert_eq!(vlog_file.0, out);
+    assert_eq!(vlog_file.to_version().unwrap(), (name.clone(), 12));
+    assert_eq!(String::try_from(vlog_file).unwrap(), name);
+
+    let invalid =
+        ["-robt.vlog", "a-robt-.vlog", "a-robt-1x.vlog", "a-robt-1.indx", "a.vlog"];
+    for file_name in invalid.iter() {
+        let vlog_file =
+            VlogFileName(AsRef::<ffi::OsStr>::as_ref(file_name).to_os_string());
+        assert!(vlog_file.to_version().is_err(), "{:?}", file_name);
+    }
+}
+
+#[test]
+fn test_robt_validate_name() {
+    use crate::robt::{Builder, Config};
+
+    let dir = std::env::temp_dir().join("test_robt_validate_name");
+
+    let invalid =
+        ["", ".", "..", "a/b", "/index", "index/", "a\0b", "index-robt-001", "-robt-"];
+    for name in invalid.iter() {
+        assert!(validate_name(name).is_err(), "{:?}", name);
+
+        let mut config = Config::new(dir.as_os_str(), "index");
+        assert!(config.set_name(name).is_err(), "{:?}", name);
+        assert_eq!(config.name, "index");
+
+        let config = Config::new(dir.as_os_str(), name);
+        assert!(Builder::<u64, u64>::initial(config, vec![]).is_err(), "{:?}", name);
+    }
+
+    let valid = ["index", "index-1", "robt", "my-robt", "robt-index", "a.b", "a_b-c"];
+    for name in valid.iter() {
+        validate_name(name).unwrap();
+
+        let mut config = Config::new(dir.as_os_str(), "index");
+        config.set_name(name).unwrap();
+        assert_eq!(config.name, name.to_string());
+
+        let index_file = IndexFileName::from(name.to_string());
+        assert_eq!(String::try_from(index_file).unwrap(), name.to_string());
+        let vlog_file = VlogFileName::from(name.to_string());
+        assert_eq!(String::try_from(vlog_file).unwrap(), name.to_string());
+        let vlog_file = VlogFileName::from_version(name.to_string(), 3);
+        assert_eq!(vlog_file.to_version().unwrap(), (name.to_string(), 3));
+    }
 }
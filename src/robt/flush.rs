@@ -2,7 +2,7 @@ use fs2::FileExt;
 
 use std::{convert::TryFrom, ffi, fs, mem};
 
-use crate::{util, write_file, Error, Result};
+use crate::{robt::BuildFsync, util, write_file, Error, Result};
 
 pub enum Flusher {
     File {
@@ -25,12 +25,18 @@ impl Drop for Flusher {
 
 impl Flusher {
     // Create a new flusher thread, there are two flushers for `robt` index, one
-    // for the index-file and the other is for value-file, if enabled.
-    pub fn new(loc: &ffi::OsStr, create: bool, chan_size: usize) -> Result<Flusher> {
+    // for the index-file and the other is for value-file, if enabled. Data is
+    // synced to disk as per `fsync` policy.
+    pub fn new(
+        loc: &ffi::OsStr,
+        create: bool,
+        chan_size: usize,
+        fsync: BuildFsync,
+    ) -> Result<Flusher> {
         let (fd, fpos) = if create {
             (util::files::create_file_a(loc)?, 0)
         } else {
-            let fpos = err_at!(IOError, fs::metadata(loc))?.len().saturating_sub(1);
+            let fpos = err_at!(IOError, fs::metadata(loc))?.len();
             (util::files::open_file_a(loc)?, fpos)
         };
 
@@ -39,7 +45,7 @@ impl Flusher {
             "flusher",
             chan_size,
             move |rx: util::thread::Rx<Vec<u8>, u64>| {
-                move || thread_flush(ffpp, fd, rx, fpos)
+                move || thread_flush(ffpp, fd, rx, fpos, fsync)
             },
         );
         let tx = th.to_tx();
@@ -85,7 +91,8 @@ impl Flusher {
         Ok(())
     }
 
-    // close this flusher and associated thread, after syncing data to disk.
+    // close this flusher and associated thread, after syncing data to disk,
+    // unless fsync policy is `Never`.
     pub fn close(&mut self) -> Result<u64> {
         match self {
             Flusher::File { tx, th, .. } => {
@@ -102,19 +109,35 @@ fn thread_flush(
     mut fd: fs::File,
     rx: util::thread::Rx<Vec<u8>, u64>,
     mut fpos: u64,
+    fsync: BuildFsync,
 ) -> Result<u64> {
     // println!("thread_flush lock_shared <");
     err_at!(IOError, fd.lock_shared(), "fail read lock for {:?}", loc)?;
 
+    let mut unsynced = 0;
     for (data, res_tx) in rx {
         // println!("flush {:?} fpos:{} len:{}", loc, fpos, data.len());
         write_file!(fd, &data, &loc, "flushing file")?;
 
-        fpos += u64::try_from(data.len()).unwrap();
+        let n = u64::try_from(data.len()).unwrap();
+        fpos += n;
+        unsynced += n;
+        match fsync {
+            BuildFsync::Periodic(limit) if unsynced >= limit => {
+                err_at!(IOError, fd.sync_data(), "fail sync_data {:?}", loc)?;
+                unsynced = 0;
+            }
+            _ => (),
+        }
         res_tx.map(|tx| tx.send(fpos).ok());
     }
 
-    err_at!(IOError, fd.sync_all(), "fail sync_all {:?}", loc)?;
+    match fsync {
+        BuildFsync::Never => (),
+        BuildFsync::Periodic(_) | BuildFsync::Final => {
+            err_at!(IOError, fd.sync_all(), "fail sync_all {:?}", loc)?
+        }
+    }
     err_at!(IOError, fd.unlock(), "fail read unlock {:?}", loc)?;
     // println!("thread_flush unlock >");
 
@@ -23,7 +23,7 @@ fn test_robt_flush() {
 
         let create = true;
         let chan_size: usize = cmp::min(uns.arbitrary().unwrap(), 12);
-        Flusher::new(file.as_ref(), create, chan_size).unwrap()
+        Flusher::new(file.as_ref(), create, chan_size, BuildFsync::Final).unwrap()
     };
 
     let mut fpos = 0;
@@ -49,3 +49,34 @@ fn test_robt_flush() {
 
     assert_eq!(flushed_data, filedata);
 }
+
+#[test]
+fn test_robt_flush_append() {
+    let seed: u64 = random();
+    println!("test_flush_append {}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let dir = std::env::temp_dir().join("test_flush_append");
+    fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("test-flusher-append.data");
+    fs::remove_file(&file).ok();
+
+    let mut filedata: Vec<u8> = (0..rng.gen_range(1..4096)).map(|_| rng.gen()).collect();
+    fs::write(&file, &filedata).unwrap();
+
+    // flusher opened on an existing file starts at its end.
+    let mut fpos = filedata.len() as u64;
+    let mut flusher = Flusher::new(file.as_ref(), false, 8).unwrap();
+    assert_eq!(flusher.to_fpos().unwrap(), fpos);
+
+    for _i in 0..100 {
+        let data: Vec<u8> = (0..rng.gen_range(1..1024)).map(|_| rng.gen()).collect();
+        fpos += data.len() as u64;
+        filedata.extend(&data);
+        flusher.flush(data).unwrap();
+        assert_eq!(fpos, flusher.to_fpos().unwrap());
+    }
+    assert_eq!(flusher.close().unwrap(), fpos);
+
+    assert_eq!(fs::read(&file).unwrap(), filedata);
+}
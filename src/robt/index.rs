@@ -1,31 +1,40 @@
-use cbordata::{Cborize, FromCbor, IntoCbor};
+use cbordata::{self as cbor, Cbor, Cborize, FromCbor, IntoCbor};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
 use std::{
     borrow::Borrow,
-    cell::RefCell,
+    cell::{Cell, RefCell},
     cmp,
+    collections::BTreeMap,
     convert::{TryFrom, TryInto},
     ffi, fmt, fs,
     hash::Hash,
-    io::{self, Read, Seek},
+    io::{self, Read, Seek, Write},
     marker, mem,
     ops::{Bound, RangeBounds},
     path,
     rc::Rc,
-    sync::Arc,
+    result,
+    sync::{
+        atomic::{AtomicBool, Ordering::SeqCst},
+        mpsc, Arc,
+    },
+    thread, time,
 };
 
 use crate::{
     dbs, read_file,
     robt::{
         build,
+        diff::{Change, DiffIter},
         lsm::YIter,
-        reader::{Iter, IterLsm, Reader},
-        scans::{BitmappedScan, BuildScan, CompactScan},
-        to_index_location, to_vlog_location, Config, Entry, Flusher, IndexFileName,
-        Stats, VlogFileName, ROOT_MARKER,
+        reader::{key_order, Iter, IterKeys, IterLocated, IterLsm, Reader},
+        scans::{BitmappedScan, BuildScan, CompactScan, MergeScan, PrefixScan},
+        sort::ExtSort,
+        to_index_location, to_vlog_location, validate_name, Config, Entry, Flusher,
+        IndexFileName, Stats, VlogFileName, ENDIAN_MARKER, ROOT_MARKER,
     },
-    util, Error, Result,
+    util, write_file, Error, Result, ResultExt,
 };
 
 /// Marker block size, not to be tampered with.
@@ -34,6 +43,29 @@ const MARKER_BLOCK_SIZE: usize = 1024 * 4;
 /// Maximum supported depth of the tree.
 const MAX_DEPTH: usize = 11;
 
+const SHARD_VER: u32 = 0x00010001;
+
+const PROVENANCE_VER: u32 = 0x00010001;
+
+const BASEREF_VER: u32 = 0x00010001;
+
+/// Number of entries fetched in a batch by [OwnedRangeIter].
+pub const PAR_BATCH: usize = 1024;
+
+/// Token to cancel an on-going build, refer to [Builder::set_cancel_token].
+/// Setting the flag to `true`, from any thread, shall cancel the build.
+pub type CancelToken = Arc<AtomicBool>;
+
+/// Custom key ordering, refer to [Builder::set_comparator].
+pub type Comparator<K> = Arc<dyn Fn(&K, &K) -> cmp::Ordering + Send + Sync>;
+
+/// Key prefix function, for prefix bloom filter, refer to [Builder::set_prefix_fn].
+pub type PrefixFn<K> = Arc<dyn Fn(&K) -> Vec<u8> + Send + Sync>;
+
+/// Item type for [Builder::build_with_deltas], `(key, value, seqno, deltas)`,
+/// where `deltas` are ordered from oldest to newest.
+pub type ExplicitDeltas<K, V> = (K, V, u64, Vec<dbs::Delta<<V as dbs::Diff>::Delta>>);
+
 /// Build an immutable read-only btree index from an iterator.
 ///
 /// Refer to package documentation for typical work-flow.
@@ -48,10 +80,25 @@ where
     // active values
     iflush: Rc<RefCell<Flusher>>,
     vflush: Rc<RefCell<Flusher>>,
+    // whether value-log is created by this build, else it is shared with
+    // older snapshots.
+    vlog_created: bool,
     // final result to be persisted
     app_meta: Vec<u8>,
     stats: Stats,
     root: Option<u64>,
+    shards: Vec<Shard>,
+    // build can be cancelled by application.
+    cancel: Option<CancelToken>,
+    // custom key ordering, if None keys are ordered by Ord.
+    cmp: Option<Comparator<K>>,
+    // user supplied tag, to persist build provenance.
+    provenance: Option<String>,
+    // reference to base index, if this is built as a delta index.
+    base: Option<BaseRef>,
+    // key prefix function, and the serialized prefix bloom filter once built.
+    prefix: Option<PrefixFn<K>>,
+    prefix_bloom: Option<Vec<u8>>,
 
     _key: marker::PhantomData<K>,
     _val: marker::PhantomData<V>,
@@ -67,20 +114,23 @@ where
     ///
     /// Subsequently call [Builder::build_index] to start building the index.
     pub fn initial(mut config: Config, meta: Vec<u8>) -> Result<Self> {
+        validate_name(&config.name)?;
+
         // set to fresh vlog location, don't carry forward.
         config.set_vlog_location(None);
 
-        let queue_size = config.flush_queue_size;
+        let (queue_size, fsync) = (config.flush_queue_size, config.build_fsync);
         let iflush = {
             let loc = to_index_location(&config.dir, &config.name);
-            Rc::new(RefCell::new(Flusher::new(&loc, true, queue_size)?))
+            Rc::new(RefCell::new(Flusher::new(&loc, true, queue_size, fsync)?))
         };
         let vflush = if config.value_in_vlog || config.delta_ok {
-            let loc = to_vlog_location(&config.dir, &config.name);
-            Rc::new(RefCell::new(Flusher::new(&loc, true, queue_size)?))
+            let loc = to_vlog_location(&config.to_vlog_dir(), &config.name);
+            Rc::new(RefCell::new(Flusher::new(&loc, true, queue_size, fsync)?))
         } else {
             Rc::new(RefCell::new(Flusher::empty()))
         };
+        let vlog_created = true;
 
         let mut stats: Stats = config.clone().into();
         stats.vlog_location = vflush.as_ref().borrow().to_location();
@@ -89,10 +139,18 @@ where
             config,
             iflush,
             vflush,
+            vlog_created,
 
             app_meta: meta,
             stats,
             root: None,
+            shards: Vec::default(),
+            cancel: None,
+            cmp: None,
+            provenance: None,
+            base: None,
+            prefix: None,
+            prefix_bloom: None,
 
             _key: marker::PhantomData,
             _val: marker::PhantomData,
@@ -111,14 +169,21 @@ where
     ///
     /// Subsequently call [Builder::build_index] to start building the index.
     fn incremental(config: Config, meta: Vec<u8>) -> Result<Self> {
-        let queue_size = config.flush_queue_size;
+        validate_name(&config.name)?;
+
+        let (queue_size, fsync) = (config.flush_queue_size, config.build_fsync);
         let iflush = {
             let loc = to_index_location(&config.dir, &config.name);
-            Rc::new(RefCell::new(Flusher::new(&loc, true, queue_size)?))
+            Rc::new(RefCell::new(Flusher::new(&loc, true, queue_size, fsync)?))
         };
-        let vflush = match config.to_vlog_location() {
-            Some(vlog) => Rc::new(RefCell::new(Flusher::new(&vlog, true, queue_size)?)),
-            None => Rc::new(RefCell::new(Flusher::empty())),
+        // value-log is shared with older snapshots, append to it.
+        let (vflush, vlog_created) = match config.to_vlog_location() {
+            Some(vlog) => {
+                let create = !path::Path::new(&vlog).exists();
+                let vflush = Flusher::new(&vlog, create, queue_size, fsync)?;
+                (Rc::new(RefCell::new(vflush)), create)
+            }
+            None => (Rc::new(RefCell::new(Flusher::empty())), false),
         };
 
         let mut stats: Stats = config.clone().into();
@@ -128,10 +193,18 @@ where
             config,
             iflush,
             vflush,
+            vlog_created,
 
             app_meta: meta,
             stats,
             root: None,
+            shards: Vec::default(),
+            cancel: None,
+            cmp: None,
+            provenance: None,
+            base: None,
+            prefix: None,
+            prefix_bloom: None,
 
             _key: marker::PhantomData,
             _val: marker::PhantomData,
@@ -139,6 +212,79 @@ where
 
         Ok(val)
     }
+
+    /// Set a cancellation token for this build. Token is checked for every
+    /// entry consumed from the input iterator, once set, the build is stopped,
+    /// flushers are closed, partially built files are removed, value-log
+    /// shared with older snapshots is truncated back, and [Error::Cancelled]
+    /// is returned.
+    pub fn set_cancel_token(&mut self, token: CancelToken) -> &mut Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Order keys using the custom comparator `cmp`, instead of [Ord]. Entries
+    /// supplied to the builder shall be sorted by `cmp`. `id` identifies the
+    /// comparator and is persisted along with the index, subsequently the same
+    /// comparator shall be supplied via [Index::set_comparator] to read the
+    /// index.
+    ///
+    /// Incremental build, compaction and lsm-merge are not supported for
+    /// indexes built with custom comparator.
+    pub fn set_comparator(&mut self, id: &str, cmp: Comparator<K>) -> &mut Self {
+        self.stats.comparator = id.to_string();
+        self.cmp = Some(cmp);
+        self
+    }
+
+    /// Persist a [Provenance] record, along with the index, capturing the
+    /// crate version, hostname and build time, along with user supplied
+    /// `tag`. Refer to [Index::to_provenance].
+    pub fn set_provenance(&mut self, tag: &str) -> &mut Self {
+        self.provenance = Some(tag.to_string());
+        self
+    }
+
+    /// Supply the function to compute key prefixes, for the prefix bloom filter
+    /// enabled via [Config::set_prefix_bloom]. Prefixes are persisted as a
+    /// secondary bloom filter, alongside the main one, refer to
+    /// [Index::may_contain_prefix].
+    ///
+    /// Prefix function is not persisted, hence compaction, merge and
+    /// incremental builds don't carry forward the prefix bloom filter.
+    pub fn set_prefix_fn(&mut self, prefix: PrefixFn<K>) -> &mut Self {
+        self.prefix = Some(prefix);
+        self
+    }
+
+    /// Build this index as a delta index over `base`, holding only the new
+    /// batch of entries. A [BaseRef] to `base` is persisted along with this
+    /// index, refer to [DeltaIndex][crate::robt::DeltaIndex] for reading
+    /// delta indexes over their base.
+    pub fn set_base<B>(&mut self, base: &Index<K, V, B>) -> &mut Self
+    where
+        K: FromCbor,
+        V: FromCbor,
+        <V as dbs::Diff>::Delta: FromCbor,
+        B: dbs::Bloom,
+    {
+        self.base = Some(BaseRef {
+            name: base.to_name(),
+            seqno: base.stats.seqno,
+            epoch: base.stats.epoch,
+        });
+        self
+    }
+
+    /// Return the custom comparator, if set via [Builder::set_comparator].
+    pub fn to_comparator(&self) -> Option<Comparator<K>> {
+        self.cmp.clone()
+    }
+
+    /// Return the configuration used by this builder.
+    pub fn as_config(&self) -> &Config {
+        &self.config
+    }
 }
 
 impl<K, V> Builder<K, V>
@@ -147,6 +293,10 @@ where
     V: dbs::Diff + IntoCbor + FromCbor,
     <V as dbs::Diff>::Delta: IntoCbor + FromCbor,
 {
+    /// Build index from iterator. Keys from the iterator are added to `bitmap`,
+    /// if configured with [Config::set_bloom_shards], `bitmap` is used as a
+    /// prototype to build one bloom filter for each sub-tree under the root.
+    /// Keys shall be strictly increasing, refer to [Config::set_strict_keys].
     pub fn build_index<B, I, E>(
         &mut self,
         iter: I,
@@ -154,22 +304,75 @@ where
         seqno: Option<u64>,
     ) -> Result<Index<K, V, B>>
     where
-        B: dbs::Bloom,
+        B: Clone + dbs::Bloom,
+        I: Iterator<Item = Result<E>>,
+        E: TryInto<Entry<K, V>>,
+        <E as TryInto<Entry<K, V>>>::Error: fmt::Display,
+    {
+        let cancel = self.cancel.clone();
+        let iter = iter.map(move |item| match &cancel {
+            Some(cancel) if cancel.load(SeqCst) => err_at!(Cancelled, msg: "build"),
+            _ => item,
+        });
+
+        match self.do_build_index(iter, bitmap, seqno) {
+            Err(err @ Error::Cancelled(_, _)) | Err(err @ Error::DuplicateKey(_, _)) => {
+                self.discard()?;
+                Err(err)
+            }
+            res => res,
+        }
+    }
+
+    fn do_build_index<B, I, E>(
+        &mut self,
+        iter: I,
+        bitmap: B,
+        seqno: Option<u64>,
+    ) -> Result<Index<K, V, B>>
+    where
+        B: Clone + dbs::Bloom,
         I: Iterator<Item = Result<E>>,
         E: TryInto<Entry<K, V>>,
         <E as TryInto<Entry<K, V>>>::Error: fmt::Display,
     {
-        let build_iter = BuildScan::new(iter, 0 /*seqno*/);
-        let bitmap_iter = BitmappedScan::<K, V, B, _>::new(build_iter, bitmap);
+        if self.config.descending && self.cmp.is_some() {
+            err_at!(InvalidInput, msg: "descending build with custom comparator")?
+        }
+
+        let prefix = match (self.config.prefix_bloom, &self.prefix) {
+            (true, Some(prefix_fn)) => Some((Arc::clone(prefix_fn), bitmap.clone())),
+            (true, None) => err_at!(InvalidInput, msg: "prefix bloom without prefix fn")?,
+            (false, _) => None,
+        };
+        let build_iter = PrefixScan::new(BuildScan::new(iter, 0 /*seqno*/), prefix);
 
         self.stats.n_abytes = self.vflush.as_ref().borrow().to_fpos().unwrap_or(0);
 
-        let (bitmap_iter, root) = self.build_tree(bitmap_iter)?;
-        let (bitmap, build_iter) = bitmap_iter.unwrap()?;
+        let (bitmap, build_iter, root, n_rbytes, shape) = if self.config.bloom_shards {
+            let proto = bitmap.clone();
+            let (build_iter, root, n_rbytes, shape) = self.build_tree(build_iter)?;
+            self.shards = self.build_shards(root, proto)?;
+            // bitmap for the whole index is left empty.
+            let mut bitmap = bitmap;
+            bitmap.build()?;
+            (bitmap, build_iter, root, n_rbytes, shape)
+        } else {
+            let bitmap_iter = BitmappedScan::<K, V, B, _>::new(build_iter, bitmap);
+            let (bitmap_iter, root, n_rbytes, shape) = self.build_tree(bitmap_iter)?;
+            let (bitmap, build_iter) = bitmap_iter.unwrap()?;
+            (bitmap, build_iter, root, n_rbytes, shape)
+        };
 
+        let (prefix_bitmap, build_iter) = build_iter.unwrap()?;
         let (build_time, build_seqno, n_count, n_deleted, epoch, _iter) =
             build_iter.unwrap()?;
 
+        self.prefix_bloom = match prefix_bitmap {
+            Some(bitmap) => Some(err_at!(Fatal, bitmap.to_bytes())?),
+            None => None,
+        };
+
         self.root = root;
         self.stats.build_time = build_time;
         self.stats.seqno =
@@ -177,10 +380,215 @@ where
         self.stats.n_count = n_count;
         self.stats.n_deleted = n_deleted.try_into().unwrap();
         self.stats.epoch = epoch;
+        self.stats.tree_depth = shape.to_depth();
+        let (n_mblocks, n_mentries) = shape.to_mblocks();
+        self.stats.n_mblocks = n_mblocks;
+        self.stats.n_mentries = n_mentries;
+
+        let n_bytes = self.vflush.as_ref().borrow().to_fpos().unwrap_or(0);
+        self.stats.n_gbytes = self.stats.n_abytes.saturating_sub(n_rbytes);
+        self.stats.n_vbytes = n_bytes.saturating_sub(self.stats.n_gbytes);
 
         self.build_flush(err_at!(Fatal, bitmap.to_bytes())?)?;
 
-        Index::open(&self.config.dir, &self.config.name)
+        let mut index = Index::open(&self.config.dir, &self.config.name)?;
+        if let Some(cmp) = &self.cmp {
+            index.set_comparator(&self.stats.comparator, Arc::clone(cmp))?;
+        }
+        Ok(index)
+    }
+
+    /// Build index from a set of shard iterators, like the ones returned by
+    /// [dbs::CommitIterator::scans]. Entries within each shard shall be in sort
+    /// order, and shards shall be supplied in key order, without overlap.
+    ///
+    /// Each shard is drained and converted in its own thread, while the btree
+    /// itself is built serially, shard after shard, into a single index file.
+    pub fn build_parallel<B, I, E>(
+        &mut self,
+        iters: Vec<I>,
+        bitmap: B,
+        seqno: Option<u64>,
+    ) -> Result<Index<K, V, B>>
+    where
+        K: 'static + Send,
+        V: 'static + Send,
+        <V as dbs::Diff>::Delta: 'static + Send,
+        B: Clone + dbs::Bloom,
+        I: 'static + Send + Iterator<Item = Result<E>>,
+        E: TryInto<Entry<K, V>>,
+        <E as TryInto<Entry<K, V>>>::Error: fmt::Display,
+    {
+        let chan_size = self.config.flush_queue_size;
+
+        let mut handles = vec![];
+        let mut rxs = vec![];
+        for iter in iters.into_iter() {
+            let (tx, rx) = mpsc::sync_channel::<Result<Entry<K, V>>>(chan_size);
+            handles.push(thread::spawn(move || {
+                for item in iter {
+                    let item = item.and_then(|e| err_at!(FailConvert, e.try_into()));
+                    if tx.send(item).is_err() {
+                        break; // builder has quit.
+                    }
+                }
+            }));
+            rxs.push(rx);
+        }
+
+        let iter = rxs.into_iter().flat_map(|rx| rx.into_iter());
+        let res = self.build_index(iter, bitmap, seqno);
+
+        for handle in handles.into_iter() {
+            match handle.join() {
+                Ok(()) => (),
+                Err(err) => err_at!(ThreadFail, msg: "shard {:?}", err)?,
+            }
+        }
+
+        res
+    }
+
+    /// Build index from an unsorted iterator. Entries are sorted using an
+    /// external merge sort, sorted runs of `mem_budget` bytes are spilled into
+    /// `tmp_dir` and k-way merged while building the index. Entries with the
+    /// same key are de-duplicated, retaining the entry with newest seqno. Run
+    /// files are removed once the build is complete.
+    pub fn build_unsorted<B, I>(
+        &mut self,
+        iter: I,
+        bitmap: B,
+        seqno: Option<u64>,
+        tmp_dir: &ffi::OsStr,
+        mem_budget: usize,
+    ) -> Result<Index<K, V, B>>
+    where
+        K: Ord,
+        B: Clone + dbs::Bloom,
+        I: Iterator<Item = Result<dbs::Entry<K, V>>>,
+    {
+        if self.config.descending {
+            err_at!(NotImplemented, msg: "unsorted build in descending order")?
+        }
+
+        let name = self.config.name.clone();
+        let iter = ExtSort::new(iter, tmp_dir, &name, mem_budget, self.cmp.clone())?;
+        self.build_index(iter, bitmap, seqno)
+    }
+
+    /// Build index from an iterator of `(key, value, seqno, deltas)`, for
+    /// applications computing deltas externally, like from a CDC source.
+    /// Supplied `deltas`, ordered from oldest to newest, are persisted as is
+    /// into the value-log, without invoking [dbs::Diff::diff]. Each delta
+    /// shall carry a seqno older than its newer delta and `seqno`. Entries
+    /// shall be in sort order. Requires [Config::set_delta] enabled, which is
+    /// the default. Read back older versions using [Index::get_versions].
+    pub fn build_with_deltas<B, I>(
+        &mut self,
+        iter: I,
+        bitmap: B,
+        seqno: Option<u64>,
+    ) -> Result<Index<K, V, B>>
+    where
+        B: Clone + dbs::Bloom,
+        I: Iterator<Item = Result<ExplicitDeltas<K, V>>>,
+    {
+        if !self.config.delta_ok {
+            err_at!(InvalidInput, msg: "build_with_deltas, delta_ok is disabled")?
+        }
+
+        let iter = iter.map(|item| -> Result<dbs::Entry<K, V>> {
+            let (key, value, seqno, deltas) = item?;
+            let mut till = seqno;
+            for delta in deltas.iter().rev() {
+                let dseqno = delta.to_seqno();
+                if dseqno >= till {
+                    err_at!(InvalidInput, msg: "delta seqno {} >= {}", dseqno, till)?
+                }
+                till = dseqno;
+            }
+            let value = dbs::Value::new_upsert(value, seqno);
+            Ok(dbs::Entry { key, value, deltas })
+        });
+
+        self.build_index(iter, bitmap, seqno)
+    }
+
+    /// Build index from line delimited JSON, as generated by
+    /// [Index::export_jsonl]. Entries shall be in sort order, that is, keys
+    /// strictly increasing from one line to the next, and keys and values are
+    /// parsed using [dbs::FromJson]. `app_meta` replaces the application
+    /// metadata supplied while creating this builder.
+    ///
+    /// Malformed lines and out-of-order keys fail the build, errors are
+    /// reported along with the line number, starting from 1.
+    pub fn from_jsonl<R, B>(
+        mut self,
+        r: R,
+        app_meta: Vec<u8>,
+        bitmap: B,
+    ) -> Result<Index<K, V, B>>
+    where
+        K: Ord + dbs::FromJson,
+        V: dbs::FromJson,
+        R: io::BufRead,
+        B: Clone + dbs::Bloom,
+    {
+        if self.config.descending {
+            err_at!(NotImplemented, msg: "jsonl build in descending order")?
+        }
+
+        self.app_meta = app_meta;
+
+        let cmp = self.cmp.clone();
+        let mut prev: Option<K> = None;
+        let iter =
+            r.lines().enumerate().map(move |(i, line)| -> Result<dbs::Entry<K, V>> {
+                let lineno = i + 1;
+                let line = err_at!(IOError, line, "line {}", lineno)?;
+                let entry = parse_jsonl_entry::<K, V>(&line, lineno)?;
+                let ord = |prev: &K| match &cmp {
+                    Some(cmp) => cmp(prev, entry.as_key()),
+                    None => prev.cmp(entry.as_key()),
+                };
+                if matches!(&prev, Some(prev) if ord(prev).is_ge()) {
+                    err_at!(InvalidInput, msg: "line {}: key out of order", lineno)?
+                }
+                prev = Some(entry.to_key());
+                Ok(entry)
+            });
+
+        self.build_index(iter, bitmap, None)
+    }
+
+    // build a bloom filter for each sub-tree under the root block, by reading
+    // back the keys from index-file, and flush them into index-file.
+    fn build_shards<B>(&mut self, root: Option<u64>, proto: B) -> Result<Vec<Shard>>
+    where
+        B: Clone + dbs::Bloom,
+    {
+        let mut reader: Reader<K, V> = {
+            let loc = to_index_location(&self.config.dir, &self.config.name);
+            let index = util::files::open_file_r(&loc)?;
+            Reader::from_root(root, &self.stats, index, None)?
+        };
+
+        let mut shards = vec![];
+        for entry in reader.as_root().iter() {
+            let mut bitmap = proto.clone();
+            reader.walk_keys(entry, &mut |key: &K| bitmap.add_key(key))?;
+            bitmap.build()?;
+
+            let data = bitmap.to_bytes()?;
+            let fpos = self.iflush.borrow().to_fpos().unwrap_or(0);
+            let length = err_at!(FailConvert, u64::try_from(data.len()))?;
+            self.iflush.borrow_mut().flush(data)?;
+
+            let key = util::into_cbor_bytes(entry.to_key())?;
+            shards.push(Shard { key, fpos, length });
+        }
+
+        Ok(shards)
     }
 }
 
@@ -190,31 +598,42 @@ where
     V: dbs::Diff + IntoCbor,
     <V as dbs::Diff>::Delta: IntoCbor,
 {
-    fn build_tree<I>(&self, iter: I) -> Result<(I, Option<u64>)>
+    fn build_tree<I>(&self, iter: I) -> Result<(I, Option<u64>, u64, build::Shape)>
     where
         I: Iterator<Item = Result<Entry<K, V>>>,
     {
         let iter = Rc::new(RefCell::new(iter));
+        let n_rbytes = Rc::new(Cell::new(0));
+        let shape = Rc::new(RefCell::new(build::Shape::default()));
 
         let zz = build::BuildZZ::new(
             &self.config,
             Rc::clone(&self.iflush),
             Rc::clone(&self.vflush),
             Rc::clone(&iter),
+            Rc::clone(&n_rbytes),
         );
-        let mz = build::BuildMZ::new(&self.config, Rc::clone(&self.iflush), zz);
-        let mut build = (0..MAX_DEPTH).fold(build::BuildIter::from(mz), |build, _| {
-            build::BuildMM::new(&self.config, Rc::clone(&self.iflush), build).into()
-        });
+        let mz = {
+            let iflush = Rc::clone(&self.iflush);
+            build::BuildMZ::new(&self.config, iflush, zz, Rc::clone(&shape))
+        };
+        let mut build =
+            (1..=MAX_DEPTH).fold(build::BuildIter::from(mz), |build, level| {
+                let iflush = Rc::clone(&self.iflush);
+                build::BuildMM::new(&self.config, iflush, build, level, Rc::clone(&shape))
+                    .into()
+            });
 
         let root = match build.next() {
-            Some(Ok((_, root))) => Some(root),
+            Some(Ok((_, root, _))) => Some(root),
             Some(Err(err)) => return Err(err),
             None => None,
         };
         mem::drop(build);
 
-        Ok((Rc::try_unwrap(iter).ok().unwrap().into_inner(), root))
+        let iter = Rc::try_unwrap(iter).ok().unwrap().into_inner();
+        let shape = Rc::try_unwrap(shape).ok().unwrap().into_inner();
+        Ok((iter, root, n_rbytes.get(), shape))
     }
 
     fn build_flush(&mut self, bitmap: Vec<u8>) -> Result<(u64, u64)> {
@@ -228,35 +647,275 @@ where
         Ok((len1, len2))
     }
 
+    // close the flushers and remove partially built files. Index-file is
+    // always created by this build, while value-log is removed only if it is
+    // created by this build, else it is truncated back to its length before
+    // this build, refer to `incremental`.
+    fn discard(&mut self) -> Result<()> {
+        // flusher errors are irrelevant for a cancelled build.
+        self.iflush.borrow_mut().close().ok();
+        self.vflush.borrow_mut().close().ok();
+
+        if let Some(loc) = self.iflush.borrow().to_location() {
+            err_at!(IOError, fs::remove_file(&loc), "remove {:?}", loc)?;
+        }
+        match self.vflush.borrow().to_location() {
+            Some(loc) if self.vlog_created => {
+                err_at!(IOError, fs::remove_file(&loc), "remove {:?}", loc)?;
+            }
+            Some(loc) => {
+                let mut opts = fs::OpenOptions::new();
+                let fd = err_at!(IOError, opts.write(true).open(&loc), "{:?}", loc)?;
+                let n = self.stats.n_abytes;
+                err_at!(IOError, fd.set_len(n), "truncate {:?}", loc)?;
+            }
+            None => (),
+        }
+
+        Ok(())
+    }
+
     fn meta_blocks(&mut self, bitmap: Vec<u8>) -> Result<Vec<u8>> {
         let stats = util::into_cbor_bytes(self.stats.clone())?;
 
-        let metas = vec![
-            MetaItem::AppMetadata(self.app_meta.clone()),
+        // app-metadata larger than a block is flushed as a separate region,
+        // 8-byte length-prefixed, just before the meta-block.
+        let app_meta = match self.app_meta.len() {
+            n if n > MARKER_BLOCK_SIZE => {
+                let length = err_at!(FailConvert, u64::try_from(n))?;
+                let fpos = self.iflush.borrow().to_fpos().unwrap_or(0);
+                let mut data = Vec::with_capacity(n + 8);
+                data.extend_from_slice(&length.to_be_bytes());
+                data.extend_from_slice(&self.app_meta);
+                self.iflush.borrow_mut().flush(data)?;
+                MetaItem::AppMetadataAt { fpos, length }
+            }
+            _ => MetaItem::AppMetadata(self.app_meta.clone()),
+        };
+
+        let mut metas = vec![
+            app_meta,
             MetaItem::Stats(stats),
             MetaItem::Bitmap(bitmap),
             MetaItem::Root(self.root),
             MetaItem::Marker(ROOT_MARKER.clone()),
+            MetaItem::Shards(util::into_cbor_bytes(self.shards.clone())?),
         ];
+        if let Some(tag) = &self.provenance {
+            let provenance = Provenance::new(tag)?;
+            metas.push(MetaItem::Provenance(util::into_cbor_bytes(provenance)?));
+        }
+        if let Some(base) = &self.base {
+            metas.push(MetaItem::Base(util::into_cbor_bytes(base.clone())?));
+        }
+        if let Some(data) = &self.prefix_bloom {
+            metas.push(MetaItem::PrefixBloom(data.clone()));
+        }
+
+        encode_meta_block(metas)
+    }
+}
+
+// encode the list of meta items into the meta-block, that is appended to the
+// tip of the index-file.
+pub(crate) fn encode_meta_block(metas: Vec<MetaItem>) -> Result<Vec<u8>> {
+    let mut block = util::into_cbor_bytes(metas)?;
+    let len = err_at!(Fatal, u64::try_from(block.len()))?;
+    let m = compute_root_block(block.len() + 24);
+    block.resize(m, 0);
+    let off = err_at!(Fatal, u64::try_from(m))?;
+
+    // 8-byte endianness marker followed by 8-byte length-prefixed-message,
+    // message is the meta-block.
+    block[m - 24..m - 16].copy_from_slice(&ENDIAN_MARKER.to_be_bytes());
+    block[m - 16..m - 8].copy_from_slice(&off.to_be_bytes());
+    block[m - 8..m].copy_from_slice(&len.to_be_bytes());
+
+    Ok(block)
+}
+
+// read the list of meta items from the meta-block at the tip of the index-file,
+// along with the size of the meta-block.
+fn read_meta_items(index: &mut fs::File) -> Result<(Vec<MetaItem>, u64)> {
+    {
+        let seek = io::SeekFrom::End(-24);
+        let data = read_file!(index, seek, 8, "reading endian-marker from index")?;
+        let data: [u8; 8] = data.try_into().unwrap();
+        if data != ENDIAN_MARKER.to_be_bytes() {
+            let layout = match u64::from_le_bytes(data) == ENDIAN_MARKER {
+                true => "little-endian",
+                false => "unknown",
+            };
+            err_at!(InvalidFile, msg: "incompatible layout {} {:?}", layout, data)?
+        }
+    }
+    let off = {
+        let seek = io::SeekFrom::End(-16);
+        let data = read_file!(index, seek, 8, "reading meta-off from index")?;
+        i64::from_be_bytes(data.try_into().unwrap())
+    };
+    let len = {
+        let seek = io::SeekFrom::End(-8);
+        let data = read_file!(index, seek, 8, "reading meta-len from index")?;
+        u64::from_be_bytes(data.try_into().unwrap())
+    };
+    let seek = io::SeekFrom::End(-off);
+    let block = read_file!(index, seek, len, "reading meta-data from index")?;
+    let metas = util::from_cbor_bytes(&block)?.0;
+
+    Ok((metas, err_at!(FailConvert, u64::try_from(off))?))
+}
+
+// collect file-position of z-blocks under `entries`, in sort order.
+fn collect_zblocks<K, V>(
+    reader: &mut Reader<K, V>,
+    entries: &[Entry<K, V>],
+    zblocks: &mut Vec<u64>,
+) -> Result<()>
+where
+    K: FromCbor,
+    V: dbs::Diff + FromCbor,
+    <V as dbs::Diff>::Delta: FromCbor,
+{
+    for entry in entries.iter() {
+        match entry {
+            Entry::MM { fpos, .. } => {
+                let (m_blocksize, codec) = (reader.m_blocksize, reader.codec);
+                let block = codec.read_block(&mut reader.index, *fpos, m_blocksize)?;
+                let entries: Vec<Entry<K, V>> =
+                    util::from_cbor_bytes(&block).at_fpos(*fpos)?.0;
+                collect_zblocks(reader, &entries, zblocks)?;
+            }
+            Entry::MZ { fpos, .. } => zblocks.push(*fpos),
+            Entry::ZZ { .. } => err_at!(Fatal, msg: "robt-zz entry in m-block")?,
+        }
+    }
+
+    Ok(())
+}
+
+// gallop from `pos`, doubling the step, to bracket the key compared by `f`,
+// and binary search within the bracket. Return value is same as that of
+// slice::binary_search_by.
+fn gallop<T, F>(block: &[T], pos: usize, f: F) -> result::Result<usize, usize>
+where
+    F: Fn(&T) -> cmp::Ordering,
+{
+    let pos = cmp::min(pos, block.len() - 1);
+    let (lo, hi) = match f(&block[pos]) {
+        cmp::Ordering::Equal => return Ok(pos),
+        cmp::Ordering::Less => {
+            let (mut lo, mut step) = (pos + 1, 1);
+            let hi = loop {
+                let hi = pos + step;
+                if hi >= block.len() {
+                    break block.len();
+                }
+                match f(&block[hi]) {
+                    cmp::Ordering::Less => lo = hi + 1,
+                    cmp::Ordering::Equal => return Ok(hi),
+                    cmp::Ordering::Greater => break hi,
+                }
+                step *= 2;
+            };
+            (lo, hi)
+        }
+        cmp::Ordering::Greater => {
+            let (mut hi, mut step) = (pos, 1);
+            let lo = loop {
+                if step > pos {
+                    break 0;
+                }
+                let lo = pos - step;
+                match f(&block[lo]) {
+                    cmp::Ordering::Greater => hi = lo,
+                    cmp::Ordering::Equal => return Ok(lo),
+                    cmp::Ordering::Less => break lo + 1,
+                }
+                step *= 2;
+            };
+            (lo, hi)
+        }
+    };
+
+    match block[lo..hi].binary_search_by(|e| f(e)) {
+        Ok(off) => Ok(lo + off),
+        Err(off) => Err(lo + off),
+    }
+}
 
-        let mut block = util::into_cbor_bytes(metas)?;
-        let len = err_at!(Fatal, u64::try_from(block.len()))?;
-        let m = Self::compute_root_block(block.len() + 16);
-        block.resize(m, 0);
-        let off = err_at!(Fatal, u64::try_from(m))?;
+// compose value-log path under `vlog_dir`, using the file name persisted in
+// `stats`, defaults to value-log file name for index `name`.
+pub(crate) fn to_vlog_path(
+    vlog_dir: &ffi::OsStr,
+    name: &str,
+    stats: &Stats,
+) -> ffi::OsString {
+    let vloc = stats.vlog_location.as_ref();
+    let file_name = match vloc.map(|f| path::Path::new(f).file_name()) {
+        Some(Some(file_name)) => file_name.to_os_string(),
+        _ => ffi::OsString::from(VlogFileName::from(name.to_string())),
+    };
+    let vp: path::PathBuf = [vlog_dir.to_os_string(), file_name].iter().collect();
+    vp.into_os_string()
+}
 
-        // 8-byte length-prefixed-message, message is the meta-block.
-        block[m - 16..m - 8].copy_from_slice(&off.to_be_bytes());
-        block[m - 8..m].copy_from_slice(&len.to_be_bytes());
+// value-log at `vlog_loc` is owned by index `name`, as against a value-log
+// shared with a base index by incremental snapshots. Versioned value-logs,
+// re-written by gc_vlog, are owned by the same index.
+fn is_vlog_owner(name: &str, vlog_loc: &ffi::OsStr) -> bool {
+    match VlogFileName(vlog_loc.to_os_string()).to_version() {
+        Ok((vname, _)) => vname == name,
+        Err(_) => false,
+    }
+}
 
-        Ok(block)
+// open value-log file for reading, if index is built with value-log. Missing
+// value-log is tolerated for index built with Config::set_values_optional.
+fn open_vlog(
+    vlog_dir: &ffi::OsStr,
+    name: &str,
+    stats: &Stats,
+) -> Result<Option<fs::File>> {
+    if !(stats.value_in_vlog || stats.delta_ok) {
+        return Ok(None);
     }
 
-    fn compute_root_block(n: usize) -> usize {
-        match n % MARKER_BLOCK_SIZE {
-            0 => n,
-            _ => ((n / MARKER_BLOCK_SIZE) + 1) * MARKER_BLOCK_SIZE,
+    let vp = to_vlog_path(vlog_dir, name, stats);
+    match fs::OpenOptions::new().read(true).open(&vp) {
+        Ok(vlog) => Ok(Some(vlog)),
+        Err(err) if stats.values_optional && err.kind() == io::ErrorKind::NotFound => {
+            Ok(None)
         }
+        Err(err) => err_at!(IOError, msg: "vlog {:?} {}", vp, err),
+    }
+}
+
+// temporary file next to `loc`, used while re-writing it, each operation
+// shall use its own `suffix`.
+fn to_temp_location(loc: &ffi::OsStr, suffix: &str) -> ffi::OsString {
+    let mut loc = loc.to_os_string();
+    loc.push(suffix);
+    loc
+}
+
+// read app-metadata persisted as a length-prefixed region at `fpos`.
+fn read_app_metadata(index: &mut fs::File, fpos: u64, length: u64) -> Result<Vec<u8>> {
+    let seek = io::SeekFrom::Start(fpos);
+    let prefix = read_file!(index, seek, 8, "reading app-metadata length")?;
+    let n = u64::from_be_bytes(prefix.try_into().unwrap());
+    if n != length {
+        err_at!(InvalidFile, msg: "app-metadata length {} != {} at {}", n, length, fpos)?
+    }
+
+    let seek = io::SeekFrom::Start(fpos + 8);
+    read_file!(index, seek, length, "reading app-metadata from index")
+}
+
+fn compute_root_block(n: usize) -> usize {
+    match n % MARKER_BLOCK_SIZE {
+        0 => n,
+        _ => ((n / MARKER_BLOCK_SIZE) + 1) * MARKER_BLOCK_SIZE,
     }
 }
 
@@ -270,6 +929,11 @@ where
 pub enum MetaItem {
     /// Application supplied metadata, typically serialized and opaque to `robt`.
     AppMetadata(Vec<u8>),
+    /// Application supplied metadata larger than a block, persisted as a
+    /// separate 8-byte length-prefixed region at `fpos`, `length` does not
+    /// include the prefix. Replaced with [MetaItem::AppMetadata] when the
+    /// index is opened.
+    AppMetadataAt { fpos: u64, length: u64 },
     /// Contains index-statistics along with configuration values.
     Stats(Vec<u8>),
     /// Bloom-filter.
@@ -279,12 +943,92 @@ pub enum MetaItem {
     Root(Option<u64>),
     /// Finger print for robt.
     Marker(Vec<u8>),
+    /// List of bloom-filters, one for each sub-tree under the root, refer to
+    /// [Config::set_bloom_shards].
+    Shards(Vec<u8>),
+    /// Build provenance, refer to [Builder::set_provenance]. Optional, absent
+    /// in indexes built without provenance.
+    Provenance(Vec<u8>),
+    /// Reference to base index, refer to [Builder::set_base]. Optional, absent
+    /// in indexes that are not built as delta index.
+    Base(Vec<u8>),
+    /// Bloom-filter over key prefixes, refer to [Config::set_prefix_bloom].
+    /// Optional, absent in indexes built without prefix bloom.
+    PrefixBloom(Vec<u8>),
 }
 
 impl MetaItem {
     const ID: &'static str = "robt/metaitem/0.0.1";
 }
 
+/// Build provenance, persisted along with the index, useful for forensic
+/// debugging of indexes across a fleet.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Cborize)]
+pub struct Provenance {
+    /// Version of this crate that built the index.
+    pub version: String,
+    /// Hostname of the machine that built the index.
+    pub hostname: String,
+    /// User supplied tag.
+    pub tag: String,
+    /// Build time, in seconds since UNIX_EPOCH.
+    pub timestamp: u64,
+}
+
+impl Provenance {
+    const ID: u32 = PROVENANCE_VER;
+
+    fn new(tag: &str) -> Result<Provenance> {
+        let timestamp = err_at!(Fatal, time::UNIX_EPOCH.elapsed())?.as_secs();
+        let val = Provenance {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            hostname: sys_info::hostname().unwrap_or_default(),
+            tag: tag.to_string(),
+            timestamp,
+        };
+        Ok(val)
+    }
+}
+
+/// Reference to base index, persisted along with a delta index, refer to
+/// [Builder::set_base].
+#[derive(Clone, Debug, Default, Eq, PartialEq, Cborize)]
+pub struct BaseRef {
+    /// Name of the base index.
+    pub name: String,
+    /// Seqno of the base index.
+    pub seqno: u64,
+    /// Timestamp when base index was built, refer to [Stats::epoch].
+    pub epoch: u64,
+}
+
+impl BaseRef {
+    const ID: u32 = BASEREF_VER;
+}
+
+/// Estimated I/O cost of a query, refer to [Index::estimate_get_cost] and
+/// [Index::estimate_scan_cost].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct IoCost {
+    /// Number of index blocks, m-blocks and z-blocks, read from disk.
+    pub block_reads: usize,
+    /// Number of values read from value-log.
+    pub vlog_reads: usize,
+}
+
+// Bloom filter for a sub-tree under root, `key` is the first key in the sub-tree
+// and bloom filter is persisted at `fpos` in index-file.
+#[derive(Clone, Debug, Cborize)]
+struct Shard {
+    key: Vec<u8>,
+    fpos: u64,
+    length: u64,
+}
+
+impl Shard {
+    const ID: u32 = SHARD_VER;
+}
+
 /// Index type, immutable, durable, fully-packed and lockless reads.
 pub struct Index<K, V, B>
 where
@@ -295,11 +1039,22 @@ where
 {
     dir: ffi::OsString,
     name: String,
+    // directory holding the value-log file, if any.
+    vlog_dir: ffi::OsString,
 
     reader: Reader<K, V>,
     metas: Arc<Vec<MetaItem>>,
     stats: Stats,
-    bitmap: Arc<B>,
+    // None, if index is opened with lazy bitmap, and bitmap is not loaded yet.
+    bitmap: Option<Arc<B>>,
+    // (first-key, fpos, length) for each bloom shard.
+    shards: Arc<Vec<(K, u64, u64)>>,
+    // lazily loaded bloom shards.
+    shard_bitmaps: Vec<Option<B>>,
+    // bloom filter over key prefixes, if built with prefix bloom.
+    prefix_bitmap: Option<Arc<B>>,
+    // permit to hold open file descriptors, if opened with a limiter.
+    permit: Option<util::FdPermit>,
 }
 
 impl<K, V, B> Index<K, V, B>
@@ -317,9 +1072,30 @@ where
         }
     }
 
+    /// Open an existing index for read-only, with its value-log file located
+    /// under `vlog_dir`, overriding the directory persisted with the index.
+    /// Refer to [Config::set_vlog_dir].
+    pub fn open_with_vlog_dir(
+        dir: &ffi::OsStr,
+        name: &str,
+        vlog_dir: &ffi::OsStr,
+    ) -> Result<Index<K, V, B>> {
+        match find_index_file(dir, name) {
+            Some(file) => Self::do_open_file(&file, Some(vlog_dir)),
+            None => err_at!(InvalidInput, msg: "no index file {:?}/{}", dir, name)?,
+        }
+    }
+
     /// Open an existing index for read-only, from index file. file must be supplied
     /// along with full-path.
     pub fn open_file(file: &ffi::OsStr) -> Result<Index<K, V, B>> {
+        Self::do_open_file(file, None)
+    }
+
+    fn do_open_file(
+        file: &ffi::OsStr,
+        vlog_dir: Option<&ffi::OsStr>,
+    ) -> Result<Index<K, V, B>> {
         let dir = match path::Path::new(file).parent() {
             Some(dir) => dir.as_os_str().to_os_string(),
             None => err_at!(IOError, msg: "file {:?} does not have parent dir", file)?,
@@ -328,21 +1104,12 @@ where
 
         let mut index = err_at!(IOError, fs::OpenOptions::new().read(true).open(&file))?;
 
-        let metas: Vec<MetaItem> = {
-            let off = {
-                let seek = io::SeekFrom::End(-16);
-                let data = read_file!(index, seek, 8, "reading meta-off from index")?;
-                i64::from_be_bytes(data.try_into().unwrap())
-            };
-            let len = {
-                let seek = io::SeekFrom::End(-8);
-                let data = read_file!(index, seek, 8, "reading meta-len from index")?;
-                u64::from_be_bytes(data.try_into().unwrap())
-            };
-            let seek = io::SeekFrom::End(-off);
-            let block = read_file!(index, seek, len, "reading meta-data from index")?;
-            util::from_cbor_bytes(&block)?.0
-        };
+        let mut metas = read_meta_items(&mut index)?.0;
+
+        if let MetaItem::AppMetadataAt { fpos, length } = &metas[0] {
+            let data = read_app_metadata(&mut index, *fpos, *length)?;
+            metas[0] = MetaItem::AppMetadata(data);
+        }
 
         let stats: Stats = match &metas[1] {
             MetaItem::Stats(stats) => util::from_cbor_bytes(stats)?.0,
@@ -350,9 +1117,16 @@ where
         };
 
         let bitmap = match &metas[2] {
-            MetaItem::Bitmap(data) => err_at!(Fatal, B::from_bytes(data))?.0,
+            MetaItem::Bitmap(_) if stats.lazy_bitmap => None,
+            MetaItem::Bitmap(data) => {
+                Some(Arc::new(err_at!(Fatal, B::from_bytes(data))?.0))
+            }
             _ => unreachable!(),
         };
+        if stats.lazy_bitmap {
+            // don't hold on to the serialized bitmap, re-read it when loaded.
+            metas[2] = MetaItem::Bitmap(vec![]);
+        }
 
         let root = match &metas[3] {
             MetaItem::Root(root) => *root,
@@ -365,30 +1139,49 @@ where
             }
         }
 
-        let vlog = match stats.value_in_vlog || stats.delta_ok {
-            true => {
-                let vloc = stats.vlog_location.as_ref();
-                let file_name = match vloc.map(|f| path::Path::new(f).file_name()) {
-                    Some(Some(file_name)) => file_name.to_os_string(),
-                    _ => ffi::OsString::from(VlogFileName::from(name.to_string())),
-                };
-                let vp: path::PathBuf = [dir.clone(), file_name].iter().collect();
-                let vlog = err_at!(IOError, fs::OpenOptions::new().read(true).open(&vp))?;
-                Some(vlog)
+        let shards = match metas.get(5) {
+            Some(MetaItem::Shards(data)) => {
+                let mut shards = vec![];
+                for shard in util::from_cbor_bytes::<Vec<Shard>>(data)?.0.into_iter() {
+                    let key: K = util::from_cbor_bytes(&shard.key)?.0;
+                    shards.push((key, shard.fpos, shard.length));
+                }
+                shards
+            }
+            _ => vec![],
+        };
+        let shard_bitmaps = shards.iter().map(|_| None).collect();
+
+        let mut prefix_bitmap = None;
+        for meta in metas.iter() {
+            if let MetaItem::PrefixBloom(data) = meta {
+                prefix_bitmap = Some(Arc::new(err_at!(Fatal, B::from_bytes(data))?.0));
             }
-            false => None,
+        }
+
+        let vlog_dir = match (vlog_dir, stats.vlog_dir.as_ref()) {
+            (Some(vlog_dir), _) => vlog_dir.to_os_string(),
+            (None, Some(vlog_dir)) => vlog_dir.clone(),
+            (None, None) => dir.clone(),
         };
+        let vlog = open_vlog(&vlog_dir, &name, &stats)?;
 
-        let reader = Reader::from_root(root, &stats, index, vlog)?;
+        let mut reader = Reader::from_root(root, &stats, index, vlog)?;
+        reader.pin_top_levels(stats.pin_top_levels)?;
 
         let val = Index {
             dir,
             name,
+            vlog_dir,
 
             reader,
             metas: Arc::new(metas),
             stats,
-            bitmap: Arc::new(bitmap),
+            bitmap,
+            shards: Arc::new(shards),
+            shard_bitmaps,
+            prefix_bitmap,
+            permit: None,
         };
 
         Ok(val)
@@ -397,45 +1190,114 @@ where
     /// Optionally set a different bitmap over this index. Know what you are doing
     /// before calling this API.
     pub fn set_bitmap(&mut self, bitmap: B) {
-        self.bitmap = Arc::new(bitmap)
+        self.bitmap = Some(Arc::new(bitmap))
     }
 
-    /// Clone this index instance, with its underlying meta-data `shared` across index
-    /// instances. Note that file-descriptors are not `shared`.
-    pub fn try_clone(&self) -> Result<Self> {
-        let index = match find_index_file(&self.dir, &self.name) {
-            Some(ip) => err_at!(IOError, fs::OpenOptions::new().read(true).open(&ip))?,
-            None => err_at!(InvalidFile, msg: "bad file {:?}/{}", &self.dir, &self.name)?,
-        };
+    /// Load the bitmap, if index is configured with
+    /// [Config::set_lazy_bitmap] and bitmap is not loaded yet, and return a
+    /// reference to the bitmap. Loaded bitmap is cached with the index.
+    pub fn load_bitmap(&mut self) -> Result<&B> {
+        if self.bitmap.is_none() {
+            let bitmap = match &read_meta_items(&mut self.reader.index)?.0[2] {
+                MetaItem::Bitmap(data) => err_at!(Fatal, B::from_bytes(data))?.0,
+                _ => unreachable!(),
+            };
+            self.bitmap = Some(Arc::new(bitmap));
+        }
+        Ok(self.bitmap.as_deref().unwrap())
+    }
 
-        let vlog = match self.stats.value_in_vlog || self.stats.delta_ok {
-            true => {
-                let vloc = self.stats.vlog_location.as_ref();
-                let fnm = match vloc.map(|f| path::Path::new(f).file_name()) {
-                    Some(Some(fnm)) => fnm.to_os_string(),
-                    _ => ffi::OsString::from(VlogFileName::from(self.name.to_string())),
-                };
-                let vp: path::PathBuf = [self.dir.to_os_string(), fnm].iter().collect();
-                Some(err_at!(IOError, fs::OpenOptions::new().read(true).open(&vp))?)
-            }
-            false => None,
-        };
+    /// Set the custom comparator used to build this index, refer to
+    /// [Builder::set_comparator]. Indexes built with a custom comparator can be
+    /// read only after setting the comparator. `id` shall match the one
+    /// persisted with the index.
+    pub fn set_comparator(&mut self, id: &str, cmp: Comparator<K>) -> Result<()> {
+        if id != self.stats.comparator {
+            err_at!(InvalidInput, msg: "comparator {:?} != {:?}", id, self.stats.comparator)?
+        }
+        self.reader.cmp = Some(cmp);
+        Ok(())
+    }
 
-        let root = match &self.metas[3] {
-            MetaItem::Root(root) => *root,
-            _ => unreachable!(),
+    /// Prefetch upto `blocks` number of leaf blocks, in a background thread,
+    /// while iterating in forward direction. Useful for sequential scans on
+    /// spinning disks. Set `blocks` to ZERO to disable read-ahead, which is
+    /// the default. Applicable only on unix platforms, a no-op on others.
+    pub fn set_readahead(&mut self, blocks: usize) -> &mut Self {
+        self.reader.readahead = match blocks {
+            0 => None,
+            n => Some(n),
         };
+        self
+    }
 
-        let reader = Reader::from_root(root, &self.stats, index, vlog)?;
+    /// Advise the kernel about the expected access pattern for index-file and
+    /// value-log file, using `posix_fadvise`. For example, `Sequential` before
+    /// a full table scan and `DontNeed` after it to release the page cache.
+    /// Applicable only on Linux, Android and FreeBSD, a no-op on other
+    /// platforms, refer to [util::files::advise_file]. Advice applies to
+    /// this instance's file descriptors, cloned instances are not affected.
+    pub fn advise(&mut self, access: util::files::Access) -> Result<()> {
+        util::files::advise_file(&self.reader.index, access)?;
+        if let Some(vlog) = self.reader.vlog.as_ref() {
+            util::files::advise_file(vlog, access)?;
+        }
+        Ok(())
+    }
+
+    /// Open an existing index for read-only, after acquiring a permit from
+    /// `limiter`. Blocks until a permit is available and the permit is released
+    /// when the index is dropped. Cloned index shall acquire its own permit from
+    /// the same limiter.
+    pub fn open_with_limiter(
+        dir: &ffi::OsStr,
+        name: &str,
+        limiter: &util::FdLimiter,
+    ) -> Result<Index<K, V, B>> {
+        let permit = limiter.acquire()?;
+        let mut index = Self::open(dir, name)?;
+        index.permit = Some(permit);
+        Ok(index)
+    }
+
+    /// Clone this index instance, with its underlying meta-data `shared` across index
+    /// instances. Note that file-descriptors are not `shared`.
+    pub fn try_clone(&self) -> Result<Self> {
+        let permit = match &self.permit {
+            Some(permit) => Some(permit.to_limiter().acquire()?),
+            None => None,
+        };
+
+        let index = match find_index_file(&self.dir, &self.name) {
+            Some(ip) => err_at!(IOError, fs::OpenOptions::new().read(true).open(&ip))?,
+            None => err_at!(InvalidFile, msg: "bad file {:?}/{}", &self.dir, &self.name)?,
+        };
+
+        let vlog = open_vlog(&self.vlog_dir, &self.name, &self.stats)?;
+
+        let root = match &self.metas[3] {
+            MetaItem::Root(root) => *root,
+            _ => unreachable!(),
+        };
+
+        let mut reader = Reader::from_root(root, &self.stats, index, vlog)?;
+        reader.cmp = self.reader.cmp.clone();
+        reader.readahead = self.reader.readahead;
+        reader.pinned = Arc::clone(&self.reader.pinned);
 
         let val = Index {
             dir: self.dir.clone(),
             name: self.name.clone(),
+            vlog_dir: self.vlog_dir.clone(),
 
             reader,
             metas: Arc::clone(&self.metas),
             stats: self.stats.clone(),
-            bitmap: Arc::clone(&self.bitmap),
+            bitmap: self.bitmap.clone(),
+            shards: Arc::clone(&self.shards),
+            shard_bitmaps: self.shards.iter().map(|_| None).collect(),
+            prefix_bitmap: self.prefix_bitmap.clone(),
+            permit,
         };
 
         Ok(val)
@@ -454,9 +1316,18 @@ where
         V: IntoCbor,
         <V as dbs::Diff>::Delta: IntoCbor,
     {
+        if !self.stats.comparator.is_empty() {
+            err_at!(NotImplemented, msg: "incremental build with custom comparator")?
+        }
+
+        let vlog_location = self.to_vlog_location();
+        let vlog_dir = self.vlog_dir.clone();
+
         let mut config: Config = self.stats.into();
         config.dir = dir.to_os_string();
         config.name = name.to_string();
+        config.vlog_dir = Some(vlog_dir);
+        config.vlog_location = vlog_location;
         Builder::incremental(config, meta)
     }
 
@@ -471,9 +1342,14 @@ where
     ) -> Result<Self>
     where
         K: Clone + Ord + Hash + IntoCbor,
+        B: Clone,
         V: IntoCbor,
         <V as dbs::Diff>::Delta: IntoCbor,
     {
+        if !self.stats.comparator.is_empty() {
+            err_at!(NotImplemented, msg: "compaction with custom comparator")?
+        }
+
         // set to fresh vlog location, don't carry forward.
         config.set_vlog_location(None);
 
@@ -482,23 +1358,356 @@ where
             Builder::<K, V>::initial(config.clone(), app_meta)?
         };
         let r = (Bound::<K>::Unbounded, Bound::<K>::Unbounded);
-        let iter = CompactScan::new(self.iter_versions(r)?, cutoff);
+        let iter = match config.descending {
+            true => CompactScan::new(self.reverse_versions(r)?, cutoff),
+            false => CompactScan::new(self.iter_versions(r)?, cutoff),
+        };
 
         builder.build_index(iter, bitmap, None)?;
 
         Index::open(&config.dir, &config.name)
     }
 
+    /// Tombstone-compact this index into a new index specified by [Config].
+    /// Only entries marked as deleted, and whose deleted seqno is older than
+    /// `seqno`, are purged. Live entries and all their versions are carried
+    /// over as is. Same as calling [compact][Index::compact] with
+    /// `Cutoff::Tombstone(Bound::Excluded(seqno))`.
+    pub fn tombstone_compact(self, config: Config, bitmap: B, seqno: u64) -> Result<Self>
+    where
+        K: Clone + Ord + Hash + IntoCbor,
+        B: Clone,
+        V: IntoCbor,
+        <V as dbs::Diff>::Delta: IntoCbor,
+    {
+        let cutoff = dbs::Cutoff::new_tombstone(Bound::Excluded(seqno));
+        self.compact(config, bitmap, cutoff)
+    }
+
+    /// Garbage collect the value-log of this index, without re-building the
+    /// btree. Values and deltas referenced by this index are copied into a
+    /// fresh value-log, z-blocks are re-written to refer to the new
+    /// file-positions, and the re-written index-file is swapped in. Return
+    /// the number of bytes reclaimed, that is, the size of older value-log
+    /// minus the size of fresh value-log.
+    ///
+    /// Cheaper than [compact][Index::compact] for value heavy indexes, but
+    /// supported only for uncompressed blocks. Fresh value-log is created
+    /// under a new versioned file name, refer to [VlogFileName::from_version].
+    /// Older value-log is never modified or removed, since it can be shared
+    /// with other snapshots, as with incremental builds, application shall
+    /// remove it once no index refers to it. Renaming the re-written
+    /// index-file over this index-file is the only commit point, on failure
+    /// this index and its older value-log are left as is.
+    pub fn gc_vlog(&mut self) -> Result<usize>
+    where
+        K: IntoCbor,
+        V: IntoCbor,
+        <V as dbs::Diff>::Delta: IntoCbor,
+    {
+        let old_vlog = match self.to_vlog_location() {
+            Some(loc) if self.reader.vlog.is_some() => loc,
+            _ => return Ok(0),
+        };
+        if !self.reader.codec.is_none() {
+            err_at!(NotImplemented, msg: "gc_vlog with {:?}", self.reader.codec)?
+        }
+
+        let index_loc = self.to_index_location();
+        let vlog_loc = self.to_next_vlog_location(&old_vlog)?;
+        let itmp = to_temp_location(&index_loc, ".gc");
+
+        let n_bytes = err_at!(IOError, fs::metadata(&old_vlog))?.len();
+        let res = self.do_gc_vlog(&index_loc, &itmp, &vlog_loc).and_then(|vfpos| {
+            err_at!(IOError, fs::rename(&itmp, &index_loc))?;
+            Ok(vfpos)
+        });
+        let vfpos = match res {
+            Ok(vfpos) => vfpos,
+            Err(err) => {
+                fs::remove_file(&itmp).ok();
+                fs::remove_file(&vlog_loc).ok();
+                return Err(err);
+            }
+        };
+
+        let mut index = Index::do_open_file(&index_loc, Some(&self.vlog_dir))?;
+        index.reader.cmp = self.reader.cmp.clone();
+        index.reader.readahead = self.reader.readahead;
+        index.bitmap = self.bitmap.clone();
+        index.permit = self.permit.take();
+        *self = index;
+
+        err_at!(FailConvert, usize::try_from(n_bytes.saturating_sub(vfpos)))
+    }
+
+    // next version of value-log file for this index, that does not exist yet.
+    fn to_next_vlog_location(&self, vlog: &ffi::OsStr) -> Result<ffi::OsString> {
+        let file_name = VlogFileName(vlog.to_os_string());
+        let mut version = match file_name.to_version()? {
+            (name, version) if name == self.name => version + 1,
+            _ => 1,
+        };
+        loop {
+            let file_name = VlogFileName::from_version(self.name.clone(), version);
+            let loc: path::PathBuf =
+                [self.vlog_dir.clone(), file_name.into()].iter().collect();
+            match loc.exists() {
+                true => version += 1,
+                false => break Ok(loc.into_os_string()),
+            }
+        }
+    }
+
+    // copy referenced values and deltas into `vlog_loc`, and write the index,
+    // with relocated z-blocks, into `itmp`. Return the size of `vlog_loc`.
+    fn do_gc_vlog(
+        &mut self,
+        index_loc: &ffi::OsStr,
+        itmp: &ffi::OsStr,
+        vlog_loc: &ffi::OsStr,
+    ) -> Result<u64>
+    where
+        K: IntoCbor,
+        V: IntoCbor,
+        <V as dbs::Diff>::Delta: IntoCbor,
+    {
+        let mut zblocks = vec![];
+        let root = self.reader.as_root();
+        collect_zblocks(&mut self.reader, &root, &mut zblocks)?;
+
+        err_at!(IOError, fs::copy(index_loc, itmp))?;
+        let mut ifd = {
+            let mut opts = fs::OpenOptions::new();
+            err_at!(IOError, opts.read(true).write(true).open(itmp))?
+        };
+        let mut vfd = util::files::create_file_a(vlog_loc)?;
+
+        let z_blocksize = self.reader.z_blocksize;
+        let mut vfpos = 0;
+        for fpos in zblocks.into_iter() {
+            let seek = io::SeekFrom::Start(fpos);
+            let block = read_file!(self.reader.index, seek, z_blocksize, "gc z-block")?;
+            let entries: Vec<Entry<K, V>> =
+                util::from_cbor_bytes(&block).at_fpos(fpos)?.0;
+
+            let mut zblock = Vec::with_capacity(z_blocksize);
+            err_at!(
+                FailCbor,
+                Cbor::Major4(cbor::Info::Indefinite, vec![]).encode(&mut zblock)
+            )?;
+            let mut vblock = vec![];
+            for entry in entries.into_iter() {
+                let vlog = self.reader.vlog.as_mut().unwrap();
+                let (entry, data) = entry.relocate(vlog, vfpos)?;
+                vfpos += err_at!(FailConvert, u64::try_from(data.len()))?;
+                vblock.extend_from_slice(&data);
+                zblock.extend_from_slice(&util::into_cbor_bytes(entry)?);
+            }
+            zblock.extend_from_slice(&util::into_cbor_bytes(cbor::SimpleValue::Break)?);
+            if zblock.len() > z_blocksize {
+                let n = zblock.len();
+                err_at!(Fatal, msg: "gc z-block {} > {} at {}", n, z_blocksize, fpos)?
+            }
+            zblock.resize(z_blocksize, 0);
+
+            write_file!(vfd, &vblock, vlog_loc, "gc value-log")?;
+            err_at!(IOError, ifd.seek(io::SeekFrom::Start(fpos)))?;
+            err_at!(IOError, ifd.write_all(&zblock))?;
+        }
+
+        // re-write the meta-block with fresh value-log statistics.
+        let (mut metas, off) = read_meta_items(&mut ifd)?;
+        let mut stats = self.stats.clone();
+        stats.vlog_location = Some(vlog_loc.to_os_string());
+        stats.n_abytes = 0;
+        stats.n_gbytes = 0;
+        stats.n_vbytes = vfpos;
+        metas[1] = MetaItem::Stats(util::into_cbor_bytes(stats)?);
+        let block = encode_meta_block(metas)?;
+
+        let n = err_at!(IOError, ifd.metadata())?.len() - off;
+        err_at!(IOError, ifd.set_len(n))?;
+        err_at!(IOError, ifd.seek(io::SeekFrom::Start(n)))?;
+        err_at!(IOError, ifd.write_all(&block))?;
+        // fresh value-log shall be durable before the index refers to it.
+        err_at!(IOError, vfd.sync_all())?;
+        err_at!(IOError, ifd.sync_all())?;
+
+        Ok(vfpos)
+    }
+
+    /// Copy the value-log file, as is, into `w`, say, to ship older values
+    /// and deltas to cold storage. Return the number of bytes copied, ZERO
+    /// if index is built without value-log. Value-log shared with older
+    /// indexes, as with incremental builds, is copied in full, so that
+    /// file-positions referred by z-blocks remain valid within the archive.
+    /// Refer to [Index::restore_vlog] to read back from the archive.
+    pub fn stream_vlog<W>(&self, w: &mut W) -> Result<u64>
+    where
+        W: io::Write,
+    {
+        match self.to_vlog_location() {
+            Some(loc) => {
+                let mut fd = util::files::open_file_r(&loc)?;
+                err_at!(IOError, io::copy(&mut fd, w), "vlog {:?}", loc)
+            }
+            None => Ok(0),
+        }
+    }
+
+    /// Restore value-log archived using [Index::stream_vlog], from `r`, into
+    /// `vlog_dir` and switch this index to read values and deltas from the
+    /// restored file. Z-blocks refer to values by their file-position in the
+    /// value-log, hence they are read back from the archive as is. Useful
+    /// along with [Config::set_values_optional], to open the index after its
+    /// value-log is archived away. Fail if the value-log file already exists
+    /// under `vlog_dir`. Return the number of bytes restored.
+    pub fn restore_vlog<R>(&mut self, r: &mut R, vlog_dir: &ffi::OsStr) -> Result<u64>
+    where
+        R: io::Read,
+    {
+        if !(self.stats.value_in_vlog || self.stats.delta_ok) {
+            err_at!(InvalidInput, msg: "index {:?} without value-log", self.name)?
+        }
+
+        let loc = to_vlog_path(vlog_dir, &self.name, &self.stats);
+        let mut fd = {
+            let mut opts = fs::OpenOptions::new();
+            err_at!(IOError, opts.write(true).create_new(true).open(&loc), "{:?}", loc)?
+        };
+        let n = err_at!(IOError, io::copy(r, &mut fd), "vlog {:?}", loc)?;
+        err_at!(IOError, fd.sync_all())?;
+
+        let vlog = util::files::open_file_r(&loc)?;
+        self.reader.set_vlog(vlog)?;
+        self.vlog_dir = vlog_dir.to_os_string();
+
+        Ok(n)
+    }
+
+    /// Re-build the bloom filter for this index using `bitmap`, possibly of a
+    /// different type, from the keys already indexed. Useful when adopting a
+    /// newer bloom implementation. Only the bitmap and the meta-block are
+    /// re-written, the btree and the value-log are left untouched, and
+    /// [Stats::bitmap_version] is bumped. If the index is built with bloom
+    /// shards, a bloom filter is re-built for each shard, older shard regions
+    /// are left in the index-file as garbage.
+    ///
+    /// Index-file is re-written into a temporary file and swapped in, hence
+    /// crash safe. Not supported for index built with prefix bloom.
+    pub fn rebuild_bitmap<B2>(mut self, bitmap: B2) -> Result<Index<K, V, B2>>
+    where
+        K: Clone + Hash + IntoCbor,
+        B2: Clone + dbs::Bloom,
+    {
+        if self.stats.prefix_bloom {
+            err_at!(NotImplemented, msg: "rebuild_bitmap with prefix bloom")?
+        }
+
+        // build fresh bloom filters, for the whole index or for each shard.
+        let root = self.reader.as_root();
+        let mut shard_bitmaps = vec![];
+        let bitmap = match self.stats.bloom_shards {
+            true => {
+                for entry in root.iter() {
+                    let mut shard = bitmap.clone();
+                    self.reader.walk_keys(entry, &mut |key: &K| shard.add_key(key))?;
+                    shard.build()?;
+                    let key = util::into_cbor_bytes(entry.to_key())?;
+                    shard_bitmaps.push((key, err_at!(Fatal, shard.to_bytes())?));
+                }
+                // bitmap for the whole index is left empty.
+                let mut bitmap = bitmap;
+                bitmap.build()?;
+                bitmap
+            }
+            false => {
+                let mut bitmap = bitmap;
+                for entry in root.iter() {
+                    self.reader.walk_keys(entry, &mut |key: &K| bitmap.add_key(key))?;
+                }
+                bitmap.build()?;
+                bitmap
+            }
+        };
+
+        let index_loc = self.to_index_location();
+        let itmp = to_temp_location(&index_loc, ".bitmap");
+        let res = self
+            .do_rebuild_bitmap(&index_loc, &itmp, shard_bitmaps, &bitmap)
+            .and_then(|_| err_at!(IOError, fs::rename(&itmp, &index_loc)));
+        if let Err(err) = res {
+            fs::remove_file(&itmp).ok();
+            return Err(err);
+        }
+
+        let mut index = Index::do_open_file(&index_loc, Some(&self.vlog_dir))?;
+        index.reader.cmp = self.reader.cmp.clone();
+        index.reader.readahead = self.reader.readahead;
+        index.permit = self.permit.take();
+        Ok(index)
+    }
+
+    // write the index-file, with re-built bitmap and meta-block, into `itmp`.
+    fn do_rebuild_bitmap<B2>(
+        &self,
+        index_loc: &ffi::OsStr,
+        itmp: &ffi::OsStr,
+        shard_bitmaps: Vec<(Vec<u8>, Vec<u8>)>,
+        bitmap: &B2,
+    ) -> Result<()>
+    where
+        B2: dbs::Bloom,
+    {
+        err_at!(IOError, fs::copy(index_loc, itmp))?;
+        let mut ifd = {
+            let mut opts = fs::OpenOptions::new();
+            err_at!(IOError, opts.read(true).write(true).open(itmp))?
+        };
+
+        // drop the older meta-block, append shards, if any, and meta-block.
+        let (mut metas, off) = read_meta_items(&mut ifd)?;
+        let mut fpos = err_at!(IOError, ifd.metadata())?.len() - off;
+        err_at!(IOError, ifd.set_len(fpos))?;
+        err_at!(IOError, ifd.seek(io::SeekFrom::Start(fpos)))?;
+
+        let mut shards = vec![];
+        for (key, data) in shard_bitmaps.into_iter() {
+            let length = err_at!(FailConvert, u64::try_from(data.len()))?;
+            err_at!(IOError, ifd.write_all(&data))?;
+            shards.push(Shard { key, fpos, length });
+            fpos += length;
+        }
+
+        let mut stats = self.stats.clone();
+        stats.bitmap_version += 1;
+        metas[1] = MetaItem::Stats(util::into_cbor_bytes(stats)?);
+        metas[2] = MetaItem::Bitmap(err_at!(Fatal, bitmap.to_bytes())?);
+        if self.stats.bloom_shards {
+            metas[5] = MetaItem::Shards(util::into_cbor_bytes(shards)?);
+        }
+        let block = encode_meta_block(metas)?;
+
+        err_at!(IOError, ifd.write_all(&block))?;
+        err_at!(IOError, ifd.sync_all())?;
+
+        Ok(())
+    }
+
     /// Close this index, releasing OS resources. To purge, call `purge()` method.
     pub fn close(self) -> Result<()> {
         Ok(())
     }
 
-    /// Purge this index from disk.
+    /// Purge this index from disk. Value-log is purged only if it is owned
+    /// by this index, incremental snapshots leave the value-log of their
+    /// base index untouched.
     pub fn purge(self) -> Result<()> {
-        let is_vlog = self.stats.value_in_vlog || self.stats.delta_ok;
         let index_loc = to_index_location(&self.dir, &self.name);
-        let vlog_loc = to_vlog_location(&self.dir, &self.name);
+        let vlog_loc = to_vlog_path(&self.vlog_dir, &self.name, &self.stats);
+        let is_vlog = (self.stats.value_in_vlog || self.stats.delta_ok)
+            && is_vlog_owner(&self.name, &vlog_loc);
 
         mem::drop(self);
 
@@ -529,19 +1738,82 @@ where
         }
     }
 
+    /// Return the build provenance, None if index was built without
+    /// provenance, refer to [Builder::set_provenance].
+    pub fn to_provenance(&self) -> Result<Option<Provenance>> {
+        for meta in self.metas.iter() {
+            if let MetaItem::Provenance(data) = meta {
+                return Ok(Some(util::from_cbor_bytes(data)?.0));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Return the reference to base index, None if index is not built as a
+    /// delta index, refer to [Builder::set_base].
+    pub fn to_base(&self) -> Result<Option<BaseRef>> {
+        for meta in self.metas.iter() {
+            if let MetaItem::Base(data) = meta {
+                return Ok(Some(util::from_cbor_bytes(data)?.0));
+            }
+        }
+        Ok(None)
+    }
+
     pub fn to_stats(&self) -> Stats {
         self.stats.clone()
     }
 
+    /// Return a reference to the bitmap.
+    ///
+    /// Panics if index is configured with [Config::set_lazy_bitmap] and
+    /// bitmap is not loaded yet, refer to [Index::load_bitmap].
     pub fn as_bitmap(&self) -> &B {
-        self.bitmap.as_ref()
+        match self.bitmap.as_deref() {
+            Some(bitmap) => bitmap,
+            None => panic!("lazy bitmap not loaded, call load_bitmap()"),
+        }
     }
 
+    /// Return a clone of the bitmap, panics like [Index::as_bitmap].
     pub fn to_bitmap(&self) -> B
     where
         B: Clone,
     {
-        self.bitmap.as_ref().clone()
+        self.as_bitmap().clone()
+    }
+
+    /// Return whether bitmap is loaded, always true unless index is configured
+    /// with [Config::set_lazy_bitmap].
+    pub fn is_bitmap_loaded(&self) -> bool {
+        self.bitmap.is_some()
+    }
+
+    /// Check whether any key, whose prefix as computed by the prefix function,
+    /// refer to [Builder::set_prefix_fn], matches `prefix`, may be present in
+    /// this index. There can be false positives but no false negatives. If
+    /// index is built without prefix bloom, always return true, unless the
+    /// index is empty.
+    pub fn may_contain_prefix(&self, prefix: &[u8]) -> bool {
+        match &self.prefix_bitmap {
+            _ if self.is_empty() => false,
+            Some(bitmap) => bitmap.contains(prefix),
+            None => true,
+        }
+    }
+
+    /// Return the number of bloom shards and the number of bloom shards loaded
+    /// so far. Refer to [Config::set_bloom_shards].
+    pub fn to_bloom_shards(&self) -> (usize, usize) {
+        let n = self.shard_bitmaps.iter().filter(|b| b.is_some()).count();
+        (self.shards.len(), n)
+    }
+
+    /// Return the number of m-blocks read from index file by this instance so
+    /// far, blocks pinned in memory are not counted. Refer to
+    /// [Config::set_pin_top_levels].
+    pub fn to_mblock_reads(&self) -> usize {
+        self.reader.n_mreads
     }
 
     pub fn to_root(&self) -> Option<u64> {
@@ -559,6 +1831,23 @@ where
         self.stats.n_abytes == 0
     }
 
+    /// Return the ratio of garbage bytes to live bytes in value-log. When an
+    /// index is built on top of an older value-log, values and deltas no longer
+    /// referenced by the new index continue to occupy space in the value-log.
+    ///
+    /// A ratio of `0.0` means there is no garbage, `1.0` means that for every
+    /// live byte there is a garbage byte in the value-log. Applications can use
+    /// this ratio to decide when to [compact][Index::compact] the index. Index
+    /// without value-log shall always return `0.0`.
+    pub fn fragmentation(&self) -> Result<f64> {
+        let (n_gbytes, n_vbytes) = (self.stats.n_gbytes, self.stats.n_vbytes);
+        match (n_gbytes, n_vbytes) {
+            (0, _) => Ok(0.0),
+            (_, 0) => Ok(f64::INFINITY),
+            (g, v) => Ok((g as f64) / (v as f64)),
+        }
+    }
+
     pub fn len(&self) -> usize {
         usize::try_from(self.stats.n_count).unwrap()
     }
@@ -577,7 +1866,7 @@ where
         match &self.stats.vlog_location {
             Some(loc) => {
                 let loc: path::PathBuf =
-                    [self.dir.clone(), path::Path::new(loc).file_name()?.into()]
+                    [self.vlog_dir.clone(), path::Path::new(loc).file_name()?.into()]
                         .iter()
                         .collect();
                 Some(loc.into())
@@ -589,6 +1878,37 @@ where
     pub fn footprint(&self) -> Result<usize> {
         self.reader.footprint()
     }
+
+    /// Estimate the I/O cost of a point lookup, derived from [Stats]. That is,
+    /// one block read for every level below the root, less the levels pinned
+    /// in memory, and one value-log read if values are stored in value-log,
+    /// an upper bound if small values are kept inline, refer to
+    /// [Config::set_inline_value_max]. Bloom filter is not accounted for,
+    /// hence this is the cost of looking up a key that is present in the
+    /// index.
+    pub fn estimate_get_cost(&self) -> IoCost {
+        match self.stats.n_count {
+            0 => IoCost::default(),
+            _ => IoCost {
+                block_reads: self.to_mblock_levels() + 1,
+                vlog_reads: if self.stats.value_in_vlog { 1 } else { 0 },
+            },
+        }
+    }
+
+    // number of m-block levels, read from disk, to reach a z-block.
+    fn to_mblock_levels(&self) -> usize {
+        // tree_depth includes the root and z-blocks.
+        let levels = usize::try_from(self.stats.tree_depth.saturating_sub(2)).unwrap();
+        levels - cmp::min(levels, self.stats.pin_top_levels)
+    }
+
+    // number of z-blocks in the index, every m-block except the root is
+    // referred by exactly one m-entry, rest of them refer to z-blocks.
+    fn to_zblocks(&self) -> u64 {
+        let n_mblocks = self.stats.n_mblocks.saturating_sub(1);
+        self.stats.n_mentries.saturating_sub(n_mblocks)
+    }
 }
 
 impl<K, V, B> Index<K, V, B>
@@ -598,32 +1918,207 @@ where
     <V as dbs::Diff>::Delta: FromCbor,
     B: dbs::Bloom,
 {
+    /// Return the latest version of entry for `key`. If `key` is deleted, and
+    /// its tombstone is present in the index, the tombstone entry is returned,
+    /// check with `is_deleted()`. [Error::NotFound] is returned only when
+    /// `key` is absent from the index, like when its tombstone is compacted
+    /// away. Bloom filters are built over all keys, including tombstones,
+    /// hence don't change this contract.
     pub fn get<Q>(&mut self, key: &Q) -> Result<dbs::Entry<K, V>>
     where
         K: Clone + Borrow<Q>,
-        Q: Ord + ?Sized,
+        Q: Ord + Hash + ?Sized + ToOwned<Owned = K>,
     {
         let versions = false;
-        dbs::Entry::try_from(self.reader.get(key, versions)?)
+        self.do_get(key, versions)
     }
 
+    /// Same as [Index::get], along with older versions of the entry.
     pub fn get_versions<Q>(&mut self, key: &Q) -> Result<dbs::Entry<K, V>>
     where
         K: Clone + Borrow<Q>,
-        Q: Ord + ?Sized,
+        Q: Ord + Hash + ?Sized + ToOwned<Owned = K>,
     {
         let versions = true;
+        self.do_get(key, versions)
+    }
+
+    /// Return whether `key` is present in the index. Only the bloom filter
+    /// and the index blocks are consulted, value-log is not read, hence
+    /// cheaper than [Index::get]. Like [Index::get], deleted keys whose
+    /// tombstone is present in the index are reported as present.
+    pub fn contains<Q>(&mut self, key: &Q) -> Result<bool>
+    where
+        K: Clone + Borrow<Q>,
+        Q: Ord + Hash + ?Sized + ToOwned<Owned = K>,
+    {
+        self.check_comparator()?;
+
+        match self.reader.cmp.clone() {
+            Some(cmp) => {
+                let ukey: K = key.to_owned();
+                match self.shard_contains(|k: &K| cmp(k, &ukey), key)? {
+                    true => self.reader.contains_by(|k: &K| cmp(k, &ukey)),
+                    false => Ok(false),
+                }
+            }
+            None => {
+                let desc = self.stats.descending;
+                let f = |k: &K| key_order(Borrow::<Q>::borrow(k), key, desc);
+                match self.shard_contains(f, key)? {
+                    true => self.reader.contains_by(f),
+                    false => Ok(false),
+                }
+            }
+        }
+    }
+
+    fn do_get<Q>(&mut self, key: &Q, versions: bool) -> Result<dbs::Entry<K, V>>
+    where
+        K: Clone + Borrow<Q>,
+        Q: Ord + Hash + ?Sized + ToOwned<Owned = K>,
+    {
+        self.check_comparator()?;
+
+        let entry = match self.reader.cmp.clone() {
+            Some(cmp) => {
+                let ukey: K = key.to_owned();
+                if !self.shard_contains(|k: &K| cmp(k, &ukey), key)? {
+                    err_at!(NotFound, msg: "missing key")?
+                }
+                self.reader.get_by(|k: &K| cmp(k, &ukey), versions)?
+            }
+            None => {
+                let desc = self.stats.descending;
+                let f = |k: &K| key_order(Borrow::<Q>::borrow(k), key, desc);
+                if !self.shard_contains(f, key)? {
+                    err_at!(NotFound, msg: "missing key")?
+                }
+                self.reader.get(key, versions)?
+            }
+        };
+        dbs::Entry::try_from(entry)
+    }
+
+    // lookup `key` for callers that can neither hash nor own the key, like
+    // the core [crate::Index]. Bloom filters are skipped, and indexes built
+    // with a custom comparator are not supported.
+    pub(crate) fn get_unhashed<Q>(
+        &mut self,
+        key: &Q,
+        versions: bool,
+    ) -> Result<dbs::Entry<K, V>>
+    where
+        K: Clone + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.check_unhashed()?;
         dbs::Entry::try_from(self.reader.get(key, versions)?)
     }
 
+    // same as contains(), refer get_unhashed() for details.
+    pub(crate) fn contains_unhashed<Q>(&mut self, key: &Q) -> Result<bool>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.check_unhashed()?;
+        let desc = self.stats.descending;
+        self.reader.contains_by(|k: &K| key_order(Borrow::<Q>::borrow(k), key, desc))
+    }
+
+    fn check_unhashed(&self) -> Result<()> {
+        self.check_comparator()?;
+        match self.reader.cmp {
+            Some(_) => err_at!(NotImplemented, msg: "custom comparator needs owned key"),
+            None => Ok(()),
+        }
+    }
+
+    /// Return a stateful cursor for clustered lookups. Cursor remembers the
+    /// last z-block and the position of the last lookup within it, a
+    /// subsequent lookup for a nearby key gallops from that position, before
+    /// falling back to the full search from the root. Useful for lookups in
+    /// monotonically increasing key order.
+    pub fn cursor(&mut self) -> Result<Cursor<K, V, B>> {
+        self.check_comparator()?;
+        if self.reader.cmp.is_some() {
+            err_at!(NotImplemented, msg: "cursor with custom comparator")?
+        }
+        self.check_ascending("cursor")?;
+        Ok(Cursor { index: self, zblock: vec![], pos: 0, n_cmps: 0 })
+    }
+
+    /// Return the number of entries, including entries marked as deleted,
+    /// within `range`. Sub-trees completely within the range are counted
+    /// from the entry count persisted in their parent m-block, only the
+    /// blocks straddling the range boundaries are read from disk. Values are
+    /// not fetched from value-log.
+    pub fn count_range<R, Q>(&mut self, range: R) -> Result<usize>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+        R: RangeBounds<Q>,
+    {
+        self.check_comparator()?;
+        if self.reader.cmp.is_some() {
+            err_at!(NotImplemented, msg: "count_range with custom comparator")?
+        }
+        self.check_ascending("count_range")?;
+        self.reader.count_range(&range)
+    }
+
+    /// Estimate the I/O cost of iterating over `range`, derived from [Stats]
+    /// and the number of entries within `range`, refer to [Index::count_range].
+    /// Estimation itself reads only the blocks along the range boundaries.
+    /// Number of z-blocks touched is computed using the average number of
+    /// entries per z-block, and the number of m-blocks touched using the
+    /// average fanout, refer to [Stats::to_fanout].
+    pub fn estimate_scan_cost<R, Q>(&mut self, range: R) -> Result<IoCost>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+        R: RangeBounds<Q>,
+    {
+        let n = match self.stats.n_count {
+            0 => return Ok(IoCost::default()),
+            _ => self.count_range(range)?,
+        };
+
+        let n_zblocks = cmp::max(self.to_zblocks(), 1);
+        let per_zblock = (self.stats.n_count as f64) / (n_zblocks as f64);
+        // a range can straddle one more z-block than it fills.
+        let zblocks = ((n as f64) / per_zblock).ceil() as u64 + 1;
+        let zblocks = usize::try_from(cmp::min(zblocks, n_zblocks)).unwrap();
+        let mblocks = match self.stats.to_fanout() {
+            fanout if fanout > 1.0 => ((zblocks as f64) / fanout).floor() as usize,
+            _ => 0,
+        };
+
+        let cost = IoCost {
+            block_reads: self.to_mblock_levels() + mblocks + zblocks,
+            vlog_reads: if self.stats.value_in_vlog { n } else { 0 },
+        };
+        Ok(cost)
+    }
+
+    /// Iterate over latest version of entries within `range`. Iterator's
+    /// `size_hint` is exact for full table iteration, counted from [Stats].
+    /// For range iteration, `size_hint` is bounded only by the number of
+    /// entries in the index, use [Index::count_range] to count the entries
+    /// within a range.
     pub fn iter<R, Q>(&mut self, range: R) -> Result<Iter<K, V>>
     where
         K: Clone + Ord + Borrow<Q>,
         Q: ?Sized + Ord + ToOwned<Owned = K>,
         R: RangeBounds<Q>,
     {
+        self.check_comparator()?;
+        let remaining = self.to_remaining(&range);
         let (reverse, versions) = (false, false);
-        self.reader.iter(range, reverse, versions)
+        let mut iter = self.do_iter(range, reverse, versions)?;
+        iter.set_remaining(remaining);
+        Ok(iter)
     }
 
     pub fn iter_versions<R, Q>(&mut self, range: R) -> Result<Iter<K, V>>
@@ -632,8 +2127,150 @@ where
         Q: ?Sized + Ord + ToOwned<Owned = K>,
         R: RangeBounds<Q>,
     {
+        self.check_comparator()?;
+        let remaining = self.to_remaining(&range);
         let (reverse, versions) = (false, true);
-        self.reader.iter(range, reverse, versions)
+        let mut iter = self.do_iter(range, reverse, versions)?;
+        iter.set_remaining(remaining);
+        Ok(iter)
+    }
+
+    // iterate over `range` in ascending key order, or descending order if
+    // `reverse`. Index built in descending order is walked backwards to yield
+    // ascending order, and vice versa, refer to Config::set_descending.
+    fn do_iter<R, Q>(
+        &mut self,
+        range: R,
+        reverse: bool,
+        versions: bool,
+    ) -> Result<Iter<K, V>>
+    where
+        K: Clone + Ord + Borrow<Q>,
+        Q: ?Sized + Ord + ToOwned<Owned = K>,
+        R: RangeBounds<Q>,
+    {
+        match self.stats.descending {
+            true => {
+                let range = (range.end_bound(), range.start_bound());
+                self.reader.iter(range, !reverse, versions)
+            }
+            false => self.reader.iter(range, reverse, versions),
+        }
+    }
+
+    // iterator's `size_hint` over `range`. Full table iteration is counted
+    // from stats, ranges are not counted upfront, since that would read every
+    // leaf block within the range.
+    fn to_remaining<R, Q>(&self, range: &R) -> (usize, Option<usize>)
+    where
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        match (range.start_bound(), range.end_bound()) {
+            (Bound::Unbounded, Bound::Unbounded) => (self.len(), Some(self.len())),
+            _ => (0, Some(self.len())),
+        }
+    }
+
+    /// Return an independent iterator for each of `ranges`, for parallel range
+    /// processing. Each iterator owns a clone of this index, refer to
+    /// [Index::try_clone], with its own file-descriptors and read buffers,
+    /// while meta-data and bitmap are shared. Iterators can be moved to
+    /// worker threads. Only latest version of entries are iterated.
+    pub fn par_ranges<R>(&self, ranges: Vec<R>) -> Result<Vec<OwnedRangeIter<K, V, B>>>
+    where
+        K: Clone + Ord,
+        R: RangeBounds<K>,
+    {
+        self.check_comparator()?;
+
+        let mut iters = vec![];
+        for range in ranges.into_iter() {
+            let (start, end) = util::to_start_end(range);
+            let index = self.try_clone()?;
+            iters.push(OwnedRangeIter::new(index, start, end));
+        }
+
+        Ok(iters)
+    }
+
+    /// Return the key level changeset from this index to `other`, a newer
+    /// version of the same index, like the one built using [Index::incremental].
+    /// Latest version of entries from both indexes are merge-walked in key
+    /// order, keys with equal seqno are treated as unchanged. Changes are
+    /// returned in key order.
+    pub fn diff<'a>(
+        &'a mut self,
+        other: &'a mut Index<K, V, B>,
+    ) -> Result<impl Iterator<Item = Result<Change<K, V>>> + 'a>
+    where
+        K: Clone + Ord,
+    {
+        if !self.stats.comparator.is_empty() || !other.stats.comparator.is_empty() {
+            err_at!(NotImplemented, msg: "diff with custom comparator")?
+        }
+
+        let r = (Bound::<K>::Unbounded, Bound::<K>::Unbounded);
+        let old = self.iter(r)?;
+        let r = (Bound::<K>::Unbounded, Bound::<K>::Unbounded);
+        let new = other.iter(r)?;
+        DiffIter::new(old, new)
+    }
+
+    /// Draw a uniform random sample of `n` entries, latest version only, using
+    /// reservoir sampling over a full table iteration. Sample is deterministic
+    /// for the same `seed`. If index has fewer than `n` entries, all entries
+    /// are returned.
+    pub fn sample(&mut self, n: usize, seed: u64) -> Result<Vec<dbs::Entry<K, V>>>
+    where
+        K: Clone + Ord,
+    {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut sample = Vec::with_capacity(n);
+        if n == 0 {
+            return Ok(sample);
+        }
+
+        let r = (Bound::<K>::Unbounded, Bound::<K>::Unbounded);
+        for (i, entry) in self.iter(r)?.enumerate() {
+            let entry = entry?;
+            if i < n {
+                sample.push(entry);
+            } else {
+                let j = rng.gen_range(0..=i);
+                if j < n {
+                    sample[j] = entry;
+                }
+            }
+        }
+
+        Ok(sample)
+    }
+
+    /// Iterate over all entries, latest version only, along with the
+    /// file-position of the z-block each entry is read from. Useful for
+    /// tooling that map entries back to the physical layout, like cache
+    /// warming and localizing corruption.
+    pub fn iter_located(&mut self) -> Result<IterLocated<K, V>>
+    where
+        K: Clone,
+    {
+        self.check_comparator()?;
+        self.reader.iter_located(false /*versions*/)
+    }
+
+    /// Iterate over keys in `range`, without reading values and deltas from
+    /// value-log. Works on index opened without its value-log, refer to
+    /// [Config::set_values_optional].
+    pub fn iter_keys<R, Q>(&mut self, range: R) -> Result<IterKeys<K, V>>
+    where
+        K: Clone + Ord + Borrow<Q>,
+        Q: ?Sized + Ord + ToOwned<Owned = K>,
+        R: RangeBounds<Q>,
+    {
+        self.check_comparator()?;
+        self.check_ascending("iter_keys")?;
+        self.reader.iter_keys(range)
     }
 
     pub fn reverse<R, Q>(&mut self, range: R) -> Result<Iter<K, V>>
@@ -642,8 +2279,12 @@ where
         Q: ?Sized + Ord + ToOwned<Owned = K>,
         R: RangeBounds<Q>,
     {
+        self.check_comparator()?;
+        let remaining = self.to_remaining(&range);
         let (reverse, versions) = (true, false);
-        self.reader.iter(range, reverse, versions)
+        let mut iter = self.do_iter(range, reverse, versions)?;
+        iter.set_remaining(remaining);
+        Ok(iter)
     }
 
     pub fn reverse_versions<R, Q>(&mut self, range: R) -> Result<Iter<K, V>>
@@ -652,8 +2293,100 @@ where
         Q: ?Sized + Ord + ToOwned<Owned = K>,
         R: RangeBounds<Q>,
     {
+        self.check_comparator()?;
+        let remaining = self.to_remaining(&range);
         let (reverse, versions) = (true, true);
-        self.reader.iter(range, reverse, versions)
+        let mut iter = self.do_iter(range, reverse, versions)?;
+        iter.set_remaining(remaining);
+        Ok(iter)
+    }
+
+    /// Export latest version of all entries as line delimited JSON, one JSON
+    /// object per line, `{"key": .., "value": .., "seqno": .., "deleted": ..}`.
+    /// `value` is null for deleted entries. Keys and values are encoded using
+    /// [dbs::ToJson]. Return the number of entries written.
+    pub fn export_jsonl<W>(&mut self, w: &mut W) -> Result<usize>
+    where
+        K: Clone + Ord + dbs::ToJson,
+        V: dbs::ToJson,
+        W: io::Write,
+    {
+        use std::io::Write;
+
+        let mut n = 0;
+        let r = (Bound::<K>::Unbounded, Bound::<K>::Unbounded);
+        for entry in self.iter(r)? {
+            let entry = entry?;
+            let value = match entry.to_value() {
+                Some(value) => value.to_json(),
+                None => "null".to_string(),
+            };
+            err_at!(
+                IOError,
+                writeln!(
+                    w,
+                    r#"{{"key": {}, "value": {}, "seqno": {}, "deleted": {}}}"#,
+                    entry.as_key().to_json(),
+                    value,
+                    entry.to_seqno(),
+                    entry.is_deleted()
+                )
+            )?;
+            n += 1;
+        }
+
+        Ok(n)
+    }
+
+    // check with bloom shard covering the key, loading the shard if not already
+    // loaded. `f` shall compare shard's first-key with the `key`. If index is
+    // not sharded, check with index's bitmap, loading it if lazy.
+    fn shard_contains<F, Q>(&mut self, f: F, key: &Q) -> Result<bool>
+    where
+        F: Fn(&K) -> cmp::Ordering,
+        Q: Hash + ?Sized,
+    {
+        // bitmaps of an empty index are built without keys, don't consult them.
+        if self.is_empty() {
+            return Ok(false);
+        } else if self.shards.is_empty() {
+            return Ok(self.load_bitmap()?.contains(key));
+        }
+
+        let off = match self.shards.binary_search_by(|(k, _, _)| f(k)) {
+            Ok(off) => off,
+            Err(0) => return Ok(false),
+            Err(off) => off - 1,
+        };
+
+        if self.shard_bitmaps[off].is_none() {
+            let (_, fpos, length) = &self.shards[off];
+            let seek = io::SeekFrom::Start(*fpos);
+            let data = read_file!(self.reader.index, seek, *length, "read bloom shard")?;
+            self.shard_bitmaps[off] = Some(err_at!(Fatal, B::from_bytes(&data))?.0);
+        }
+
+        Ok(self.shard_bitmaps[off].as_ref().unwrap().contains(key))
+    }
+
+    // index built with custom comparator can't be read without setting the
+    // comparator.
+    fn check_comparator(&self) -> Result<()> {
+        match self.reader.cmp {
+            None if !self.stats.comparator.is_empty() => {
+                let id = &self.stats.comparator;
+                err_at!(InvalidInput, msg: "comparator {:?} not set", id)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // operations that walk the index blocks in ascending key order.
+    fn check_ascending(&self, op: &str) -> Result<()> {
+        match self.stats.descending {
+            true => err_at!(NotImplemented, msg: "{} with descending index", op),
+            false => Ok(()),
+        }
     }
 
     pub fn lsm_merge<I, E>(
@@ -668,6 +2401,11 @@ where
         I: Iterator<Item = Result<E>>,
         E: Into<Entry<K, V>>,
     {
+        if !self.stats.comparator.is_empty() {
+            err_at!(NotImplemented, msg: "lsm-merge with custom comparator")?
+        }
+        self.check_ascending("lsm-merge")?;
+
         let start_bound = Bound::<&K>::Unbounded;
         let stack = self.reader.fwd_stack(start_bound, self.reader.as_root())?;
         let iter = YIter::new(snapshot, IterLsm::new(&mut self.reader, stack, versions));
@@ -679,6 +2417,7 @@ where
     where
         K: Clone + PartialOrd + Ord + fmt::Debug,
     {
+        let cmp = self.reader.cmp.clone();
         let iter = self.iter((Bound::<K>::Unbounded, Bound::<K>::Unbounded))?;
 
         let mut prev_key: Option<K> = None;
@@ -694,7 +2433,11 @@ where
 
             seqno = cmp::max(seqno, entry.to_seqno());
 
-            match prev_key.as_ref().map(|pk| pk.lt(&entry.key)) {
+            let ord = |pk: &K| match &cmp {
+                Some(cmp) => cmp(pk, &entry.key),
+                None => pk.cmp(&entry.key),
+            };
+            match prev_key.as_ref().map(|pk| ord(pk).is_lt()) {
                 Some(true) | None => (),
                 Some(false) => err_at!(Fatal, msg: "{:?} >= {:?}", prev_key, entry.key)?,
             }
@@ -739,18 +2482,55 @@ where
         println!("  delta_ok     : {}", stats.delta_ok);
         println!("  vlog_location: {:?}", stats.vlog_location);
         println!("  value_in_vlog: {}", stats.value_in_vlog);
+        println!("  compression  : {:?}", stats.block_compression);
         println!("  n_count      : {}", stats.n_count);
         println!("  n_deleted    : {}", stats.n_deleted);
         println!("  seqno        : {}", stats.seqno);
         println!("  n_abytes     : {}", stats.n_abytes);
+        println!("  n_vbytes     : {}", stats.n_vbytes);
+        println!("  n_gbytes     : {}", stats.n_gbytes);
+        println!("  tree_depth   : {}", stats.tree_depth);
+        println!("  fanout       : {:.2}", stats.to_fanout());
         println!("  build_time   : {}", stats.build_time);
         println!("  epoch        : {}", stats.epoch);
+        println!("  bitmap_ver   : {}", stats.bitmap_version);
         println!();
         self.reader.print()
     }
 }
 
-fn find_index_file(dir: &ffi::OsStr, name: &str) -> Option<ffi::OsString> {
+// parse a single line from line delimited JSON, refer [Index::export_jsonl].
+fn parse_jsonl_entry<K, V>(line: &str, lineno: usize) -> Result<dbs::Entry<K, V>>
+where
+    K: dbs::FromJson,
+    V: dbs::Diff + dbs::FromJson,
+{
+    let val: serde_json::Value =
+        err_at!(InvalidFormat, serde_json::from_str(line), "line {}", lineno)?;
+
+    let key = match val.get("key") {
+        Some(key) => K::from_json(&key.to_string())?,
+        None => err_at!(InvalidFormat, msg: "line {}: missing key", lineno)?,
+    };
+    let seqno = match val.get("seqno").map(|seqno| seqno.as_u64()) {
+        Some(Some(seqno)) => seqno,
+        _ => err_at!(InvalidFormat, msg: "line {}: invalid seqno", lineno)?,
+    };
+    let entry = match val.get("deleted").map(|deleted| deleted.as_bool()) {
+        Some(Some(true)) => dbs::Entry::new_delete(key, seqno),
+        Some(Some(false)) => match val.get("value") {
+            Some(value) if !value.is_null() => {
+                dbs::Entry::new(key, V::from_json(&value.to_string())?, seqno)
+            }
+            _ => err_at!(InvalidFormat, msg: "line {}: missing value", lineno)?,
+        },
+        _ => err_at!(InvalidFormat, msg: "line {}: invalid deleted flag", lineno)?,
+    };
+
+    Ok(entry)
+}
+
+pub(crate) fn find_index_file(dir: &ffi::OsStr, name: &str) -> Option<ffi::OsString> {
     let iter = fs::read_dir(dir).ok()?;
     let entry = iter.filter_map(|entry| entry.ok()).find(|entry| {
         let filen = IndexFileName(entry.file_name());
@@ -766,6 +2546,311 @@ fn find_index_file(dir: &ffi::OsStr, name: &str) -> Option<ffi::OsString> {
     })
 }
 
+/// Range iterator owning its index instance, refer to [Index::par_ranges].
+///
+/// Entries are fetched in batches of [PAR_BATCH], where each batch resumes
+/// the iteration from the last key of the previous batch.
+pub struct OwnedRangeIter<K, V, B>
+where
+    K: FromCbor,
+    V: dbs::Diff + FromCbor,
+    <V as dbs::Diff>::Delta: FromCbor,
+    B: dbs::Bloom,
+{
+    index: Index<K, V, B>,
+    start: Bound<K>,
+    end: Bound<K>,
+    batch: std::vec::IntoIter<dbs::Entry<K, V>>,
+    done: bool,
+}
+
+impl<K, V, B> OwnedRangeIter<K, V, B>
+where
+    K: FromCbor,
+    V: dbs::Diff + FromCbor,
+    <V as dbs::Diff>::Delta: FromCbor,
+    B: dbs::Bloom,
+{
+    fn new(index: Index<K, V, B>, start: Bound<K>, end: Bound<K>) -> Self {
+        OwnedRangeIter {
+            index,
+            start,
+            end,
+            batch: vec![].into_iter(),
+            done: false,
+        }
+    }
+
+    fn next_batch(&mut self) -> Result<Vec<dbs::Entry<K, V>>>
+    where
+        K: Clone + Ord,
+    {
+        let range = (self.start.clone(), self.end.clone());
+        let mut batch = Vec::with_capacity(PAR_BATCH);
+        // batches don't need size_hint, skip counting the range.
+        let (reverse, versions) = (false, false);
+        let iter = self.index.do_iter(range, reverse, versions)?;
+        for entry in iter.take(PAR_BATCH) {
+            batch.push(entry?);
+        }
+
+        match batch.last() {
+            Some(entry) if batch.len() == PAR_BATCH => {
+                self.start = Bound::Excluded(entry.to_key());
+            }
+            _ => self.done = true,
+        }
+
+        Ok(batch)
+    }
+
+    /// Return the underlying index instance.
+    pub fn as_index(&self) -> &Index<K, V, B> {
+        &self.index
+    }
+}
+
+impl<K, V, B> Iterator for OwnedRangeIter<K, V, B>
+where
+    K: Clone + Ord + FromCbor,
+    V: dbs::Diff + FromCbor,
+    <V as dbs::Diff>::Delta: FromCbor,
+    B: dbs::Bloom,
+{
+    type Item = Result<dbs::Entry<K, V>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.batch.next() {
+                Some(entry) => break Some(Ok(entry)),
+                None if self.done => break None,
+                None => match self.next_batch() {
+                    Ok(batch) => self.batch = batch.into_iter(),
+                    Err(err) => {
+                        self.done = true;
+                        break Some(Err(err));
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Stateful cursor for clustered lookups, refer to [Index::cursor].
+pub struct Cursor<'a, K, V, B>
+where
+    K: FromCbor,
+    V: dbs::Diff + FromCbor,
+    <V as dbs::Diff>::Delta: FromCbor,
+    B: dbs::Bloom,
+{
+    index: &'a mut Index<K, V, B>,
+    // leaf entries from the last z-block.
+    zblock: Vec<Entry<K, V>>,
+    // position of the last lookup within `zblock`.
+    pos: usize,
+    // number of key comparisons done so far.
+    n_cmps: usize,
+}
+
+impl<'a, K, V, B> Cursor<'a, K, V, B>
+where
+    K: FromCbor,
+    V: dbs::Diff + FromCbor,
+    <V as dbs::Diff>::Delta: FromCbor,
+    B: dbs::Bloom,
+{
+    /// Same as [Index::get], starting from the remembered position.
+    pub fn get<Q>(&mut self, key: &Q) -> Result<dbs::Entry<K, V>>
+    where
+        K: Clone + Borrow<Q>,
+        Q: Ord + Hash + ?Sized,
+    {
+        self.do_get(key, false /*versions*/)
+    }
+
+    /// Same as [Index::get_versions], starting from the remembered position.
+    pub fn get_versions<Q>(&mut self, key: &Q) -> Result<dbs::Entry<K, V>>
+    where
+        K: Clone + Borrow<Q>,
+        Q: Ord + Hash + ?Sized,
+    {
+        self.do_get(key, true /*versions*/)
+    }
+
+    /// Forget the remembered z-block, next lookup shall do a full search.
+    pub fn reset(&mut self) {
+        self.zblock = vec![];
+        self.pos = 0;
+    }
+
+    /// Return the number of key comparisons done by this cursor so far,
+    /// including comparisons made while descending the tree.
+    pub fn to_comparisons(&self) -> usize {
+        self.n_cmps
+    }
+
+    fn do_get<Q>(&mut self, key: &Q, versions: bool) -> Result<dbs::Entry<K, V>>
+    where
+        K: Clone + Borrow<Q>,
+        Q: Ord + Hash + ?Sized,
+    {
+        let n_cmps = Cell::new(0);
+        let f = |k: &K| {
+            n_cmps.set(n_cmps.get() + 1);
+            Borrow::<Q>::borrow(k).cmp(key)
+        };
+
+        let hit = match (self.zblock.first(), self.zblock.last()) {
+            (Some(first), Some(last)) => {
+                f(first.as_key()).is_le() && f(last.as_key()).is_ge()
+            }
+            _ => false,
+        };
+        if !hit {
+            self.reset();
+            let g = |k: &K| Borrow::<Q>::borrow(k).cmp(key);
+            if self.index.shard_contains(g, key)? {
+                if let Some(zblock) = self.index.reader.zblock_by(&f)? {
+                    self.zblock = zblock;
+                }
+            }
+        }
+
+        let res = match self.zblock.is_empty() {
+            true => Err(0),
+            false => gallop(&self.zblock, self.pos, |e| f(e.as_key())),
+        };
+        self.n_cmps += n_cmps.get();
+
+        match res {
+            Ok(off) => {
+                self.pos = off;
+                let entry = self.zblock[off].clone();
+                dbs::Entry::try_from(self.index.reader.to_native(entry, versions)?)
+            }
+            Err(off) => {
+                self.pos = cmp::min(off, self.zblock.len().saturating_sub(1));
+                err_at!(NotFound, msg: "missing key")
+            }
+        }
+    }
+}
+
+/// List indexes found under `dir`, as (name, version) tuples sorted by name.
+///
+/// Every index-file, refer to [IndexFileName], is listed as its own entry,
+/// and version is the seqno of the latest mutation folded into that index,
+/// refer to [Stats::seqno]. Only the meta-block of each index-file is read.
+pub fn list_indexes(dir: &ffi::OsStr) -> Result<Vec<(String, usize)>> {
+    let mut indexes = vec![];
+
+    for (name, file) in read_index_files(dir)?.into_iter() {
+        let mut index = err_at!(IOError, fs::OpenOptions::new().read(true).open(&file))?;
+        let stats: Stats = match &read_meta_items(&mut index)?.0[1] {
+            MetaItem::Stats(stats) => util::from_cbor_bytes(stats)?.0,
+            _ => unreachable!(),
+        };
+        indexes.push((name, err_at!(FailConvert, usize::try_from(stats.seqno))?));
+    }
+
+    Ok(indexes)
+}
+
+/// Open every index found under `dir`, refer to [list_indexes]. Returned
+/// indexes are keyed by their name.
+pub fn open_all<K, V, B>(dir: &ffi::OsStr) -> Result<BTreeMap<String, Index<K, V, B>>>
+where
+    K: FromCbor,
+    V: dbs::Diff + FromCbor,
+    <V as dbs::Diff>::Delta: FromCbor,
+    B: dbs::Bloom,
+{
+    let mut indexes = BTreeMap::new();
+    for (name, file) in read_index_files(dir)?.into_iter() {
+        indexes.insert(name, Index::open_file(&file)?);
+    }
+
+    Ok(indexes)
+}
+
+// index-files under `dir`, as (name, file-path) tuples sorted by name.
+fn read_index_files(dir: &ffi::OsStr) -> Result<BTreeMap<String, ffi::OsString>> {
+    let mut files = BTreeMap::new();
+
+    for entry in err_at!(IOError, fs::read_dir(dir), "read_dir {:?}", dir)? {
+        let entry = err_at!(IOError, entry)?;
+        let name = match String::try_from(IndexFileName(entry.file_name())) {
+            Ok(name) => name,
+            Err(_) => continue,
+        };
+        let file_path: path::PathBuf =
+            [dir.to_os_string(), entry.file_name()].iter().collect();
+        files.insert(name, file_path.into_os_string());
+    }
+
+    Ok(files)
+}
+
+/// Merge `inputs` indexes into a new index specified by [Config], without
+/// going through a memory index. Version-aware scans from each input are
+/// k-way merged, with entries for the same key merged into a single entry
+/// carrying versions from all inputs, and subsequently compacted using
+/// `cutoff`. Versions shall be exclusive across inputs, as with indexes
+/// built from sharded ingestion of the same write-ahead-log.
+///
+/// Bitmap for the merged index is computed by OR-ing bitmaps from all the
+/// inputs, hence `B` shall implement [dbs::Bloom::or].
+pub fn merge<K, V, B>(
+    mut inputs: Vec<Index<K, V, B>>,
+    mut config: Config,
+    cutoff: dbs::Cutoff,
+) -> Result<Index<K, V, B>>
+where
+    K: Clone + Ord + Hash + IntoCbor + FromCbor,
+    V: dbs::Diff + IntoCbor + FromCbor,
+    <V as dbs::Diff>::Delta: IntoCbor + FromCbor + From<V>,
+    B: Clone + dbs::Bloom,
+{
+    if inputs.is_empty() {
+        err_at!(InvalidInput, msg: "no input index to merge")?
+    }
+    if inputs.iter().any(|index| !index.stats.comparator.is_empty()) {
+        err_at!(NotImplemented, msg: "merge with custom comparator")?
+    }
+    if config.descending {
+        err_at!(NotImplemented, msg: "merge into descending index")?
+    }
+
+    let mut bitmap: Option<B> = None;
+    for index in inputs.iter_mut() {
+        let other = index.load_bitmap()?;
+        bitmap = match bitmap {
+            Some(bitmap) => Some(bitmap.or(other)?),
+            None => Some(other.clone()),
+        };
+    }
+    let seqno = inputs.iter().map(|index| index.to_seqno()).max();
+
+    // set to fresh vlog location, don't carry forward.
+    config.set_vlog_location(None);
+
+    let mut builder = Builder::<K, V>::initial(config.clone(), vec![])?;
+    let iters = {
+        let mut iters = vec![];
+        for index in inputs.iter_mut() {
+            let r = (Bound::<K>::Unbounded, Bound::<K>::Unbounded);
+            iters.push(index.iter_versions(r)?);
+        }
+        iters
+    };
+    let iter = CompactScan::new(MergeScan::new(iters), cutoff);
+
+    builder.build_index(iter, bitmap.unwrap(), seqno)?;
+
+    Index::open(&config.dir, &config.name)
+}
+
 fn purge_file(file: ffi::OsString) -> Result<()> {
     use fs2::FileExt;
 
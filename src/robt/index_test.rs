@@ -5,7 +5,11 @@ use xorfilter::{BuildHasherDefault, Xor8};
 use std::{fs, mem, thread};
 
 use super::*;
-use crate::{bitmaps::NoBitmap, dbs, llrb};
+use crate::{
+    bitmaps::NoBitmap,
+    dbs, llrb,
+    robt::{BuildFsync, Codec},
+};
 
 trait Key:
     Sync
@@ -78,6 +82,737 @@ fn test_robt_build_read() {
     );
 }
 
+#[test]
+fn test_robt_fragmentation() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_fragmentation {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_fragmentation");
+    fs::remove_dir_all(&dir).ok();
+    let mut config = Config::new(dir.as_os_str(), "frag-base");
+    config.set_value_log(true).set_delta(true);
+
+    let mdb = llrb::load_index::<u16, u64>(seed, 10_000, 10_000, 1_000, 1_000, None);
+
+    // fresh value-log does not carry any garbage.
+    let mut base = {
+        let mut build = Builder::<u16, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter_versions().unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+    assert_eq!(base.fragmentation().unwrap(), 0.0);
+    let vlog = base.to_vlog_location().unwrap();
+    let n_abytes = fs::metadata(&vlog).unwrap().len();
+
+    // incremental snapshot, with updates over some of the older keys, is
+    // appended to the base's value-log. lsm-merge fetches every older value
+    // and delta, hence all of base's value-log bytes turn into garbage.
+    let keys: Vec<u16> = mdb.iter().unwrap().map(|e| e.to_key()).collect();
+    let mut seqno = mdb.to_seqno();
+    let mut batch = vec![];
+    for key in keys.into_iter().filter(|_| rng.gen::<u8>() % 4 == 0) {
+        seqno += 1;
+        batch.push(dbs::Entry::new(key, rng.gen::<u64>(), seqno));
+    }
+    let mut index = {
+        let mut build = base
+            .try_clone()
+            .unwrap()
+            .incremental(dir.as_os_str(), "frag-incr", vec![])
+            .unwrap();
+        let iter = base.lsm_merge(batch.clone().into_iter().map(Ok), true).unwrap();
+        build.build_index(iter, NoBitmap, Some(seqno)).unwrap()
+    };
+    assert_eq!(index.to_vlog_location(), Some(vlog.clone()));
+    let n_bytes = fs::metadata(&vlog).unwrap().len();
+
+    let stats = index.to_stats();
+    assert_eq!(stats.n_abytes, n_abytes);
+    assert_eq!(stats.n_gbytes, n_abytes);
+    assert_eq!(stats.n_vbytes, n_bytes - n_abytes);
+
+    let ratio = index.fragmentation().unwrap();
+    println!("test_robt_fragmentation ratio {}", ratio);
+    assert_eq!(ratio, (n_abytes as f64) / (stats.n_vbytes as f64));
+    // the snapshot carries every base version, plus the updates.
+    assert!(ratio > 0.0 && ratio < 1.0, "{}", ratio);
+
+    for entry in batch.iter() {
+        assert_eq!(index.get(entry.as_key()).unwrap().to_value(), entry.to_value());
+    }
+    index.validate().unwrap();
+
+    index.purge().unwrap();
+    base.purge().unwrap();
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_robt_gc_vlog() {
+    let seed: u64 = random();
+    println!("test_robt_gc_vlog {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_gc_vlog");
+    fs::remove_dir_all(&dir).ok();
+    let mut config = Config::new(dir.as_os_str(), "gc-initial");
+    config.set_value_log(true).set_delta(true);
+
+    let mdb = llrb::load_index::<u16, u64>(seed, 10_000, 10_000, 1_000, 1_000, None);
+
+    let index = {
+        let mut build = Builder::<u16, u64>::initial(config.clone(), vec![]).unwrap();
+        let iter = mdb.iter_versions().unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+    let vlog = index.to_vlog_location().unwrap();
+    let n_abytes = fs::metadata(&vlog).unwrap().len();
+    index.close().unwrap();
+
+    // build the same entries on top of the older value-log.
+    config.name = "gc-append".to_string();
+    let mut index = {
+        let mut build = Builder::<u16, u64>::initial(config.clone(), vec![]).unwrap();
+        build.vflush = Rc::new(RefCell::new(
+            Flusher::new(&vlog, false, 64, BuildFsync::Final).unwrap(),
+        ));
+        build.stats.vlog_location = Some(vlog.clone());
+        let iter = mdb.iter_versions().unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+    assert_eq!(index.fragmentation().unwrap(), 1.0);
+
+    let r = (Bound::<u16>::Unbounded, Bound::<u16>::Unbounded);
+    let entries: Vec<dbs::Entry<u16, u64>> =
+        index.iter_versions(r).unwrap().map(|e| e.unwrap()).collect();
+    let root = index.to_root();
+
+    let reclaimed = index.gc_vlog().unwrap();
+    assert_eq!(reclaimed as u64, n_abytes);
+
+    let stats = index.to_stats();
+    let new_vlog = index.to_vlog_location().unwrap();
+    let file_name = VlogFileName::from_version("gc-append".to_string(), 1);
+    assert_eq!(path::Path::new(&new_vlog).file_name(), Some(file_name.0.as_ref()));
+    assert_eq!(fs::metadata(&new_vlog).unwrap().len(), stats.n_vbytes);
+    assert_eq!(stats.n_vbytes, n_abytes);
+    assert_eq!(stats.n_abytes, 0);
+    assert_eq!(index.fragmentation().unwrap(), 0.0);
+    assert!(index.is_compacted());
+    assert_eq!(index.to_root(), root);
+
+    // older value-log is not referenced by the index anymore.
+    fs::remove_file(&vlog).unwrap();
+
+    let r = (Bound::<u16>::Unbounded, Bound::<u16>::Unbounded);
+    let iter = index.iter_versions(r).unwrap();
+    let mut n = 0;
+    for (a, b) in iter.map(|e| e.unwrap()).zip(entries.iter()) {
+        assert_eq!(a, *b);
+        n += 1;
+    }
+    assert_eq!(n, entries.len());
+    for entry in entries.iter() {
+        assert_eq!(index.get_versions(&entry.key).unwrap(), *entry);
+    }
+    index.validate().unwrap();
+
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_robt_gc_vlog_incremental() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_gc_vlog_incremental {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_gc_vlog_incremental");
+    fs::remove_dir_all(&dir).ok();
+    let mut config = Config::new(dir.as_os_str(), "gc-base");
+    config.set_value_log(true).set_delta(true);
+
+    let mdb = llrb::load_index::<u16, u64>(seed, 10_000, 10_000, 1_000, 1_000, None);
+
+    let mut base = {
+        let mut build = Builder::<u16, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter_versions().unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+    let vlog = base.to_vlog_location().unwrap();
+
+    // incremental snapshot appends to the base's value-log.
+    let keys: Vec<u16> = mdb.iter().unwrap().map(|e| e.to_key()).collect();
+    let mut seqno = mdb.to_seqno();
+    let mut batch = vec![];
+    for key in keys.into_iter().filter(|_| rng.gen::<u8>() % 4 == 0) {
+        seqno += 1;
+        batch.push(dbs::Entry::new(key, rng.gen::<u64>(), seqno));
+    }
+    let child = {
+        let mut build = base
+            .try_clone()
+            .unwrap()
+            .incremental(dir.as_os_str(), "gc-incr", vec![])
+            .unwrap();
+        let iter = base.lsm_merge(batch.into_iter().map(Ok), true).unwrap();
+        build.build_index(iter, NoBitmap, Some(seqno)).unwrap()
+    };
+    assert_eq!(child.to_vlog_location(), Some(vlog.clone()));
+    child.close().unwrap();
+    let n_bytes = fs::metadata(&vlog).unwrap().len();
+
+    let refs: Vec<dbs::Entry<u16, u64>> =
+        base.iter_versions(..).unwrap().map(|e| e.unwrap()).collect();
+    let mut child =
+        Index::<u16, u64, NoBitmap>::open(dir.as_os_str(), "gc-incr").unwrap();
+    let child_refs: Vec<dbs::Entry<u16, u64>> =
+        child.iter_versions(..).unwrap().map(|e| e.unwrap()).collect();
+
+    // gc the base twice, each time into a fresh versioned value-log.
+    for version in 1..3 {
+        let old_vlog = base.to_vlog_location().unwrap();
+        let old_n_bytes = fs::metadata(&old_vlog).unwrap().len();
+
+        let reclaimed = base.gc_vlog().unwrap() as u64;
+        let new_vlog = base.to_vlog_location().unwrap();
+        let file_name = VlogFileName::from_version("gc-base".to_string(), version);
+        assert_eq!(path::Path::new(&new_vlog).file_name(), Some(file_name.0.as_ref()));
+        assert_eq!(reclaimed, old_n_bytes - base.to_stats().n_vbytes);
+        // older value-log is left as is.
+        assert_eq!(fs::metadata(&old_vlog).unwrap().len(), old_n_bytes);
+
+        let entries: Vec<dbs::Entry<u16, u64>> =
+            base.iter_versions(..).unwrap().map(|e| e.unwrap()).collect();
+        assert_eq!(entries, refs);
+    }
+    assert_eq!(fs::metadata(&vlog).unwrap().len(), n_bytes);
+    base.validate().unwrap();
+
+    // incremental snapshot still reads from the base's older value-log.
+    let entries: Vec<dbs::Entry<u16, u64>> =
+        child.iter_versions(..).unwrap().map(|e| e.unwrap()).collect();
+    assert_eq!(entries, child_refs);
+    let mut child =
+        Index::<u16, u64, NoBitmap>::open(dir.as_os_str(), "gc-incr").unwrap();
+    let entries: Vec<dbs::Entry<u16, u64>> =
+        child.iter_versions(..).unwrap().map(|e| e.unwrap()).collect();
+    assert_eq!(entries, child_refs);
+    for entry in child_refs.iter() {
+        assert_eq!(child.get_versions(&entry.key).unwrap(), *entry);
+    }
+    child.validate().unwrap();
+
+    let mut base = Index::<u16, u64, NoBitmap>::open(dir.as_os_str(), "gc-base").unwrap();
+    let entries: Vec<dbs::Entry<u16, u64>> =
+        base.iter_versions(..).unwrap().map(|e| e.unwrap()).collect();
+    assert_eq!(entries, refs);
+
+    child.purge().unwrap();
+    assert!(path::Path::new(&vlog).exists());
+    base.purge().unwrap();
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_robt_block_compression() {
+    let seed: u64 = random();
+    println!("test_robt_block_compression {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_block_compression");
+    fs::remove_dir_all(&dir).ok();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 20_000, 20_000, 2_000, 2_000, None);
+
+    let mut indexes = vec![];
+    for (name, codec) in [
+        ("codec-none", Codec::None),
+        ("codec-zstd", Codec::Zstd { level: 3 }),
+        ("codec-lz4", Codec::Lz4),
+    ]
+    .iter()
+    {
+        let mut config = Config::new(dir.as_os_str(), name);
+        config.set_delta(true).set_block_compression(*codec);
+
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter_versions().unwrap().map(Ok);
+        let index = build.build_index(iter, NoBitmap, None).unwrap();
+        assert_eq!(index.to_stats().block_compression, *codec);
+
+        let n = fs::metadata(index.to_index_location()).unwrap().len();
+        println!("test_robt_block_compression {:?} index-file {} bytes", codec, n);
+        indexes.push((n, index));
+    }
+
+    let (n_none, mut index) = indexes.remove(0);
+    let entries: Vec<dbs::Entry<u64, u64>> = index
+        .iter_versions((Bound::<u64>::Unbounded, Bound::<u64>::Unbounded))
+        .unwrap()
+        .map(|e| e.unwrap())
+        .collect();
+    assert_eq!(entries.len(), mdb.len());
+
+    for (n, mut cindex) in indexes.into_iter() {
+        assert!(n < n_none, "{} {}", n, n_none);
+
+        let r = (Bound::<u64>::Unbounded, Bound::<u64>::Unbounded);
+        let citer = cindex.iter_versions(r).unwrap().map(|e| e.unwrap());
+        assert!(citer.eq(entries.clone().into_iter()));
+
+        let r = (Bound::<u64>::Unbounded, Bound::<u64>::Unbounded);
+        let mut citer = cindex.reverse_versions(r).unwrap().map(|e| e.unwrap());
+        for entry in entries.iter().rev() {
+            assert_eq!(citer.next().as_ref(), Some(entry));
+        }
+        assert!(citer.next().is_none());
+
+        for entry in entries.iter() {
+            assert_eq!(cindex.get_versions(&entry.key).unwrap(), *entry);
+        }
+        cindex.validate().unwrap();
+        cindex.purge().unwrap();
+    }
+
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_robt_count_range() {
+    let seed: u64 = random();
+    println!("test_robt_count_range {}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let dir = std::env::temp_dir().join("test_robt_count_range");
+    fs::remove_dir_all(&dir).ok();
+    let mut config = Config::new(dir.as_os_str(), "count-range");
+    config.set_blocksize(512, 4096, 512);
+
+    let mdb = llrb::load_index::<u16, u64>(seed, 20_000, 20_000, 2_000, 2_000, None);
+    let mut index = {
+        let mut build = Builder::<u16, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter_versions().unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+
+    // full range is counted from the root block.
+    let n_mreads = index.to_mblock_reads();
+    let r = (Bound::<u16>::Unbounded, Bound::<u16>::Unbounded);
+    assert_eq!(index.count_range(r).unwrap(), mdb.len());
+    assert_eq!(index.to_mblock_reads(), n_mreads);
+
+    for _i in 0..1000 {
+        let (a, b) = (rng.gen::<u16>(), rng.gen::<u16>());
+        let (a, b) = (cmp::min(a, b), cmp::max(a, b));
+        let start = match rng.gen::<u8>() % 3 {
+            0 => Bound::Included(a),
+            1 => Bound::Excluded(a),
+            _ => Bound::Unbounded,
+        };
+        let end = match rng.gen::<u8>() % 3 {
+            0 => Bound::Included(b),
+            1 => Bound::Excluded(b),
+            _ => Bound::Unbounded,
+        };
+        let r = (start, end);
+
+        // only m-blocks along the range boundaries are read.
+        let n_mreads = index.to_mblock_reads();
+        let n = index.count_range(r).unwrap();
+        let n_mreads = index.to_mblock_reads() - n_mreads;
+        assert!(n_mreads <= 2 * index.to_mblock_levels(), "{} {:?}", n_mreads, r);
+
+        let m = index.iter(r).unwrap().count();
+        assert_eq!(n, m, "{:?}", r);
+        assert_eq!(n, mdb.range(r).unwrap().count(), "{:?}", r);
+    }
+
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_robt_lazy_bitmap() {
+    let seed: u64 = random();
+    println!("test_robt_lazy_bitmap {}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let dir = std::env::temp_dir().join("test_robt_lazy_bitmap");
+    fs::remove_dir_all(&dir).ok();
+    let mut config = Config::new(dir.as_os_str(), "lazy-bitmap");
+    config.set_lazy_bitmap(true);
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 20_000, 20_000, 2_000, 2_000, None);
+    let bitmap = Xor8::<BuildHasherDefault>::new();
+    let index = {
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter().unwrap().map(Ok);
+        build.build_index(iter, bitmap, None).unwrap()
+    };
+    assert!(index.to_stats().lazy_bitmap);
+    index.close().unwrap();
+
+    let mut index =
+        Index::<u64, u64, Xor8<BuildHasherDefault>>::open(dir.as_os_str(), "lazy-bitmap")
+            .unwrap();
+    assert!(!index.is_bitmap_loaded());
+
+    // iterating and ranging shall not load the bitmap.
+    assert_eq!(index.iter(..).unwrap().count(), mdb.len());
+    let (lo, hi) = (Bound::Included(1000_u64), Bound::Excluded(u64::MAX / 2));
+    index.iter((lo, hi)).unwrap().for_each(|e| {
+        e.unwrap();
+    });
+    index.reverse(..).unwrap().for_each(|e| {
+        e.unwrap();
+    });
+    assert!(!index.is_bitmap_loaded());
+
+    let entry = mdb.iter().unwrap().next().unwrap();
+    assert_eq!(index.get(&entry.key).unwrap().key, entry.key);
+    assert!(index.is_bitmap_loaded());
+
+    for entry in mdb.iter().unwrap() {
+        assert_eq!(index.get(&entry.key).unwrap().key, entry.key);
+        assert!(index.as_bitmap().contains(&entry.key));
+    }
+    for _i in 0..10_000 {
+        let key = rng.gen::<u64>();
+        match (index.get(&key), mdb.get(&key)) {
+            (Ok(e), Ok(re)) => assert_eq!(e.key, re.key),
+            (Err(Error::NotFound(_, _)), Err(Error::NotFound(_, _))) => (),
+            (res, re) => panic!("{:?} {:?}", res, re),
+        }
+    }
+
+    // cloned index shares the loaded bitmap.
+    let index2 = index.try_clone().unwrap();
+    assert!(index2.is_bitmap_loaded());
+    index2.close().unwrap();
+
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_robt_bloom_shards() {
+    let seed: u64 = random();
+    println!("test_robt_bloom_shards {}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let dir = std::env::temp_dir().join("test_robt_bloom_shards");
+    fs::remove_dir_all(&dir).ok();
+    let mut config = Config::new(dir.as_os_str(), "bloom-shards");
+    config.set_blocksize(512, 4096, 512).set_bloom_shards(true);
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 20_000, 20_000, 2_000, 2_000, None);
+    let bitmap = Xor8::<BuildHasherDefault>::new();
+    let index = {
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter().unwrap().map(Ok);
+        build.build_index(iter, bitmap, None).unwrap()
+    };
+    assert!(index.to_stats().bloom_shards);
+    index.close().unwrap();
+
+    let mut index = Index::<u64, u64, Xor8<BuildHasherDefault>>::open(
+        dir.as_os_str(),
+        "bloom-shards",
+    )
+    .unwrap();
+    let (n_shards, n_loaded) = index.to_bloom_shards();
+    println!("test_robt_bloom_shards n_shards:{}", n_shards);
+    assert!(n_shards > 1, "{}", n_shards);
+    assert_eq!(n_loaded, 0);
+
+    // lookup keys from the first shard.
+    let high = index.shards[1].0;
+    let mut keys = vec![];
+    for entry in mdb.iter().unwrap().take_while(|e| e.key < high) {
+        let e = index.get(&entry.key).unwrap();
+        assert_eq!(e.key, entry.key);
+        keys.push(entry.key);
+    }
+    assert!(!keys.is_empty());
+    assert_eq!(index.to_bloom_shards(), (n_shards, 1));
+
+    // missing keys in the first shard.
+    let low = index.shards[0].0;
+    for _i in 0..1000 {
+        let key = low + (rng.gen::<u64>() % (high - low));
+        match index.get(&key) {
+            Ok(e) => assert!(keys.binary_search(&e.key).is_ok()),
+            Err(Error::NotFound(_, _)) => assert!(keys.binary_search(&key).is_err()),
+            Err(err) => panic!("{}", err),
+        }
+    }
+    assert_eq!(index.to_bloom_shards(), (n_shards, 1));
+
+    // rest of the keys.
+    for entry in mdb.iter().unwrap() {
+        assert_eq!(index.get(&entry.key).unwrap().key, entry.key);
+    }
+    assert_eq!(index.to_bloom_shards(), (n_shards, n_shards));
+
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_robt_fd_limiter() {
+    use std::{sync::mpsc, time};
+
+    let seed: u64 = random();
+    println!("test_robt_fd_limiter {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_fd_limiter");
+    fs::remove_dir_all(&dir).ok();
+    let mut config = Config::new(dir.as_os_str(), "fd-limiter");
+    config.set_value_log(true);
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 1_000, 1_000, 100, 100, None);
+    {
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter().unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap().close().unwrap();
+    }
+
+    let n = 4;
+    let limiter = util::FdLimiter::new(n);
+    let mut indexes = vec![];
+    for i in 0..n {
+        let index = match i {
+            0 => Index::<u64, u64, NoBitmap>::open_with_limiter(
+                dir.as_os_str(),
+                "fd-limiter",
+                &limiter,
+            )
+            .unwrap(),
+            _ => indexes[0].try_clone().unwrap(),
+        };
+        indexes.push(index);
+    }
+    assert_eq!(limiter.to_available(), 0);
+
+    let (tx, rx) = mpsc::channel();
+    let handle = {
+        let (dir, limiter) = (dir.clone(), limiter.clone());
+        thread::spawn(move || {
+            let index = Index::<u64, u64, NoBitmap>::open_with_limiter(
+                dir.as_os_str(),
+                "fd-limiter",
+                &limiter,
+            )
+            .unwrap();
+            tx.send(()).unwrap();
+            index
+        })
+    };
+
+    assert!(rx.recv_timeout(time::Duration::from_millis(200)).is_err());
+    indexes.pop().unwrap().close().unwrap();
+    rx.recv_timeout(time::Duration::from_secs(10)).unwrap();
+
+    let mut index = handle.join().unwrap();
+    assert_eq!(index.len(), mdb.len());
+    for entry in mdb.iter().unwrap() {
+        assert_eq!(index.get(&entry.key).unwrap().key, entry.key);
+    }
+    assert_eq!(limiter.to_available(), 0);
+
+    mem::drop(indexes);
+    assert_eq!(limiter.to_available(), 1);
+    index.purge().unwrap();
+    assert_eq!(limiter.to_available(), n);
+}
+
+#[test]
+fn test_robt_tree_shape() {
+    let seed: u64 = random();
+    println!("test_robt_tree_shape {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_tree_shape");
+    fs::remove_dir_all(&dir).ok();
+
+    let mut depths = vec![];
+    for (i, n) in [10, 1_000, 10_000, 100_000].iter().enumerate() {
+        let name = format!("tree-shape-{}", i);
+        let mut config = Config::new(dir.as_os_str(), &name);
+        config.set_blocksize(512, 4096, 512).set_delta(false);
+
+        let mdb = llrb::load_index::<u64, u64>(seed, *n, 0, 0, 0, None);
+        let index = {
+            let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+            let iter = mdb.iter().unwrap().map(Ok);
+            build.build_index(iter, NoBitmap, None).unwrap()
+        };
+        let stats = index.to_stats();
+        let (depth, fanout) = (stats.tree_depth, stats.to_fanout());
+        println!("n:{} depth:{} fanout:{:.2}", stats.n_count, depth, fanout);
+
+        assert!(depth >= 2, "{}", depth);
+        assert!(fanout >= 1.0, "{}", fanout);
+        // depth shall not exceed log(n) to the base of fanout, plus root and leaf.
+        if fanout > 1.0 {
+            let max_depth = (stats.n_count as f64).log(fanout).ceil() as u64 + 2;
+            assert!(depth <= max_depth, "{} {}", depth, max_depth);
+        }
+        assert!(dbs::ToJson::to_json(&stats).contains(r#""tree_depth""#));
+        depths.push(depth);
+
+        index.purge().unwrap();
+    }
+
+    assert!(depths.windows(2).all(|w| w[0] <= w[1]), "{:?}", depths);
+    assert!(depths[0] < depths[3], "{:?}", depths);
+}
+
+#[test]
+fn test_robt_build_parallel() {
+    use crate::dbs::CommitIterator;
+
+    let seed: u64 = random();
+    println!("test_robt_build_parallel {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_build_parallel");
+    fs::remove_dir_all(&dir).ok();
+
+    for (i, n_shards) in [0, 1, 2, 7, 16].iter().enumerate() {
+        let mdb = llrb::load_index::<u64, u64>(
+            seed.wrapping_add(i as u64),
+            20_000,
+            5_000,
+            1_000,
+            1_000,
+            None,
+        );
+
+        let name = format!("build-parallel-{}", i);
+        let mut config = Config::new(dir.as_os_str(), &name);
+        config.set_blocksize(512, 4096, 512).set_delta(true);
+
+        let iters = mdb.scans(*n_shards, true /*versions*/).unwrap();
+        assert!(iters.len() <= std::cmp::max(*n_shards, 1), "{}", iters.len());
+        println!("n_shards:{} iters:{}", n_shards, iters.len());
+
+        let iters: Vec<_> = iters.into_iter().map(|iter| iter.map(Ok)).collect();
+        let mut index = {
+            let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+            build.build_parallel(iters, NoBitmap, Some(mdb.to_seqno())).unwrap()
+        };
+
+        let stats = index.to_stats();
+        assert_eq!(stats.n_count, mdb.len() as u64);
+        assert_eq!(stats.n_deleted, mdb.deleted_count());
+        assert_eq!(stats.seqno, mdb.to_seqno());
+
+        let r = (Bound::<u64>::Unbounded, Bound::<u64>::Unbounded);
+        let iter = index.iter_versions(r).unwrap().map(|e| e.unwrap());
+        assert!(iter.eq(mdb.iter_versions().unwrap()));
+
+        index.purge().unwrap();
+    }
+}
+
+#[test]
+fn test_robt_export_jsonl() {
+    let seed: u64 = random();
+    println!("test_robt_export_jsonl {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_export_jsonl");
+    fs::remove_dir_all(&dir).ok();
+
+    let mdb = llrb::load_index::<u64, dbs::Binary>(seed, 1_000, 200, 100, 100, None);
+    let mut index = {
+        let config = Config::new(dir.as_os_str(), "export-jsonl");
+        let mut build = Builder::<u64, dbs::Binary>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter().unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+
+    let mut data: Vec<u8> = vec![];
+    let n = index.export_jsonl(&mut data).unwrap();
+    assert_eq!(n, mdb.len());
+
+    let text = std::str::from_utf8(&data).unwrap();
+    let mut iter = mdb.iter().unwrap();
+    for line in text.lines() {
+        let entry = iter.next().unwrap();
+        let val: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(val["key"].as_u64(), Some(entry.to_key()));
+        assert_eq!(val["seqno"].as_u64(), Some(entry.to_seqno()));
+        assert_eq!(val["deleted"].as_bool(), Some(entry.is_deleted()));
+        match entry.to_value() {
+            Some(value) => {
+                let s = val["value"].as_str().unwrap();
+                assert_eq!(base64::decode(s).unwrap(), value.val);
+            }
+            None => assert!(val["value"].is_null()),
+        }
+    }
+    assert!(iter.next().is_none());
+
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_robt_from_jsonl() {
+    let seed: u64 = random();
+    println!("test_robt_from_jsonl {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_from_jsonl");
+    fs::remove_dir_all(&dir).ok();
+
+    let mdb =
+        llrb::load_index::<u64, dbs::Binary>(seed, 10_000, 2_000, 1_000, 1_000, None);
+    let mut index = {
+        let config = Config::new(dir.as_os_str(), "export");
+        let mut build = Builder::<u64, dbs::Binary>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter().unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+
+    let mut data: Vec<u8> = vec![];
+    index.export_jsonl(&mut data).unwrap();
+
+    let mut rindex = {
+        let config = Config::new(dir.as_os_str(), "import");
+        let build = Builder::<u64, dbs::Binary>::initial(config, vec![]).unwrap();
+        build.from_jsonl(data.as_slice(), b"jsonl".to_vec(), NoBitmap).unwrap()
+    };
+    assert_eq!(rindex.to_app_metadata(), b"jsonl".to_vec());
+
+    let (stats, rstats) = (index.to_stats(), rindex.to_stats());
+    assert_eq!(stats.n_count, rstats.n_count);
+    assert_eq!(stats.n_deleted, rstats.n_deleted);
+    assert_eq!(stats.seqno, rstats.seqno);
+
+    let r = (Bound::<u64>::Unbounded, Bound::<u64>::Unbounded);
+    let iter = index.iter(r).unwrap().map(|e| e.unwrap());
+    let r = (Bound::<u64>::Unbounded, Bound::<u64>::Unbounded);
+    assert!(iter.eq(rindex.iter(r).unwrap().map(|e| e.unwrap())));
+
+    // out of order keys and malformed lines are reported with line number.
+    let text = std::str::from_utf8(&data).unwrap();
+    let mut lines: Vec<&str> = text.lines().take(10).collect();
+    lines.swap(4, 5);
+    let errs = [
+        (lines.join("\n"), "line 6"),
+        (format!("{}\n{{\"key\": 1", lines[0]), "line 2"),
+        (
+            r#"{"key": 1, "value": null, "seqno": 1, "deleted": false}"#.to_string(),
+            "line 1",
+        ),
+    ];
+    for (i, (text, lineno)) in errs.iter().enumerate() {
+        let config = Config::new(dir.as_os_str(), &format!("import-err-{}", i));
+        let build = Builder::<u64, dbs::Binary>::initial(config, vec![]).unwrap();
+        match build.from_jsonl(text.as_bytes(), vec![], NoBitmap) {
+            Ok(_) => panic!("expected error for {:?}", text),
+            Err(err) => assert!(err.to_string().contains(lineno), "{}", err),
+        }
+    }
+
+    index.purge().unwrap();
+    rindex.purge().unwrap();
+}
+
 fn do_robt_build_read<K, V, B>(prefix: &str, seed: u64, bitmap: B)
 where
     for<'a> K: 'static + Key + Arbitrary<'a>,
@@ -92,7 +827,7 @@ where
     // initial build
     let dir = std::env::temp_dir().join("test_robt_build_read");
     fs::remove_dir(&dir).ok();
-    let name = "do-robt-read";
+    let name = "do-build-read";
     let mut config = Config {
         dir: dir.as_os_str().to_os_string(),
         name: name.to_string(),
@@ -102,6 +837,18 @@ where
         delta_ok: rng.gen::<bool>(),
         value_in_vlog: rng.gen::<bool>(),
         flush_queue_size: [32, 64, 1024][rng.gen::<usize>() % 3],
+        block_compression: Codec::None,
+        bloom_shards: false,
+        lazy_bitmap: false,
+        values_optional: false,
+        prefix_bloom: false,
+        value_compress_threshold: 0,
+        inline_value_max: 0,
+        build_fsync: BuildFsync::Final,
+        strict_keys: true,
+        pin_top_levels: rng.gen::<usize>() % 3,
+        descending: false,
+        vlog_dir: None,
         vlog_location: None,
     };
     println!("do_robt_build_read-{} index file {:?}", prefix, config.to_index_location());
@@ -666,3 +1413,2074 @@ impl<T> From<Limit<T>> for Bound<T> {
         }
     }
 }
+
+#[test]
+fn test_robt_build_cancel() {
+    use std::sync::atomic::{AtomicBool, Ordering::SeqCst};
+
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_build_cancel {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_build_cancel");
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 20_000, 5_000, 1_000, 1_000, None);
+    let n_cancel = rng.gen::<usize>() % mdb.len();
+    println!("test_robt_build_cancel n_cancel:{}", n_cancel);
+
+    let mut config = Config::new(dir.as_os_str(), "build-cancel");
+    config.set_blocksize(512, 4096, 512).set_value_log(true).set_delta(true);
+
+    let token: CancelToken = Arc::new(AtomicBool::new(false));
+    let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+    build.set_cancel_token(Arc::clone(&token));
+
+    let iter = mdb.iter_versions().unwrap().enumerate().map(|(i, e)| {
+        if i == n_cancel {
+            token.store(true, SeqCst);
+        }
+        Ok(e)
+    });
+    match build.build_index(iter, NoBitmap, None) {
+        Err(Error::Cancelled(_, _)) => (),
+        Err(err) => panic!("unexpected error {}", err),
+        Ok(_) => panic!("build not cancelled"),
+    }
+
+    let files: Vec<fs::DirEntry> =
+        fs::read_dir(&dir).unwrap().map(|x| x.unwrap()).collect();
+    assert!(files.is_empty(), "{:?}", files);
+}
+
+#[test]
+fn test_robt_build_cancel_incremental() {
+    use std::sync::atomic::{AtomicBool, Ordering::SeqCst};
+
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_build_cancel_incremental {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_build_cancel_incremental");
+    fs::remove_dir_all(&dir).ok();
+    let mut config = Config::new(dir.as_os_str(), "cancel-base");
+    config.set_blocksize(512, 4096, 512).set_value_log(true).set_delta(true);
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 20_000, 5_000, 1_000, 1_000, None);
+    let mut base = {
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter_versions().unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+    let vlog = base.to_vlog_location().unwrap();
+    let n_bytes = fs::metadata(&vlog).unwrap().len();
+    let refs: Vec<dbs::Entry<u64, u64>> =
+        base.iter_versions(..).unwrap().map(|e| e.unwrap()).collect();
+
+    let keys: Vec<u64> = mdb.iter().unwrap().map(|e| e.to_key()).collect();
+    let mut seqno = mdb.to_seqno();
+    let mut batch = vec![];
+    for key in keys.into_iter().filter(|_| rng.gen::<u8>() % 4 == 0) {
+        seqno += 1;
+        batch.push(dbs::Entry::new(key, rng.gen::<u64>(), seqno));
+    }
+
+    // cancel the incremental build after it has appended to base's value-log.
+    let n_cancel = (mdb.len() / 2) + (rng.gen::<usize>() % (mdb.len() / 2));
+    println!("test_robt_build_cancel_incremental n_cancel:{}", n_cancel);
+    let token: CancelToken = Arc::new(AtomicBool::new(false));
+    let mut build = base
+        .try_clone()
+        .unwrap()
+        .incremental(dir.as_os_str(), "cancel-incr", vec![])
+        .unwrap();
+    build.set_cancel_token(Arc::clone(&token));
+
+    let iter = base.lsm_merge(batch.into_iter().map(Ok), true).unwrap();
+    let iter = iter.enumerate().map(|(i, e)| {
+        if i == n_cancel {
+            token.store(true, SeqCst);
+        }
+        e
+    });
+    match build.build_index(iter, NoBitmap, Some(seqno)) {
+        Err(Error::Cancelled(_, _)) => (),
+        Err(err) => panic!("unexpected error {}", err),
+        Ok(_) => panic!("build not cancelled"),
+    }
+
+    // base's value-log is truncated back, not removed.
+    assert_eq!(fs::metadata(&vlog).unwrap().len(), n_bytes);
+    let files: Vec<String> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|x| x.unwrap().file_name().to_str().unwrap().to_string())
+        .filter(|x| x.contains("cancel-incr"))
+        .collect();
+    assert!(files.is_empty(), "{:?}", files);
+
+    let mut base =
+        Index::<u64, u64, NoBitmap>::open(dir.as_os_str(), "cancel-base").unwrap();
+    let entries: Vec<dbs::Entry<u64, u64>> =
+        base.iter_versions(..).unwrap().map(|e| e.unwrap()).collect();
+    assert_eq!(entries, refs);
+    for entry in refs.iter() {
+        assert_eq!(base.get_versions(&entry.key).unwrap(), *entry);
+    }
+    base.validate().unwrap();
+
+    base.purge().unwrap();
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_robt_comparator() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_comparator {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_comparator");
+    fs::remove_dir_all(&dir).ok();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 20_000, 5_000, 1_000, 1_000, None);
+    let rcmp: Comparator<u64> = Arc::new(|a: &u64, b: &u64| b.cmp(a));
+
+    let mut index = {
+        let mut config = Config::new(dir.as_os_str(), "comparator");
+        config.set_blocksize(512, 4096, 512);
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        build.set_comparator("reverse", Arc::clone(&rcmp));
+
+        let mut entries: Vec<dbs::Entry<u64, u64>> =
+            mdb.iter_versions().unwrap().collect();
+        entries.reverse();
+        build.build_index(entries.into_iter().map(Ok), NoBitmap, None).unwrap()
+    };
+    assert_eq!(index.to_stats().comparator, "reverse");
+    index.validate().unwrap();
+
+    // iteration follows the comparator order.
+    let mut refs: Vec<dbs::Entry<u64, u64>> = mdb.iter().unwrap().collect();
+    refs.reverse();
+    let r = (Bound::<u64>::Unbounded, Bound::<u64>::Unbounded);
+    let entries: Vec<dbs::Entry<u64, u64>> =
+        index.iter(r).unwrap().map(|e| e.unwrap()).collect();
+    assert_eq!(entries, refs);
+
+    // range bounds are interpreted using the comparator.
+    for _i in 0..100 {
+        let (a, b) = (rng.gen::<usize>() % refs.len(), rng.gen::<usize>() % refs.len());
+        let (a, b) = (std::cmp::min(a, b), std::cmp::max(a, b));
+        let r = (Bound::Included(refs[a].key), Bound::Excluded(refs[b].key));
+        let entries: Vec<dbs::Entry<u64, u64>> =
+            index.iter(r).unwrap().map(|e| e.unwrap()).collect();
+        assert_eq!(entries, refs[a..b]);
+
+        let entries: Vec<dbs::Entry<u64, u64>> =
+            index.reverse(r).unwrap().map(|e| e.unwrap()).collect();
+        let mut rrefs = refs[a..b].to_vec();
+        rrefs.reverse();
+        assert_eq!(entries, rrefs);
+    }
+
+    for entry in refs.iter() {
+        assert_eq!(index.get(&entry.key).unwrap(), *entry);
+    }
+
+    // re-open shall reject reads without the matching comparator.
+    let mut index =
+        Index::<u64, u64, NoBitmap>::open(dir.as_os_str(), "comparator").unwrap();
+    assert!(index.get(&refs[0].key).is_err());
+    assert!(index
+        .set_comparator("forward", Arc::new(|a: &u64, b: &u64| a.cmp(b)))
+        .is_err());
+    index.set_comparator("reverse", Arc::clone(&rcmp)).unwrap();
+    for entry in mdb.iter_versions().unwrap() {
+        assert_eq!(index.get_versions(&entry.key).unwrap(), entry);
+    }
+
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_robt_list_indexes() {
+    let seed: u64 = random();
+    println!("test_robt_list_indexes {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_list_indexes");
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 2_000, 500, 100, 100, None);
+
+    let names = ["alpha-0", "alpha-1", "beta", "my-index", "gamma"];
+    let mut refs = vec![];
+    let mut n_count = 0;
+    for (i, name) in names.iter().enumerate() {
+        let seqno = mdb.to_seqno() + (i as u64);
+        let config = Config::new(dir.as_os_str(), name);
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter().unwrap().map(Ok);
+        n_count = build.build_index(iter, NoBitmap, Some(seqno)).unwrap().len();
+        refs.push((name.to_string(), seqno as usize));
+    }
+    fs::write(dir.join("not-an-index.txt"), b"hello").unwrap();
+    refs.sort();
+
+    assert_eq!(list_indexes(dir.as_os_str()).unwrap(), refs);
+
+    let indexes = open_all::<u64, u64, NoBitmap>(dir.as_os_str()).unwrap();
+    assert_eq!(indexes.len(), refs.len());
+    for ((name, version), (key, index)) in refs.iter().zip(indexes.into_iter()) {
+        assert_eq!(name, &key);
+        assert_eq!(index.to_name(), key);
+        assert_eq!(index.to_seqno() as usize, *version);
+        assert_eq!(index.len(), n_count);
+    }
+}
+
+#[test]
+fn test_robt_readahead() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_readahead {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_readahead");
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 20_000, 5_000, 1_000, 1_000, None);
+
+    let mut config = Config::new(dir.as_os_str(), "readahead");
+    config.set_blocksize(512, 4096, 512).set_value_log(true).set_delta(true);
+    let mut index = {
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter_versions().unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+
+    let refs: Vec<dbs::Entry<u64, u64>> =
+        index.iter_versions(..).unwrap().map(|e| e.unwrap()).collect();
+    assert_eq!(refs.len(), mdb.len());
+
+    for readahead in [1, 2, 8, 64].iter() {
+        let mut index = index.try_clone().unwrap();
+        index.set_readahead(*readahead);
+
+        let entries: Vec<dbs::Entry<u64, u64>> =
+            index.iter_versions(..).unwrap().map(|e| e.unwrap()).collect();
+        assert_eq!(entries, refs, "readahead:{}", readahead);
+
+        for _ in 0..20 {
+            let (a, b) = (rng.gen::<u64>(), rng.gen::<u64>());
+            let range = (Bound::Included(a.min(b)), Bound::Excluded(a.max(b)));
+            let xs: Vec<dbs::Entry<u64, u64>> =
+                index.iter(range).unwrap().map(|e| e.unwrap()).collect();
+            let ys: Vec<dbs::Entry<u64, u64>> = {
+                let mut index = index.try_clone().unwrap();
+                index.set_readahead(0);
+                index.iter(range).unwrap().map(|e| e.unwrap()).collect()
+            };
+            assert_eq!(xs, ys, "readahead:{} range:{:?}", readahead, range);
+        }
+    }
+
+    // read-ahead shares the open index-file, it is not re-opened by path.
+    let file = index.to_index_location();
+    let moved = dir.join("readahead.moved");
+    fs::rename(&file, &moved).unwrap();
+    index.set_readahead(8);
+    let entries: Vec<dbs::Entry<u64, u64>> =
+        index.iter_versions(..).unwrap().map(|e| e.unwrap()).collect();
+    assert_eq!(entries, refs);
+    fs::rename(&moved, &file).unwrap();
+
+    index.set_readahead(0);
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_robt_large_app_metadata() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_large_app_metadata {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_large_app_metadata");
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 2_000, 500, 100, 100, None);
+
+    for (i, n) in [0, 100, MARKER_BLOCK_SIZE, 3 * 1024 * 1024 + 7].iter().enumerate() {
+        let app_meta: Vec<u8> = (0..*n).map(|_| rng.gen::<u8>()).collect();
+
+        let name = format!("large-app-meta-{}", i);
+        let config = Config::new(dir.as_os_str(), &name);
+        let mut build = Builder::<u64, u64>::initial(config, app_meta.clone()).unwrap();
+        let iter = mdb.iter_versions().unwrap().map(Ok);
+        let mut index = build.build_index(iter, NoBitmap, None).unwrap();
+
+        assert_eq!(index.to_app_metadata(), app_meta, "n:{}", n);
+        index.validate().unwrap();
+
+        let mut index =
+            Index::<u64, u64, NoBitmap>::open(dir.as_os_str(), &name).unwrap();
+        assert_eq!(index.to_app_metadata(), app_meta, "n:{}", n);
+        index.validate().unwrap();
+        index.purge().unwrap();
+    }
+}
+
+#[test]
+fn test_robt_decode_error_fpos() {
+    use std::io::Write;
+
+    let seed: u64 = random();
+    println!("test_robt_decode_error_fpos {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_decode_error_fpos");
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 2_000, 0, 0, 0, None);
+
+    let mut config = Config::new(dir.as_os_str(), "decode-error-fpos");
+    config.set_blocksize(512, 4096, 512);
+    let index = {
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter().unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+    let keys: Vec<u64> = mdb.iter().unwrap().map(|e| e.key).collect();
+    let loc = index.to_index_location();
+    index.close().unwrap();
+
+    // corrupt the second z-block.
+    let fpos = 512;
+    {
+        let mut fd = fs::OpenOptions::new().write(true).open(&loc).unwrap();
+        fd.seek(io::SeekFrom::Start(fpos)).unwrap();
+        fd.write_all(&[0xFF; 512]).unwrap();
+    }
+
+    let mut index =
+        Index::<u64, u64, NoBitmap>::open(dir.as_os_str(), "decode-error-fpos").unwrap();
+    let mut n_errs = 0;
+    for key in keys.iter() {
+        if let Err(err) = index.get(key) {
+            let msg = err.to_string();
+            assert!(msg.contains(&format!("at fpos:{}", fpos)), "{}", msg);
+            n_errs += 1;
+        }
+    }
+    assert!(n_errs > 0);
+
+    match index.iter::<_, u64>(..).unwrap().find(|e| e.is_err()) {
+        Some(Err(err)) => {
+            let msg = err.to_string();
+            assert!(msg.contains(&format!("at fpos:{}", fpos)), "{}", msg);
+        }
+        _ => panic!("expected decode error"),
+    }
+
+    index.purge().unwrap();
+}
+
+static N_CLONES: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+// value type that counts the number of times it is cloned.
+#[derive(Debug, PartialEq, cbordata::Cborize)]
+struct Counted {
+    val: Vec<u8>,
+}
+
+impl Counted {
+    const ID: u32 = 0x00010001;
+}
+
+impl Clone for Counted {
+    fn clone(&self) -> Self {
+        N_CLONES.fetch_add(1, SeqCst);
+        Counted { val: self.val.clone() }
+    }
+}
+
+impl dbs::Diff for Counted {
+    type Delta = Self;
+
+    fn diff(&self, old: &Self) -> Self::Delta {
+        old.clone()
+    }
+
+    fn merge(&self, delta: &Self::Delta) -> Self {
+        delta.clone()
+    }
+}
+
+#[test]
+fn test_robt_value_ref() {
+    let dir = std::env::temp_dir().join("test_robt_value_ref");
+    fs::remove_dir_all(&dir).ok();
+
+    for (name, vlog) in [("value-inline", false), ("value-vlog", true)].iter() {
+        let mut config = Config::new(dir.as_os_str(), name);
+        config.set_value_log(*vlog);
+
+        let n: u64 = 100;
+        let mut index = {
+            let iter = (0..n).map(|key| {
+                let value = Counted { val: vec![key as u8; 1024] };
+                Ok(dbs::Entry::new(key, value, key + 1))
+            });
+            let mut build = Builder::<u64, Counted>::initial(config, vec![]).unwrap();
+            build.build_index(iter, NoBitmap, None).unwrap()
+        };
+
+        N_CLONES.store(0, SeqCst);
+        for key in 0..n {
+            let entry = index.get(&key).unwrap();
+            let value = entry.value_ref().unwrap();
+            assert_eq!(value.val.len(), 1024);
+            assert_eq!(value.val[0], key as u8);
+        }
+        assert_eq!(N_CLONES.load(SeqCst), 0, "{}", name);
+
+        index.purge().unwrap();
+    }
+}
+
+#[test]
+fn test_robt_build_unsorted() {
+    use std::collections::BTreeMap;
+
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_build_unsorted {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_build_unsorted");
+    fs::remove_dir_all(&dir).ok();
+    let tmp_dir = dir.join("tmp");
+
+    // unsorted input, with duplicate keys, seqno is unique across entries.
+    let (mut entries, mut btmap) = (vec![], BTreeMap::new());
+    for seqno in 1..=10_000_u64 {
+        let (key, value) = (rng.gen::<u16>() % 2_000, rng.gen::<u64>());
+        let entry = match rng.gen::<u8>() % 10 {
+            0 => dbs::Entry::new_delete(key, seqno),
+            _ => dbs::Entry::new(key, value, seqno),
+        };
+        entries.push(entry);
+    }
+    for i in (1..entries.len()).rev() {
+        entries.swap(i, rng.gen::<usize>() % (i + 1));
+    }
+    for entry in entries.iter() {
+        match btmap.get(entry.as_key()) {
+            Some(e) if e.to_seqno() > entry.to_seqno() => (),
+            _ => {
+                btmap.insert(entry.to_key(), entry.clone());
+            }
+        }
+    }
+
+    let config = Config::new(dir.as_os_str(), "unsorted");
+    let mut index = {
+        let mut build = Builder::<u16, u64>::initial(config, vec![]).unwrap();
+        let iter = entries.into_iter().map(Ok);
+        let (tmp_dir, mem_budget) = (tmp_dir.as_os_str(), 4096);
+        build.build_unsorted(iter, NoBitmap, None, tmp_dir, mem_budget).unwrap()
+    };
+    assert_eq!(index.len(), btmap.len());
+    assert_eq!(index.to_seqno(), 10_000);
+
+    let r = (Bound::<u16>::Unbounded, Bound::<u16>::Unbounded);
+    let mut n = 0;
+    for (entry, (key, ref_entry)) in index.iter(r).unwrap().zip(btmap.iter()) {
+        let entry = entry.unwrap();
+        assert_eq!(entry.as_key(), key);
+        assert_eq!(entry, *ref_entry);
+        n += 1;
+    }
+    assert_eq!(n, btmap.len());
+
+    // run files are removed after the build.
+    assert_eq!(fs::read_dir(&tmp_dir).unwrap().count(), 0);
+    index.validate().unwrap();
+
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_robt_endian_marker() {
+    let dir = std::env::temp_dir().join("test_robt_endian_marker");
+    fs::remove_dir_all(&dir).ok();
+
+    let name = "endian-marker";
+    let config = Config::new(dir.as_os_str(), name);
+    let file = {
+        let iter = (0..100_u64).map(|key| Ok(dbs::Entry::new(key, key, key + 1)));
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let index = build.build_index(iter, NoBitmap, None).unwrap();
+        index.to_index_location()
+    };
+    Index::<u64, u64, NoBitmap>::open(dir.as_os_str(), name).unwrap();
+
+    // tamper the marker, as if the index was built with little-endian layout.
+    {
+        let mut fd = fs::OpenOptions::new().write(true).open(&file).unwrap();
+        fd.seek(io::SeekFrom::End(-24)).unwrap();
+        fd.write_all(&ENDIAN_MARKER.to_le_bytes()).unwrap();
+    }
+    match Index::<u64, u64, NoBitmap>::open(dir.as_os_str(), name) {
+        Err(Error::InvalidFile(_, _)) => (),
+        Err(err) => panic!("unexpected error {}", err),
+        Ok(_) => panic!("expected InvalidFile error"),
+    }
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_robt_merge() {
+    use crate::bitmaps::CRoaring;
+
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_merge {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_merge");
+    fs::remove_dir_all(&dir).ok();
+
+    // three overlapping key ranges, with versions exclusive across indexes.
+    let mut refs: BTreeMap<u64, Vec<(u64, u64)>> = BTreeMap::new();
+    let mut inputs = vec![];
+    for i in 0..3_u64 {
+        let name = format!("merge-input-{}", i);
+        let mut config = Config::new(dir.as_os_str(), &name);
+        config.set_delta(true).set_value_log(rng.gen::<bool>());
+
+        let entries: Vec<dbs::Entry<u64, u64>> = ((i * 500)..(i * 500 + 1000))
+            .map(|key| {
+                let seqno = (key * 4) + i + 1;
+                let value = rng.gen::<u64>();
+                refs.entry(key).or_insert_with(Vec::new).push((seqno, value));
+                dbs::Entry::new(key, value, seqno)
+            })
+            .collect();
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = entries.into_iter().map(Ok);
+        inputs.push(build.build_index(iter, CRoaring::new(), None).unwrap());
+    }
+
+    let config = Config::new(dir.as_os_str(), "merge-output");
+    let mut index =
+        merge(inputs, config, dbs::Cutoff::new_lsm(Bound::Excluded(0))).unwrap();
+
+    assert_eq!(index.len(), refs.len());
+    for (key, versions) in refs.iter() {
+        let entry = index.get_versions(key).unwrap();
+        let values: Vec<(u64, u64)> = entry
+            .to_values()
+            .into_iter()
+            .map(|v| (v.to_seqno(), v.to_value().unwrap()))
+            .collect();
+        let mut versions = versions.clone();
+        versions.sort_unstable();
+        assert_eq!(values, versions, "key {}", key);
+        assert!(dbs::Bloom::contains(index.as_bitmap(), key), "{}", key);
+    }
+    index.validate().unwrap();
+
+    index.purge().unwrap();
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_robt_iter_located() {
+    let seed: u64 = random();
+    println!("test_robt_iter_located {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_iter_located");
+    fs::remove_dir_all(&dir).ok();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 20_000, 5_000, 1_000, 1_000, None);
+
+    let mut config = Config::new(dir.as_os_str(), "iter-located");
+    config.set_blocksize(512, 4096, 512);
+    let mut index = {
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter().unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+
+    let located: Vec<(dbs::Entry<u64, u64>, u64)> =
+        index.iter_located().unwrap().map(|item| item.unwrap()).collect();
+    let keys: Vec<(u64, u64)> =
+        located.iter().map(|(e, _)| (e.to_key(), e.to_seqno())).collect();
+    let refs: Vec<(u64, u64)> =
+        mdb.iter().unwrap().map(|e| (e.to_key(), e.to_seqno())).collect();
+    assert_eq!(keys, refs);
+
+    // entries from the same z-block are consecutive, and z-blocks are in
+    // file order.
+    let mut zblocks: Vec<(u64, Vec<u64>)> = vec![];
+    for (entry, zfpos) in located.into_iter() {
+        match zblocks.last_mut() {
+            Some((fpos, keys)) if *fpos == zfpos => keys.push(entry.to_key()),
+            Some((fpos, _)) => {
+                assert!(*fpos < zfpos, "{} {}", fpos, zfpos);
+                zblocks.push((zfpos, vec![entry.to_key()]));
+            }
+            None => zblocks.push((zfpos, vec![entry.to_key()])),
+        }
+    }
+    assert!(zblocks.len() > 1);
+
+    let mut fd = util::files::open_file_r(&index.to_index_location()).unwrap();
+    let codec = index.to_stats().block_compression;
+    for (zfpos, keys) in zblocks.into_iter() {
+        let block = codec.read_block(&mut fd, zfpos, 512).unwrap();
+        let block: Vec<Entry<u64, u64>> = util::from_cbor_bytes(&block).unwrap().0;
+        let zkeys: Vec<u64> = block.iter().map(|e| *e.as_key()).collect();
+        assert_eq!(keys, zkeys, "zfpos {}", zfpos);
+    }
+
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_robt_provenance() {
+    let dir = std::env::temp_dir().join("test_robt_provenance");
+    fs::remove_dir_all(&dir).ok();
+
+    for (name, tag) in
+        [("without-provenance", None), ("with-provenance", Some("ingest-7"))]
+    {
+        let config = Config::new(dir.as_os_str(), name);
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        if let Some(tag) = tag {
+            build.set_provenance(tag);
+        }
+        let iter = (0..100_u64).map(|key| Ok(dbs::Entry::new(key, key, key + 1)));
+        build.build_index(iter, NoBitmap, None).unwrap();
+
+        let index = Index::<u64, u64, NoBitmap>::open(dir.as_os_str(), name).unwrap();
+        let provenance = index.to_provenance().unwrap();
+        match tag {
+            Some(tag) => {
+                let provenance = provenance.unwrap();
+                assert_eq!(provenance.tag, tag);
+                assert_eq!(provenance.version, env!("CARGO_PKG_VERSION"));
+                assert!(provenance.timestamp > 0);
+            }
+            None => assert!(provenance.is_none()),
+        }
+        index.purge().unwrap();
+    }
+}
+
+#[test]
+fn test_robt_sample() {
+    let seed: u64 = random();
+    println!("test_robt_sample {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_sample");
+    fs::remove_dir_all(&dir).ok();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 10_000, 2_000, 500, 500, None);
+    let n_count = mdb.len();
+
+    let config = Config::new(dir.as_os_str(), "sample");
+    let mut index = {
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter().unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+
+    let to_keys = |entries: Vec<dbs::Entry<u64, u64>>| -> Vec<(u64, u64)> {
+        entries.into_iter().map(|e| (e.to_key(), e.to_seqno())).collect()
+    };
+
+    for n in [0, 1, 100, n_count, n_count + 10] {
+        let sample = to_keys(index.sample(n, seed).unwrap());
+        assert_eq!(sample.len(), std::cmp::min(n, n_count), "n:{}", n);
+
+        for (key, seqno) in sample.iter() {
+            let e = mdb.get(key).unwrap();
+            assert_eq!(e.to_seqno(), *seqno, "key:{}", key);
+        }
+
+        let again = to_keys(index.sample(n, seed).unwrap());
+        assert_eq!(sample, again, "n:{}", n);
+    }
+
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_robt_tombstone_compact() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_tombstone_compact {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_tombstone_compact");
+    fs::remove_dir_all(&dir).ok();
+
+    // live, deleted, re-inserted after delete, and deleted after update.
+    let entries: Vec<dbs::Entry<u64, u64>> = (0..2000_u64)
+        .map(|key| {
+            let seqno = (key * 3) + 1;
+            let entry = dbs::Entry::new(key, rng.gen::<u64>(), seqno);
+            match key % 4 {
+                0 => entry.insert(rng.gen::<u64>(), seqno + 1),
+                1 => entry.delete(seqno + 1),
+                2 => entry.delete(seqno + 1).insert(rng.gen::<u64>(), seqno + 2),
+                _ => entry.insert(rng.gen::<u64>(), seqno + 1).delete(seqno + 2),
+            }
+        })
+        .collect();
+
+    let mut config = Config::new(dir.as_os_str(), "tombstone-input");
+    config.set_delta(true).set_value_log(rng.gen::<bool>());
+    let index = {
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = entries.clone().into_iter().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+
+    let cutoff = rng.gen::<u64>() % 6000;
+    println!("test_robt_tombstone_compact cutoff:{}", cutoff);
+
+    let mut config = Config::new(dir.as_os_str(), "tombstone-output");
+    config.set_delta(true);
+    let mut index = index.tombstone_compact(config, NoBitmap, cutoff).unwrap();
+
+    let to_versions = |e: &dbs::Entry<u64, u64>| -> (u64, Vec<(u64, Option<u64>)>) {
+        let vs = e.to_values().into_iter().map(|v| (v.to_seqno(), v.to_value()));
+        (e.to_key(), vs.collect())
+    };
+    let refs: Vec<(u64, Vec<(u64, Option<u64>)>)> = entries
+        .iter()
+        .filter(|e| cutoff == 0 || !e.is_deleted() || e.to_seqno() >= cutoff)
+        .map(to_versions)
+        .collect();
+
+    let r = (Bound::<u64>::Unbounded, Bound::<u64>::Unbounded);
+    let items: Vec<(u64, Vec<(u64, Option<u64>)>)> =
+        index.iter_versions(r).unwrap().map(|e| to_versions(&e.unwrap())).collect();
+    assert_eq!(items, refs);
+
+    index.purge().unwrap();
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_robt_build_fsync() {
+    let seed: u64 = random();
+    println!("test_robt_build_fsync {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_build_fsync");
+    fs::remove_dir_all(&dir).ok();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 10_000, 2_000, 500, 500, None);
+    let refs: Vec<(u64, u64)> =
+        mdb.iter().unwrap().map(|e| (e.to_key(), e.to_seqno())).collect();
+
+    let policies = [
+        BuildFsync::Never,
+        BuildFsync::Periodic(4096),
+        BuildFsync::Periodic(1024 * 1024),
+        BuildFsync::Final,
+    ];
+    for (i, policy) in policies.iter().enumerate() {
+        let name = format!("build-fsync-{}", i);
+        let mut config = Config::new(dir.as_os_str(), &name);
+        config.set_value_log(true).set_build_fsync(*policy);
+        {
+            let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+            let iter = mdb.iter().unwrap().map(Ok);
+            build.build_index(iter, NoBitmap, None).unwrap();
+        }
+
+        // re-open the index, to read back what was flushed.
+        let mut index =
+            Index::<u64, u64, NoBitmap>::open(dir.as_os_str(), &name).unwrap();
+        index.validate().unwrap();
+        let r = (Bound::<u64>::Unbounded, Bound::<u64>::Unbounded);
+        let items: Vec<(u64, u64)> = index
+            .iter(r)
+            .unwrap()
+            .map(|e| e.unwrap())
+            .map(|e| (e.to_key(), e.to_seqno()))
+            .collect();
+        assert_eq!(items, refs, "{:?}", policy);
+
+        index.purge().unwrap();
+    }
+}
+
+#[test]
+fn test_robt_diff() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_diff {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_diff");
+    fs::remove_dir_all(&dir).ok();
+
+    // every 10th key is already deleted in the older version.
+    let entries: Vec<dbs::Entry<u64, u64>> = (0..2000_u64)
+        .map(|key| {
+            let seqno = (key * 2) + 1;
+            let entry = dbs::Entry::new(key, rng.gen::<u64>(), seqno);
+            match key % 10 {
+                9 => entry.delete(seqno + 1),
+                _ => entry,
+            }
+        })
+        .collect();
+
+    let config = Config::new(dir.as_os_str(), "diff-old");
+    let mut old = {
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = entries.clone().into_iter().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+
+    // committed batch, updates and deletes over existing keys, inserts and
+    // deletes over new keys.
+    let (mut batch, mut refs, mut seqno) = (vec![], vec![], 10_000);
+    for key in 0..2500_u64 {
+        let old_live = key < 2000 && key % 10 != 9;
+        match rng.gen::<u8>() % 3 {
+            0 => continue,
+            1 => {
+                seqno += 1;
+                batch.push(dbs::Entry::new(key, rng.gen::<u64>(), seqno));
+                match old_live {
+                    true => refs.push((key, 'U', Some(seqno))),
+                    false => refs.push((key, 'A', Some(seqno))),
+                }
+            }
+            _ => {
+                seqno += 1;
+                batch.push(dbs::Entry::new_delete(key, seqno));
+                if old_live {
+                    refs.push((key, 'D', None));
+                }
+            }
+        }
+    }
+
+    let mut new = {
+        let mut build = old
+            .try_clone()
+            .unwrap()
+            .incremental(dir.as_os_str(), "diff-new", vec![])
+            .unwrap();
+        let iter = old.lsm_merge(batch.into_iter().map(Ok), false).unwrap();
+        build.build_index(iter, NoBitmap, Some(seqno)).unwrap()
+    };
+
+    let changes: Vec<(u64, char, Option<u64>)> = old
+        .diff(&mut new)
+        .unwrap()
+        .map(|change| match change.unwrap() {
+            Change::Added(e) => (e.to_key(), 'A', Some(e.to_seqno())),
+            Change::Updated(e) => (e.to_key(), 'U', Some(e.to_seqno())),
+            Change::Deleted(key) => (key, 'D', None),
+        })
+        .collect();
+    assert_eq!(changes, refs);
+
+    // diff against itself is empty.
+    let mut same = new.try_clone().unwrap();
+    assert_eq!(new.diff(&mut same).unwrap().count(), 0);
+
+    old.purge().unwrap();
+    new.purge().unwrap();
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_robt_advise() {
+    use crate::util::files::Access;
+
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_advise {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_advise");
+    fs::remove_dir_all(&dir).ok();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 20_000, 5_000, 1_000, 1_000, None);
+
+    let mut config = Config::new(dir.as_os_str(), "advise");
+    config.set_value_log(rng.gen::<bool>()).set_delta(true);
+    let mut index = {
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter_versions().unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+
+    let refs: Vec<dbs::Entry<u64, u64>> =
+        index.iter_versions(..).unwrap().map(|e| e.unwrap()).collect();
+    assert_eq!(refs.len(), mdb.len());
+
+    for access in [Access::Sequential, Access::Random, Access::DontNeed].iter() {
+        index.advise(*access).unwrap();
+
+        let entries: Vec<dbs::Entry<u64, u64>> =
+            index.iter_versions(..).unwrap().map(|e| e.unwrap()).collect();
+        assert_eq!(entries, refs, "access:{:?}", access);
+
+        for _ in 0..100 {
+            let e = &refs[rng.gen::<usize>() % refs.len()];
+            let entry = index.get_versions(e.as_key()).unwrap();
+            assert_eq!(&entry, e, "access:{:?}", access);
+        }
+    }
+
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_robt_prefix_bloom() {
+    use std::collections::BTreeSet;
+
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_prefix_bloom {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_prefix_bloom");
+    fs::remove_dir_all(&dir).ok();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 20_000, 0, 0, 0, None);
+
+    // prefix is the 5 most significant bytes of the key.
+    let prefix_fn: PrefixFn<u64> = Arc::new(|key: &u64| key.to_be_bytes()[..5].to_vec());
+    let prefixes: BTreeSet<Vec<u8>> =
+        mdb.iter().unwrap().map(|e| prefix_fn(e.as_key())).collect();
+
+    let mut config = Config::new(dir.as_os_str(), "prefix-bloom");
+    config.set_prefix_bloom(true);
+
+    let err = {
+        let mut build = Builder::<u64, u64>::initial(config.clone(), vec![]).unwrap();
+        let iter = mdb.iter().unwrap().map(Ok);
+        build.build_index(iter, Xor8::<BuildHasherDefault>::new(), None)
+    };
+    assert!(err.is_err());
+
+    let mut index = {
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        build.set_prefix_fn(Arc::clone(&prefix_fn));
+        let iter = mdb.iter().unwrap().map(Ok);
+        build.build_index(iter, Xor8::<BuildHasherDefault>::new(), None).unwrap()
+    };
+    assert!(index.to_stats().prefix_bloom);
+
+    // no false negatives.
+    for prefix in prefixes.iter() {
+        assert!(index.may_contain_prefix(prefix), "{:?}", prefix);
+    }
+
+    // absent prefixes are short-circuited, barring false positives.
+    let (mut n_absent, mut n_skipped) = (0, 0);
+    for _ in 0..10_000 {
+        let prefix = rng.gen::<u64>().to_be_bytes()[..5].to_vec();
+        if !prefixes.contains(&prefix) {
+            n_absent += 1;
+            if !index.may_contain_prefix(&prefix) {
+                n_skipped += 1;
+            }
+        }
+    }
+    println!("test_robt_prefix_bloom absent:{} skipped:{}", n_absent, n_skipped);
+    assert!(n_skipped * 100 >= n_absent * 95, "{}/{}", n_skipped, n_absent);
+
+    // prefix bloom is loaded along with the index, and cloned with the index.
+    let clone = index.try_clone().unwrap();
+    let reopen = Index::<u64, u64, Xor8<BuildHasherDefault>>::open(
+        dir.as_os_str(),
+        "prefix-bloom",
+    )
+    .unwrap();
+    for prefix in prefixes.iter() {
+        assert!(clone.may_contain_prefix(prefix), "{:?}", prefix);
+        assert!(reopen.may_contain_prefix(prefix), "{:?}", prefix);
+    }
+    mem::drop(clone);
+    mem::drop(reopen);
+
+    // index built without prefix bloom can't rule out any prefix.
+    let mut config = Config::new(dir.as_os_str(), "no-prefix-bloom");
+    config.set_delta(false);
+    let mut plain = {
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter().unwrap().map(Ok);
+        build.build_index(iter, Xor8::<BuildHasherDefault>::new(), None).unwrap()
+    };
+    assert!(!plain.to_stats().prefix_bloom);
+    assert!(plain.may_contain_prefix(&[0, 0, 0, 0, 0]));
+
+    let r = (Bound::<u64>::Unbounded, Bound::<u64>::Unbounded);
+    assert_eq!(index.iter(r).unwrap().count(), mdb.len());
+
+    plain.purge().unwrap();
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_robt_value_compress() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_value_compress {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_value_compress");
+    fs::remove_dir_all(&dir).ok();
+
+    // mix of small and large compressible values.
+    let entries: Vec<dbs::Entry<u64, dbs::Binary>> = (0..1_000_u64)
+        .map(|key| {
+            let n = if rng.gen::<bool>() { 16 } else { 4096 };
+            let value = dbs::Binary { val: vec![(key % 256) as u8; n] };
+            dbs::Entry::new(key, value, key + 1)
+        })
+        .collect();
+
+    let mut indexes = vec![];
+    for (name, threshold) in [("plain", 0), ("compress", 1024)].iter() {
+        let mut config = Config::new(dir.as_os_str(), name);
+        config.set_value_log(true).set_value_compress_threshold(*threshold);
+        let mut build = Builder::<u64, dbs::Binary>::initial(config, vec![]).unwrap();
+        let iter = entries.clone().into_iter().map(Ok);
+        indexes.push(build.build_index(iter, NoBitmap, None).unwrap());
+    }
+
+    let mut compress = indexes.pop().unwrap();
+    let mut plain = indexes.pop().unwrap();
+    assert_eq!(compress.to_stats().value_compress_threshold, 1024);
+
+    let vlog_len = |index: &Index<u64, dbs::Binary, NoBitmap>| {
+        fs::metadata(index.to_vlog_location().unwrap()).unwrap().len()
+    };
+    let (n, m) = (vlog_len(&plain), vlog_len(&compress));
+    println!("test_robt_value_compress vlog plain:{} compress:{}", n, m);
+    assert!(m < n, "{} {}", m, n);
+
+    for entry in entries.iter() {
+        let key = entry.as_key();
+        assert_eq!(compress.get(key).unwrap(), *entry);
+        assert_eq!(plain.get(key).unwrap(), *entry);
+    }
+
+    let r = (Bound::<u64>::Unbounded, Bound::<u64>::Unbounded);
+    let iter = compress.iter(r).unwrap().map(|e| e.unwrap());
+    assert!(iter.eq(entries.clone().into_iter()));
+
+    compress.purge().unwrap();
+    plain.purge().unwrap();
+}
+
+#[test]
+fn test_robt_inline_value_max() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_inline_value_max {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_inline_value_max");
+    fs::remove_dir_all(&dir).ok();
+
+    // mix of small values, to be inlined, and large values, to be spilled.
+    let entries: Vec<dbs::Entry<u64, dbs::Binary>> = (0..1_000_u64)
+        .map(|key| {
+            let n = if rng.gen::<bool>() { 16 } else { 1024 };
+            let value = dbs::Binary { val: vec![(key % 256) as u8; n] };
+            dbs::Entry::new(key, value, key + 1)
+        })
+        .collect();
+    let is_small = |e: &dbs::Entry<u64, dbs::Binary>| match e.to_value() {
+        Some(value) => value.val.len() == 16,
+        None => unreachable!(),
+    };
+
+    let mut index = {
+        let mut config = Config::new(dir.as_os_str(), "inline-value-max");
+        config.set_value_log(true).set_inline_value_max(256).set_values_optional(true);
+        let mut build = Builder::<u64, dbs::Binary>::initial(config, vec![]).unwrap();
+        let iter = entries.clone().into_iter().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+    assert_eq!(index.to_stats().inline_value_max, 256);
+
+    for entry in entries.iter() {
+        assert_eq!(index.get(entry.as_key()).unwrap(), *entry);
+    }
+    let r = (Bound::<u64>::Unbounded, Bound::<u64>::Unbounded);
+    let iter = index.iter(r).unwrap().map(|e| e.unwrap());
+    assert!(iter.eq(entries.clone().into_iter()));
+
+    // archive away the value-log, only inlined values shall be readable.
+    fs::remove_file(index.to_vlog_location().unwrap()).unwrap();
+    index.close().unwrap();
+
+    let mut index =
+        Index::<u64, dbs::Binary, NoBitmap>::open(dir.as_os_str(), "inline-value-max")
+            .unwrap();
+    for entry in entries.iter() {
+        match index.get(entry.as_key()) {
+            Ok(e) if is_small(entry) => assert_eq!(e, *entry),
+            Ok(_) => panic!("expected vlog unavailable for {}", entry.to_key()),
+            Err(Error::IOError(_, msg)) if !is_small(entry) => {
+                assert!(msg.contains("vlog unavailable"), "{}", msg)
+            }
+            Err(err) => panic!("unexpected error {} for {}", err, entry.to_key()),
+        }
+    }
+    mem::drop(index);
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_robt_par_ranges() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_par_ranges {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_par_ranges");
+    fs::remove_dir_all(&dir).ok();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 20_000, 2_000, 1_000, 1_000, None);
+    let index = {
+        let config = Config::new(dir.as_os_str(), "par-ranges");
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter().unwrap().map(Ok);
+        build.build_index(iter, Xor8::<BuildHasherDefault>::new(), None).unwrap()
+    };
+
+    // disjoint ranges, covering the entire key space.
+    let n_ranges = rng.gen_range(1..9);
+    let mut splits: Vec<u64> = (1..n_ranges).map(|_| rng.gen()).collect();
+    splits.sort_unstable();
+    let mut ranges = vec![];
+    let mut start = Bound::Unbounded;
+    for split in splits.into_iter() {
+        ranges.push((start, Bound::Excluded(split)));
+        start = Bound::Included(split);
+    }
+    ranges.push((start, Bound::Unbounded));
+    println!("test_robt_par_ranges n_ranges:{}", ranges.len());
+
+    let iters = index.par_ranges(ranges).unwrap();
+    for iter in iters.iter() {
+        let bitmap = iter.as_index().bitmap.as_ref().unwrap();
+        assert!(Arc::ptr_eq(bitmap, index.bitmap.as_ref().unwrap()));
+    }
+
+    let mut handles = vec![];
+    for iter in iters.into_iter() {
+        handles.push(thread::spawn(move || {
+            iter.map(|e| e.unwrap()).collect::<Vec<dbs::Entry<u64, u64>>>()
+        }));
+    }
+    let mut entries = vec![];
+    for handle in handles.into_iter() {
+        entries.extend(handle.join().unwrap());
+    }
+
+    assert_eq!(entries.len(), mdb.len());
+    for (entry, ref_entry) in entries.into_iter().zip(mdb.iter().unwrap()) {
+        assert_eq!(entry.to_key(), ref_entry.to_key());
+        assert_eq!(entry.to_seqno(), ref_entry.to_seqno());
+        assert_eq!(entry.is_deleted(), ref_entry.is_deleted());
+        assert_eq!(entry.to_value(), ref_entry.to_value());
+    }
+
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_robt_vlog_dir() {
+    let seed: u64 = random();
+    println!("test_robt_vlog_dir {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_vlog_dir");
+    let (idir, vdir, mdir) = (dir.join("index"), dir.join("vlog"), dir.join("moved"));
+    fs::remove_dir_all(&dir).ok();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 10_000, 2_000, 1_000, 1_000, None);
+    let index = {
+        let mut config = Config::new(idir.as_os_str(), "vlog-dir");
+        config.set_value_log(true).set_vlog_dir(vdir.as_os_str());
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter().unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+
+    let vlog_file = to_vlog_location(vdir.as_os_str(), "vlog-dir");
+    assert_eq!(index.to_vlog_location(), Some(vlog_file.clone()));
+    assert_eq!(index.to_stats().vlog_dir, Some(vdir.clone().into_os_string()));
+    assert!(path::Path::new(&vlog_file).exists());
+    assert!(!path::Path::new(&to_vlog_location(idir.as_os_str(), "vlog-dir")).exists());
+    mem::drop(index);
+
+    let check = |index: &mut Index<u64, u64, NoBitmap>| {
+        for entry in mdb.iter_versions().unwrap() {
+            assert_eq!(index.get_versions(&entry.key).unwrap(), entry);
+        }
+        let r = (Bound::<u64>::Unbounded, Bound::<u64>::Unbounded);
+        let iter = index.iter_versions(r).unwrap().map(|e| e.unwrap());
+        assert!(iter.eq(mdb.iter_versions().unwrap()));
+    };
+
+    // vlog directory is persisted along with the index.
+    let mut index = Index::open(idir.as_os_str(), "vlog-dir").unwrap();
+    check(&mut index);
+    check(&mut index.try_clone().unwrap());
+    mem::drop(index);
+
+    // vlog moved to a different mount, override the persisted directory.
+    fs::create_dir_all(&mdir).unwrap();
+    let moved = to_vlog_location(mdir.as_os_str(), "vlog-dir");
+    fs::rename(&vlog_file, &moved).unwrap();
+    assert!(Index::<u64, u64, NoBitmap>::open(idir.as_os_str(), "vlog-dir").is_err());
+
+    let mut index =
+        Index::open_with_vlog_dir(idir.as_os_str(), "vlog-dir", mdir.as_os_str())
+            .unwrap();
+    assert_eq!(index.to_vlog_location(), Some(moved.clone()));
+    check(&mut index);
+    check(&mut index.try_clone().unwrap());
+
+    index.purge().unwrap();
+    assert!(!path::Path::new(&moved).exists());
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_robt_cursor() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_cursor {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_cursor");
+    fs::remove_dir_all(&dir).ok();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 50_000, 5_000, 1_000, 1_000, None);
+    let mut index = {
+        let mut config = Config::new(dir.as_os_str(), "cursor");
+        config.set_value_log(rng.gen::<bool>());
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter().unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+    let entries: Vec<dbs::Entry<u64, u64>> = mdb.iter_versions().unwrap().collect();
+
+    // sorted gets, galloping from the remembered position.
+    let n_sorted = {
+        let mut cursor = index.cursor().unwrap();
+        for entry in entries.iter() {
+            assert_eq!(cursor.get_versions(&entry.key).unwrap(), *entry);
+        }
+        cursor.to_comparisons()
+    };
+    // sorted gets, with full search for every get.
+    let n_full = {
+        let mut cursor = index.cursor().unwrap();
+        for entry in entries.iter() {
+            cursor.reset();
+            assert_eq!(cursor.get_versions(&entry.key).unwrap(), *entry);
+        }
+        cursor.to_comparisons()
+    };
+    println!("test_robt_cursor comparisons sorted:{} full:{}", n_sorted, n_full);
+    assert!(n_sorted * 2 < n_full, "{} {}", n_sorted, n_full);
+
+    // random gets, and missing keys, are still correct.
+    let mut cursor = index.cursor().unwrap();
+    for _ in 0..10_000 {
+        let key = match rng.gen::<bool>() {
+            true => entries[rng.gen::<usize>() % entries.len()].key,
+            false => rng.gen::<u64>(),
+        };
+        match (cursor.get(&key), mdb.get(&key)) {
+            (Ok(e), Ok(x)) => {
+                assert_eq!(e.to_key(), x.to_key());
+                assert_eq!(e.to_seqno(), x.to_seqno());
+                assert_eq!(e.to_value(), x.to_value());
+            }
+            (Err(Error::NotFound(_, _)), Err(Error::NotFound(_, _))) => (),
+            (Err(err), _) => panic!("{}", err),
+            (Ok(e), Err(_)) => panic!("unexpected {}", e.to_key()),
+        }
+    }
+    for entry in entries.iter().rev().step_by(7) {
+        assert_eq!(cursor.get(&entry.key).unwrap().to_seqno(), entry.to_seqno());
+    }
+
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_robt_get_tombstone() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_get_tombstone {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_get_tombstone");
+    fs::remove_dir_all(&dir).ok();
+
+    // even keys are present, every third present key is a tombstone.
+    let mut entries: Vec<dbs::Entry<u64, u64>> = vec![];
+    let mut seqno = 0;
+    for key in (0..20_000_u64).step_by(2) {
+        seqno += 1;
+        let entry = dbs::Entry::new(key, rng.gen(), seqno);
+        if key % 3 == 0 {
+            seqno += 1;
+            entries.push(entry.delete(seqno));
+        } else {
+            entries.push(entry);
+        }
+    }
+
+    let check = |index: &mut Index<u64, u64, Xor8<BuildHasherDefault>>| {
+        for entry in entries.iter() {
+            let e = index.get(&entry.key).unwrap();
+            assert_eq!(e.is_deleted(), entry.is_deleted(), "{}", entry.key);
+            assert_eq!(e.to_seqno(), entry.to_seqno(), "{}", entry.key);
+            match index.get(&(entry.key + 1)) {
+                Err(Error::NotFound(_, _)) => (),
+                res => panic!("{} {:?}", entry.key + 1, res.map(|e| e.to_seqno())),
+            }
+        }
+
+        let mut cursor = index.cursor().unwrap();
+        for entry in entries.iter() {
+            let e = cursor.get(&entry.key).unwrap();
+            assert_eq!(e.is_deleted(), entry.is_deleted(), "{}", entry.key);
+            assert!(cursor.get(&(entry.key + 1)).is_err());
+        }
+    };
+
+    for (i, (shards, vlog)) in
+        [(false, false), (true, false), (false, true)].iter().enumerate()
+    {
+        let name = format!("tombstone-{}", i);
+        let mut index = {
+            let mut config = Config::new(dir.as_os_str(), &name);
+            config.set_bloom_shards(*shards).set_value_log(*vlog);
+            let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+            let iter = entries.clone().into_iter().map(Ok);
+            build.build_index(iter, Xor8::<BuildHasherDefault>::new(), None).unwrap()
+        };
+        check(&mut index);
+
+        // compacted away tombstones are absent.
+        let config = Config::new(dir.as_os_str(), &format!("{}-compact", name));
+        let mut index = index
+            .tombstone_compact(config, Xor8::<BuildHasherDefault>::new(), seqno + 1)
+            .unwrap();
+        for entry in entries.iter() {
+            match index.get(&entry.key) {
+                Ok(e) if !entry.is_deleted() => assert!(!e.is_deleted()),
+                Err(Error::NotFound(_, _)) if entry.is_deleted() => (),
+                res => panic!("{} {:?}", entry.key, res.map(|e| e.to_seqno())),
+            }
+        }
+        index.purge().unwrap();
+    }
+}
+
+#[test]
+fn test_robt_size_hint() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_size_hint {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_size_hint");
+    fs::remove_dir_all(&dir).ok();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 20_000, 5_000, 1_000, 1_000, None);
+    let mut index = {
+        let mut config = Config::new(dir.as_os_str(), "size-hint");
+        config.set_value_log(rng.gen::<bool>());
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter().unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+    let n = index.len();
+
+    // full table iteration.
+    let r = (Bound::<u64>::Unbounded, Bound::<u64>::Unbounded);
+    assert_eq!(index.iter(r).unwrap().size_hint(), (n, Some(n)));
+    assert_eq!(index.reverse(r).unwrap().size_hint(), (n, Some(n)));
+    assert_eq!(index.iter_versions(r).unwrap().size_hint(), (n, Some(n)));
+
+    let mut iter = index.iter(r).unwrap();
+    for i in 0..(n / 2) {
+        iter.next().unwrap().unwrap();
+        assert_eq!(iter.size_hint(), (n - i - 1, Some(n - i - 1)));
+    }
+    let entries: Vec<dbs::Entry<u64, u64>> =
+        iter.collect::<Result<Vec<dbs::Entry<u64, u64>>>>().unwrap();
+    assert_eq!(entries.len(), n - (n / 2));
+
+    // range iteration.
+    for _ in 0..100 {
+        let a = entries[rng.gen::<usize>() % entries.len()].to_key();
+        let b = entries[rng.gen::<usize>() % entries.len()].to_key();
+        let r = (Bound::Included(cmp::min(a, b)), Bound::Excluded(cmp::max(a, b)));
+        let m = index.iter(r).unwrap().count();
+        assert!(m <= n, "{:?}", r);
+        assert_eq!(index.iter(r).unwrap().size_hint(), (0, Some(n)), "{:?}", r);
+        assert_eq!(index.reverse(r).unwrap().size_hint(), (0, Some(n)), "{:?}", r);
+
+        let mut iter = index.iter(r).unwrap();
+        let upper = match iter.next() {
+            Some(_) => n - 1,
+            None => 0,
+        };
+        assert_eq!(iter.size_hint(), (0, Some(upper)), "{:?}", r);
+    }
+
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_robt_build_with_deltas() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_build_with_deltas {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_build_with_deltas");
+    fs::remove_dir_all(&dir).ok();
+
+    // deltas are made up, not computed using dbs::Diff.
+    let mut items: Vec<ExplicitDeltas<u64, u64>> = vec![];
+    let mut seqno = 0;
+    for key in 0..10_000_u64 {
+        let mut deltas = vec![];
+        for _ in 0..(rng.gen::<usize>() % 4) {
+            seqno += 1;
+            let delta = match rng.gen::<u8>() % 4 {
+                0 => dbs::Delta::new_delete(seqno),
+                _ => dbs::Delta::new_upsert(rng.gen::<u64>(), seqno),
+            };
+            deltas.push(delta);
+        }
+        seqno += 1;
+        items.push((key * 2, rng.gen::<u64>(), seqno, deltas));
+    }
+
+    let mut index = {
+        let mut config = Config::new(dir.as_os_str(), "build-with-deltas");
+        config.set_value_log(rng.gen::<bool>());
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = items.clone().into_iter().map(Ok);
+        build.build_with_deltas(iter, NoBitmap, None).unwrap()
+    };
+    assert_eq!(index.len(), items.len());
+    assert_eq!(index.to_seqno(), seqno);
+
+    for (key, value, seqno, deltas) in items.into_iter() {
+        let entry = index.get_versions(&key).unwrap();
+        assert_eq!(entry.to_value(), Some(value));
+        assert_eq!(entry.to_seqno(), seqno);
+        assert_eq!(entry.deltas, deltas, "key:{}", key);
+        assert_eq!(index.get(&key).unwrap().deltas.len(), 0);
+    }
+    index.purge().unwrap();
+
+    // delta seqno shall be older than the value's seqno.
+    let mut build = {
+        let config = Config::new(dir.as_os_str(), "build-with-deltas");
+        Builder::<u64, u64>::initial(config, vec![]).unwrap()
+    };
+    let deltas = vec![dbs::Delta::new_upsert(10, 20)];
+    let iter = vec![Ok((1_u64, 100_u64, 20_u64, deltas))].into_iter();
+    match build.build_with_deltas(iter, NoBitmap, None) {
+        Err(Error::InvalidInput(_, _)) => (),
+        Err(err) => panic!("unexpected {}", err),
+        Ok(_) => panic!("expected error"),
+    }
+}
+
+#[test]
+fn test_robt_contains() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_contains {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_contains");
+    fs::remove_dir_all(&dir).ok();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 20_000, 5_000, 1_000, 1_000, None);
+    let mut index = {
+        let mut config = Config::new(dir.as_os_str(), "contains");
+        config.set_value_log(true).set_bloom_shards(rng.gen::<bool>());
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter().unwrap().map(Ok);
+        let bitmap = Xor8::<BuildHasherDefault>::new();
+        build.build_index(iter, bitmap, None).unwrap()
+    };
+    let keys: Vec<u64> = mdb.iter().unwrap().map(|e| e.to_key()).collect();
+    let missing: Vec<u64> = (0..10_000)
+        .map(|_| rng.gen::<u64>())
+        .filter(|key| mdb.get(key).is_err())
+        .collect();
+
+    // instrument by truncating the value-log, reading the value-log fails
+    // hereafter, while contains doesn't read from value-log.
+    {
+        let vlog = index.to_vlog_location().unwrap();
+        let fd = fs::OpenOptions::new().write(true).open(&vlog).unwrap();
+        fd.set_len(0).unwrap();
+    }
+    assert!(index.get(&keys[0]).is_err());
+
+    for key in keys.iter() {
+        assert!(index.contains(key).unwrap(), "{}", key);
+    }
+    for key in missing.iter() {
+        assert!(!index.contains(key).unwrap(), "{}", key);
+    }
+
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_robt_rebuild_bitmap() {
+    use crate::bitmaps::CRoaring;
+
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_rebuild_bitmap {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_rebuild_bitmap");
+    fs::remove_dir_all(&dir).ok();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 20_000, 5_000, 1_000, 1_000, None);
+    let bloom_shards: bool = rng.gen();
+    let index = {
+        let mut config = Config::new(dir.as_os_str(), "rebuild-bitmap");
+        config.set_value_log(rng.gen::<bool>()).set_bloom_shards(bloom_shards);
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter().unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+    println!("test_robt_rebuild_bitmap bloom_shards:{}", bloom_shards);
+
+    let entries: Vec<dbs::Entry<u64, u64>> = mdb.iter_versions().unwrap().collect();
+    let missing: Vec<u64> = (0..10_000)
+        .map(|_| rng.gen::<u64>())
+        .filter(|key| mdb.get(key).is_err())
+        .collect();
+    // NoBitmap, every missing key is a false-positive.
+    assert!(missing.iter().all(|key| index.as_bitmap().contains(key)));
+    assert_eq!(index.to_stats().bitmap_version, 0);
+
+    let old_stats = index.to_stats();
+    let index_loc = index.to_index_location();
+    let index = index.rebuild_bitmap(CRoaring::new()).unwrap();
+    // temporary index-file is swapped in.
+    let mut itmp = index_loc.clone();
+    itmp.push(".bitmap");
+    assert!(!path::Path::new(&itmp).exists());
+
+    let check = |mut index: Index<u64, u64, CRoaring>| -> Index<u64, u64, CRoaring> {
+        let stats = index.to_stats();
+        assert_eq!(stats.bitmap_version, 1);
+        assert_eq!(stats.n_count, old_stats.n_count);
+        assert_eq!(stats.seqno, old_stats.seqno);
+        assert_eq!(stats.vlog_location, old_stats.vlog_location);
+
+        if !bloom_shards {
+            let n = missing.iter().filter(|key| index.as_bitmap().contains(*key)).count();
+            println!("test_robt_rebuild_bitmap false-positives {}", n);
+            assert!(n * 100 < missing.len(), "{}/{}", n, missing.len());
+        }
+        for key in missing.iter() {
+            match index.get(key) {
+                Err(Error::NotFound(_, _)) => (),
+                res => panic!("unexpected {:?}", res),
+            }
+        }
+        for entry in entries.iter() {
+            assert_eq!(index.get_versions(&entry.key).unwrap(), *entry);
+        }
+        let r = (Bound::<u64>::Unbounded, Bound::<u64>::Unbounded);
+        let items: Vec<dbs::Entry<u64, u64>> =
+            index.iter_versions(r).unwrap().map(|e| e.unwrap()).collect();
+        assert_eq!(items, entries);
+        index
+    };
+
+    let index = check(index);
+    mem::drop(index);
+    // re-open from disk.
+    let index = Index::<u64, u64, CRoaring>::open(dir.as_os_str(), "rebuild-bitmap");
+    let index = check(index.unwrap());
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_robt_values_optional() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_values_optional {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_values_optional");
+    fs::remove_dir_all(&dir).ok();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 20_000, 5_000, 1_000, 1_000, None);
+    let index = {
+        let mut config = Config::new(dir.as_os_str(), "values-optional");
+        config.set_value_log(true).set_values_optional(true);
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter_versions().unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+    let keys: Vec<u64> = mdb.iter().unwrap().map(|e| e.to_key()).collect();
+    let live: Vec<u64> = mdb
+        .iter()
+        .unwrap()
+        .filter(|e| !e.is_deleted())
+        .map(|e| e.to_key())
+        .collect();
+    assert!(index.to_stats().values_optional);
+
+    // archive away the value-log.
+    fs::remove_file(index.to_vlog_location().unwrap()).unwrap();
+    index.close().unwrap();
+
+    let mut index =
+        Index::<u64, u64, NoBitmap>::open(dir.as_os_str(), "values-optional").unwrap();
+
+    let ks: Vec<u64> = index.iter_keys(..).unwrap().map(|k| k.unwrap()).collect();
+    assert_eq!(ks, keys);
+    assert_eq!(index.count_range(..).unwrap(), keys.len());
+
+    for _ in 0..100 {
+        let (a, b) = (rng.gen::<usize>() % keys.len(), rng.gen::<usize>() % keys.len());
+        let (low, high) = (keys[cmp::min(a, b)], keys[cmp::max(a, b)]);
+        let ks: Vec<u64> =
+            index.iter_keys(low..high).unwrap().map(|k| k.unwrap()).collect();
+        let refs: Vec<u64> =
+            keys.iter().filter(|k| (low..high).contains(*k)).cloned().collect();
+        assert_eq!(ks, refs);
+        assert_eq!(index.count_range(low..high).unwrap(), refs.len());
+    }
+
+    match index.get(&live[rng.gen::<usize>() % live.len()]) {
+        Err(Error::IOError(_, msg)) => {
+            assert!(msg.contains("vlog unavailable"), "{}", msg)
+        }
+        Err(err) => panic!("unexpected error {}", err),
+        Ok(_) => panic!("expected vlog unavailable"),
+    }
+
+    // without values_optional, re-opening the missing value-log shall fail.
+    index.stats.values_optional = false;
+    assert!(index.try_clone().is_err());
+    mem::drop(index);
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_robt_empty_index() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_empty_index {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_empty_index");
+    fs::remove_dir_all(&dir).ok();
+
+    for i in 0..8 {
+        let name = format!("empty-index-{}", i);
+        let mut config = Config::new(dir.as_os_str(), &name);
+        config
+            .set_value_log(rng.gen())
+            .set_delta(rng.gen())
+            .set_bloom_shards(rng.gen())
+            .set_lazy_bitmap(rng.gen())
+            .set_pin_top_levels(rng.gen::<usize>() % 3);
+        println!("test_robt_empty_index {:?}", config);
+
+        let index = {
+            let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+            let iter = std::iter::empty::<Result<dbs::Entry<u64, u64>>>();
+            build.build_index(iter, Xor8::<BuildHasherDefault>::new(), None).unwrap()
+        };
+        index.close().unwrap();
+
+        let mut index =
+            Index::<u64, u64, Xor8<BuildHasherDefault>>::open(dir.as_os_str(), &name)
+                .unwrap();
+        assert!(index.is_empty());
+        assert_eq!(index.len(), 0);
+        assert_eq!(index.to_root(), None);
+        assert_eq!(index.validate().unwrap().n_count, 0);
+
+        let key: u64 = rng.gen();
+        match index.get(&key) {
+            Err(Error::NotFound(_, _)) => (),
+            res => panic!("unexpected {:?}", res),
+        }
+        match index.get_versions(&key) {
+            Err(Error::NotFound(_, _)) => (),
+            res => panic!("unexpected {:?}", res),
+        }
+        assert!(!index.contains(&key).unwrap());
+        assert!(!index.may_contain_prefix(&key.to_be_bytes()));
+        {
+            let mut cursor = index.cursor().unwrap();
+            match cursor.get(&key) {
+                Err(Error::NotFound(_, _)) => (),
+                res => panic!("unexpected {:?}", res),
+            }
+        }
+
+        let ranges = [
+            (Bound::Unbounded, Bound::Unbounded),
+            (Bound::Included(key), Bound::Unbounded),
+            (Bound::Unbounded, Bound::Excluded(key)),
+            (Bound::Included(key / 2), Bound::Included(key)),
+        ];
+        for r in ranges.iter() {
+            let iter = index.iter(*r).unwrap();
+            assert_eq!(iter.size_hint(), (0, Some(0)));
+            assert_eq!(iter.count(), 0);
+            assert_eq!(index.iter_versions(*r).unwrap().count(), 0);
+            assert_eq!(index.reverse(*r).unwrap().count(), 0);
+            assert_eq!(index.reverse_versions(*r).unwrap().count(), 0);
+            assert_eq!(index.iter_keys(*r).unwrap().count(), 0);
+            assert_eq!(index.count_range(*r).unwrap(), 0);
+            assert_eq!(index.estimate_scan_cost(*r).unwrap(), IoCost::default());
+        }
+        assert_eq!(index.estimate_get_cost(), IoCost::default());
+        assert_eq!(index.iter_located().unwrap().count(), 0);
+        assert!(index.sample(10, seed).unwrap().is_empty());
+
+        let iters = index.par_ranges(ranges.to_vec()).unwrap();
+        assert!(iters.into_iter().all(|mut iter| iter.next().is_none()));
+
+        let mut other = index.try_clone().unwrap();
+        assert_eq!(index.diff(&mut other).unwrap().count(), 0);
+        mem::drop(other);
+
+        index.purge().unwrap();
+    }
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_robt_pin_top_levels() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_pin_top_levels {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_pin_top_levels");
+    fs::remove_dir_all(&dir).ok();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 50_000, 5_000, 1_000, 1_000, None);
+    let keys: Vec<u64> = mdb.iter().unwrap().map(|e| e.to_key()).collect();
+
+    let mut indexes = vec![];
+    for (name, levels) in [("unpinned", 0), ("pinned", 16)].iter() {
+        let mut config = Config::new(dir.as_os_str(), name);
+        config
+            .set_blocksize(4096, 4096, 512)
+            .set_value_log(rng.gen::<bool>())
+            .set_pin_top_levels(*levels);
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter().unwrap().map(Ok);
+        indexes.push(build.build_index(iter, NoBitmap, None).unwrap());
+    }
+    let mut pinned = indexes.pop().unwrap();
+    let mut unpinned = indexes.pop().unwrap();
+
+    let stats = pinned.to_stats();
+    println!("test_robt_pin_top_levels depth:{}", stats.tree_depth);
+    assert_eq!(stats.pin_top_levels, 16);
+    assert!(stats.tree_depth > 2, "{}", stats.tree_depth);
+
+    let n_gets = 1_000;
+    for _ in 0..n_gets {
+        let key = keys[rng.gen::<usize>() % keys.len()];
+        let entry = mdb.get(&key).unwrap();
+        assert_eq!(unpinned.get(&key).unwrap(), entry);
+        assert_eq!(pinned.get(&key).unwrap(), entry);
+    }
+    let (n_unpinned, n_pinned) = (unpinned.to_mblock_reads(), pinned.to_mblock_reads());
+    println!("test_robt_pin_top_levels mblock reads {} {}", n_unpinned, n_pinned);
+    assert!(n_unpinned >= n_gets, "{}", n_unpinned);
+    assert_eq!(n_pinned, 0);
+
+    // cloned index shares the pinned blocks.
+    let mut cloned = pinned.try_clone().unwrap();
+    assert_eq!(cloned.get(&keys[0]).unwrap(), mdb.get(&keys[0]).unwrap());
+    assert_eq!(cloned.count_range(..).unwrap(), keys.len());
+    assert_eq!(cloned.to_mblock_reads(), 0);
+
+    // iterators read through the pinned levels.
+    let iter = cloned.iter(..).unwrap();
+    assert_eq!(iter.map(|e| e.unwrap().to_key()).collect::<Vec<u64>>(), keys);
+    let iter = cloned.reverse(..).unwrap();
+    let mut rkeys: Vec<u64> = iter.map(|e| e.unwrap().to_key()).collect();
+    rkeys.reverse();
+    assert_eq!(rkeys, keys);
+
+    unpinned.purge().unwrap();
+    pinned.purge().unwrap();
+}
+
+#[test]
+fn test_robt_duplicate_key() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_duplicate_key {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_duplicate_key");
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap();
+
+    let mut entries: Vec<dbs::Entry<u64, u64>> = (0..10_000_u64)
+        .map(|key| dbs::Entry::new(key * 2, rng.gen(), key + 1))
+        .collect();
+    let off = rng.gen::<usize>() % (entries.len() - 1);
+    let dup = dbs::Entry::new(entries[off].to_key(), rng.gen(), 10_001);
+    entries.insert(off + 1, dup);
+    println!("test_robt_duplicate_key off:{}", off);
+
+    let mut config = Config::new(dir.as_os_str(), "duplicate-key");
+    config.set_blocksize(512, 4096, 512).set_value_log(rng.gen::<bool>());
+
+    // strict mode, the default, fails the build and removes partial files.
+    let mut build = Builder::<u64, u64>::initial(config.clone(), vec![]).unwrap();
+    let iter = entries.clone().into_iter().map(Ok);
+    match build.build_index(iter, NoBitmap, None) {
+        Err(Error::DuplicateKey(_, msg)) => println!("{}", msg),
+        Err(err) => panic!("unexpected error {}", err),
+        Ok(_) => panic!("duplicate key not detected"),
+    }
+    let files: Vec<fs::DirEntry> =
+        fs::read_dir(&dir).unwrap().map(|x| x.unwrap()).collect();
+    assert!(files.is_empty(), "{:?}", files);
+
+    // opt-out of strict mode.
+    config.set_strict_keys(false);
+    let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+    let iter = entries.clone().into_iter().map(Ok);
+    let index = build.build_index(iter, NoBitmap, None).unwrap();
+    assert_eq!(index.len(), entries.len());
+
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_robt_estimate_cost() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_estimate_cost {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_estimate_cost");
+    fs::remove_dir_all(&dir).ok();
+
+    let value_in_vlog = rng.gen::<bool>();
+    let mut indexes = vec![];
+    for (name, n) in [("small", 1_000), ("large", 100_000)].iter() {
+        let mdb = llrb::load_index::<u64, u64>(seed, *n, 0, 0, 0, None);
+        let mut config = Config::new(dir.as_os_str(), name);
+        config.set_blocksize(4096, 4096, 512).set_value_log(value_in_vlog);
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter().unwrap().map(Ok);
+        let index = build.build_index(iter, NoBitmap, None).unwrap();
+        let keys: Vec<u64> = mdb.iter().unwrap().map(|e| e.to_key()).collect();
+        indexes.push((index, keys));
+    }
+    let (mut large, large_keys) = indexes.pop().unwrap();
+    let (mut small, _) = indexes.pop().unwrap();
+
+    // get cost scales with index size.
+    let (small_cost, large_cost) = (small.estimate_get_cost(), large.estimate_get_cost());
+    println!("test_robt_estimate_cost get {:?} {:?}", small_cost, large_cost);
+    assert!(large_cost.block_reads > small_cost.block_reads);
+    assert!(small_cost.block_reads >= 1);
+    let vlog_reads = if value_in_vlog { 1 } else { 0 };
+    assert_eq!(small_cost.vlog_reads, vlog_reads);
+    assert_eq!(large_cost.vlog_reads, vlog_reads);
+
+    // estimate matches the m-blocks read from disk.
+    let key = large_keys[rng.gen::<usize>() % large_keys.len()];
+    large.get(&key).unwrap();
+    assert_eq!(large.to_mblock_reads(), large_cost.block_reads - 1);
+
+    // scan cost scales with range width.
+    let mut prev = IoCost::default();
+    let start = large_keys[0];
+    for width in [10, 1_000, 10_000, large_keys.len() - 1].iter() {
+        let end = large_keys[*width];
+        let cost = large.estimate_scan_cost(start..end).unwrap();
+        println!("test_robt_estimate_cost scan {} {:?}", width, cost);
+        assert!(cost.block_reads >= prev.block_reads, "{:?} {:?}", cost, prev);
+        assert!(cost.block_reads >= large_cost.block_reads);
+        assert_eq!(cost.vlog_reads, if value_in_vlog { *width } else { 0 });
+        prev = cost;
+    }
+    let cost = large.estimate_scan_cost(..).unwrap();
+    let narrow = large.estimate_scan_cost(start..large_keys[10]).unwrap();
+    assert!(cost.block_reads > narrow.block_reads);
+    assert!(cost.block_reads > small.estimate_scan_cost(..).unwrap().block_reads);
+
+    small.purge().unwrap();
+    large.purge().unwrap();
+}
+
+#[test]
+fn test_robt_descending() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_descending {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_descending");
+    fs::remove_dir_all(&dir).ok();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 20_000, 2_000, 1_000, 1_000, None);
+    let keys: Vec<u64> = mdb.iter().unwrap().map(|e| e.to_key()).collect();
+    let rkeys: Vec<u64> = keys.iter().rev().cloned().collect();
+
+    let mut config = Config::new(dir.as_os_str(), "descending");
+    config
+        .set_blocksize(4096, 4096, 512)
+        .set_value_log(rng.gen::<bool>())
+        .set_descending(true);
+    let mut index = {
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        // latest-first input.
+        let iter = mdb.reverse(..).unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+    assert!(index.to_stats().descending);
+    assert_eq!(index.len(), keys.len());
+
+    // reverse is the input order, iter yields ascending order.
+    let iter = index.reverse(..).unwrap();
+    assert_eq!(iter.size_hint(), (keys.len(), Some(keys.len())));
+    assert_eq!(iter.map(|e| e.unwrap().to_key()).collect::<Vec<u64>>(), rkeys);
+    let iter = index.iter(..).unwrap();
+    assert_eq!(iter.map(|e| e.unwrap().to_key()).collect::<Vec<u64>>(), keys);
+    let iter = index.iter_versions(..).unwrap();
+    for (entry, ref_entry) in iter.zip(mdb.iter_versions().unwrap()) {
+        assert_eq!(entry.unwrap(), ref_entry);
+    }
+
+    for _ in 0..100 {
+        let (a, b) = (rng.gen::<u64>(), rng.gen::<u64>());
+        let (a, b) = (cmp::min(a, b), cmp::max(a, b));
+        let refs: Vec<u64> =
+            keys.iter().filter(|k| a <= **k && **k < b).cloned().collect();
+
+        let iter = index.iter(a..b).unwrap();
+        assert_eq!(iter.map(|e| e.unwrap().to_key()).collect::<Vec<u64>>(), refs);
+        let iter = index.reverse(a..b).unwrap();
+        let mut items: Vec<u64> = iter.map(|e| e.unwrap().to_key()).collect();
+        items.reverse();
+        assert_eq!(items, refs);
+    }
+
+    for _ in 0..1_000 {
+        let key = keys[rng.gen::<usize>() % keys.len()];
+        assert_eq!(index.get(&key).unwrap(), mdb.get(&key).unwrap());
+        assert!(index.contains(&key).unwrap());
+    }
+    let missing = (0..).map(|_| rng.gen::<u64>()).find(|k| mdb.get(k).is_err()).unwrap();
+    assert!(matches!(index.get(&missing), Err(Error::NotFound(_, _))));
+
+    // operations walking the blocks in ascending order are not supported.
+    assert!(matches!(index.count_range(..), Err(Error::NotImplemented(_, _))));
+    assert!(matches!(index.iter_keys(..), Err(Error::NotImplemented(_, _))));
+    assert_eq!(index.validate().unwrap().n_count, keys.len() as u64);
+
+    // compact into ascending order.
+    let mut config = Config::new(dir.as_os_str(), "ascending");
+    config.set_blocksize(4096, 4096, 512);
+    let cutoff = dbs::Cutoff::new_lsm(Bound::Excluded(0));
+    let mut index = index.compact(config, NoBitmap, cutoff).unwrap();
+    assert!(!index.to_stats().descending);
+    let iter = index.iter(..).unwrap();
+    assert_eq!(iter.map(|e| e.unwrap().to_key()).collect::<Vec<u64>>(), keys);
+    assert_eq!(index.count_range(..).unwrap(), keys.len());
+
+    index.purge().unwrap();
+}
+
+#[test]
+fn test_robt_stream_vlog() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_robt_stream_vlog {}", seed);
+
+    let dir = std::env::temp_dir().join("test_robt_stream_vlog");
+    let archive_dir = std::env::temp_dir().join("test_robt_stream_vlog_archive");
+    fs::remove_dir_all(&dir).ok();
+    fs::remove_dir_all(&archive_dir).ok();
+    fs::create_dir_all(&archive_dir).unwrap();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 20_000, 5_000, 1_000, 1_000, None);
+    let index = {
+        let mut config = Config::new(dir.as_os_str(), "stream-vlog");
+        config.set_value_log(rng.gen::<bool>()).set_values_optional(true);
+        let mut build = Builder::<u64, u64>::initial(config, vec![]).unwrap();
+        let iter = mdb.iter_versions().unwrap().map(Ok);
+        build.build_index(iter, NoBitmap, None).unwrap()
+    };
+    let keys: Vec<u64> = mdb.iter().unwrap().map(|e| e.to_key()).collect();
+
+    // archive the value-log and delete the local copy.
+    let vlog_loc = index.to_vlog_location().unwrap();
+    let mut archive = vec![];
+    let n = index.stream_vlog(&mut archive).unwrap();
+    assert_eq!(n, fs::metadata(&vlog_loc).unwrap().len());
+    assert_eq!(n, archive.len() as u64);
+    fs::remove_file(&vlog_loc).unwrap();
+    index.close().unwrap();
+
+    let mut index =
+        Index::<u64, u64, NoBitmap>::open(dir.as_os_str(), "stream-vlog").unwrap();
+    assert!(index.stream_vlog(&mut vec![]).is_err());
+
+    // restore from the archive, values and deltas are read back as is.
+    let m = index.restore_vlog(&mut archive.as_slice(), archive_dir.as_os_str());
+    assert_eq!(m.unwrap(), n);
+    for _ in 0..1_000 {
+        let key = keys[rng.gen::<usize>() % keys.len()];
+        assert_eq!(index.get_versions(&key).unwrap(), mdb.get_versions(&key).unwrap());
+    }
+    let iter = index.iter_versions(..).unwrap();
+    for (entry, ref_entry) in iter.zip(mdb.iter_versions().unwrap()) {
+        assert_eq!(entry.unwrap(), ref_entry);
+    }
+
+    // clones read from the restored value-log.
+    let mut cloned = index.try_clone().unwrap();
+    let key = keys[rng.gen::<usize>() % keys.len()];
+    assert_eq!(cloned.get_versions(&key).unwrap(), mdb.get_versions(&key).unwrap());
+    let mut restored = vec![];
+    assert_eq!(cloned.stream_vlog(&mut restored).unwrap(), n);
+    assert!(restored == archive);
+
+    // restoring over an existing value-log shall fail.
+    let res = index.restore_vlog(&mut archive.as_slice(), archive_dir.as_os_str());
+    assert!(res.is_err());
+
+    mem::drop(cloned);
+    mem::drop(index);
+    fs::remove_dir_all(&dir).ok();
+    fs::remove_dir_all(&archive_dir).ok();
+}
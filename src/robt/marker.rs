@@ -1,5 +1,11 @@
 use lazy_static::lazy_static;
 
+/// Endianness marker, persisted in the meta-block trailer. All fixed width
+/// integers in index-file and vlog-file are persisted in big-endian, an
+/// index-file whose trailer does not carry this marker, as big-endian bytes,
+/// is rejected.
+pub const ENDIAN_MARKER: u64 = 0x0102_0304_0506_0708;
+
 lazy_static! {
     pub static ref ROOT_MARKER: Vec<u8> = {
         let marker = "அறம் செய விரும்பு";
@@ -21,11 +21,15 @@
 //! * Additionally, incoming iterator, to build index, can supply older
 //!   versions for value using the [Diff] mechanics.
 //! * Bloom filter can help optimize false lookups.
-//! * API `get()` operation, with bloom-filter support.
+//! * API `get()` operation, with bloom-filter support. Deleted keys are
+//!   returned as tombstone entries, `NotFound` error only for absent keys.
 //! * API `iter()` and `reverse()` operation for forward and reverse iteration.
 //! * API `iter_version()` and `reverse_version()` operation similar to
 //!   iter/reverse but also fetches older versions for a entry. Note that
 //!   iter/reverse do not fetch the older versions.
+//! * Index built from an empty iterator is a valid index, without a root
+//!   block. Lookups fail with `NotFound`, iterators are empty and range
+//!   counts are ZERO.
 //!
 //! **Value-log file**
 //!
@@ -136,6 +140,10 @@
 //! (when it is cloned), every index instance will keep an open
 //! file-descriptor for underlying file(s).
 //!
+//! _Parallel range iteration_. To process disjoint key ranges in parallel,
+//! use `par_ranges()` on Index. It returns an iterator for each range, each
+//! owning a clone of the index, that can be moved to worker threads.
+//!
 //! **Simple Key-Value index**
 //!
 //! `robt` indexes are parametrized over key-type, value-type, delta-type,
@@ -206,7 +214,10 @@
 use crate::dbs::{self, Diff};
 
 mod build;
+mod codec;
 mod config;
+mod delta;
+mod diff;
 mod entry;
 mod files;
 mod flush;
@@ -214,17 +225,26 @@ mod index;
 mod lsm;
 mod marker;
 mod reader;
+mod repair;
 mod scans;
+mod sort;
 mod vlog;
 
+pub use codec::Codec;
 pub use config::{
-    to_index_location, to_vlog_location, Config, Stats, FLUSH_QUEUE_SIZE, MBLOCKSIZE,
-    VBLOCKSIZE, ZBLOCKSIZE,
+    to_index_location, to_vlog_location, BuildFsync, Config, Stats, FLUSH_QUEUE_SIZE,
+    MBLOCKSIZE, VBLOCKSIZE, ZBLOCKSIZE,
+};
+pub use delta::DeltaIndex;
+pub use diff::Change;
+pub use files::{validate_name, IndexFileName, VlogFileName, RESERVED_TOKEN};
+pub use index::{
+    list_indexes, merge, open_all, BaseRef, Builder, CancelToken, Comparator, Cursor,
+    ExplicitDeltas, Index, IoCost, OwnedRangeIter, PrefixFn, Provenance, PAR_BATCH,
 };
-pub use files::{IndexFileName, VlogFileName};
-pub use index::{Builder, Index};
-pub use reader::Iter;
+pub use reader::{Iter, IterKeys, IterLocated};
+pub use repair::repair;
 
 use entry::Entry;
 use flush::Flusher;
-use marker::ROOT_MARKER;
+use marker::{ENDIAN_MARKER, ROOT_MARKER};
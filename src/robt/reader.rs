@@ -4,17 +4,18 @@ use fs2::FileExt;
 use std::{
     borrow::Borrow,
     cmp,
+    collections::{HashMap, VecDeque},
     convert::TryFrom,
     fmt, fs,
-    io::{self, Read, Seek},
     ops::{Bound, RangeBounds},
-    sync::Arc,
+    sync::{mpsc, Arc},
+    thread,
 };
 
 use crate::{
-    dbs, read_file,
-    robt::{self, Stats},
-    util, Error, Result,
+    dbs,
+    robt::{self, Codec, Comparator, Stats},
+    util, Error, Result, ResultExt,
 };
 
 // TODO: check for panic!()
@@ -37,7 +38,19 @@ where
 {
     pub m_blocksize: usize,
     pub z_blocksize: usize,
+    pub codec: Codec,
     pub root: Arc<Vec<robt::Entry<K, V>>>,
+    // custom key ordering, if None keys are ordered by Ord.
+    pub cmp: Option<Comparator<K>>,
+    // keys are laid out in descending order, refer to Config::set_descending.
+    pub descending: bool,
+    // n-blocks, prefetch z-blocks for forward iteration.
+    pub readahead: Option<usize>,
+    // m-blocks, from the top levels below root, decoded once and kept resident,
+    // indexed by their file-position. Refer to Config::set_pin_top_levels.
+    pub pinned: Arc<HashMap<u64, Arc<Vec<robt::Entry<K, V>>>>>,
+    // number of m-blocks read from index file, excluding pinned blocks.
+    pub n_mreads: usize,
 
     pub index: fs::File,
     pub vlog: Option<fs::File>,
@@ -73,14 +86,13 @@ where
         mut index: fs::File,
         vlog: Option<fs::File>,
     ) -> Result<Self> {
-        let m_blocksize = stats.m_blocksize;
+        let (m_blocksize, codec) = (stats.m_blocksize, stats.block_compression);
         let root: Vec<robt::Entry<K, V>> = match root {
             None => vec![],
             Some(root) => {
-                let fpos = io::SeekFrom::Start(root);
-                let block = read_file!(&mut index, fpos, m_blocksize, "read block")?;
+                let block = codec.read_block(&mut index, root, m_blocksize)?;
                 // println!("read root fpos:{:?} len:{}", fpos, block.len());
-                util::from_cbor_bytes(&block)?.0
+                util::from_cbor_bytes(&block).at_fpos(root)?.0
             }
         };
         // println!("read root:{}", root.len());
@@ -95,7 +107,13 @@ where
         Ok(Reader {
             m_blocksize: stats.m_blocksize,
             z_blocksize: stats.z_blocksize,
+            codec,
             root: Arc::new(root),
+            cmp: None,
+            descending: stats.descending,
+            readahead: None,
+            pinned: Arc::new(HashMap::new()),
+            n_mreads: 0,
 
             index,
             vlog,
@@ -106,6 +124,72 @@ where
         Arc::clone(&self.root)
     }
 
+    // decode m-blocks from `levels` number of levels below the root block and
+    // keep them resident, refer to Config::set_pin_top_levels.
+    pub fn pin_top_levels(&mut self, levels: usize) -> Result<()> {
+        let mut pinned = HashMap::new();
+        let mut fposs: Vec<u64> =
+            self.root.iter().filter_map(|e| e.to_mblock_fpos()).collect();
+        for _ in 0..levels {
+            let mut next = vec![];
+            for fpos in fposs.into_iter() {
+                let block =
+                    self.codec.read_block(&mut self.index, fpos, self.m_blocksize)?;
+                let block: Vec<robt::Entry<K, V>> =
+                    util::from_cbor_bytes(&block).at_fpos(fpos)?.0;
+                next.extend(block.iter().filter_map(|e| e.to_mblock_fpos()));
+                pinned.insert(fpos, Arc::new(block));
+            }
+            fposs = next;
+        }
+        self.pinned = Arc::new(pinned);
+
+        Ok(())
+    }
+
+    // read the child block pointed to by an intermediate `entry`.
+    fn read_child(
+        &mut self,
+        entry: &robt::Entry<K, V>,
+    ) -> Result<Arc<Vec<robt::Entry<K, V>>>> {
+        match entry {
+            robt::Entry::MM { fpos, .. } => self.read_mblock(*fpos),
+            robt::Entry::MZ { fpos, .. } => {
+                let block =
+                    self.codec.read_block(&mut self.index, *fpos, self.z_blocksize)?;
+                let block = util::from_cbor_bytes::<Vec<robt::Entry<K, V>>>(&block)
+                    .at_fpos(*fpos)?;
+                Ok(Arc::new(block.0))
+            }
+            robt::Entry::ZZ { .. } => unreachable!(),
+        }
+    }
+
+    // read m-block at `fpos`, from the pinned blocks if it is resident.
+    pub fn read_mblock(&mut self, fpos: u64) -> Result<Arc<Vec<robt::Entry<K, V>>>> {
+        match self.pinned.get(&fpos) {
+            Some(block) => Ok(Arc::clone(block)),
+            None => {
+                self.n_mreads += 1;
+                let block =
+                    self.codec.read_block(&mut self.index, fpos, self.m_blocksize)?;
+                let block = util::from_cbor_bytes::<Vec<robt::Entry<K, V>>>(&block)
+                    .at_fpos(fpos)?;
+                Ok(Arc::new(block.0))
+            }
+        }
+    }
+
+    // swap in `vlog` as the value-log file, older file, if any, is unlocked
+    // and closed.
+    pub fn set_vlog(&mut self, vlog: fs::File) -> Result<()> {
+        err_at!(IOError, vlog.lock_shared())?;
+        if let Some(old) = self.vlog.replace(vlog) {
+            err_at!(IOError, old.unlock())?
+        }
+        Ok(())
+    }
+
     pub fn footprint(&self) -> Result<usize> {
         let mut n = err_at!(IOError, self.index.metadata())?.len();
         n += match &self.vlog {
@@ -128,45 +212,200 @@ where
         K: Clone + Borrow<Q>,
         Q: Ord + ?Sized,
     {
-        let m_blocksize = self.m_blocksize;
+        let desc = self.descending;
+        self.get_by(|key: &K| key_order(Borrow::<Q>::borrow(key), ukey, desc), versions)
+    }
+
+    // `f` shall compare the index key with the key being looked up.
+    pub fn get_by<F>(&mut self, f: F, versions: bool) -> Result<robt::Entry<K, V>>
+    where
+        K: Clone,
+        F: Fn(&K) -> cmp::Ordering,
+    {
         let z_blocksize = self.z_blocksize;
-        let fd = &mut self.index;
+        let codec = self.codec;
 
         let mut es = Arc::clone(&self.root);
         loop {
-            let off = match es.binary_search_by(|e| e.borrow_key().cmp(ukey)) {
+            let off = match es.binary_search_by(|e| f(e.borrow_key())) {
                 Ok(off) => off,
                 Err(off) if off == 0 => break err_at!(NotFound, msg: "missing key"),
                 Err(off) => off - 1,
             };
-            es = match es[off].clone() {
+            // blocks read from disk are not shared, move the entry out of the
+            // block instead of cloning its key and value.
+            let entry = match Arc::get_mut(&mut es) {
+                Some(block) => block.swap_remove(off),
+                None => es[off].clone(),
+            };
+            es = match entry {
+                robt::Entry::MM { fpos, .. } => self.read_mblock(fpos)?,
+                robt::Entry::MZ { fpos, .. } => {
+                    let block = codec.read_block(&mut self.index, fpos, z_blocksize)?;
+                    let block = util::from_cbor_bytes::<Vec<robt::Entry<K, V>>>(&block)
+                        .at_fpos(fpos)?;
+                    Arc::new(block.0)
+                }
+                entry @ robt::Entry::ZZ { .. } if f(entry.as_key()).is_eq() => {
+                    break self.to_native(entry, versions);
+                }
+                _ => break err_at!(NotFound, msg: "missing key"),
+            }
+        }
+    }
+
+    // descend the tree, using `f` to compare index key with the key being
+    // looked up, and return the z-block that may contain the key. Return None
+    // if key is less than the first key in the index.
+    pub fn zblock_by<F>(&mut self, f: F) -> Result<Option<Vec<robt::Entry<K, V>>>>
+    where
+        F: Fn(&K) -> cmp::Ordering,
+    {
+        let codec = self.codec;
+
+        let mut es = Arc::clone(&self.root);
+        loop {
+            let off = match es.binary_search_by(|e| f(e.as_key())) {
+                Ok(off) => off,
+                Err(off) if off == 0 => break Ok(None),
+                Err(off) => off - 1,
+            };
+            let block = match &es[off] {
                 robt::Entry::MM { fpos, .. } => {
-                    let fpos = io::SeekFrom::Start(fpos);
-                    let block = read_file!(fd, fpos, m_blocksize, "read mm-block")?;
-                    Arc::new(util::from_cbor_bytes::<Vec<robt::Entry<K, V>>>(&block)?.0)
+                    es = self.read_mblock(*fpos)?;
+                    continue;
                 }
                 robt::Entry::MZ { fpos, .. } => {
-                    let fpos = io::SeekFrom::Start(fpos);
-                    let block = read_file!(fd, fpos, z_blocksize, "read mz-block")?;
-                    Arc::new(util::from_cbor_bytes::<Vec<robt::Entry<K, V>>>(&block)?.0)
+                    codec.read_block(&mut self.index, *fpos, self.z_blocksize)?
                 }
-                robt::Entry::ZZ { key, value, deltas } if key.borrow() == ukey => {
-                    let deltas = if versions { deltas } else { Vec::default() };
-                    let mut entry = robt::Entry::ZZ { key, value, deltas };
-                    let entry = match &mut self.vlog {
-                        Some(fd) => entry.into_native(fd, versions)?,
-                        None => {
-                            entry.drain_deltas();
-                            entry
-                        }
-                    };
-                    break Ok(entry);
+                robt::Entry::ZZ { .. } => {
+                    err_at!(Fatal, msg: "robt-zz entry in m-block")?
                 }
-                _ => break err_at!(NotFound, msg: "missing key"),
+            };
+            let block = util::from_cbor_bytes::<Vec<robt::Entry<K, V>>>(&block)?.0;
+            match block.first() {
+                Some(_) => break Ok(Some(block)),
+                None => break Ok(None),
+            }
+        }
+    }
+
+    // return whether there is an entry for key, as compared by `f`. Only the
+    // index blocks are read, value-log is not accessed.
+    pub fn contains_by<F>(&mut self, f: F) -> Result<bool>
+    where
+        F: Fn(&K) -> cmp::Ordering,
+    {
+        match self.zblock_by(&f)? {
+            Some(block) => Ok(block.binary_search_by(|e| f(e.as_key())).is_ok()),
+            None => Ok(false),
+        }
+    }
+
+    // fetch value, and deltas if `versions` is true, for a leaf entry.
+    pub fn to_native(
+        &mut self,
+        entry: robt::Entry<K, V>,
+        versions: bool,
+    ) -> Result<robt::Entry<K, V>> {
+        match entry {
+            robt::Entry::ZZ { key, value, deltas } => {
+                let deltas = if versions { deltas } else { Vec::default() };
+                let mut entry = robt::Entry::ZZ { key, value, deltas };
+                let entry = match &mut self.vlog {
+                    Some(fd) => entry.into_native(fd, versions)?,
+                    None if entry.is_reference(versions) => {
+                        err_at!(IOError, msg: "vlog unavailable")?
+                    }
+                    None => {
+                        entry.drain_deltas();
+                        entry
+                    }
+                };
+                Ok(entry)
             }
+            _ => err_at!(Fatal, msg: "robt entry not a leaf-node"),
         }
     }
 
+    pub fn count_range<R, Q>(&mut self, range: &R) -> Result<usize>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+        R: RangeBounds<Q>,
+    {
+        let root = Arc::clone(&self.root);
+        self.count_block(&root, None, range)
+    }
+
+    // count entries under `block`, whose keys fall within [first-key, hi).
+    // Children completely within range are counted from their m-entry, only
+    // children straddling the range boundaries are read.
+    fn count_block<R, Q>(
+        &mut self,
+        block: &[robt::Entry<K, V>],
+        hi: Option<&Q>,
+        range: &R,
+    ) -> Result<usize>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+        R: RangeBounds<Q>,
+    {
+        let n = match block.first().map(|e| e.is_zblock()) {
+            None => 0,
+            Some(true) => {
+                block.iter().filter(|e| range.contains(e.borrow_key::<Q>())).count()
+            }
+            Some(false) => {
+                let mut n = 0;
+                for (i, entry) in block.iter().enumerate() {
+                    let lo = entry.borrow_key();
+                    let hi = block.get(i + 1).map(|e| e.borrow_key()).or(hi);
+                    if is_after_range(lo, range) {
+                        break;
+                    } else if is_before_range(hi, range) {
+                        continue;
+                    } else if is_within_range(lo, hi, range) {
+                        n += err_at!(FailConvert, usize::try_from(entry.to_count()))?;
+                    } else {
+                        let block = self.read_child(entry)?;
+                        n += self.count_block(&block, hi, range)?;
+                    }
+                }
+                n
+            }
+        };
+
+        Ok(n)
+    }
+
+    // call `callb` for each key in the sub-tree under `entry`.
+    pub fn walk_keys<F>(&mut self, entry: &robt::Entry<K, V>, callb: &mut F) -> Result<()>
+    where
+        F: FnMut(&K),
+    {
+        let codec = self.codec;
+        let block = match entry {
+            robt::Entry::MM { fpos, .. } => {
+                codec.read_block(&mut self.index, *fpos, self.m_blocksize)?
+            }
+            robt::Entry::MZ { fpos, .. } => {
+                codec.read_block(&mut self.index, *fpos, self.z_blocksize)?
+            }
+            robt::Entry::ZZ { key, .. } => {
+                callb(key);
+                return Ok(());
+            }
+        };
+
+        for entry in util::from_cbor_bytes::<Vec<robt::Entry<K, V>>>(&block)?.0.iter() {
+            self.walk_keys(entry, callb)?
+        }
+
+        Ok(())
+    }
+
     pub fn iter<R, Q>(
         &mut self,
         range: R,
@@ -178,33 +417,32 @@ where
         Q: ?Sized + Ord + ToOwned<Owned = K>,
         R: RangeBounds<Q>,
     {
+        let start = to_owned_bound(range.start_bound());
+        let end = to_owned_bound(range.end_bound());
+
+        let (comparator, desc) = (self.cmp.clone(), self.descending);
         let (stack, bound) = if reverse {
-            let stack = self.rwd_stack(range.end_bound(), Arc::clone(&self.root))?;
-            let bound: Bound<K> = match range.start_bound() {
-                Bound::Unbounded => Bound::Unbounded,
-                Bound::Included(q) => Bound::Included(q.to_owned()),
-                Bound::Excluded(q) => Bound::Excluded(q.to_owned()),
-            };
-            (stack, bound)
+            let f =
+                |key: &K| bound_cmp(&comparator, desc, key, &end, cmp::Ordering::Less);
+            let stack = self.rwd_stack_by(&f, Arc::clone(&self.root))?;
+            (stack, start.clone())
         } else {
-            let stack = self.fwd_stack(range.start_bound(), Arc::clone(&self.root))?;
-            // println!("iter stack:{:?}", stack.len());
-            let bound: Bound<K> = match range.end_bound() {
-                Bound::Unbounded => Bound::Unbounded,
-                Bound::Included(q) => Bound::Included(q.to_owned()),
-                Bound::Excluded(q) => Bound::Excluded(q.to_owned()),
+            let f = |key: &K| {
+                bound_cmp(&comparator, desc, key, &start, cmp::Ordering::Greater)
             };
-            (stack, bound)
+            let stack = self.fwd_stack_by(&f, Arc::clone(&self.root))?;
+            // println!("iter stack:{:?}", stack.len());
+            (stack, end.clone())
         };
         let mut iter = Iter::new(self, bound, stack, reverse, versions);
 
         while let Some(item) = iter.next() {
             match item {
                 Ok(entry) if reverse => {
-                    let key = entry.borrow_key();
-                    match range.end_bound() {
-                        Bound::Included(ekey) if key.gt(ekey) => (),
-                        Bound::Excluded(ekey) if key.ge(ekey) => (),
+                    let ord = |ekey: &K| compare(&comparator, desc, &entry.key, ekey);
+                    match &end {
+                        Bound::Included(ekey) if ord(ekey).is_gt() => (),
+                        Bound::Excluded(ekey) if ord(ekey).is_ge() => (),
                         _ => {
                             iter.push(entry);
                             break;
@@ -212,10 +450,10 @@ where
                     }
                 }
                 Ok(entry) => {
-                    let key = entry.borrow_key();
-                    match range.start_bound() {
-                        Bound::Included(skey) if key.lt(skey) => (),
-                        Bound::Excluded(skey) if key.le(skey) => (),
+                    let ord = |skey: &K| compare(&comparator, desc, &entry.key, skey);
+                    match &start {
+                        Bound::Included(skey) if ord(skey).is_lt() => (),
+                        Bound::Excluded(skey) if ord(skey).is_le() => (),
                         _ => {
                             iter.push(entry);
                             break;
@@ -229,6 +467,53 @@ where
         Ok(iter)
     }
 
+    // full table iteration, starting from root block, so that every z-block
+    // is loaded by the iterator and its file-position is known.
+    pub fn iter_located(&mut self, versions: bool) -> Result<IterLocated<K, V>>
+    where
+        K: Clone,
+    {
+        let stack = match self.root.is_empty() {
+            true => vec![],
+            false => vec![self.root.to_vec()],
+        };
+        let iter = Iter::new(self, Bound::Unbounded, stack, false, versions);
+        Ok(IterLocated { iter })
+    }
+
+    pub fn iter_keys<R, Q>(&mut self, range: R) -> Result<IterKeys<K, V>>
+    where
+        K: Clone + Ord + Borrow<Q>,
+        Q: ?Sized + Ord + ToOwned<Owned = K>,
+        R: RangeBounds<Q>,
+    {
+        let start = to_owned_bound(range.start_bound());
+        let end = to_owned_bound(range.end_bound());
+
+        let (comparator, desc) = (self.cmp.clone(), self.descending);
+        let f =
+            |key: &K| bound_cmp(&comparator, desc, key, &start, cmp::Ordering::Greater);
+        let stack = self.fwd_stack_by(&f, Arc::clone(&self.root))?;
+        let mut iter = Iter::new(self, end, stack, false, false);
+
+        let key = loop {
+            match iter.next_key() {
+                Some(Ok(key)) => {
+                    let ord = |skey: &K| compare(&comparator, desc, &key, skey);
+                    match &start {
+                        Bound::Included(skey) if ord(skey).is_lt() => (),
+                        Bound::Excluded(skey) if ord(skey).is_le() => (),
+                        _ => break Some(key),
+                    }
+                }
+                Some(Err(err)) => return Err(err),
+                None => break None,
+            }
+        };
+
+        Ok(IterKeys { iter, key })
+    }
+
     pub fn fwd_stack<Q>(
         &mut self,
         sk: Bound<&Q>,
@@ -237,17 +522,30 @@ where
     where
         K: Clone + Borrow<Q>,
         Q: ?Sized + Ord,
+    {
+        self.fwd_stack_by(&|key: &K| fcmp(key.borrow(), sk), block)
+    }
+
+    // `f` shall compare the index key with the start bound.
+    fn fwd_stack_by<F>(
+        &mut self,
+        f: &F,
+        block: Arc<Vec<robt::Entry<K, V>>>,
+    ) -> Result<Vec<Vec<robt::Entry<K, V>>>>
+    where
+        K: Clone,
+        F: Fn(&K) -> cmp::Ordering,
     {
         // println!("fwd_stack block_len:{}", block.len());
         let (entry, rem) = match block.first().map(|e| e.is_zblock()) {
-            Some(false) => match block.binary_search_by(|e| fcmp(e.borrow_key(), sk)) {
+            Some(false) => match block.binary_search_by(|e| f(e.borrow_key())) {
                 Ok(off) => (block[off].clone(), block[off + 1..].to_vec()),
                 Err(off) => {
                     let off = off.saturating_sub(1);
                     (block[off].clone(), block[off + 1..].to_vec())
                 }
             },
-            Some(true) => match block.binary_search_by(|e| fcmp(e.borrow_key(), sk)) {
+            Some(true) => match block.binary_search_by(|e| f(e.borrow_key())) {
                 Ok(off) | Err(off) => {
                     return Ok(vec![block[off..].to_vec()]);
                 }
@@ -255,47 +553,31 @@ where
             None => return Ok(vec![]),
         };
 
-        let fd = &mut self.index;
-        let m_blocksize = self.m_blocksize;
-        let z_blocksize = self.z_blocksize;
-
-        let block = match entry {
-            robt::Entry::MM { fpos, .. } => {
-                // println!("mm-entry fpos:{}", fpos);
-                read_file!(fd, io::SeekFrom::Start(fpos), m_blocksize, "read mm-block")?
-            }
-            robt::Entry::MZ { fpos, .. } => {
-                // println!("mz-entry fpos:{}", fpos);
-                read_file!(fd, io::SeekFrom::Start(fpos), z_blocksize, "read mz-block")?
-            }
-            _ => unreachable!(),
-        };
-        // println!("read-block len:{} start..:{:?}", block.len(), &block[..32]);
-
-        let block = Arc::new(util::from_cbor_bytes::<Vec<robt::Entry<K, V>>>(&block)?.0);
-        let mut stack = self.fwd_stack(sk, block)?;
+        let block = self.read_child(&entry)?;
+        let mut stack = self.fwd_stack_by(f, block)?;
         stack.insert(0, rem);
         Ok(stack)
     }
 
-    fn rwd_stack<Q>(
+    // `f` shall compare the index key with the end bound.
+    fn rwd_stack_by<F>(
         &mut self,
-        ek: Bound<&Q>,
+        f: &F,
         block: Arc<Vec<robt::Entry<K, V>>>,
     ) -> Result<Vec<Vec<robt::Entry<K, V>>>>
     where
-        K: Clone + Borrow<Q>,
-        Q: ?Sized + Ord,
+        K: Clone,
+        F: Fn(&K) -> cmp::Ordering,
     {
         let (entry, mut rem) = match block.first().map(|e| e.is_zblock()) {
-            Some(false) => match block.binary_search_by(|e| rcmp(e.borrow_key(), ek)) {
+            Some(false) => match block.binary_search_by(|e| f(e.borrow_key())) {
                 Ok(off) => (block[off].clone(), block[..off].to_vec()),
                 Err(off) => {
                     let off = off.saturating_sub(1);
                     (block[off].clone(), block[..off].to_vec())
                 }
             },
-            Some(true) => match block.binary_search_by(|e| rcmp(e.borrow_key(), ek)) {
+            Some(true) => match block.binary_search_by(|e| f(e.borrow_key())) {
                 Ok(off) | Err(off) => {
                     let off = cmp::min(off + 1, block.len());
                     let mut rem = block[..off].to_vec();
@@ -307,22 +589,8 @@ where
         };
         rem.reverse();
 
-        let fd = &mut self.index;
-        let m_blocksize = self.m_blocksize;
-        let z_blocksize = self.z_blocksize;
-
-        let block = match entry {
-            robt::Entry::MM { fpos, .. } => {
-                read_file!(fd, io::SeekFrom::Start(fpos), m_blocksize, "read mm-block")?
-            }
-            robt::Entry::MZ { fpos, .. } => {
-                read_file!(fd, io::SeekFrom::Start(fpos), z_blocksize, "read mz-block")?
-            }
-            _ => unreachable!(),
-        };
-
-        let block = Arc::new(util::from_cbor_bytes::<Vec<robt::Entry<K, V>>>(&block)?.0);
-        let mut stack = self.rwd_stack(ek, block)?;
+        let block = self.read_child(&entry)?;
+        let mut stack = self.rwd_stack_by(f, block)?;
         stack.insert(0, rem);
         Ok(stack)
     }
@@ -350,6 +618,12 @@ where
     versions: bool,
     entry: Option<dbs::Entry<K, V>>,
     bound: Bound<K>,
+    prefetch: Option<Prefetch>,
+    // file-position of the last z-block loaded by this iterator.
+    zfpos: Option<u64>,
+    // size_hint, lower and upper bound on the number of entries yet to be
+    // iterated.
+    remaining: (usize, Option<usize>),
 }
 
 impl<'a, K, V> Iter<'a, K, V>
@@ -363,6 +637,17 @@ where
         reverse: bool,
         versions: bool,
     ) -> Self {
+        // prefetch shares the index-file via positional reads, which are
+        // supported only on unix. Fallback to reading z-blocks in-line, if
+        // the index-file could not be cloned.
+        let prefetch = match r.readahead {
+            Some(n) if !reverse && cfg!(unix) => match r.index.try_clone() {
+                Ok(fd) => Some(Prefetch::new(fd, n, r.codec, r.z_blocksize)),
+                Err(_) => None,
+            },
+            _ => None,
+        };
+
         Iter {
             reader: r,
             stack,
@@ -370,6 +655,9 @@ where
             versions,
             entry: None,
             bound,
+            prefetch,
+            zfpos: None,
+            remaining: (0, None),
         }
     }
 
@@ -377,33 +665,48 @@ where
         self.entry = Some(entry);
     }
 
+    /// Set the bounds on number of entries yet to be iterated, used as
+    /// `size_hint`.
+    pub(crate) fn set_remaining(&mut self, remaining: (usize, Option<usize>)) {
+        self.remaining = remaining;
+    }
+
     fn till(&mut self, e: dbs::Entry<K, V>) -> Option<Result<dbs::Entry<K, V>>>
     where
         K: Ord,
     {
-        let key = &e.key;
+        match self.is_within(&e.key) {
+            true => Some(Ok(e)),
+            false => None,
+        }
+    }
+
+    // check whether `key` is within the iterator's bound, if not, the
+    // iterator is drained.
+    fn is_within(&mut self, key: &K) -> bool
+    where
+        K: Ord,
+    {
+        let desc = self.reader.descending;
+        let ord = |till: &K| compare(&self.reader.cmp, desc, key, till);
 
-        if self.reverse {
+        let ok = if self.reverse {
             match &self.bound {
-                Bound::Unbounded => Some(Ok(e)),
-                Bound::Included(till) if key.ge(till) => Some(Ok(e)),
-                Bound::Excluded(till) if key.gt(till) => Some(Ok(e)),
-                _ => {
-                    self.stack.drain(..);
-                    None
-                }
+                Bound::Unbounded => true,
+                Bound::Included(till) => ord(till).is_ge(),
+                Bound::Excluded(till) => ord(till).is_gt(),
             }
         } else {
             match &self.bound {
-                Bound::Unbounded => Some(Ok(e)),
-                Bound::Included(till) if key.le(till) => Some(Ok(e)),
-                Bound::Excluded(till) if key.lt(till) => Some(Ok(e)),
-                _ => {
-                    self.stack.drain(..);
-                    None
-                }
+                Bound::Unbounded => true,
+                Bound::Included(till) => ord(till).is_le(),
+                Bound::Excluded(till) => ord(till).is_lt(),
             }
+        };
+        if !ok {
+            self.stack.drain(..);
         }
+        ok
     }
 
     fn fetchzz(&mut self, mut entry: robt::Entry<K, V>) -> Result<robt::Entry<K, V>>
@@ -417,6 +720,9 @@ where
                 entry.drain_deltas();
                 entry.into_native(fd, self.versions)
             }
+            None if entry.is_reference(self.versions) => {
+                err_at!(IOError, msg: "vlog unavailable")
+            }
             None => {
                 entry.drain_deltas();
                 Ok(entry)
@@ -425,65 +731,94 @@ where
     }
 }
 
-impl<'a, K, V> Iterator for Iter<'a, K, V>
+impl<'a, K, V> Iter<'a, K, V>
 where
     K: Ord + FromCbor,
     V: dbs::Diff + FromCbor,
     <V as dbs::Diff>::Delta: FromCbor,
 {
-    type Item = Result<dbs::Entry<K, V>>;
-
-    fn next(&mut self) -> Option<Self::Item> {
+    fn next_entry(&mut self) -> Option<Result<dbs::Entry<K, V>>> {
         if let Some(entry) = self.entry.take() {
             return Some(Ok(entry));
         }
 
+        let entry = match self.next_leaf()? {
+            Ok(entry) => iter_result!(self.fetchzz(entry)),
+            Err(err) => return Some(Err(err)),
+        };
+        match dbs::Entry::try_from(entry) {
+            Ok(entry) => self.till(entry),
+            err => Some(err),
+        }
+    }
+
+    // return the key of next leaf entry, without reading its value and
+    // deltas from value-log.
+    fn next_key(&mut self) -> Option<Result<K>> {
+        let key = match self.next_leaf()? {
+            Ok(robt::Entry::ZZ { key, .. }) => key,
+            Ok(_) => return Some(err_at!(Fatal, msg: "robt entry not a leaf-node")),
+            Err(err) => return Some(Err(err)),
+        };
+        match self.is_within(&key) {
+            true => Some(Ok(key)),
+            false => None,
+        }
+    }
+
+    // walk the stack and return the next leaf entry, as is, in its
+    // serialized form.
+    fn next_leaf(&mut self) -> Option<Result<robt::Entry<K, V>>> {
         let fd = &mut self.reader.index;
         let m_blocksize = self.reader.m_blocksize;
         let z_blocksize = self.reader.z_blocksize;
+        let codec = self.reader.codec;
 
         match self.stack.pop() {
-            Some(block) if block.is_empty() => self.next(),
+            Some(block) if block.is_empty() => self.next_leaf(),
             Some(mut block) => match block.remove(0) {
                 entry @ robt::Entry::ZZ { .. } => {
                     self.stack.push(block);
-                    let entry = iter_result!(self.fetchzz(entry));
-                    match dbs::Entry::try_from(entry) {
-                        Ok(entry) => self.till(entry),
-                        err => Some(err),
-                    }
+                    Some(Ok(entry))
                 }
                 robt::Entry::MM { fpos, .. } => {
                     self.stack.push(block);
 
+                    // iterator consumes the entries, hence read the m-block
+                    // from disk, even if it is pinned.
+                    self.reader.n_mreads += 1;
                     let mut entries =
                         iter_result!(|| -> Result<Vec<robt::Entry<K, V>>> {
-                            let fpos = io::SeekFrom::Start(fpos);
-                            let block =
-                                read_file!(fd, fpos, m_blocksize, "read mm-block")?;
-                            Ok(util::from_cbor_bytes(&block)?.0)
+                            let block = codec.read_block(fd, fpos, m_blocksize)?;
+                            Ok(util::from_cbor_bytes(&block).at_fpos(fpos)?.0)
                         }());
                     if self.reverse {
                         entries.reverse();
                     }
                     self.stack.push(entries);
-                    self.next()
+                    self.next_leaf()
                 }
                 robt::Entry::MZ { fpos, .. } => {
+                    let prefetched = match self.prefetch.as_mut() {
+                        Some(prefetch) => prefetch.read(fpos, &block),
+                        None => None,
+                    };
                     self.stack.push(block);
 
                     let mut entries =
                         iter_result!(|| -> Result<Vec<robt::Entry<K, V>>> {
-                            let fpos = io::SeekFrom::Start(fpos);
-                            let block =
-                                read_file!(fd, fpos, z_blocksize, "read mm-block")?;
-                            Ok(util::from_cbor_bytes(&block)?.0)
+                            let block = match prefetched {
+                                Some(res) => res?,
+                                None => codec.read_block(fd, fpos, z_blocksize)?,
+                            };
+                            Ok(util::from_cbor_bytes(&block).at_fpos(fpos)?.0)
                         }());
                     if self.reverse {
                         entries.reverse();
                     }
                     self.stack.push(entries);
-                    self.next()
+                    self.zfpos = Some(fpos);
+                    self.next_leaf()
                 }
             },
             None => None,
@@ -491,6 +826,168 @@ where
     }
 }
 
+impl<'a, K, V> Iterator for Iter<'a, K, V>
+where
+    K: Ord + FromCbor,
+    V: dbs::Diff + FromCbor,
+    <V as dbs::Diff>::Delta: FromCbor,
+{
+    type Item = Result<dbs::Entry<K, V>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.next_entry();
+        let (lower, upper) = self.remaining;
+        self.remaining = match &item {
+            Some(_) => (lower.saturating_sub(1), upper.map(|n| n.saturating_sub(1))),
+            None => (0, upper.map(|_| 0)),
+        };
+        item
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.remaining
+    }
+}
+
+/// Iterator type, yielding entries along with the file-position of the
+/// z-block they are read from, refer to [Index::iter_located].
+///
+/// [Index::iter_located]: crate::robt::Index::iter_located
+pub struct IterLocated<'a, K, V>
+where
+    V: dbs::Diff,
+{
+    iter: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for IterLocated<'a, K, V>
+where
+    K: Ord + FromCbor,
+    V: dbs::Diff + FromCbor,
+    <V as dbs::Diff>::Delta: FromCbor,
+{
+    type Item = Result<(dbs::Entry<K, V>, u64)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.iter.next()? {
+            Ok(entry) => match self.iter.zfpos {
+                Some(zfpos) => Some(Ok((entry, zfpos))),
+                None => Some(err_at!(Fatal, msg: "entry without z-block")),
+            },
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+/// Iterator type, yielding only keys, without reading values and deltas from
+/// value-log, refer to [Index::iter_keys].
+///
+/// [Index::iter_keys]: crate::robt::Index::iter_keys
+pub struct IterKeys<'a, K, V>
+where
+    V: dbs::Diff,
+{
+    iter: Iter<'a, K, V>,
+    key: Option<K>,
+}
+
+impl<'a, K, V> Iterator for IterKeys<'a, K, V>
+where
+    K: Ord + FromCbor,
+    V: dbs::Diff + FromCbor,
+    <V as dbs::Diff>::Delta: FromCbor,
+{
+    type Item = Result<K>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.key.take() {
+            Some(key) => Some(Ok(key)),
+            None => self.iter.next_key(),
+        }
+    }
+}
+
+// Prefetch z-blocks in a background thread, using a handle cloned from the
+// reader's index-file, so that disk reads overlap with the consumer
+// processing the current block.
+struct Prefetch {
+    n: usize,
+    tx: Option<mpsc::Sender<u64>>,
+    rx: mpsc::Receiver<(u64, Result<Vec<u8>>)>,
+    pending: VecDeque<u64>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl Drop for Prefetch {
+    fn drop(&mut self) {
+        self.tx.take();
+        if let Some(handle) = self.handle.take() {
+            handle.join().ok();
+        }
+    }
+}
+
+impl Prefetch {
+    fn new(fd: fs::File, n: usize, codec: Codec, z_blocksize: usize) -> Self {
+        let (tx, req_rx) = mpsc::channel::<u64>();
+        let (res_tx, rx) = mpsc::channel();
+
+        let handle = thread::spawn(move || {
+            for fpos in req_rx {
+                let res = codec.read_block_at(&fd, fpos, z_blocksize);
+                if res_tx.send((fpos, res)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Prefetch {
+            n,
+            tx: Some(tx),
+            rx,
+            pending: VecDeque::default(),
+            handle: Some(handle),
+        }
+    }
+
+    // read z-block at `fpos`, and request the next `n` z-blocks from `siblings`.
+    // Return None if z-block could not be served from the prefetch thread.
+    fn read<K, V>(
+        &mut self,
+        fpos: u64,
+        siblings: &[robt::Entry<K, V>],
+    ) -> Option<Result<Vec<u8>>>
+    where
+        V: dbs::Diff,
+    {
+        let fposs = siblings.iter().filter_map(|entry| match entry {
+            robt::Entry::MZ { fpos, .. } => Some(*fpos),
+            _ => None,
+        });
+        let tx = self.tx.as_ref()?;
+        for fpos in std::iter::once(fpos).chain(fposs.take(self.n)) {
+            if !self.pending.contains(&fpos) {
+                tx.send(fpos).ok()?;
+                self.pending.push_back(fpos);
+            }
+        }
+
+        // responses arrive in the order of requests.
+        while let Some(pfpos) = self.pending.pop_front() {
+            match self.rx.recv() {
+                Ok((rfpos, res)) if rfpos == pfpos && rfpos == fpos => return Some(res),
+                Ok(_) => (),
+                Err(_) => {
+                    self.tx.take();
+                    return None;
+                }
+            }
+        }
+
+        None
+    }
+}
+
 pub struct IterLsm<'a, K, V>
 where
     V: dbs::Diff,
@@ -523,6 +1020,9 @@ where
                 entry.drain_deltas();
                 entry.into_native(fd, self.versions)
             }
+            None if entry.is_reference(self.versions) => {
+                err_at!(IOError, msg: "vlog unavailable")
+            }
             None => {
                 entry.drain_deltas();
                 Ok(entry)
@@ -543,6 +1043,7 @@ where
         let fd = &mut self.reader.index;
         let m_blocksize = self.reader.m_blocksize;
         let z_blocksize = self.reader.z_blocksize;
+        let codec = self.reader.codec;
 
         match self.stack.pop() {
             Some(block) if block.is_empty() => self.next(),
@@ -555,9 +1056,8 @@ where
                     self.stack.push(block);
 
                     let entries = iter_result!(|| -> Result<Vec<robt::Entry<K, V>>> {
-                        let fpos = io::SeekFrom::Start(fpos);
-                        let block = read_file!(fd, fpos, m_blocksize, "read mm-block")?;
-                        Ok(util::from_cbor_bytes(&block)?.0)
+                        let block = codec.read_block(fd, fpos, m_blocksize)?;
+                        Ok(util::from_cbor_bytes(&block).at_fpos(fpos)?.0)
                     }());
 
                     self.stack.push(entries);
@@ -567,9 +1067,8 @@ where
                     self.stack.push(block);
 
                     let entries = iter_result!(|| -> Result<Vec<robt::Entry<K, V>>> {
-                        let fpos = io::SeekFrom::Start(fpos);
-                        let block = read_file!(fd, fpos, z_blocksize, "read mm-block")?;
-                        Ok(util::from_cbor_bytes(&block)?.0)
+                        let block = codec.read_block(fd, fpos, z_blocksize)?;
+                        Ok(util::from_cbor_bytes(&block).at_fpos(fpos)?.0)
                     }());
 
                     self.stack.push(entries);
@@ -581,6 +1080,50 @@ where
     }
 }
 
+// all keys, less than `hi`, are before the range.
+fn is_before_range<R, Q>(hi: Option<&Q>, range: &R) -> bool
+where
+    Q: ?Sized + Ord,
+    R: RangeBounds<Q>,
+{
+    match (range.start_bound(), hi) {
+        (Bound::Included(s), Some(hi)) | (Bound::Excluded(s), Some(hi)) => hi <= s,
+        (_, _) => false,
+    }
+}
+
+// all keys, greater than or equal to `lo`, are after the range.
+fn is_after_range<R, Q>(lo: &Q, range: &R) -> bool
+where
+    Q: ?Sized + Ord,
+    R: RangeBounds<Q>,
+{
+    match range.end_bound() {
+        Bound::Included(e) => lo > e,
+        Bound::Excluded(e) => lo >= e,
+        Bound::Unbounded => false,
+    }
+}
+
+// all keys in [lo, hi) are within the range.
+fn is_within_range<R, Q>(lo: &Q, hi: Option<&Q>, range: &R) -> bool
+where
+    Q: ?Sized + Ord,
+    R: RangeBounds<Q>,
+{
+    let start = match range.start_bound() {
+        Bound::Included(s) => lo >= s,
+        Bound::Excluded(s) => lo > s,
+        Bound::Unbounded => true,
+    };
+    let end = match (range.end_bound(), hi) {
+        (Bound::Included(e), Some(hi)) | (Bound::Excluded(e), Some(hi)) => hi <= e,
+        (Bound::Unbounded, _) => true,
+        (_, None) => false,
+    };
+    start && end
+}
+
 fn fcmp<Q>(key: &Q, skey: Bound<&Q>) -> cmp::Ordering
 where
     Q: ?Sized + Ord,
@@ -591,12 +1134,58 @@ where
     }
 }
 
-fn rcmp<Q>(key: &Q, ekey: Bound<&Q>) -> cmp::Ordering
+// compare keys using the custom comparator, if any, or using Ord. Ordering is
+// reversed for index built in descending order.
+fn compare<K>(cmp: &Option<Comparator<K>>, desc: bool, a: &K, b: &K) -> cmp::Ordering
+where
+    K: Ord,
+{
+    let ord = match cmp {
+        Some(cmp) => cmp(a, b),
+        None => a.cmp(b),
+    };
+    match desc {
+        true => ord.reverse(),
+        false => ord,
+    }
+}
+
+// compare index `key` with user key `ukey`, using Ord. Ordering is reversed
+// for index built in descending order.
+pub fn key_order<Q>(key: &Q, ukey: &Q, desc: bool) -> cmp::Ordering
 where
     Q: ?Sized + Ord,
 {
-    match ekey {
-        Bound::Unbounded => cmp::Ordering::Less,
-        Bound::Included(ekey) | Bound::Excluded(ekey) => key.cmp(ekey),
+    match desc {
+        true => key.cmp(ukey).reverse(),
+        false => key.cmp(ukey),
+    }
+}
+
+// compare key with range bound, return `unbounded` for unbounded ranges.
+fn bound_cmp<K>(
+    cmp: &Option<Comparator<K>>,
+    desc: bool,
+    key: &K,
+    bound: &Bound<K>,
+    unbounded: cmp::Ordering,
+) -> cmp::Ordering
+where
+    K: Ord,
+{
+    match bound {
+        Bound::Unbounded => unbounded,
+        Bound::Included(bkey) | Bound::Excluded(bkey) => compare(cmp, desc, key, bkey),
+    }
+}
+
+fn to_owned_bound<Q>(bound: Bound<&Q>) -> Bound<Q::Owned>
+where
+    Q: ?Sized + ToOwned,
+{
+    match bound {
+        Bound::Unbounded => Bound::Unbounded,
+        Bound::Included(q) => Bound::Included(q.to_owned()),
+        Bound::Excluded(q) => Bound::Excluded(q.to_owned()),
     }
 }
@@ -0,0 +1,182 @@
+use cbordata::FromCbor;
+
+use std::{
+    cmp, fs,
+    hash::Hash,
+    io::{self, Read, Seek},
+    ops::Bound,
+};
+
+use crate::{
+    dbs, read_file,
+    robt::{
+        index::{encode_meta_block, find_index_file, to_vlog_path, MetaItem},
+        reader::Reader,
+        Config, Entry, Stats, ROOT_MARKER,
+    },
+    util, Error, Result,
+};
+
+/// Repair an index whose meta-block, at the tip of the index-file, is damaged
+/// while its block region is intact.
+///
+/// Blocks are not self describing, hence `config` shall be the same
+/// configuration that was used to build the index. Index-file is scanned from
+/// the start of its block region to locate the root block, entries are read back
+/// to re-compute statistics and `bitmap`, and a fresh meta-block is written in
+/// place of the damaged one.
+///
+/// Application metadata, build-time, epoch, bloom-shards and prefix-bloom cannot
+/// be recovered, they are reset to their default values.
+pub fn repair<K, V, B>(config: Config, mut bitmap: B) -> Result<()>
+where
+    K: Clone + Ord + Hash + FromCbor,
+    V: dbs::Diff + FromCbor,
+    <V as dbs::Diff>::Delta: FromCbor,
+    B: dbs::Bloom,
+{
+    let (dir, name) = (config.dir.clone(), config.name.clone());
+    let file = match find_index_file(&dir, &name) {
+        Some(file) => file,
+        None => err_at!(InvalidInput, msg: "no index file {:?}/{}", dir, name)?,
+    };
+
+    let vlog_dir = config.to_vlog_dir();
+    let mut stats: Stats = config.into();
+    stats.bloom_shards = false;
+    stats.prefix_bloom = false;
+
+    let (root, n_bytes) = {
+        let mut index = util::files::open_file_r(&file)?;
+        scan_blocks::<K, V>(&mut index, &mut stats)?
+    };
+
+    {
+        let index = util::files::open_file_r(&file)?;
+        let vlog = match stats.value_in_vlog || stats.delta_ok {
+            true => {
+                let vp = to_vlog_path(&vlog_dir, &name, &stats);
+                let vlog = util::files::open_file_r(&vp)?;
+                stats.n_vbytes = err_at!(IOError, vlog.metadata())?.len();
+                Some(vlog)
+            }
+            false => None,
+        };
+
+        let mut reader = Reader::<K, V>::from_root(root, &stats, index, vlog)?;
+        let range = (Bound::<K>::Unbounded, Bound::<K>::Unbounded);
+        let iter = reader.iter(range, false /*reverse*/, false /*versions*/)?;
+        for entry in iter {
+            let entry = entry?;
+            bitmap.add_key(entry.as_key());
+            stats.n_count += 1;
+            if entry.is_deleted() {
+                stats.n_deleted += 1;
+            }
+            stats.seqno = cmp::max(stats.seqno, entry.to_seqno());
+        }
+        bitmap.build()?;
+    }
+
+    let metas = vec![
+        MetaItem::AppMetadata(vec![]),
+        MetaItem::Stats(util::into_cbor_bytes(stats)?),
+        MetaItem::Bitmap(bitmap.to_bytes()?),
+        MetaItem::Root(root),
+        MetaItem::Marker(ROOT_MARKER.clone()),
+    ];
+    let block = encode_meta_block(metas)?;
+
+    // discard everything after the root block and append the fresh meta-block.
+    let mut fd = err_at!(IOError, fs::OpenOptions::new().write(true).open(&file))?;
+    err_at!(IOError, fd.set_len(n_bytes))?;
+    err_at!(IOError, fd.seek(io::SeekFrom::Start(n_bytes)))?;
+    util::files::sync_write(&mut fd, &block)?;
+
+    Ok(())
+}
+
+// scan blocks from the start of index-file, until a block fails to decode.
+// Builder flushes the root block as the last m-block, return its file-position
+// and the file-position just after the root block. Shape of the tree is updated
+// in `stats`.
+fn scan_blocks<K, V>(
+    index: &mut fs::File,
+    stats: &mut Stats,
+) -> Result<(Option<u64>, u64)>
+where
+    K: FromCbor,
+    V: dbs::Diff + FromCbor,
+    <V as dbs::Diff>::Delta: FromCbor,
+{
+    let (z_blocksize, m_blocksize) = (stats.z_blocksize, stats.m_blocksize);
+    let codec = stats.block_compression;
+    let file_len = err_at!(IOError, index.metadata())?.len();
+
+    let (mut fpos, mut root, mut n_bytes) = (0, None, 0);
+    while fpos < file_len {
+        // uncompressed blocks are of fixed size, and compressed blocks are framed
+        // with 4-byte length and 1-byte codec-id.
+        let (res, frame_len) = match codec.is_none() {
+            true => {
+                let n =
+                    cmp::min(cmp::max(z_blocksize, m_blocksize) as u64, file_len - fpos);
+                let seek = io::SeekFrom::Start(fpos);
+                (read_file!(index, seek, n, "scan block"), None)
+            }
+            false if (fpos + 4) < file_len => {
+                let seek = io::SeekFrom::Start(fpos);
+                let data = read_file!(index, seek, 4, "scan block-length")?;
+                let len = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+                let frame_len = u64::from(len) + 5;
+                match (fpos + frame_len) <= file_len {
+                    true => {
+                        let blocksize = cmp::max(z_blocksize, m_blocksize);
+                        (codec.read_block(index, fpos, blocksize), Some(frame_len))
+                    }
+                    false => break,
+                }
+            }
+            false => break,
+        };
+
+        let entries =
+            match res.and_then(|b| util::from_cbor_bytes::<Vec<Entry<K, V>>>(&b)) {
+                Ok((entries, _)) if !entries.is_empty() => entries,
+                _ => break,
+            };
+
+        let is_mblock = !matches!(entries[0], Entry::ZZ { .. });
+        let size = match (is_mblock, frame_len) {
+            (_, Some(frame_len)) => frame_len,
+            (true, None) => m_blocksize as u64,
+            (false, None) => z_blocksize as u64,
+        };
+        if is_mblock {
+            stats.n_mblocks += 1;
+            stats.n_mentries += entries.len() as u64;
+            root = Some(fpos);
+            n_bytes = fpos + size;
+        }
+        fpos += size;
+    }
+
+    // walk down the left most path from root to compute the depth.
+    let mut next = root.map(|fpos| (fpos, m_blocksize));
+    while let Some((fpos, blocksize)) = next {
+        let block = codec.read_block(index, fpos, blocksize)?;
+        let entries = util::from_cbor_bytes::<Vec<Entry<K, V>>>(&block)?.0;
+        stats.tree_depth += 1;
+        next = match entries.first() {
+            Some(Entry::MM { fpos, .. }) => Some((*fpos, m_blocksize)),
+            Some(Entry::MZ { fpos, .. }) => Some((*fpos, z_blocksize)),
+            _ => None,
+        };
+    }
+
+    Ok((root, n_bytes))
+}
+
+#[cfg(test)]
+#[path = "repair_test.rs"]
+mod repair_test;
@@ -0,0 +1,86 @@
+use rand::{prelude::random, rngs::StdRng, Rng, SeedableRng};
+use xorfilter::{BuildHasherDefault, Xor8};
+
+use std::{convert::TryInto, io::Write};
+
+use super::*;
+use crate::{
+    llrb,
+    robt::{Builder, Codec, Index},
+};
+
+#[test]
+fn test_robt_repair() {
+    let seed: u64 = random();
+    println!("test_robt_repair {}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let dir = std::env::temp_dir().join("test_robt_repair");
+    fs::remove_dir_all(&dir).ok();
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 20_000, 5_000, 1_000, 1_000, None);
+
+    for (i, codec) in
+        [Codec::None, Codec::Zstd { level: 3 }, Codec::Lz4].iter().enumerate()
+    {
+        let name = format!("repair-{}", i);
+        let mut config = Config::new(dir.as_os_str(), &name);
+        config
+            .set_blocksize(512, 4096, 512)
+            .set_delta(true)
+            .set_value_log(rng.gen::<bool>())
+            .set_block_compression(*codec);
+        println!("test_robt_repair config:{:?}", config);
+
+        let (file, stats) = {
+            let mut build = Builder::<u64, u64>::initial(config.clone(), vec![]).unwrap();
+            let iter = mdb.iter_versions().unwrap().map(Ok);
+            let bitmap = Xor8::<BuildHasherDefault>::new();
+            let index = build.build_index(iter, bitmap, Some(mdb.to_seqno())).unwrap();
+            (index.to_index_location(), index.to_stats())
+        };
+
+        // zero the meta-block at the tip of the index-file.
+        {
+            let mut fd =
+                fs::OpenOptions::new().read(true).write(true).open(&file).unwrap();
+            let seek = io::SeekFrom::End(-16);
+            let data = read_file!(fd, seek, 8, "reading meta-off").unwrap();
+            let off = u64::from_be_bytes(data.try_into().unwrap());
+            let n = fd.metadata().unwrap().len();
+            fd.seek(io::SeekFrom::Start(n - off)).unwrap();
+            fd.write_all(&vec![0; off as usize]).unwrap();
+        }
+        assert!(Index::<u64, u64, Xor8<BuildHasherDefault>>::open(
+            dir.as_os_str(),
+            &name
+        )
+        .is_err());
+
+        repair::<u64, u64, _>(config, Xor8::<BuildHasherDefault>::new()).unwrap();
+
+        let mut index =
+            Index::<u64, u64, Xor8<BuildHasherDefault>>::open(dir.as_os_str(), &name)
+                .unwrap();
+        let rstats = index.to_stats();
+        assert_eq!(rstats.n_count, stats.n_count);
+        assert_eq!(rstats.n_deleted, stats.n_deleted);
+        assert_eq!(rstats.seqno, stats.seqno);
+        assert_eq!(rstats.tree_depth, stats.tree_depth);
+        assert_eq!(rstats.n_mblocks, stats.n_mblocks);
+        assert_eq!(rstats.n_mentries, stats.n_mentries);
+        assert_eq!(rstats.block_compression, *codec);
+
+        let r = (Bound::<u64>::Unbounded, Bound::<u64>::Unbounded);
+        let iter = index.iter_versions(r).unwrap().map(|e| e.unwrap());
+        assert!(iter.eq(mdb.iter_versions().unwrap()));
+
+        for entry in mdb.iter().unwrap() {
+            let key = entry.to_key();
+            assert!(dbs::Bloom::contains(index.as_bitmap(), &key), "{}", key);
+            assert_eq!(index.get(&key).unwrap(), entry);
+        }
+
+        index.purge().unwrap();
+    }
+}
@@ -1,12 +1,12 @@
 use std::{
     cmp,
     convert::{TryFrom, TryInto},
-    fmt, hash, marker, time,
+    fmt, hash, marker, mem, time,
 };
 
 use crate::{dbs, robt, Error, Result};
 
-// BuildScan, BitmappedScan, CompactScan
+// BuildScan, BitmappedScan, PrefixScan, CompactScan, MergeScan
 
 /// Iterator wrapper, to wrap full-table scanners and count seqno,
 /// index-items, deleted items and epoch.
@@ -150,6 +150,74 @@ where
     }
 }
 
+/// Iterator wrapper, to add key prefixes into a secondary bloom filter, refer
+/// to [Config::set_prefix_bloom][robt::Config::set_prefix_bloom]. If `prefix`
+/// is None, entries are passed through as is. Entries are in sort order, hence
+/// same prefix is added only once for consecutive keys.
+pub struct PrefixScan<K, V, B, I>
+where
+    V: dbs::Diff,
+    I: Iterator<Item = Result<robt::Entry<K, V>>>,
+{
+    iter: I,
+    prefix: Option<(robt::PrefixFn<K>, B)>,
+    last: Option<Vec<u8>>,
+    _val: marker::PhantomData<V>,
+}
+
+impl<K, V, B, I> PrefixScan<K, V, B, I>
+where
+    V: dbs::Diff,
+    B: dbs::Bloom,
+    I: Iterator<Item = Result<robt::Entry<K, V>>>,
+{
+    pub fn new(iter: I, prefix: Option<(robt::PrefixFn<K>, B)>) -> Self {
+        PrefixScan {
+            iter,
+            prefix,
+            last: None,
+            _val: marker::PhantomData,
+        }
+    }
+
+    pub fn unwrap(self) -> Result<(Option<B>, I)> {
+        let bitmap = match self.prefix {
+            Some((_, mut bitmap)) => {
+                bitmap.build()?;
+                Some(bitmap)
+            }
+            None => None,
+        };
+        Ok((bitmap, self.iter))
+    }
+}
+
+impl<K, V, B, I> Iterator for PrefixScan<K, V, B, I>
+where
+    V: dbs::Diff,
+    B: dbs::Bloom,
+    I: Iterator<Item = Result<robt::Entry<K, V>>>,
+{
+    type Item = Result<robt::Entry<K, V>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.iter.next()? {
+            Ok(entry) => {
+                if let Some((prefix_fn, bitmap)) = self.prefix.as_mut() {
+                    let prefix = prefix_fn(entry.as_key());
+                    if self.last.as_ref() != Some(&prefix) {
+                        bitmap.add_key(prefix.as_slice());
+                        self.last = Some(prefix);
+                    }
+                }
+                Some(Ok(entry))
+            }
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
 /// Iterator type, for continuous full table iteration filtering out
 /// older mutations.
 pub struct CompactScan<K, V, I>
@@ -201,6 +269,80 @@ where
     }
 }
 
+/// Iterator type, to k-way merge full table iterators, each in key order.
+/// Entries with same key across iterators are merged into a single entry
+/// carrying versions from all of them, versions shall be exclusive across
+/// iterators.
+pub struct MergeScan<K, V, I>
+where
+    V: dbs::Diff,
+{
+    iters: Vec<I>,
+    heads: Vec<Option<dbs::Entry<K, V>>>,
+    err: Option<Error>,
+}
+
+impl<K, V, I> MergeScan<K, V, I>
+where
+    V: dbs::Diff,
+    I: Iterator<Item = Result<dbs::Entry<K, V>>>,
+{
+    pub fn new(mut iters: Vec<I>) -> Self {
+        let mut heads = vec![];
+        let mut err = None;
+        for iter in iters.iter_mut() {
+            match iter.next().transpose() {
+                Ok(head) => heads.push(head),
+                Err(e) => {
+                    heads.push(None);
+                    err = err.or(Some(e));
+                }
+            }
+        }
+
+        MergeScan { iters, heads, err }
+    }
+}
+
+impl<K, V, I> Iterator for MergeScan<K, V, I>
+where
+    K: Clone + Ord,
+    V: dbs::Diff,
+    <V as dbs::Diff>::Delta: From<V>,
+    I: Iterator<Item = Result<dbs::Entry<K, V>>>,
+{
+    type Item = Result<dbs::Entry<K, V>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = self.err.take() {
+            return Some(Err(err));
+        }
+
+        let key = self.heads.iter().flatten().map(|e| e.as_key()).min()?.clone();
+
+        let mut entry: Option<dbs::Entry<K, V>> = None;
+        for (head, iter) in self.heads.iter_mut().zip(self.iters.iter_mut()) {
+            match head {
+                Some(e) if e.as_key() == &key => (),
+                _ => continue,
+            }
+            let e = match iter.next().transpose() {
+                Ok(next) => mem::replace(head, next).unwrap(),
+                Err(err) => return Some(Err(err)),
+            };
+            entry = match entry {
+                Some(entry) => match entry.commit(&e) {
+                    Ok(entry) => Some(entry),
+                    Err(err) => return Some(Err(err)),
+                },
+                None => Some(e),
+            };
+        }
+
+        entry.map(Ok)
+    }
+}
+
 #[cfg(test)]
 #[path = "scans_test.rs"]
 mod scans_test;
@@ -0,0 +1,216 @@
+use cbordata::{FromCbor, IntoCbor};
+
+use std::{
+    cmp,
+    convert::TryFrom,
+    ffi, fs,
+    io::{self, Read, Write},
+    path, vec,
+};
+
+use crate::{dbs, robt::Comparator, util, Error, Result};
+
+/// Iterator wrapper, to sort entries from an unsorted iterator using an
+/// external merge sort. Entries are collected into runs of `mem_budget`
+/// bytes, each run is sorted and spilled into a file under `dir`, the last
+/// run is retained in memory. Runs are k-way merged while iterating and
+/// entries with same key are de-duplicated, retaining the entry with newest
+/// seqno. Run files are removed when the iterator is dropped.
+pub struct ExtSort<K, V>
+where
+    V: dbs::Diff,
+{
+    cmp: Option<Comparator<K>>,
+    files: Vec<ffi::OsString>,
+    runs: Vec<Run>,
+    heads: Vec<Option<dbs::Entry<K, V>>>,
+}
+
+impl<K, V> Drop for ExtSort<K, V>
+where
+    V: dbs::Diff,
+{
+    fn drop(&mut self) {
+        self.runs.clear();
+        for file in self.files.iter() {
+            fs::remove_file(file).ok(); // NOTE: ignore remove errors.
+        }
+    }
+}
+
+impl<K, V> ExtSort<K, V>
+where
+    K: Clone + Ord + IntoCbor + FromCbor,
+    V: dbs::Diff + IntoCbor + FromCbor,
+    <V as dbs::Diff>::Delta: IntoCbor + FromCbor,
+{
+    pub fn new<I>(
+        iter: I,
+        dir: &ffi::OsStr,
+        name: &str,
+        mem_budget: usize,
+        cmp: Option<Comparator<K>>,
+    ) -> Result<Self>
+    where
+        I: Iterator<Item = Result<dbs::Entry<K, V>>>,
+    {
+        let mut val = ExtSort { cmp, files: vec![], runs: vec![], heads: vec![] };
+
+        // entries are held in their serialized form, along with key and seqno.
+        let (mut run, mut size) = (vec![], 0);
+        for entry in iter {
+            let entry = entry?;
+            let (key, seqno) = (entry.to_key(), entry.to_seqno());
+            let data = util::into_cbor_bytes(entry)?;
+            size += data.len();
+            run.push((key, seqno, data));
+
+            if size >= mem_budget {
+                let file = format!("{}-sort-{}.run", name, val.files.len());
+                let file = path::Path::new(dir).join(file).into_os_string();
+                val.sort_run(&mut run);
+                val.files.push(file.clone());
+                val.runs.push(spill_run(run.drain(..), &file)?);
+                size = 0;
+            }
+        }
+        val.sort_run(&mut run);
+        let run = run.into_iter().map(|(_, _, data)| data).collect::<Vec<Vec<u8>>>();
+        val.runs.push(Run::Mem(run.into_iter()));
+
+        for run in val.runs.iter_mut() {
+            val.heads.push(run.next_entry()?);
+        }
+
+        Ok(val)
+    }
+
+    // sort by key, and for same key by descending order of seqno.
+    fn sort_run(&self, run: &mut [(K, u64, Vec<u8>)]) {
+        run.sort_by(|a, b| compare(&self.cmp, &a.0, &b.0).then(b.1.cmp(&a.1)))
+    }
+
+    fn advance(&mut self, off: usize) -> Result<()> {
+        self.heads[off] = self.runs[off].next_entry()?;
+        Ok(())
+    }
+
+    fn do_next(&mut self) -> Result<Option<dbs::Entry<K, V>>> {
+        // pick the smallest key, for same key pick the newest seqno.
+        let mut off: Option<usize> = None;
+        for (i, head) in self.heads.iter().enumerate() {
+            let (a, b) = match (head, off) {
+                (None, _) => continue,
+                (Some(_), None) => {
+                    off = Some(i);
+                    continue;
+                }
+                (Some(a), Some(j)) => (a, self.heads[j].as_ref().unwrap()),
+            };
+            match compare(&self.cmp, a.as_key(), b.as_key()) {
+                cmp::Ordering::Less => off = Some(i),
+                cmp::Ordering::Equal if a.to_seqno() > b.to_seqno() => off = Some(i),
+                _ => (),
+            }
+        }
+
+        let entry = match off {
+            Some(off) => {
+                let entry = self.heads[off].take().unwrap();
+                self.advance(off)?;
+                entry
+            }
+            None => return Ok(None),
+        };
+
+        // skip older entries with same key, across all the runs.
+        for i in 0..self.heads.len() {
+            while let Some(head) = self.heads[i].as_ref() {
+                match compare(&self.cmp, head.as_key(), entry.as_key()) {
+                    cmp::Ordering::Equal => self.advance(i)?,
+                    _ => break,
+                }
+            }
+        }
+
+        Ok(Some(entry))
+    }
+}
+
+impl<K, V> Iterator for ExtSort<K, V>
+where
+    K: Clone + Ord + IntoCbor + FromCbor,
+    V: dbs::Diff + IntoCbor + FromCbor,
+    <V as dbs::Diff>::Delta: IntoCbor + FromCbor,
+{
+    type Item = Result<dbs::Entry<K, V>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.do_next().transpose()
+    }
+}
+
+// a sorted run, entries are serialized and run files are persisted as 8-byte
+// length-prefixed entries.
+enum Run {
+    Mem(vec::IntoIter<Vec<u8>>),
+    File(io::BufReader<fs::File>),
+}
+
+impl Run {
+    fn next_entry<K, V>(&mut self) -> Result<Option<dbs::Entry<K, V>>>
+    where
+        K: FromCbor,
+        V: dbs::Diff + FromCbor,
+        <V as dbs::Diff>::Delta: FromCbor,
+    {
+        let data = match self {
+            Run::Mem(iter) => match iter.next() {
+                Some(data) => data,
+                None => return Ok(None),
+            },
+            Run::File(fd) => {
+                let mut prefix = [0_u8; 8];
+                match fd.read_exact(&mut prefix) {
+                    Ok(()) => (),
+                    Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                        return Ok(None)
+                    }
+                    Err(err) => err_at!(IOError, Err(err))?,
+                }
+                let n = u64::from_be_bytes(prefix);
+                let mut data = vec![0; err_at!(FailConvert, usize::try_from(n))?];
+                err_at!(IOError, fd.read_exact(&mut data))?;
+                data
+            }
+        };
+
+        Ok(Some(util::from_cbor_bytes(&data)?.0))
+    }
+}
+
+fn spill_run<K, I>(run: I, file: &ffi::OsStr) -> Result<Run>
+where
+    I: Iterator<Item = (K, u64, Vec<u8>)>,
+{
+    let mut fd = io::BufWriter::new(util::files::create_file_a(file)?);
+    for (_, _, data) in run {
+        let n = err_at!(FailConvert, u64::try_from(data.len()))?;
+        err_at!(IOError, fd.write_all(&n.to_be_bytes()))?;
+        err_at!(IOError, fd.write_all(&data))?;
+    }
+    err_at!(IOError, fd.flush())?;
+
+    let fd = util::files::open_file_r(file)?;
+    Ok(Run::File(io::BufReader::new(fd)))
+}
+
+fn compare<K>(cmp: &Option<Comparator<K>>, a: &K, b: &K) -> cmp::Ordering
+where
+    K: Ord,
+{
+    match cmp {
+        Some(cmp) => cmp(a, b),
+        None => a.cmp(b),
+    }
+}
@@ -2,11 +2,15 @@ use cbordata::{Cborize, FromCbor, IntoCbor};
 
 use std::{convert::TryFrom, io};
 
-use crate::{dbs, err_at, read_file, util, Error, Result};
+use crate::{dbs, err_at, read_file, robt::Codec, util, Error, Result};
 
 const VALUE_VER: u32 = 0x000d0001;
 const DELTA_VER: u32 = 0x00110001;
 
+// Header byte for compressed value blobs in value-log. Uncompressed blobs are
+// cbor encoded values and shall never start with the cbor break-code.
+const ZMARKER: u8 = 0xFF;
+
 #[derive(Clone, Debug, Eq, PartialEq, Cborize)]
 pub enum Value<V> {
     N { value: dbs::Value<V> },
@@ -39,15 +43,15 @@ impl<V> TryFrom<Value<V>> for dbs::Value<V> {
 }
 
 impl<V> Value<V> {
-    pub fn into_reference(self, fpos: u64) -> Result<(Self, Vec<u8>)>
+    // serialize value into a value-log blob, compressing it if the encoded
+    // size exceeds `threshold`, ZERO threshold disables compression.
+    pub fn into_reference(self, fpos: u64, threshold: usize) -> Result<(Self, Vec<u8>)>
     where
         V: IntoCbor,
     {
         let (value, data) = match self {
             Value::N { value } => {
-                let data = util::into_cbor_bytes(value)?;
-                let length = err_at!(FailConvert, u64::try_from(data.len()))?;
-                (Value::R { fpos, length }, data)
+                Value::from_encoded(fpos, util::into_cbor_bytes(value)?, threshold)?
             }
             val @ Value::R { .. } => (val, vec![]),
         };
@@ -55,6 +59,17 @@ impl<V> Value<V> {
         Ok((value, data))
     }
 
+    // same as into_reference, for a value already cbor encoded into `data`.
+    pub fn from_encoded(
+        fpos: u64,
+        data: Vec<u8>,
+        threshold: usize,
+    ) -> Result<(Self, Vec<u8>)> {
+        let data = compress_value(data, threshold);
+        let length = err_at!(FailConvert, u64::try_from(data.len()))?;
+        Ok((Value::R { fpos, length }, data))
+    }
+
     pub fn into_native<F>(self, f: &mut F) -> Result<Self>
     where
         F: io::Seek + io::Read,
@@ -65,7 +80,7 @@ impl<V> Value<V> {
             Value::R { fpos, length } => {
                 let seek = io::SeekFrom::Start(fpos);
                 let block = read_file!(f, seek, length, "reading value from vlog")?;
-                let value = util::from_cbor_bytes(&block)?.0;
+                let value = util::from_cbor_bytes(&decompress_value(block)?)?.0;
                 Value::N { value }
             }
         };
@@ -149,6 +164,46 @@ impl<D> Delta<D> {
             }
         }
     }
+
+    pub fn to_seqno(&self) -> Option<u64> {
+        match self {
+            Delta::N { delta } => Some(delta.to_seqno()),
+            Delta::R { .. } => None,
+        }
+    }
+}
+
+// compressed blob is framed as [ZMARKER, codec-id, payload], payload is lz4
+// compressed with uncompressed size prepended. Values that don't shrink are
+// returned as is.
+fn compress_value(data: Vec<u8>, threshold: usize) -> Vec<u8> {
+    if threshold == 0 || data.len() <= threshold {
+        return data;
+    }
+
+    let payload = lz4_flex::compress_prepend_size(&data);
+    if (payload.len() + 2) >= data.len() {
+        return data;
+    }
+
+    let mut blob = Vec::with_capacity(payload.len() + 2);
+    blob.push(ZMARKER);
+    blob.push(Codec::Lz4.to_id());
+    blob.extend_from_slice(&payload);
+    blob
+}
+
+fn decompress_value(blob: Vec<u8>) -> Result<Vec<u8>> {
+    if blob.first() != Some(&ZMARKER) {
+        return Ok(blob);
+    }
+
+    match blob.get(1) {
+        Some(id) if *id == Codec::Lz4.to_id() => {
+            err_at!(InvalidFile, lz4_flex::decompress_size_prepended(&blob[2..]))
+        }
+        id => err_at!(InvalidFile, msg: "invalid value codec {:?}", id),
+    }
 }
 
 #[cfg(test)]
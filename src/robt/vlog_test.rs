@@ -10,7 +10,7 @@ fn test_robt_vlog_value() {
     assert_eq!(dbval, dbs::Value::try_from(Value::from(dbval.clone())).unwrap());
 
     let value = Value::from(dbval.clone());
-    let (value, data) = value.into_reference(1023).unwrap();
+    let (value, data) = value.into_reference(1023, 0).unwrap();
     let mut buf = vec![0; 1023];
     buf.extend(&data);
     assert_eq!(value, Value::R { fpos: 1023, length: data.len() as u64 });
@@ -37,3 +37,50 @@ fn test_robt_vlog_delta() {
     let mut buf = io::Cursor::new(buf);
     assert_eq!(delta.into_native(&mut buf).unwrap(), Delta::from(dbdelta));
 }
+
+#[test]
+fn test_robt_vlog_value_compress() {
+    let small = dbs::Value::U { value: dbs::Binary { val: vec![7; 16] }, seqno: 1 };
+    let large = dbs::Value::U {
+        value: dbs::Binary { val: vec![7; 4096] },
+        seqno: 2,
+    };
+
+    // small values are not compressed.
+    let (_, data) = Value::from(small.clone()).into_reference(0, 1024).unwrap();
+    let (_, plain) = Value::from(small.clone()).into_reference(0, 0).unwrap();
+    assert_eq!(data, plain);
+
+    let (value, data) = Value::from(small.clone()).into_reference(1023, 1024).unwrap();
+    let mut buf = vec![0; 1023];
+    buf.extend(&data);
+    let mut buf = io::Cursor::new(buf);
+    assert_eq!(value.into_native(&mut buf).unwrap(), Value::from(small));
+
+    // large values are compressed, flagged in the header.
+    let (_, plain) = Value::from(large.clone()).into_reference(0, 0).unwrap();
+    assert_ne!(plain[0], ZMARKER);
+
+    let (value, data) = Value::from(large.clone()).into_reference(1023, 1024).unwrap();
+    assert_eq!(data[0], ZMARKER);
+    assert_eq!(data[1], Codec::Lz4.to_id());
+    assert!(data.len() < plain.len(), "{} {}", data.len(), plain.len());
+    assert_eq!(value, Value::R { fpos: 1023, length: data.len() as u64 });
+
+    // value already encoded, is referenced the same way.
+    let encoded = util::into_cbor_bytes(large.clone()).unwrap();
+    let res = Value::<dbs::Binary>::from_encoded(1023, encoded, 1024).unwrap();
+    assert_eq!(res, (value.clone(), data.clone()));
+
+    let mut buf = vec![0; 1023];
+    buf.extend(&data);
+    let mut buf = io::Cursor::new(buf);
+    assert_eq!(value.into_native(&mut buf).unwrap(), Value::from(large));
+
+    // unknown codec is an error.
+    let mut data = data;
+    data[1] = 0xAB;
+    let value = Value::<dbs::Binary>::R { fpos: 0, length: data.len() as u64 };
+    let mut buf = io::Cursor::new(data);
+    assert!(value.into_native(&mut buf).is_err());
+}
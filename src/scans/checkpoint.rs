@@ -0,0 +1,88 @@
+use std::{fmt, ops::Bound, result};
+
+use crate::{dbs, Result};
+
+/// Iterator adapter for resumable long running scans.
+///
+/// CheckpointScan calls the application supplied `callb` with the key of the
+/// last entry yielded, once every `n` entries, so that applications, like
+/// exports and migrations, can persist their progress. Subsequently a scan
+/// can be resumed from the persisted key, refer to [CheckpointScan::resume_from].
+/// Errors from the underlying iterator are passed through as is.
+pub struct CheckpointScan<I, K> {
+    iter: I,
+    every: usize,
+    callb: Box<dyn FnMut(&K) + Send>,
+    n: usize,
+    last_key: Option<K>,
+}
+
+impl<I, K> fmt::Debug for CheckpointScan<I, K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
+        write!(f, "CheckpointScan<every:{},n:{}>", self.every, self.n)
+    }
+}
+
+impl<I, K> CheckpointScan<I, K> {
+    /// Call `callb` with the last seen key after every `every` entries. An
+    /// `every` of ZERO disables checkpointing.
+    pub fn new<F>(iter: I, every: usize, callb: F) -> CheckpointScan<I, K>
+    where
+        F: 'static + FnMut(&K) + Send,
+    {
+        CheckpointScan {
+            iter,
+            every,
+            callb: Box::new(callb),
+            n: 0,
+            last_key: None,
+        }
+    }
+
+    /// Return the range to resume a scan, strictly after the checkpointed
+    /// `key`. Returned range can be passed to the `range` method of indexes.
+    pub fn resume_from(key: K) -> (Bound<K>, Bound<K>) {
+        (Bound::Excluded(key), Bound::Unbounded)
+    }
+
+    /// Return the key of the last entry yielded so far. Useful to checkpoint
+    /// once more after the scan is complete.
+    pub fn as_last_key(&self) -> Option<&K> {
+        self.last_key.as_ref()
+    }
+
+    /// Return the number of entries yielded so far.
+    pub fn to_count(&self) -> usize {
+        self.n
+    }
+
+    /// Unwrap the underlying iterator.
+    pub fn into_inner(self) -> I {
+        self.iter
+    }
+}
+
+impl<I, K, V, D> Iterator for CheckpointScan<I, K>
+where
+    K: Clone,
+    V: dbs::Diff<Delta = D>,
+    I: Iterator<Item = Result<dbs::Entry<K, V, D>>>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next()?;
+        if let Ok(entry) = &item {
+            self.n += 1;
+            self.last_key = Some(entry.to_key());
+            if self.every > 0 && self.n % self.every == 0 {
+                (self.callb)(entry.as_key())
+            }
+        }
+        Some(item)
+    }
+}
+
+#[cfg(test)]
+#[path = "checkpoint_test.rs"]
+mod checkpoint_test;
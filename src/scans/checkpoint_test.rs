@@ -0,0 +1,56 @@
+use rand::{prelude::random, rngs::StdRng, Rng, SeedableRng};
+
+use std::sync::{Arc, Mutex};
+
+use super::*;
+use crate::llrb;
+
+#[test]
+fn test_checkpoint_scan() {
+    let seed: u64 = random();
+    println!("test_checkpoint_scan seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mdb = llrb::load_index::<u64, u64>(seed, 10_000, 1_000, 1_000, 1_000, None);
+    let keys: Vec<u64> = mdb.iter().unwrap().map(|e| e.to_key()).collect();
+    let every = (rng.gen::<usize>() % 1000) + 1;
+    println!("test_checkpoint_scan keys:{} every:{}", keys.len(), every);
+
+    let checkpoints = Arc::new(Mutex::new(vec![]));
+    let mut scan = {
+        let checkpoints = Arc::clone(&checkpoints);
+        let callb = move |key: &u64| checkpoints.lock().unwrap().push(*key);
+        CheckpointScan::new(mdb.iter().unwrap().map(Ok), every, callb)
+    };
+
+    // interrupt the scan after a random number of entries.
+    let n_scan = rng.gen::<usize>() % keys.len();
+    let mut scanned: Vec<u64> = vec![];
+    for entry in scan.by_ref().take(n_scan) {
+        scanned.push(entry.unwrap().to_key());
+    }
+    assert_eq!(scan.to_count(), n_scan);
+    assert_eq!(scan.as_last_key(), scanned.last());
+
+    // callback fired once every `every` entries, with the last seen key.
+    let checkpoints = checkpoints.lock().unwrap().clone();
+    let refs: Vec<u64> = scanned.iter().skip(every - 1).step_by(every).cloned().collect();
+    assert_eq!(checkpoints, refs);
+
+    // resume from the last checkpoint, remainder is scanned.
+    let (resumed, n_skip): (Vec<u64>, usize) = match checkpoints.last() {
+        Some(key) => {
+            let r = CheckpointScan::<(), u64>::resume_from(*key);
+            (
+                mdb.range(r).unwrap().map(|e| e.to_key()).collect(),
+                checkpoints.len() * every,
+            )
+        }
+        None => (mdb.iter().unwrap().map(|e| e.to_key()).collect(), 0),
+    };
+    assert_eq!(resumed, keys[n_skip..].to_vec());
+
+    // zero disables checkpointing.
+    let scan = CheckpointScan::new(mdb.iter().unwrap().map(Ok), 0, |_: &u64| panic!());
+    assert_eq!(scan.count(), keys.len());
+}
@@ -0,0 +1,17 @@
+//! Module implement iterator adapters that are useful for constructing
+//! complex scans over indexes.
+//!
+//! * [PacedScan], yield control to background operations, while doing a
+//!   piece-wise full table scan.
+//! * [TaggedScan], tag each entry with its originating level, while merging
+//!   several levels.
+//! * [CheckpointScan], periodically report scan progress, for resumable
+//!   scans.
+
+mod checkpoint;
+mod paced;
+mod tagged;
+
+pub use checkpoint::CheckpointScan;
+pub use paced::PacedScan;
+pub use tagged::TaggedScan;
@@ -0,0 +1,84 @@
+use std::{fmt, result, thread, time};
+
+/// Iterator adapter for piece-wise full table scanning.
+///
+/// PacedScan yields up to `chunk` entries from the underlying iterator and
+/// then yields control, either by sleeping for `pause` duration or by calling
+/// the application supplied `yield_fn`, before continuing with the next chunk.
+/// This gives a scheduling gap for background reads and writes, while a long
+/// running scan is in progress.
+pub struct PacedScan<I> {
+    iter: I,
+    chunk: usize,
+    pace: Pace,
+    n: usize,
+}
+
+enum Pace {
+    Sleep(time::Duration),
+    Yield(Box<dyn FnMut() + Send>),
+}
+
+impl<I> fmt::Debug for PacedScan<I> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
+        match &self.pace {
+            Pace::Sleep(pause) => {
+                write!(f, "PacedScan<chunk:{},pause:{:?}>", self.chunk, pause)
+            }
+            Pace::Yield(_) => write!(f, "PacedScan<chunk:{},yield_fn>", self.chunk),
+        }
+    }
+}
+
+impl<I> PacedScan<I> {
+    /// Sleep for `pause` duration after every `chunk` entries. A `chunk` of
+    /// ZERO disables pacing.
+    pub fn new(iter: I, chunk: usize, pause: time::Duration) -> PacedScan<I> {
+        PacedScan { iter, chunk, pace: Pace::Sleep(pause), n: 0 }
+    }
+
+    /// Call `yield_fn` after every `chunk` entries. A `chunk` of ZERO disables
+    /// pacing.
+    pub fn with_yield<F>(iter: I, chunk: usize, yield_fn: F) -> PacedScan<I>
+    where
+        F: 'static + FnMut() + Send,
+    {
+        PacedScan {
+            iter,
+            chunk,
+            pace: Pace::Yield(Box::new(yield_fn)),
+            n: 0,
+        }
+    }
+
+    /// Unwrap the underlying iterator.
+    pub fn into_inner(self) -> I {
+        self.iter
+    }
+}
+
+impl<I> Iterator for PacedScan<I>
+where
+    I: Iterator,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // pace only between chunks, not after the last chunk.
+        if self.chunk > 0 && self.n == self.chunk {
+            match &mut self.pace {
+                Pace::Sleep(pause) => thread::sleep(*pause),
+                Pace::Yield(yield_fn) => yield_fn(),
+            }
+            self.n = 0;
+        }
+
+        let item = self.iter.next()?;
+        self.n += 1;
+        Some(item)
+    }
+}
+
+#[cfg(test)]
+#[path = "paced_test.rs"]
+mod paced_test;
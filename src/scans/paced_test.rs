@@ -0,0 +1,49 @@
+use rand::{prelude::random, rngs::StdRng, Rng, SeedableRng};
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering::SeqCst},
+    Arc,
+};
+
+use super::*;
+
+#[test]
+fn test_paced_scan_yield() {
+    let seed: u64 = random();
+    println!("test_paced_scan_yield seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for _ in 0..10 {
+        let n = rng.gen::<usize>() % 10_000;
+        let chunk = (rng.gen::<usize>() % 100) + 1;
+        println!("test_paced_scan_yield n:{} chunk:{}", n, chunk);
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let iter = {
+            let count = Arc::clone(&count);
+            PacedScan::with_yield(0..n, chunk, move || {
+                count.fetch_add(1, SeqCst);
+            })
+        };
+
+        let mut items = vec![];
+        for (i, item) in iter.enumerate() {
+            assert_eq!(count.load(SeqCst), i / chunk, "at {}", i);
+            items.push(item);
+        }
+        assert_eq!(items, (0..n).collect::<Vec<usize>>());
+        assert_eq!(count.load(SeqCst), n.saturating_sub(1) / chunk);
+    }
+}
+
+#[test]
+fn test_paced_scan_sleep() {
+    let pause = time::Duration::from_millis(10);
+    let start = time::Instant::now();
+    let n = PacedScan::new(0..100, 25, pause).count();
+    assert_eq!(n, 100);
+    assert!(start.elapsed() >= pause * 3, "{:?}", start.elapsed());
+
+    let n = PacedScan::new(0..100, 0, pause).count();
+    assert_eq!(n, 100);
+}
@@ -0,0 +1,63 @@
+use std::{fmt, result};
+
+/// Iterator adapter tagging each item with its originating level.
+///
+/// When merging several levels, say during dgm compaction, wrap each level's
+/// iterator with TaggedScan, to know which level an entry came from. Items
+/// are yielded as `(item, level)` tuples.
+pub struct TaggedScan<I> {
+    iter: I,
+    level: usize,
+    n: usize,
+}
+
+impl<I> fmt::Debug for TaggedScan<I> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
+        write!(f, "TaggedScan<level:{},n:{}>", self.level, self.n)
+    }
+}
+
+impl<I> TaggedScan<I> {
+    /// Tag items from `iter` with `level`.
+    pub fn new(iter: I, level: usize) -> TaggedScan<I> {
+        TaggedScan { iter, level, n: 0 }
+    }
+
+    /// Tag each iterator with its position in `iters`, as its level.
+    pub fn from_levels(iters: Vec<I>) -> Vec<TaggedScan<I>> {
+        let iter = iters.into_iter().enumerate();
+        iter.map(|(level, iter)| TaggedScan::new(iter, level)).collect()
+    }
+
+    /// Return the level tagged to this iterator.
+    pub fn to_level(&self) -> usize {
+        self.level
+    }
+
+    /// Return the number of items consumed so far from this level.
+    pub fn to_count(&self) -> usize {
+        self.n
+    }
+
+    /// Unwrap the underlying iterator.
+    pub fn into_inner(self) -> I {
+        self.iter
+    }
+}
+
+impl<I> Iterator for TaggedScan<I>
+where
+    I: Iterator,
+{
+    type Item = (I::Item, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next()?;
+        self.n += 1;
+        Some((item, self.level))
+    }
+}
+
+#[cfg(test)]
+#[path = "tagged_test.rs"]
+mod tagged_test;
@@ -0,0 +1,68 @@
+use rand::{prelude::random, rngs::StdRng, Rng, SeedableRng};
+
+use std::collections::BTreeMap;
+
+use super::*;
+
+#[test]
+fn test_tagged_scan() {
+    let seed: u64 = random();
+    println!("test_tagged_scan seed:{}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let n_levels = (rng.gen::<usize>() % 8) + 1;
+
+    // distinct keys across levels, remember the level for each key.
+    let mut refs: BTreeMap<u64, usize> = BTreeMap::new();
+    let mut levels: Vec<Vec<u64>> = vec![vec![]; n_levels];
+    for _ in 0..10_000 {
+        let key = rng.gen::<u64>();
+        if !refs.contains_key(&key) {
+            let level = rng.gen::<usize>() % n_levels;
+            refs.insert(key, level);
+            levels[level].push(key);
+        }
+    }
+    for level in levels.iter_mut() {
+        level.sort_unstable();
+    }
+    let counts: Vec<usize> = levels.iter().map(|l| l.len()).collect();
+    println!("test_tagged_scan levels:{:?}", counts);
+
+    let iters: Vec<std::vec::IntoIter<u64>> =
+        levels.into_iter().map(|l| l.into_iter()).collect();
+    let mut scans = TaggedScan::from_levels(iters);
+    for (level, scan) in scans.iter().enumerate() {
+        assert_eq!(scan.to_level(), level);
+    }
+
+    // merge the tagged levels in key order.
+    let mut heads: Vec<Option<(u64, usize)>> =
+        scans.iter_mut().map(|s| s.next()).collect();
+    let mut histogram = vec![0; n_levels];
+    let mut merged = vec![];
+    loop {
+        let pos = heads
+            .iter()
+            .enumerate()
+            .filter_map(|(i, h)| h.map(|(key, _)| (key, i)))
+            .min()
+            .map(|(_, i)| i);
+        match pos {
+            Some(i) => {
+                let (key, level) = heads[i].take().unwrap();
+                assert_eq!(level, i);
+                assert_eq!(refs[&key], level, "key {}", key);
+                histogram[level] += 1;
+                merged.push(key);
+                heads[i] = scans[i].next();
+            }
+            None => break,
+        }
+    }
+
+    assert_eq!(merged, refs.keys().cloned().collect::<Vec<u64>>());
+    assert_eq!(histogram, counts);
+    let consumed: Vec<usize> = scans.iter().map(|s| s.to_count()).collect();
+    assert_eq!(consumed, counts);
+}
@@ -1,4 +1,5 @@
-use serde::de::DeserializeOwned;
+use regex::Regex;
+use serde::{de::DeserializeOwned, Serialize};
 
 use std::{env, ffi, fs, path};
 
@@ -73,6 +74,24 @@ pub fn open_file_r(file: &ffi::OsStr) -> Result<fs::File> {
     Ok(err_at!(IOError, fs::OpenOptions::new().read(true).open(os_file))?)
 }
 
+/// Read `n` bytes from `file` at `fpos`, using positional read that doesn't
+/// move the file cursor, hence safe to use on handles shared across threads
+/// via [fs::File::try_clone]. Supported only on unix platforms.
+#[cfg(unix)]
+pub fn read_at(file: &fs::File, fpos: u64, n: usize) -> Result<Vec<u8>> {
+    use std::os::unix::fs::FileExt;
+
+    let mut buf = vec![0; n];
+    err_at!(IOError, file.read_exact_at(&mut buf, fpos), "read_at {} {}", fpos, n)?;
+    Ok(buf)
+}
+
+/// Read `n` bytes from `file` at `fpos`, not supported on this platform.
+#[cfg(not(unix))]
+pub fn read_at(_file: &fs::File, fpos: u64, n: usize) -> Result<Vec<u8>> {
+    err_at!(NotImplemented, msg: "read_at {} {} on this platform", fpos, n)
+}
+
 pub fn sync_write(file: &mut fs::File, data: &[u8]) -> Result<usize> {
     use std::io::Write;
 
@@ -84,9 +103,104 @@ pub fn sync_write(file: &mut fs::File, data: &[u8]) -> Result<usize> {
     Ok(n)
 }
 
+/// Access pattern hint for an open file, refer to [advise_file].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Access {
+    /// Expect sequential reads, kernel may read-ahead aggressively.
+    Sequential,
+    /// Expect random reads, kernel may disable read-ahead.
+    Random,
+    /// Data won't be accessed in near future, kernel may drop cached pages.
+    DontNeed,
+}
+
+/// Advise the kernel about the access pattern for the entire `file`, using
+/// `posix_fadvise`. Hints are only applicable on Linux, Android and FreeBSD,
+/// on other platforms, including macOS and Windows, this call is a no-op.
+/// Hints are advisory and don't change the semantics of subsequent reads.
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+pub fn advise_file(file: &fs::File, access: Access) -> Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let advice = match access {
+        Access::Sequential => libc::POSIX_FADV_SEQUENTIAL,
+        Access::Random => libc::POSIX_FADV_RANDOM,
+        Access::DontNeed => libc::POSIX_FADV_DONTNEED,
+    };
+    // offset and length as ZERO apply the advice to the entire file.
+    match unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, advice) } {
+        0 => Ok(()),
+        errno => err_at!(IOError, msg: "posix_fadvise {:?} errno:{}", access, errno),
+    }
+}
+
+/// Advise the kernel about the access pattern for the entire `file`, this is
+/// a no-op on this platform.
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+pub fn advise_file(_file: &fs::File, _access: Access) -> Result<()> {
+    Ok(())
+}
+
+/// Return value from [walk] callback, to control the directory traversal.
 pub enum WalkRes {
+    /// Continue walking, descend into the entry if it is a directory.
     Ok,
+    /// Don't descend into this entry, continue with its siblings.
+    SkipDepth,
+    /// Same as [WalkRes::SkipDepth], kept for backward compatibility.
     SkipDir,
+    /// Descend into this entry, but skip its remaining siblings.
+    SkipBreath,
+    /// Neither descend into this entry nor continue with its siblings.
+    SkipBoth,
+}
+
+/// Options to filter the entries visited by [walk].
+///
+/// Patterns in `includes` and `excludes` are matched against the full path of
+/// each entry, that is, `parent` joined with the entry's file name.
+#[derive(Clone, Debug, Default)]
+pub struct WalkOpts {
+    /// Follow symbolic links that point to directories. By default symbolic links
+    /// are reported to callback, but are never descended into.
+    pub follow_links: bool,
+    /// If not empty, only entries matching one of the patterns are reported to
+    /// the callback. Sub-directories are walked irrespective of `includes`.
+    pub includes: Vec<Regex>,
+    /// Entries matching any of the patterns are neither reported to the callback
+    /// nor descended into.
+    pub excludes: Vec<Regex>,
+}
+
+impl WalkOpts {
+    pub fn new() -> WalkOpts {
+        WalkOpts::default()
+    }
+
+    pub fn set_follow_links(mut self, follow_links: bool) -> Self {
+        self.follow_links = follow_links;
+        self
+    }
+
+    pub fn add_include(mut self, patt: &str) -> Result<Self> {
+        let re = err_at!(InvalidInput, Regex::new(patt), "bad include {:?}", patt)?;
+        self.includes.push(re);
+        Ok(self)
+    }
+
+    pub fn add_exclude(mut self, patt: &str) -> Result<Self> {
+        let re = err_at!(InvalidInput, Regex::new(patt), "bad exclude {:?}", patt)?;
+        self.excludes.push(re);
+        Ok(self)
+    }
+
+    fn is_excluded(&self, loc: &str) -> bool {
+        self.excludes.iter().any(|re| re.is_match(loc))
+    }
+
+    fn is_included(&self, loc: &str) -> bool {
+        self.includes.is_empty() || self.includes.iter().any(|re| re.is_match(loc))
+    }
 }
 
 /// Breadth first directory walking.
@@ -98,39 +212,81 @@ pub enum WalkRes {
 /// * _dir_entry_, for each entry in a sub-directory.
 /// * _depth_, depth level at which _dir-entry_ is located, start with ZERO.
 /// * _breath_, index of _dir-entry_ as stored in its parent directory, start with ZERO.
-pub fn walk<P, S, F>(root: P, state: S, mut callb: F) -> Result<S>
+///
+/// Entries are filtered using `opts`, refer to [WalkOpts] for details. When
+/// `follow_links` is enabled, a directory that resolves to one of its own
+/// ancestors is skipped, to avoid looping over symbolic-link cycles.
+pub fn walk<P, S, F>(root: P, opts: &WalkOpts, state: S, mut callb: F) -> Result<S>
 where
     P: AsRef<path::Path>,
     F: FnMut(&mut S, &path::Path, &fs::DirEntry, usize, usize) -> Result<WalkRes>,
 {
     let depth = 0;
-    do_walk(root, state, &mut callb, depth)
+    let mut ancestors = vec![];
+    do_walk(root.as_ref(), opts, state, &mut callb, depth, &mut ancestors)
 }
 
-fn do_walk<P, S, F>(parent: P, mut state: S, callb: &mut F, depth: usize) -> Result<S>
+fn do_walk<S, F>(
+    parent: &path::Path,
+    opts: &WalkOpts,
+    mut state: S,
+    callb: &mut F,
+    depth: usize,
+    ancestors: &mut Vec<path::PathBuf>,
+) -> Result<S>
 where
-    P: AsRef<path::Path>,
     F: FnMut(&mut S, &path::Path, &fs::DirEntry, usize, usize) -> Result<WalkRes>,
 {
+    if opts.follow_links {
+        let loc = err_at!(IOError, parent.canonicalize(), "canonicalize({:?})", parent)?;
+        if ancestors.contains(&loc) {
+            return Ok(state);
+        }
+        ancestors.push(loc);
+    }
+
     let mut subdirs = vec![];
 
-    let parent = {
-        let parent: &path::Path = parent.as_ref();
-        parent.to_path_buf()
-    };
-    let dirs = err_at!(IOError, fs::read_dir(&parent), "read_dir({:?})", parent)?;
+    let dirs = err_at!(IOError, fs::read_dir(parent), "read_dir({:?})", parent)?;
     for (breath, entry) in dirs.enumerate() {
         let entry = err_at!(IOError, entry)?;
-        match callb(&mut state, &parent, &entry, depth, breath)? {
-            WalkRes::Ok if err_at!(IOError, entry.file_type())?.is_dir() => {
-                subdirs.push(entry)
+        let loc = entry.path();
+        let loc_str = loc.to_string_lossy();
+
+        if opts.is_excluded(&loc_str) {
+            continue;
+        }
+
+        let is_dir = {
+            let file_type = err_at!(IOError, entry.file_type())?;
+            match file_type.is_symlink() {
+                true if opts.follow_links => {
+                    fs::metadata(&loc).map(|m| m.is_dir()).unwrap_or(false)
+                }
+                true => false,
+                false => file_type.is_dir(),
             }
-            WalkRes::Ok | WalkRes::SkipDir => (),
+        };
+
+        let res = match opts.is_included(&loc_str) {
+            true => callb(&mut state, parent, &entry, depth, breath)?,
+            false => WalkRes::Ok,
+        };
+        match res {
+            WalkRes::Ok | WalkRes::SkipBreath if is_dir => subdirs.push(loc),
+            _ => (),
+        }
+        if let WalkRes::SkipBreath | WalkRes::SkipBoth = res {
+            break;
         }
     }
 
     for subdir in subdirs.into_iter() {
-        state = do_walk(subdir.path(), state, callb, depth + 1)?;
+        state = do_walk(&subdir, opts, state, callb, depth + 1, ancestors)?;
+    }
+
+    if opts.follow_links {
+        ancestors.pop();
     }
 
     Ok(state)
@@ -171,6 +327,58 @@ where
     err_at!(FailConvert, toml::from_str(s), "file:{:?}", ploc)
 }
 
+/// Serialize `value` into toml and save it to file `loc`, atomically.
+///
+/// Data is written to a temporary file, `<loc>.tmp`, fsynced and renamed over
+/// `loc`, so that readers shall either see the older version or the newer
+/// version, never a partial write. If `loc` already exists, the older version
+/// is preserved as `<loc>.bak`, replacing any previous backup.
+pub fn save_toml<P, T>(loc: P, value: &T) -> Result<()>
+where
+    P: AsRef<path::Path>,
+    T: Serialize,
+{
+    let ploc: &path::Path = loc.as_ref();
+    let data = err_at!(FailConvert, toml::to_string(value), "file:{:?}", ploc)?;
+
+    let tmp_loc = with_suffix(ploc, ".tmp");
+    {
+        let mut fd = create_file_a(tmp_loc.as_os_str())?;
+        sync_write(&mut fd, data.as_bytes())?;
+    }
+
+    if ploc.is_file() {
+        let bak_loc = with_suffix(ploc, ".bak");
+        err_at!(IOError, fs::copy(ploc, &bak_loc), "backup {:?}", ploc)?;
+        err_at!(IOError, open_file_r(bak_loc.as_os_str())?.sync_all())?;
+    }
+    err_at!(IOError, fs::rename(&tmp_loc, ploc), "rename {:?}", tmp_loc)?;
+
+    // persist the rename by syncing the parent directory.
+    match ploc.parent() {
+        Some(parent) if parent.as_os_str().is_empty() => Ok(()),
+        Some(parent) => sync_dir(parent),
+        None => Ok(()),
+    }
+}
+
+#[cfg(unix)]
+fn sync_dir(dir: &path::Path) -> Result<()> {
+    let fd = err_at!(IOError, fs::File::open(dir), "{:?}", dir)?;
+    err_at!(IOError, fd.sync_all())
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &path::Path) -> Result<()> {
+    Ok(())
+}
+
+fn with_suffix(loc: &path::Path, suffix: &str) -> path::PathBuf {
+    let mut loc = loc.as_os_str().to_os_string();
+    loc.push(suffix);
+    loc.into()
+}
+
 /// Return the configuration file pointed by `loc` or, if it is None, search of
 /// file_names under current/directory or home/directory.
 pub fn find_config<P>(
@@ -89,3 +89,134 @@ fn test_open_file_rw() {
 
     fd.write("hello world".as_bytes()).expect_err("expected write error");
 }
+
+// symlinks are created using unix api.
+#[cfg(unix)]
+#[test]
+fn test_walk() {
+    let root: PathBuf =
+        [std::env::temp_dir(), "rust.rdms.util.walk".into()].iter().collect();
+    fs::remove_dir_all(&root).ok();
+
+    for dir in ["a", "b", "b/skip"].iter() {
+        fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    for file in ["a/x.txt", "a/y.rs", "b/z.txt", "b/skip/w.txt"].iter() {
+        fs::write(root.join(file), "hello world").unwrap();
+    }
+    std::os::unix::fs::symlink(root.join("a"), root.join("link")).unwrap();
+    // cycle back to root, must be walked only once when following links.
+    std::os::unix::fs::symlink(&root, root.join("b/cycle")).unwrap();
+
+    let do_walk = |opts: &WalkOpts| -> Vec<String> {
+        let mut locs = walk(&root, opts, vec![], |locs, _, entry, _, _| {
+            let loc = entry.path();
+            let loc = loc.strip_prefix(&root).unwrap().to_str().unwrap().to_string();
+            locs.push(loc);
+            Ok(WalkRes::Ok)
+        })
+        .unwrap();
+        locs.sort();
+        locs
+    };
+
+    // case 1: walk everything, symlinks are reported but not followed.
+    let locs = do_walk(&WalkOpts::new());
+    let refs = vec![
+        "a",
+        "a/x.txt",
+        "a/y.rs",
+        "b",
+        "b/cycle",
+        "b/skip",
+        "b/skip/w.txt",
+        "b/z.txt",
+        "link",
+    ];
+    assert_eq!(locs, refs);
+
+    // case 2: exclusion.
+    let opts = WalkOpts::new().add_exclude("/skip$").unwrap();
+    let locs = do_walk(&opts);
+    let refs = vec!["a", "a/x.txt", "a/y.rs", "b", "b/cycle", "b/z.txt", "link"];
+    assert_eq!(locs, refs);
+
+    // case 3: inclusion, sub-directories are still walked.
+    let opts = WalkOpts::new().add_include(r"\.txt$").unwrap();
+    let locs = do_walk(&opts);
+    assert_eq!(locs, vec!["a/x.txt", "b/skip/w.txt", "b/z.txt"]);
+
+    // case 4: follow symlinks.
+    let opts = WalkOpts::new()
+        .set_follow_links(true)
+        .add_include(r"\.txt$")
+        .unwrap()
+        .add_exclude("/skip$")
+        .unwrap();
+    let locs = do_walk(&opts);
+    assert_eq!(locs, vec!["a/x.txt", "b/z.txt", "link/x.txt"]);
+
+    // case 5: skip semantics.
+    let locs = walk(&root, &WalkOpts::new(), vec![], |locs, _, entry, _, _| {
+        let loc = entry.path();
+        let loc = loc.strip_prefix(&root).unwrap().to_str().unwrap().to_string();
+        let res = match loc.as_str() {
+            "a" => WalkRes::SkipDepth,
+            "b" => WalkRes::SkipDir,
+            _ => WalkRes::Ok,
+        };
+        locs.push(loc);
+        Ok(res)
+    })
+    .unwrap();
+    assert!(locs.iter().all(|loc| !loc.contains('/')), "{:?}", locs);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_save_toml() {
+    #[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct TomlConfig {
+        name: String,
+        limit: usize,
+        paths: Vec<String>,
+    }
+
+    let dir: PathBuf =
+        [std::env::temp_dir(), "rust.rdms.util.save_toml".into()].iter().collect();
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap();
+
+    let loc = dir.join("config.toml");
+    let (tmp_loc, bak_loc) = (dir.join("config.toml.tmp"), dir.join("config.toml.bak"));
+
+    // case 1: fresh save, no backup.
+    let mut config = TomlConfig {
+        name: "save-toml".to_string(),
+        limit: 10,
+        paths: vec!["a".to_string(), "b".to_string()],
+    };
+    save_toml(&loc, &config).unwrap();
+    assert_eq!(load_toml::<_, TomlConfig>(&loc).unwrap(), config);
+    assert!(!bak_loc.exists());
+    assert!(!tmp_loc.exists());
+
+    // case 2: mutate and save, older version is backed up.
+    let older = config.clone();
+    config.limit = 20;
+    config.paths.push("c".to_string());
+    save_toml(&loc, &config).unwrap();
+    assert_eq!(load_toml::<_, TomlConfig>(&loc).unwrap(), config);
+    assert_eq!(load_toml::<_, TomlConfig>(&bak_loc).unwrap(), older);
+    assert!(!tmp_loc.exists());
+
+    // case 3: backup always holds the previous version.
+    let older = config.clone();
+    config.name = "save-toml-again".to_string();
+    save_toml(&loc, &config).unwrap();
+    assert_eq!(load_toml::<_, TomlConfig>(&loc).unwrap(), config);
+    assert_eq!(load_toml::<_, TomlConfig>(&bak_loc).unwrap(), older);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
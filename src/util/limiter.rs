@@ -0,0 +1,104 @@
+//! Module `limiter` implement a counting semaphore to bound the number of
+//! open file descriptors.
+//!
+//! Each open [robt][crate::robt] snapshot hold one file descriptor for the
+//! index file, and one more if value-log is enabled. Services that open or
+//! clone several snapshots concurrently can exhaust the process limit on
+//! open files. Refer to [Index::open_with_limiter][crate::robt::Index].
+
+use std::{
+    fmt,
+    sync::{Arc, Condvar, Mutex},
+};
+
+use crate::{Error, Result};
+
+/// Counting semaphore, cloned values share the same permits.
+#[derive(Clone)]
+pub struct FdLimiter {
+    limit: usize,
+    inner: Arc<(Mutex<usize>, Condvar)>,
+}
+
+impl fmt::Debug for FdLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FdLimiter<{}/{}>", self.to_available(), self.limit)
+    }
+}
+
+impl FdLimiter {
+    /// Create a new limiter with `limit` permits.
+    pub fn new(limit: usize) -> FdLimiter {
+        FdLimiter {
+            limit,
+            inner: Arc::new((Mutex::new(limit), Condvar::new())),
+        }
+    }
+
+    /// Acquire a permit, block until a permit is available. Permit is
+    /// released when the returned value is dropped.
+    pub fn acquire(&self) -> Result<FdPermit> {
+        let (mu, cond) = self.inner.as_ref();
+        let mut avail = err_at!(Fatal, mu.lock())?;
+        while *avail == 0 {
+            avail = err_at!(Fatal, cond.wait(avail))?;
+        }
+        *avail -= 1;
+
+        Ok(FdPermit { limiter: self.clone() })
+    }
+
+    /// Acquire a permit if available, without blocking.
+    pub fn try_acquire(&self) -> Result<Option<FdPermit>> {
+        let (mu, _) = self.inner.as_ref();
+        let mut avail = err_at!(Fatal, mu.lock())?;
+        match *avail {
+            0 => Ok(None),
+            _ => {
+                *avail -= 1;
+                Ok(Some(FdPermit { limiter: self.clone() }))
+            }
+        }
+    }
+
+    /// Return the total number of permits.
+    pub fn to_limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Return the number of permits available.
+    pub fn to_available(&self) -> usize {
+        let (mu, _) = self.inner.as_ref();
+        mu.lock().map(|avail| *avail).unwrap_or(0)
+    }
+
+    fn release(&self) {
+        let (mu, cond) = self.inner.as_ref();
+        if let Ok(mut avail) = mu.lock() {
+            *avail += 1;
+            cond.notify_one();
+        }
+    }
+}
+
+/// Permit acquired from [FdLimiter], released on drop.
+pub struct FdPermit {
+    limiter: FdLimiter,
+}
+
+impl Drop for FdPermit {
+    fn drop(&mut self) {
+        self.limiter.release()
+    }
+}
+
+impl FdPermit {
+    /// Return the limiter from which this permit was acquired.
+    pub fn to_limiter(&self) -> FdLimiter {
+        self.limiter.clone()
+    }
+}
+
+#[cfg(test)]
+#[path = "limiter_test.rs"]
+mod limiter_test;
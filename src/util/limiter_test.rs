@@ -0,0 +1,34 @@
+use std::{sync::mpsc, thread, time};
+
+use super::*;
+
+#[test]
+fn test_fd_limiter() {
+    let limiter = FdLimiter::new(4);
+    assert_eq!(limiter.to_limit(), 4);
+
+    let mut permits: Vec<FdPermit> = (0..4).map(|_| limiter.acquire().unwrap()).collect();
+    assert_eq!(limiter.to_available(), 0);
+    assert!(limiter.try_acquire().unwrap().is_none());
+
+    let (tx, rx) = mpsc::channel();
+    let handle = {
+        let limiter = limiter.clone();
+        thread::spawn(move || {
+            let permit = limiter.acquire().unwrap();
+            tx.send(()).unwrap();
+            permit
+        })
+    };
+
+    let timeout = time::Duration::from_millis(100);
+    assert!(rx.recv_timeout(timeout).is_err());
+
+    permits.pop();
+    rx.recv_timeout(time::Duration::from_secs(10)).unwrap();
+    permits.push(handle.join().unwrap());
+    assert_eq!(limiter.to_available(), 0);
+
+    permits.drain(..);
+    assert_eq!(limiter.to_available(), 4);
+}
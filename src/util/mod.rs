@@ -1,6 +1,6 @@
 //! Module implement common utility functions and types.
 
-use cbordata::{Cbor, FromCbor, IntoCbor};
+use cbordata::{self as cbor, Cbor, FromCbor, IntoCbor};
 
 use std::{
     borrow::Borrow,
@@ -11,6 +11,8 @@ use crate::{dbs, Error, Result};
 
 mod cmdline;
 pub mod files;
+mod limiter;
+mod size;
 pub mod spinlock;
 pub mod thread;
 
@@ -18,8 +20,10 @@ pub mod thread;
 pub mod print;
 
 pub use cmdline::parse_os_args;
+pub use limiter::{FdLimiter, FdPermit};
+pub use size::{humanize_bytes, SizeArg};
 pub use spinlock::Spinlock;
-pub use thread::{Pool, Thread};
+pub use thread::{Mode, Pool, Thread};
 
 #[macro_export]
 macro_rules! check_remaining {
@@ -58,6 +62,75 @@ where
     Ok((err_at!(FailCbor, T::from_cbor(val))?, n))
 }
 
+/// Helper function to serialize value `T` implementing IntoCbor, into byte-string,
+/// in [canonical form][canonical]. Unlike [into_cbor_bytes] the returned bytes are
+/// deterministic for logically equal values, hence suitable for reproducible
+/// builds and content addressing.
+///
+/// * Integers, lengths and item-counts use the shortest possible encoding.
+/// * Indefinite length byte-strings, text-strings, arrays and maps are
+///   encoded as definite length items.
+/// * Map entries are sorted by their encoded key, shorter keys first and
+///   lexically otherwise. Duplicate keys are reported as error.
+///
+/// Floating point numbers, simple values and tagged items are encoded as is.
+///
+/// [canonical]: https://tools.ietf.org/html/rfc7049#section-3.9
+pub fn into_cbor_canonical<T>(val: T) -> Result<Vec<u8>>
+where
+    T: IntoCbor,
+{
+    let val = to_canonical_cbor(err_at!(FailCbor, val.into_cbor())?)?;
+
+    let mut data: Vec<u8> = vec![];
+    let n = err_at!(FailCbor, val.encode(&mut data))?;
+    if n != data.len() {
+        err_at!(Fatal, msg: "cbor encoding len mistmatch {} {}", n, data.len())
+    } else {
+        Ok(data)
+    }
+}
+
+fn to_canonical_cbor(val: Cbor) -> Result<Cbor> {
+    use std::convert::TryFrom;
+
+    let to_info = |n: usize| -> Result<cbor::Info> {
+        Ok(err_at!(FailConvert, u64::try_from(n))?.into())
+    };
+
+    let val = match val {
+        Cbor::Major0(_, num) => Cbor::Major0(num.into(), num),
+        Cbor::Major1(_, num) => Cbor::Major1(num.into(), num),
+        Cbor::Major2(_, byts) => Cbor::Major2(to_info(byts.len())?, byts),
+        Cbor::Major3(_, text) => Cbor::Major3(to_info(text.len())?, text),
+        Cbor::Major4(_, items) => {
+            let mut list = Vec::with_capacity(items.len());
+            for item in items.into_iter() {
+                list.push(to_canonical_cbor(item)?);
+            }
+            Cbor::Major4(to_info(list.len())?, list)
+        }
+        Cbor::Major5(_, items) => {
+            let mut list = Vec::with_capacity(items.len());
+            for (key, value) in items.into_iter() {
+                let kbytes = into_cbor_canonical(key.clone())?;
+                list.push((kbytes, key, to_canonical_cbor(value)?));
+            }
+            list.sort_by(|a, b| (a.0.len(), &a.0).cmp(&(b.0.len(), &b.0)));
+            if let Some(w) = list.windows(2).find(|w| w[0].0 == w[1].0) {
+                err_at!(InvalidInput, msg: "duplicate cbor map key {:?}", w[0].0)?
+            }
+
+            let list: Vec<(cbor::Key, Cbor)> =
+                list.into_iter().map(|(_, key, value)| (key, value)).collect();
+            Cbor::Major5(to_info(list.len())?, list)
+        }
+        val => val,
+    };
+
+    Ok(val)
+}
+
 pub fn to_start_end<G, K>(within: G) -> (Bound<K>, Bound<K>)
 where
     K: Clone,
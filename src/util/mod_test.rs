@@ -20,3 +20,58 @@ fn test_as_sharded_array() {
         }
     }
 }
+
+// a cbor-map, whose entries are encoded in the order they were inserted.
+struct Doc(Vec<(String, Cbor)>);
+
+impl IntoCbor for Doc {
+    fn into_cbor(self) -> cbordata::Result<Cbor> {
+        let n = self.0.len() as u64;
+        let items = self.0.into_iter().map(|(k, v)| (cbor::Key::Text(k), v)).collect();
+        Ok(Cbor::Major5(n.into(), items))
+    }
+}
+
+#[test]
+fn test_into_cbor_canonical() {
+    let fields = vec![
+        ("planet".to_string(), "earth".to_string().into_cbor().unwrap()),
+        ("size".to_string(), 6371_u64.into_cbor().unwrap()),
+        ("moons".to_string(), vec![1_u64].into_cbor().unwrap()),
+        ("z".to_string(), 1_000_000_u64.into_cbor().unwrap()),
+    ];
+    let mut rev = fields.clone();
+    rev.reverse();
+
+    let a = into_cbor_bytes(Doc(fields.clone())).unwrap();
+    let b = into_cbor_bytes(Doc(rev.clone())).unwrap();
+    assert_ne!(a, b);
+
+    let a = into_cbor_canonical(Doc(fields.clone())).unwrap();
+    let b = into_cbor_canonical(Doc(rev)).unwrap();
+    assert_eq!(a, b);
+
+    // shorter keys sort first.
+    let val = Cbor::decode(&mut a.as_slice()).unwrap().0;
+    match val {
+        Cbor::Major5(_, items) => {
+            let keys: Vec<cbor::Key> = items.into_iter().map(|(k, _)| k).collect();
+            assert_eq!(keys[0], cbor::Key::Text("z".to_string()));
+            assert_eq!(keys[1], cbor::Key::Text("size".to_string()));
+        }
+        _ => unreachable!(),
+    }
+
+    // canonical form is stable across a round trip.
+    let val = Cbor::decode(&mut a.as_slice()).unwrap().0;
+    assert_eq!(into_cbor_canonical(val).unwrap(), a);
+
+    // values without maps encode the same in both forms.
+    let val = vec![10_u64, 300, 70_000];
+    assert_eq!(into_cbor_canonical(val.clone()).unwrap(), into_cbor_bytes(val).unwrap());
+
+    // duplicate keys are rejected.
+    let mut dups = fields.clone();
+    dups.push(fields[0].clone());
+    assert!(into_cbor_canonical(Doc(dups)).is_err());
+}
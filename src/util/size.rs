@@ -0,0 +1,114 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use std::str::FromStr;
+
+use crate::{Error, Result};
+
+lazy_static! {
+    static ref RANGE_RE: Regex = {
+        let patt = r"^([0-9]+[kKmMgGtT]?)(\.\.[0-9]+[kKmMgGtT]?)?$";
+        Regex::new(patt).unwrap()
+    };
+    static ref LIST_RE: Regex = {
+        let patt = r"^([0-9]+[kKmMgGtT]?)(,[0-9]+[kKmMgGtT]?)*$";
+        Regex::new(patt).unwrap()
+    };
+}
+
+/// Format `bytes` in human readable form, rounded down to the largest unit,
+/// like `512B`, `10KB`, `100MB`, `1GB`, `2TB`.
+pub fn humanize_bytes(bytes: usize) -> String {
+    let bytes = bytes as u64;
+    if bytes < 1024 {
+        format!("{}B", bytes)
+    } else if bytes < (1024 * 1024) {
+        format!("{}KB", bytes / 1024)
+    } else if bytes < (1024 * 1024 * 1024) {
+        format!("{}MB", bytes / (1024 * 1024))
+    } else if bytes < (1024 * 1024 * 1024 * 1024) {
+        format!("{}GB", bytes / (1024 * 1024 * 1024))
+    } else {
+        format!("{}TB", bytes / (1024 * 1024 * 1024 * 1024))
+    }
+}
+
+/// Size argument, typically parsed from command line. Sizes can be suffixed
+/// with `k`, `m`, `g`, `t` (case insensitive) for KB, MB, GB and TB.
+///
+/// * `128` or `1k` parse as `Range(Some(x), None)`, a single size.
+/// * `1k..1m` parse as `Range(Some(x), Some(y))`, all candidate sizes between
+///   `x` and `y`, refer to [SizeArg::to_sizes].
+/// * `512,1024,4k` parse as `List(sizes)`.
+///
+/// Any other input parse as `SizeArg::None`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SizeArg {
+    None,
+    Range(Option<isize>, Option<isize>),
+    List(Vec<isize>),
+}
+
+impl FromStr for SizeArg {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<SizeArg> {
+        if let Some(captrs) = RANGE_RE.captures(s) {
+            let x = captrs.get(1).map(|m| SizeArg::to_isize(m.as_str()));
+            let y = captrs.get(2).map(|m| SizeArg::to_isize(&m.as_str()[2..]));
+            return Ok(SizeArg::Range(x.transpose()?, y.transpose()?));
+        }
+
+        match LIST_RE.is_match(s) {
+            true => {
+                let mut sizes = vec![];
+                for item in s.split(',') {
+                    sizes.push(SizeArg::to_isize(item)?);
+                }
+                Ok(SizeArg::List(sizes))
+            }
+            false => Ok(SizeArg::None),
+        }
+    }
+}
+
+impl SizeArg {
+    /// Parse a single size, with an optional `k`, `m`, `g`, `t` suffix.
+    pub fn to_isize(s: &str) -> Result<isize> {
+        let (s, amp) = match s.chars().last() {
+            Some('k') | Some('K') => (&s[..s.len() - 1], 1024),
+            Some('m') | Some('M') => (&s[..s.len() - 1], 1024 * 1024),
+            Some('g') | Some('G') => (&s[..s.len() - 1], 1024 * 1024 * 1024),
+            Some('t') | Some('T') => (&s[..s.len() - 1], 1024 * 1024 * 1024 * 1024),
+            _ => (s, 1),
+        };
+
+        let n = err_at!(InvalidInput, s.parse::<isize>(), "size {:?}", s)?;
+        match n.checked_mul(amp) {
+            Some(n) => Ok(n),
+            None => err_at!(InvalidInput, msg: "size overflow {:?}", s),
+        }
+    }
+
+    /// Resolve this argument into a list of sizes. For `Range(x, y)` pick
+    /// sizes from `candidates`, which shall be sorted, falling within `x..=y`.
+    pub fn to_sizes(&self, candidates: &[isize]) -> Vec<isize> {
+        let (from, till) = match self {
+            SizeArg::None => return vec![],
+            SizeArg::List(sizes) => return sizes.clone(),
+            SizeArg::Range(None, _) => return vec![],
+            SizeArg::Range(Some(x), None) => return vec![*x],
+            SizeArg::Range(Some(x), Some(y)) => (*x, *y),
+        };
+        candidates
+            .iter()
+            .skip_while(|x| **x < from)
+            .take_while(|x| **x <= till)
+            .copied()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+#[path = "size_test.rs"]
+mod size_test;
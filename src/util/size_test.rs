@@ -0,0 +1,52 @@
+use super::*;
+
+#[test]
+fn test_humanize_bytes() {
+    assert_eq!(humanize_bytes(0), "0B");
+    assert_eq!(humanize_bytes(1023), "1023B");
+    assert_eq!(humanize_bytes(1024), "1KB");
+    assert_eq!(humanize_bytes(10 * 1024 * 1024 + 1), "10MB");
+    assert_eq!(humanize_bytes(3 * 1024 * 1024 * 1024), "3GB");
+}
+
+#[test]
+fn test_size_arg_suffix() {
+    assert_eq!(SizeArg::to_isize("128").unwrap(), 128);
+    assert_eq!(SizeArg::to_isize("4k").unwrap(), 4 * 1024);
+    assert_eq!(SizeArg::to_isize("4K").unwrap(), 4 * 1024);
+    assert_eq!(SizeArg::to_isize("2m").unwrap(), 2 * 1024 * 1024);
+    assert_eq!(SizeArg::to_isize("1G").unwrap(), 1024 * 1024 * 1024);
+    assert_eq!(SizeArg::to_isize("1t").unwrap(), 1024 * 1024 * 1024 * 1024);
+    assert!(SizeArg::to_isize("").is_err());
+    assert!(SizeArg::to_isize("k").is_err());
+    assert!(SizeArg::to_isize("1x").is_err());
+}
+
+#[test]
+fn test_size_arg_range() {
+    let arg: SizeArg = "128".parse().unwrap();
+    assert_eq!(arg, SizeArg::Range(Some(128), None));
+    assert_eq!(arg.to_sizes(&[128, 256]), vec![128]);
+
+    let arg: SizeArg = "1k..1m".parse().unwrap();
+    assert_eq!(arg, SizeArg::Range(Some(1024), Some(1024 * 1024)));
+    let candidates = [512, 1024, 10 * 1024, 1024 * 1024, 10 * 1024 * 1024];
+    assert_eq!(arg.to_sizes(&candidates), vec![1024, 10 * 1024, 1024 * 1024]);
+
+    let arg: SizeArg = "2m..1g".parse().unwrap();
+    assert_eq!(arg.to_sizes(&candidates), vec![10 * 1024 * 1024]);
+}
+
+#[test]
+fn test_size_arg_list() {
+    let arg: SizeArg = "512,1024".parse().unwrap();
+    assert_eq!(arg, SizeArg::List(vec![512, 1024]));
+
+    let arg: SizeArg = "512,4k,1M".parse().unwrap();
+    assert_eq!(arg, SizeArg::List(vec![512, 4096, 1024 * 1024]));
+    assert_eq!(arg.to_sizes(&[]), vec![512, 4096, 1024 * 1024]);
+
+    let arg: SizeArg = "512;1024".parse().unwrap();
+    assert_eq!(arg, SizeArg::None);
+    assert!(arg.to_sizes(&[512, 1024]).is_empty());
+}
@@ -6,7 +6,10 @@
 
 use std::{
     mem,
-    sync::{mpsc, Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering::SeqCst},
+        mpsc, Arc, Mutex,
+    },
     thread,
 };
 
@@ -162,13 +165,29 @@ impl<Q, R> Tx<Q, R> {
 /// Refer to [Thread::new] for details.
 pub type Rx<Q, R = ()> = mpsc::Receiver<(Q, Option<mpsc::Sender<R>>)>;
 
+/// Shutdown mode for [Pool].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    /// Process all the queued tasks and then exit.
+    Drain,
+    /// Stop after the current task, tasks queued with the pool are dropped and
+    /// returned back to the caller.
+    Immediate,
+}
+
 /// Create a pool of threads of same type.
 ///
 /// That is, the thread's main-loop takes the same Request type and return the same
 /// Response type. Load balancing across the threads are handled in random fashion.
+///
+/// Tasks are queued with the pool and handed over to the thread one at a time,
+/// refer to [Pool::shutdown] on how queued tasks are handled while shutting down.
 pub struct Pool<Q, R = (), T = ()> {
     name: String,
+    txs: Vec<Arc<Mutex<Tx<Q, R>>>>,
+    relays: Vec<thread::JoinHandle<Vec<Q>>>,
     threads: Vec<Thread<Q, R, T>>,
+    immediate: Arc<AtomicBool>,
     pool_size: usize,
     chan_size: Option<usize>,
 }
@@ -178,7 +197,10 @@ impl<Q, R, T> Pool<Q, R, T> {
     pub fn new(name: &str) -> Pool<Q, R, T> {
         Pool {
             name: name.to_string(),
+            txs: Vec::default(),
+            relays: Vec::default(),
             threads: Vec::default(),
+            immediate: Arc::new(AtomicBool::new(false)),
             pool_size: num_cpus::get(),
             chan_size: None,
         }
@@ -189,7 +211,10 @@ impl<Q, R, T> Pool<Q, R, T> {
     pub fn new_sync(name: &str, chan_size: usize) -> Pool<Q, R, T> {
         Pool {
             name: name.to_string(),
+            txs: Vec::default(),
+            relays: Vec::default(),
             threads: Vec::default(),
+            immediate: Arc::new(AtomicBool::new(false)),
             pool_size: num_cpus::get(),
             chan_size: Some(chan_size),
         }
@@ -203,27 +228,67 @@ impl<Q, R, T> Pool<Q, R, T> {
     /// Spawn all the threads configured for this pool.
     pub fn spawn<F, N>(&mut self, main_loop: F)
     where
+        Q: 'static + Send,
+        R: 'static + Send,
         F: 'static + FnOnce(Rx<Q, R>) -> N + Send + Clone,
         N: 'static + Send + FnOnce() -> T,
         T: 'static + Send,
     {
         for i in 0..self.pool_size {
             let (name, main_loop) = (format!("{}-{}", self.name, i), main_loop.clone());
-            let thread = match self.chan_size {
-                Some(chan_size) => Thread::new_sync(&name, chan_size, main_loop),
-                None => Thread::new(&name, main_loop),
+            // rendezvous channel, thread is handed one task at a time.
+            let th = Thread::new_sync(&name, 0, main_loop);
+            let (tx, rx) = match self.chan_size {
+                Some(chan_size) => {
+                    let (tx, rx) = mpsc::sync_channel(chan_size);
+                    (Tx::S(tx), rx)
+                }
+                None => {
+                    let (tx, rx) = mpsc::channel();
+                    (Tx::N(tx), rx)
+                }
             };
-            self.threads.push(thread)
+
+            let (th_tx, immediate) = (th.to_tx(), Arc::clone(&self.immediate));
+            self.relays.push(thread::spawn(move || relay(rx, th_tx, immediate)));
+            self.txs.push(Arc::new(Mutex::new(tx)));
+            self.threads.push(th)
         }
     }
 
     /// Shutdown all threads, wait for them to exit and cleanup this pool.
+    /// Same as `shutdown(Mode::Drain)`.
     pub fn close_wait(self) -> Result<Vec<T>> {
+        Ok(self.shutdown(Mode::Drain)?.0)
+    }
+
+    /// Shutdown all threads and wait for them to exit. With [Mode::Drain] all
+    /// queued tasks are processed before exit. With [Mode::Immediate] threads
+    /// exit after completing the task they have picked up, rest of the queued
+    /// tasks are dropped and returned, in the order they were queued for each
+    /// thread. Response channels of dropped requests are closed.
+    ///
+    /// Return the result from each thread and the list of dropped tasks.
+    pub fn shutdown(mut self, mode: Mode) -> Result<(Vec<T>, Vec<Q>)> {
+        if let Mode::Immediate = mode {
+            self.immediate.store(true, SeqCst);
+        }
+        mem::drop(mem::take(&mut self.txs));
+
+        let mut tasks = vec![];
+        for relay in self.relays.drain(..) {
+            match relay.join() {
+                Ok(dropped) => tasks.extend(dropped),
+                Err(err) => err_at!(ThreadFail, msg: "relay fail {:?}", err)?,
+            }
+        }
+
         let mut results = vec![];
-        for th in self.threads.into_iter() {
+        for th in self.threads.drain(..) {
             results.push(th.join()?)
         }
-        Ok(results)
+
+        Ok((results, tasks))
     }
 }
 
@@ -235,25 +300,45 @@ impl<Q, R, T> Pool<Q, R, T> {
 
     /// Post a message to thread and don't wait for response.
     pub fn post(&self, msg: Q) -> Result<()> {
-        let n: usize = rand::random::<usize>() % self.threads.len();
-        let th: &Thread<Q, R, T> = &self.threads[n];
-
-        th.to_tx().post(msg)
+        self.to_tx().post(msg)
     }
 
     /// Send a request message to thread and wait for a response.
     pub fn request(&self, request: Q) -> Result<R> {
-        let n: usize = rand::random::<usize>() % self.threads.len();
-        let th: &Thread<Q, R, T> = &self.threads[n];
-
-        th.to_tx().request(request)
+        self.to_tx().request(request)
     }
 
     /// Send a request message to thread and wait for a response.
     pub fn request_tx(&self, request: Q, rt_tx: mpsc::Sender<R>) -> Result<()> {
-        let n: usize = rand::random::<usize>() % self.threads.len();
-        let th: &Thread<Q, R, T> = &self.threads[n];
+        self.to_tx().request_tx(request, rt_tx)
+    }
+
+    fn to_tx(&self) -> Tx<Q, R> {
+        let n: usize = rand::random::<usize>() % self.txs.len();
+        self.txs[n].lock().unwrap().clone()
+    }
+}
 
-        th.to_tx().request_tx(request, rt_tx)
+// forward tasks queued with the pool to its thread, one at a time. Once the pool
+// is shutdown in immediate mode, rest of the queued tasks are returned back.
+fn relay<Q, R>(rx: Rx<Q, R>, tx: Tx<Q, R>, immediate: Arc<AtomicBool>) -> Vec<Q> {
+    let mut dropped = vec![];
+    for (msg, rt_tx) in rx {
+        if immediate.load(SeqCst) {
+            dropped.push(msg);
+            continue;
+        }
+        let res = match rt_tx {
+            Some(rt_tx) => tx.request_tx(msg, rt_tx),
+            None => tx.post(msg),
+        };
+        if res.is_err() {
+            break; // thread has exited.
+        }
     }
+    dropped
 }
+
+#[cfg(test)]
+#[path = "thread_test.rs"]
+mod thread_test;
@@ -0,0 +1,75 @@
+use rand::{prelude::random, rngs::StdRng, Rng, SeedableRng};
+
+use std::time;
+
+use super::*;
+
+#[test]
+fn test_pool_shutdown_drain() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_pool_shutdown_drain {}", seed);
+
+    let pool_size = [1, 2, 4, 8][rng.gen::<usize>() % 4];
+    let n_tasks = [0, 1, 10, 1_000][rng.gen::<usize>() % 4];
+    println!("test_pool_shutdown_drain pool_size:{} n_tasks:{}", pool_size, n_tasks);
+
+    let mut pool: Pool<u64, (), Vec<u64>> = Pool::new_sync("test-drain", 16);
+    pool.set_pool_size(pool_size);
+    pool.spawn(|rx: Rx<u64, ()>| {
+        move || {
+            let mut tasks = vec![];
+            for (task, _) in rx {
+                thread::sleep(time::Duration::from_micros(100));
+                tasks.push(task);
+            }
+            tasks
+        }
+    });
+
+    for task in 0..n_tasks {
+        pool.post(task).unwrap();
+    }
+    let (results, dropped) = pool.shutdown(Mode::Drain).unwrap();
+
+    assert!(dropped.is_empty(), "{:?}", dropped);
+    assert_eq!(results.len(), pool_size);
+    let mut tasks: Vec<u64> = results.into_iter().flatten().collect();
+    tasks.sort_unstable();
+    assert_eq!(tasks, (0..n_tasks).collect::<Vec<u64>>());
+}
+
+#[test]
+fn test_pool_shutdown_immediate() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_pool_shutdown_immediate {}", seed);
+
+    let n_tasks = [10, 100, 1_000][rng.gen::<usize>() % 3];
+    println!("test_pool_shutdown_immediate n_tasks:{}", n_tasks);
+
+    let mut pool: Pool<u64, (), Vec<u64>> = Pool::new("test-immediate");
+    pool.set_pool_size(1);
+    pool.spawn(|rx: Rx<u64, ()>| {
+        move || {
+            let mut tasks = vec![];
+            for (task, _) in rx {
+                thread::sleep(time::Duration::from_millis(10));
+                tasks.push(task);
+            }
+            tasks
+        }
+    });
+
+    for task in 0..n_tasks {
+        pool.post(task).unwrap();
+    }
+    let (results, dropped) = pool.shutdown(Mode::Immediate).unwrap();
+    let processed = results.into_iter().flatten().collect::<Vec<u64>>();
+    println!("processed:{} dropped:{}", processed.len(), dropped.len());
+
+    // tasks are processed in queue order, rest of them are returned back.
+    assert!(!dropped.is_empty());
+    let tasks: Vec<u64> = processed.into_iter().chain(dropped.into_iter()).collect();
+    assert_eq!(tasks, (0..n_tasks).collect::<Vec<u64>>());
+}
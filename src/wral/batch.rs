@@ -3,6 +3,7 @@ use cbordata::Cborize;
 
 use std::{
     cmp,
+    convert::TryFrom,
     fmt::{self, Display},
     fs,
     io::{self, Read, Seek},
@@ -12,7 +13,7 @@ use std::{
 use crate::{
     util,
     wral::{self, state},
-    Error, Result,
+    Error, Result, ResultExt,
 };
 
 pub struct Worker<S> {
@@ -43,14 +44,45 @@ impl<S> Worker<S> {
         Ok(())
     }
 
-    pub fn flush(&mut self, file: &mut fs::File) -> Result<Option<Index>>
+    /// Flush entries as a single batch into `file`. When `locate` is true,
+    /// also return the (seqno, file-position) of each entry in the batch.
+    pub fn flush(
+        &mut self,
+        file: &mut fs::File,
+        locate: bool,
+    ) -> Result<Option<(Index, Vec<(u64, u64)>)>>
+    where
+        S: state::State,
+    {
+        let fpos = err_at!(IOError, file.metadata())?.len();
+        self.flush_at(file, fpos, locate)
+    }
+
+    /// Same as [Worker::flush], but `file` is written at its current cursor,
+    /// which is expected to be at `fpos`. Useful for preallocated files where
+    /// file length is not the write head.
+    pub fn flush_at(
+        &mut self,
+        file: &mut fs::File,
+        fpos: u64,
+        locate: bool,
+    ) -> Result<Option<(Index, Vec<(u64, u64)>)>>
     where
         S: state::State,
     {
         if !self.entries.is_empty() {
-            let fpos = err_at!(IOError, file.metadata())?.len();
             let first_seqno = self.entries.first().map(wral::Entry::to_seqno).unwrap();
             let last_seqno = self.entries.last().map(wral::Entry::to_seqno).unwrap();
+            let items = match locate {
+                true => {
+                    let iter = self
+                        .entries
+                        .iter()
+                        .map(|e| Ok((e.to_seqno(), util::into_cbor_bytes(e.clone())?)));
+                    iter.collect::<Result<Vec<(u64, Vec<u8>)>>>()?
+                }
+                false => vec![],
+            };
             let batch = Batch {
                 first_seqno,
                 last_seqno,
@@ -58,21 +90,41 @@ impl<S> Worker<S> {
                 entries: self.entries.drain(..).collect(),
             };
 
-            let length = {
+            let (length, offsets) = {
                 let data = util::into_cbor_bytes(batch)?;
+                let offsets = locate_entries(&data, &items, fpos)?;
                 util::files::sync_write(file, &data)?;
-                data.len()
+                (data.len(), offsets)
             };
 
             let index = Index::new(fpos, length, first_seqno, last_seqno);
             self.batches.push(index.clone());
-            Ok(Some(index))
+            Ok(Some((index, offsets)))
         } else {
             Ok(None)
         }
     }
 }
 
+// entries are the last item in a batch, serialized back to back, locate them
+// from the tail of the serialized batch.
+fn locate_entries(
+    data: &[u8],
+    items: &[(u64, Vec<u8>)],
+    fpos: u64,
+) -> Result<Vec<(u64, u64)>> {
+    let mut offsets = vec![(0, 0); items.len()];
+    let mut off = data.len();
+    for (i, (seqno, item)) in items.iter().enumerate().rev() {
+        off = match off.checked_sub(item.len()) {
+            Some(off) if &data[off..(off + item.len())] == item.as_slice() => off,
+            _ => err_at!(Fatal, msg: "entry {} not found in batch at {}", seqno, fpos)?,
+        };
+        offsets[i] = (*seqno, fpos + err_at!(FailConvert, u64::try_from(off))?);
+    }
+    Ok(offsets)
+}
+
 impl<S> Worker<S> {
     pub fn to_last_seqno(&self) -> Option<u64> {
         match self.entries.len() {
@@ -156,10 +208,11 @@ impl Batch {
     const ID: u32 = 0x0;
 
     pub fn from_index(index: Index, file: &mut fs::File) -> Result<Batch> {
-        err_at!(IOError, file.seek(io::SeekFrom::Start(index.fpos)))?;
+        let fpos = index.fpos;
+        err_at!(IOError, file.seek(io::SeekFrom::Start(fpos))).at_fpos(fpos)?;
         let mut buf = vec![0; index.length];
-        err_at!(IOError, file.read_exact(&mut buf))?;
-        Ok(util::from_cbor_bytes(&buf)?.0)
+        err_at!(IOError, file.read_exact(&mut buf)).at_fpos(fpos)?;
+        Ok(util::from_cbor_bytes(&buf).at_fpos(fpos)?.0)
     }
 
     #[inline]
@@ -216,6 +269,12 @@ impl Index {
     pub fn to_last_seqno(&self) -> u64 {
         self.last_seqno
     }
+
+    /// Return the file-position just after this batch.
+    #[inline]
+    pub fn to_next_fpos(&self) -> u64 {
+        self.fpos + (self.length as u64)
+    }
 }
 
 #[cfg(test)]
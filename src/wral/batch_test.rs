@@ -109,7 +109,7 @@ fn test_wral_worker() {
             assert_eq!(entries.last().map(|e| e.to_seqno()), worker.to_last_seqno())
         }
 
-        if let Some(x) = worker.flush(&mut file).unwrap() {
+        if let Some((x, _)) = worker.flush(&mut file, false).unwrap() {
             index.push(x)
         };
 
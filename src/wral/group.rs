@@ -0,0 +1,166 @@
+use std::{
+    mem,
+    sync::{Arc, RwLock},
+};
+
+use crate::{
+    util,
+    wral::{
+        self,
+        journals::{Journals, Req, Res},
+        Config, Wal,
+    },
+    Error, Result,
+};
+
+/// Group of named write-ahead-logs, under the same directory, sharing a
+/// single flush thread.
+///
+/// Each named log keep its own journal files, seqno space and replay, while
+/// writes across all the logs are batched by the same flush thread, thus
+/// amortizing the cost of fsync. Use [WalGroup::to_wal] to obtain a [Wal]
+/// handle for a named log, handles can be cloned and shared across threads.
+///
+/// `config.dir`, `config.journal_limit`, `config.fsync` and `config.preallocate`
+/// apply to all the logs in the group, `config.name` names the group itself.
+pub struct WalGroup<S = wral::NoState> {
+    config: Config,
+    names: Vec<String>,
+
+    logs: Vec<Arc<RwLock<Journals<S>>>>,
+    th: Arc<util::thread::Thread<Req, Res, Result<u64>>>,
+    tx: util::thread::Tx<Req, Res>,
+}
+
+impl<S> WalGroup<S> {
+    /// Create a new group of logs, one for each of `names`. Older journals
+    /// matching any of the `names` shall be purged.
+    pub fn create(config: Config, names: &[&str], state: S) -> Result<WalGroup<S>>
+    where
+        S: wral::State,
+    {
+        Self::validate_names(names)?;
+
+        let mut logs = vec![];
+        for name in names.iter() {
+            let config = Self::to_log_config(&config, name);
+            let (seqno, journals, journal) =
+                Wal::create_journals(&config, state.clone())?;
+            logs.push(Journals::new(config, seqno, journals, journal));
+        }
+
+        Self::start(config, names, logs)
+    }
+
+    /// Load an existing group of logs, one for each of `names`, refer to
+    /// [Wal::load] for details. Missing logs are started afresh.
+    pub fn load(config: Config, names: &[&str]) -> Result<WalGroup<S>>
+    where
+        S: wral::State,
+    {
+        Self::validate_names(names)?;
+
+        let mut logs = vec![];
+        for name in names.iter() {
+            let config = Self::to_log_config(&config, name);
+            let (seqno, journals, journal) = Wal::<S>::load_journals(&config)?;
+            logs.push(Journals::new(config, seqno, journals, journal));
+        }
+
+        Self::start(config, names, logs)
+    }
+
+    fn start(
+        config: Config,
+        names: &[&str],
+        logs: Vec<Arc<RwLock<Journals<S>>>>,
+    ) -> Result<WalGroup<S>>
+    where
+        S: wral::State,
+    {
+        let (th, tx) = Journals::start_group(config.clone(), logs.clone())?;
+        let names = names.iter().map(|n| n.to_string()).collect();
+
+        let val = WalGroup { config, names, logs, th: Arc::new(th), tx };
+
+        Ok(val)
+    }
+
+    fn validate_names(names: &[&str]) -> Result<()> {
+        for (i, name) in names.iter().enumerate() {
+            if name.is_empty() {
+                err_at!(InvalidInput, msg: "empty log name in group")?
+            }
+            if names[..i].contains(name) {
+                err_at!(InvalidInput, msg: "duplicate log name {:?} in group", name)?
+            }
+        }
+        Ok(())
+    }
+
+    fn to_log_config(config: &Config, name: &str) -> Config {
+        Config { name: name.to_string(), ..config.clone() }
+    }
+
+    /// Close the group, return the last seqno of each log in the same order
+    /// as the log names. Return None if [Wal] handles into this group are
+    /// still alive.
+    pub fn close(self) -> Result<Option<Vec<u64>>> {
+        self.shutdown(false /*purge*/)
+    }
+
+    /// Close the group and purge all its logs, refer to [WalGroup::close].
+    pub fn purge(self) -> Result<Option<Vec<u64>>> {
+        self.shutdown(true /*purge*/)
+    }
+
+    fn shutdown(self, purge: bool) -> Result<Option<Vec<u64>>> {
+        match Arc::try_unwrap(self.th) {
+            Ok(th) => {
+                mem::drop(self.tx);
+                th.join()??;
+
+                let mut seqnos = vec![];
+                for w in self.logs.into_iter() {
+                    let w = match Arc::try_unwrap(w) {
+                        Ok(w) => err_at!(IPCFail, w.into_inner())?,
+                        Err(_) => return Ok(None),
+                    };
+                    match purge {
+                        true => seqnos.push(w.purge()?),
+                        false => seqnos.push(w.close()?),
+                    }
+                }
+                Ok(Some(seqnos))
+            }
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+impl<S> WalGroup<S> {
+    /// Return the name of logs in this group.
+    pub fn to_names(&self) -> Vec<String> {
+        self.names.clone()
+    }
+
+    /// Return a [Wal] handle for log `name`. Operations on the handle are
+    /// logged only into the named log, while sharing the group's flush thread.
+    pub fn to_wal(&self, name: &str) -> Result<Wal<S>> {
+        match self.names.iter().position(|n| n == name) {
+            Some(log) => {
+                let config = Self::to_log_config(&self.config, name);
+                let w = Arc::clone(&self.logs[log]);
+                let th = Arc::clone(&self.th);
+                Ok(Wal::from_group(config, log, w, th, self.tx.clone()))
+            }
+            None => err_at!(
+                InvalidInput, msg: "log {:?} not in group {}", name, self.config.name
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "group_test.rs"]
+mod group_test;
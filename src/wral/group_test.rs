@@ -0,0 +1,60 @@
+use rand::{prelude::random, rngs::StdRng, Rng, SeedableRng};
+
+use std::{env, path};
+
+use super::*;
+
+#[test]
+fn test_wral_group() {
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_wral_group {}", seed);
+
+    let name = "test-wral-group";
+    let dir: path::PathBuf = vec![env::temp_dir(), name.into()].into_iter().collect();
+    let fsync: bool = rng.gen();
+    let config =
+        Config::new(dir.as_os_str(), name).set_journal_limit(1000).set_fsync(fsync);
+    println!("test_wral_group fsync:{}", fsync);
+
+    let names = ["log-a", "log-b", "log-c"];
+    let group = WalGroup::create(config.clone(), &names, wral::NoState).unwrap();
+    assert_eq!(group.to_names(), names.to_vec());
+    assert!(group.to_wal("log-d").is_err());
+
+    // interleaved writes across the logs, each log has its own seqno space.
+    let wals: Vec<Wal> = names.iter().map(|n| group.to_wal(n).unwrap()).collect();
+    let mut entries: Vec<Vec<wral::Entry>> = names.iter().map(|_| vec![]).collect();
+    for _i in 0..3000 {
+        let off = rng.gen::<usize>() % names.len();
+        let op: Vec<u8> = (0..(rng.gen::<usize>() % 100)).map(|_| rng.gen()).collect();
+        let seqno = wals[off].add_op(&op).unwrap();
+        assert_eq!(seqno, (entries[off].len() as u64) + 1);
+        entries[off].push(wral::Entry::new(seqno, op));
+    }
+
+    for (wal, entries) in wals.iter().zip(entries.iter()) {
+        assert_eq!(wal.commit().unwrap(), entries.len() as u64);
+        let items: Vec<wral::Entry> = wal.iter().unwrap().map(|e| e.unwrap()).collect();
+        assert_eq!(&items, entries);
+    }
+
+    // group can be closed only after all its handles are dropped.
+    mem::drop(wals);
+    let seqnos = group.close().unwrap().unwrap();
+    let refs: Vec<u64> = entries.iter().map(|e| e.len() as u64).collect();
+    assert_eq!(seqnos, refs);
+
+    // replay each log after re-loading the group.
+    let group = WalGroup::<wral::NoState>::load(config, &names).unwrap();
+    for (name, entries) in names.iter().zip(entries.iter()) {
+        let wal = group.to_wal(name).unwrap();
+        let items: Vec<wral::Entry> = wal.iter().unwrap().map(|e| e.unwrap()).collect();
+        assert_eq!(&items, entries);
+
+        let seqno = wal.add_op(b"after-load").unwrap();
+        assert_eq!(seqno, (entries.len() as u64) + 1);
+    }
+
+    assert!(group.purge().unwrap().is_some());
+}
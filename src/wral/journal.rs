@@ -1,15 +1,18 @@
 use cbordata::{Cbor, FromCbor};
+use fs2::FileExt;
 
 use std::{
     convert::TryFrom,
     ffi,
     fmt::{self, Display},
-    fs, ops, path, result, vec,
+    fs,
+    io::{self, Read, Seek},
+    ops, path, result, vec,
 };
 
 use crate::{
-    wral::{self, batch, files, state},
-    Error, Result,
+    wral::{self, batch, files, state, Config},
+    Error, Result, ResultExt,
 };
 
 // A journal is uniquely located by specifying the (`dir`, `name`, `num`). Where,
@@ -37,10 +40,13 @@ impl<S> Display for Journal<S> {
 
 // A journal can be in three state, Working, Archive and cold.
 enum InnerJournal<S> {
-    // Active journal, the latest journal, in the journal-set.
+    // Active journal, the latest journal, in the journal-set. `head` is the
+    // real write position, file may be preallocated upto `prealloc` bytes.
     Working {
         worker: batch::Worker<S>,
         file: Option<fs::File>,
+        head: u64,
+        prealloc: Option<u64>,
     },
     // All journals except lastest journal are archives, which means only
     // the metadata for each batch shall be stored.
@@ -54,30 +60,34 @@ enum InnerJournal<S> {
 }
 
 impl<S> Journal<S> {
-    /// Start a new journal under directory `dir`, with initial `state`. If a file
-    /// already exists with (name, num) under dir, then that journal shall be removed.
+    /// Start a new journal under directory `config.dir`, with initial `state`. If
+    /// a file already exists with (name, num) under dir, then that journal shall
+    /// be removed. If `config.preallocate` is true, journal file shall be
+    /// preallocated upto `config.journal_limit` bytes.
     ///
     /// Returned journal shall be in `Working` state.
-    pub fn start(
-        dir: &ffi::OsStr,
-        name: &str,
-        num: usize,
-        state: S,
-    ) -> Result<Journal<S>> {
+    pub fn start(config: &Config, num: usize, state: S) -> Result<Journal<S>> {
         let location: path::PathBuf = {
-            let file: ffi::OsString = files::make_filename(name, num);
-            [dir, &file].iter().collect()
+            let file: ffi::OsString = files::make_filename(&config.name, num);
+            [config.dir.as_os_str(), &file].iter().collect()
         };
 
         fs::remove_file(&location).ok(); // cleanup a single journal file
 
+        let prealloc = match config.preallocate {
+            true => Some(err_at!(FailConvert, u64::try_from(config.journal_limit))?),
+            false => None,
+        };
+
         Ok(Journal {
-            name: name.to_string(),
+            name: config.name.to_string(),
             num,
             location: location.into_os_string(),
             inner: InnerJournal::Working {
                 worker: batch::Worker::new(state),
                 file: None,
+                head: 0,
+                prealloc,
             },
         })
     }
@@ -106,6 +116,9 @@ impl<S> Journal<S> {
         let len = file.metadata().ok()?.len();
 
         while u64::try_from(fpos).ok()? < len {
+            if is_padding(&mut file, u64::try_from(fpos).ok()?).ok()? {
+                break;
+            }
             let (val, n) = Cbor::decode(&mut file).ok()?;
             let batch = batch::Batch::from_cbor(val).ok()?;
             index.push(batch::Index::new(
@@ -188,30 +201,77 @@ impl<S> Journal<S> {
         }
     }
 
-    pub fn flush(&mut self) -> Result<()>
+    /// Flush added entries as a batch. When `locate` is true, return the
+    /// (seqno, file-position) of each flushed entry.
+    pub fn flush(&mut self, locate: bool) -> Result<Vec<(u64, u64)>>
     where
         S: state::State,
     {
-        match &mut self.inner {
-            InnerJournal::Working { worker, file } if file.is_some() => {
-                worker.flush(file.as_mut().unwrap())?;
-                Ok(())
+        let res = match &mut self.inner {
+            InnerJournal::Working { worker, file, head, .. } if file.is_some() => {
+                worker.flush_at(file.as_mut().unwrap(), *head, locate)?
             }
-            InnerJournal::Working { worker, file } if worker.is_flush_required() => {
+            InnerJournal::Working { worker, file, head, prealloc }
+                if worker.is_flush_required() =>
+            {
+                // preallocated files are written from the start, not appended.
                 let jfile = {
                     let mut opts = fs::OpenOptions::new();
+                    match prealloc {
+                        Some(_) => opts.write(true),
+                        None => opts.append(true),
+                    };
                     let location = self.location.clone();
-                    err_at!(IOError, opts.append(true).create_new(true).open(&location))?
+                    err_at!(IOError, opts.create_new(true).open(&location))?
                 };
+                if let Some(size) = prealloc {
+                    err_at!(IOError, jfile.allocate(*size), "{:?}", self.location)?;
+                }
                 *file = Some(jfile);
-                worker.flush(file.as_mut().unwrap())?;
-                Ok(())
+                worker.flush_at(file.as_mut().unwrap(), *head, locate)?
+            }
+            InnerJournal::Working { .. } => None,
+            InnerJournal::Archive { .. } => unreachable!(),
+            InnerJournal::Cold { .. } => unreachable!(),
+        };
+
+        if let (InnerJournal::Working { head, .. }, Some((index, _))) =
+            (&mut self.inner, &res)
+        {
+            *head = index.to_next_fpos();
+        }
+
+        Ok(res.map(|(_, offsets)| offsets).unwrap_or_default())
+    }
+
+    /// Truncate a preallocated journal file to its real size, that is, upto
+    /// the last flushed batch. Shall be called before rotating or closing
+    /// the journal, no-op if journal is not preallocated.
+    pub fn truncate(&self) -> Result<()> {
+        match &self.inner {
+            InnerJournal::Working {
+                file: Some(file), head, prealloc: Some(_), ..
+            } => {
+                err_at!(IOError, file.set_len(*head), "{:?}", self.location)?;
+                err_at!(IOError, file.sync_all(), "{:?}", self.location)
             }
             InnerJournal::Working { .. } => Ok(()),
             InnerJournal::Archive { .. } => unreachable!(),
             InnerJournal::Cold { .. } => unreachable!(),
         }
     }
+
+    /// Read a single entry persisted at file-position `fpos`, refer to
+    /// [Journal::flush].
+    pub fn read_at(&self, fpos: u64) -> Result<wral::Entry> {
+        let mut file = {
+            let mut opts = fs::OpenOptions::new();
+            err_at!(IOError, opts.read(true).open(&self.location))?
+        };
+        err_at!(IOError, file.seek(io::SeekFrom::Start(fpos))).at_fpos(fpos)?;
+        let (val, _) = err_at!(FailCbor, Cbor::decode(&mut file)).at_fpos(fpos)?;
+        err_at!(FailCbor, wral::Entry::from_cbor(val)).at_fpos(fpos)
+    }
 }
 
 impl<S> Journal<S> {
@@ -238,12 +298,13 @@ impl<S> Journal<S> {
         }
     }
 
+    /// Return the size of journal file, upto the last flushed batch. For
+    /// preallocated journals this is less than the file length.
     pub fn file_size(&self) -> Result<usize> {
         let n = match &self.inner {
             InnerJournal::Working { file: None, .. } => 0,
-            InnerJournal::Working { file, .. } => {
-                let m = err_at!(IOError, file.as_ref().unwrap().metadata())?;
-                err_at!(FailConvert, usize::try_from(m.len()))?
+            InnerJournal::Working { head, .. } => {
+                err_at!(FailConvert, usize::try_from(*head))?
             }
             InnerJournal::Archive { .. } => unreachable!(),
             InnerJournal::Cold => unreachable!(),
@@ -287,6 +348,7 @@ pub struct IterJournal {
     index: vec::IntoIter<batch::Index>,  // list of all batches
     entries: vec::IntoIter<wral::Entry>, // list of entries in latest batch
     file: fs::File,
+    location: ffi::OsString,
 }
 
 impl IterJournal {
@@ -319,10 +381,20 @@ impl IterJournal {
             err_at!(IOError, opts.read(true).open(&journal.location))?
         };
 
-        Ok(IterJournal { range, batch, index, entries, file })
+        let location = journal.location.clone();
+        Ok(IterJournal { range, batch, index, entries, file, location })
     }
 }
 
+// preallocated journals, that were not truncated due to a crash, are padded
+// with zeros after the last batch. Batches never start with a ZERO byte.
+fn is_padding(file: &mut fs::File, fpos: u64) -> Result<bool> {
+    let mut byte = [0_u8; 1];
+    err_at!(IOError, file.read_exact(&mut byte)).at_fpos(fpos)?;
+    err_at!(IOError, file.seek(io::SeekFrom::Start(fpos))).at_fpos(fpos)?;
+    Ok(byte[0] == 0)
+}
+
 impl Iterator for IterJournal {
     type Item = Result<wral::Entry>;
 
@@ -335,7 +407,7 @@ impl Iterator for IterJournal {
                         self.batch = batch.into_iter(self.range.clone());
                         self.next()
                     }
-                    Err(err) => Some(Err(err)),
+                    Err(err) => Some(Err(err).at_file(&self.location)),
                 },
                 None => self.entries.next().map(Ok),
             },
@@ -14,7 +14,8 @@ fn test_wral_journal() {
     let name = "test_wral_journal";
     let dir = env::temp_dir().into_os_string();
     println!("test_wral_journal {:?}", dir);
-    let mut jn = Journal::start(&dir, name, 0, state::NoState).unwrap();
+    let config = Config::new(&dir, name);
+    let mut jn = Journal::start(&config, 0, state::NoState).unwrap();
     assert_eq!(jn.to_journal_number(), 0);
     assert_eq!(jn.len_batches(), 0);
     assert_eq!(jn.as_state().clone(), state::NoState);
@@ -42,7 +43,7 @@ fn test_wral_journal() {
 
         assert_eq!(jn.to_last_seqno(), Some(entries[offset - 1].to_seqno()));
 
-        jn.flush().unwrap();
+        jn.flush(false).unwrap();
         if n > 0 {
             n_batches += 1;
         }
@@ -4,7 +4,7 @@ use std::{
     borrow::BorrowMut,
     sync::{
         atomic::{AtomicU64, Ordering::SeqCst},
-        mpsc, Arc, RwLock,
+        mpsc, Arc, Condvar, Mutex, RwLock,
     },
 };
 
@@ -14,69 +14,148 @@ use crate::{
     Error, Result,
 };
 
+// requests carry the offset of the log, within the group of logs managed by
+// the flush thread, refer to [Journals::start_group]. Stand-alone Wal instances
+// are a group of one log.
 #[derive(Debug)]
 pub enum Req {
     // serialized opaque entry to be logged into the journal
-    AddEntry { op: Vec<u8> },
+    AddEntry { log: usize, op: Vec<u8> },
+    // same as AddEntry, reply with entry's location once it is flushed.
+    AppendEntry { log: usize, op: Vec<u8> },
     // commit outstanding operations.
-    Commit,
+    Commit { log: usize },
 }
 
-#[derive(Debug)]
-pub enum Res {
-    // monotonously increasing seqno
-    Seqno(u64),
+impl Req {
+    fn to_log(&self) -> usize {
+        match self {
+            Req::AddEntry { log, .. } => *log,
+            Req::AppendEntry { log, .. } => *log,
+            Req::Commit { log } => *log,
+        }
+    }
 }
 
 // Journals can be concurrently accessed.
 pub struct Journals<S> {
     config: Config,
-    seqno: Arc<AtomicU64>,
+    pub seqno: Arc<AtomicU64>,
+    pub durable: Arc<Durable>,
     pub journals: Vec<Journal<S>>,
     pub journal: Journal<S>,
 }
 
+// Watermark for the highest seqno that is flushed and fsynced into journal,
+// advanced by the flush thread.
+pub struct Durable {
+    seqno: Mutex<u64>,
+    cond: Condvar,
+}
+
+impl Durable {
+    fn new(seqno: u64) -> Durable {
+        Durable { seqno: Mutex::new(seqno), cond: Condvar::new() }
+    }
+
+    fn advance(&self, seqno: u64) -> Result<()> {
+        let mut durable = err_at!(Fatal, self.seqno.lock())?;
+        if seqno > *durable {
+            *durable = seqno;
+            self.cond.notify_all();
+        }
+        Ok(())
+    }
+
+    pub fn to_seqno(&self) -> Result<u64> {
+        Ok(*err_at!(Fatal, self.seqno.lock())?)
+    }
+
+    // block until watermark reaches `seqno`.
+    pub fn wait(&self, seqno: u64) -> Result<()> {
+        let mut durable = err_at!(Fatal, self.seqno.lock())?;
+        while *durable < seqno {
+            durable = err_at!(Fatal, self.cond.wait(durable))?;
+        }
+        Ok(())
+    }
+}
+
 type StartJournals<S> = (
     Arc<RwLock<Journals<S>>>,
     util::thread::Thread<Req, Res, Result<u64>>,
     util::thread::Tx<Req, Res>,
 );
 
+type StartGroup =
+    (util::thread::Thread<Req, Res, Result<u64>>, util::thread::Tx<Req, Res>);
+
 impl<S> Journals<S> {
+    pub fn new(
+        config: Config,
+        seqno: u64,
+        journals: Vec<Journal<S>>,
+        journal: Journal<S>,
+    ) -> Arc<RwLock<Journals<S>>> {
+        // entries before `seqno` are already persisted.
+        let durable = Arc::new(Durable::new(seqno.saturating_sub(1)));
+        let seqno = Arc::new(AtomicU64::new(seqno));
+        Arc::new(RwLock::new(Journals { config, seqno, durable, journals, journal }))
+    }
+
     pub fn start(
         config: Config,
         seqno: u64,
         journals: Vec<Journal<S>>,
         journal: Journal<S>,
-    ) -> StartJournals<S>
+    ) -> Result<StartJournals<S>>
     where
         S: state::State,
     {
-        let seqno = Arc::new(AtomicU64::new(seqno));
-        let journals = Arc::new(RwLock::new(Journals {
-            config: config.clone(),
-            seqno: Arc::clone(&seqno),
-            journals,
-            journal,
-        }));
+        let journals = Journals::new(config.clone(), seqno, journals, journal);
+        let (th, tx) = Journals::start_group(config, vec![Arc::clone(&journals)])?;
+
+        Ok((journals, th, tx))
+    }
+
+    // start a single flush thread for a group of logs, `config` supplies the
+    // thread name, fsync and journal-limit for all the logs. Requests shall
+    // carry the offset of the log within `logs`.
+    pub fn start_group(
+        config: Config,
+        logs: Vec<Arc<RwLock<Journals<S>>>>,
+    ) -> Result<StartGroup>
+    where
+        S: state::State,
+    {
+        let mut items = vec![];
+        for journals in logs.into_iter() {
+            let (seqno, durable) = {
+                let rd = err_at!(Fatal, journals.read())?;
+                (Arc::clone(&rd.seqno), Arc::clone(&rd.durable))
+            };
+            items.push(Log { seqno, durable, journals });
+        }
+
         let name = format!("wral-journals-{}", config.name);
-        let thread_w = Arc::clone(&journals);
         let th = util::thread::Thread::new_sync(
             &name,
             wral::SYNC_BUFFER,
             move |rx: util::thread::Rx<Req, Res>| {
                 || {
-                    let l = MainLoop { config, seqno, journals: thread_w, rx };
+                    let l = MainLoop { config, logs: items, rx };
                     l.run()
                 }
             },
         );
         let tx = th.to_tx();
 
-        (journals, th, tx)
+        Ok((th, tx))
     }
 
     pub fn close(&self) -> Result<u64> {
+        self.journal.truncate()?;
+
         let n_batches: usize = self.journals.iter().map(|j| j.len_batches()).sum();
         let (_n, _m) = match self.journal.len_batches() {
             0 => (self.journals.len(), n_batches),
@@ -97,10 +176,21 @@ impl<S> Journals<S> {
     }
 }
 
-struct MainLoop<S> {
-    config: Config,
+struct Log<S> {
     seqno: Arc<AtomicU64>,
+    durable: Arc<Durable>,
     journals: Arc<RwLock<Journals<S>>>,
+}
+
+impl<S> Log<S> {
+    fn to_last_seqno(&self) -> u64 {
+        self.seqno.load(SeqCst).saturating_sub(1)
+    }
+}
+
+struct MainLoop<S> {
+    config: Config,
+    logs: Vec<Log<S>>,
     rx: util::thread::Rx<Req, Res>,
 }
 
@@ -108,6 +198,7 @@ impl<S> MainLoop<S>
 where
     S: Clone + IntoCbor + FromCbor + state::State,
 {
+    // return the highest seqno across all the logs.
     fn run(self) -> Result<u64> {
         use std::sync::mpsc::{RecvTimeoutError, TryRecvError};
 
@@ -120,7 +211,7 @@ where
         'a: loop {
             // block for the first request.
             match self.rx.recv_timeout(timeout) {
-                Ok((Req::Commit, Some(tx))) => commit_txs.push(tx),
+                Ok((Req::Commit { log }, Some(tx))) => commit_txs.push((log, tx)),
                 Ok(req) => reqs.push(req),
                 Err(RecvTimeoutError::Timeout) => (),
                 Err(RecvTimeoutError::Disconnected) => break 'a,
@@ -130,7 +221,7 @@ where
             // the channel.
             loop {
                 match self.rx.try_recv() {
-                    Ok((Req::Commit, Some(tx))) => commit_txs.push(tx),
+                    Ok((Req::Commit { log }, Some(tx))) => commit_txs.push((log, tx)),
                     Ok(req) => reqs.push(req),
                     Err(TryRecvError::Empty) => break,
                     Err(TryRecvError::Disconnected) => break 'a,
@@ -140,31 +231,38 @@ where
             batch_payload += reqs
                 .iter()
                 .map(|r| match r {
-                    (Req::AddEntry { op }, _) => op.len(),
+                    (Req::AddEntry { op, .. }, _) => op.len(),
+                    (Req::AppendEntry { op, .. }, _) => op.len(),
                     _ => unreachable!(),
                 })
                 .sum::<usize>();
+            let locate = reqs.iter().any(|r| matches!(r, (Req::AppendEntry { .. }, _)));
 
+            // a single fsync decision for the whole batch, across all the logs.
             let fsync = self.config.fsync
+                || locate
                 || !commit_txs.is_empty()
                 || flush_time.elapsed() > std::time::Duration::from_secs(2)
                 || batch_payload > self.config.journal_limit;
 
-            if Self::write_journal(&self, &mut reqs, fsync)? {
+            if Self::write_journals(&self, &mut reqs, fsync)? {
                 // println!("took {:?} for flushing requests", flush_time.elapsed());
                 flush_time = std::time::Instant::now();
                 batch_payload = 0;
             }
 
-            let seqno = self.seqno.load(SeqCst).saturating_sub(1);
-            for tx in commit_txs.drain(..) {
+            for (log, tx) in commit_txs.drain(..) {
+                let seqno = match self.logs.get(log) {
+                    Some(log) => log.to_last_seqno(),
+                    None => err_at!(Fatal, msg: "invalid log {}", log)?,
+                };
                 err_at!(IPCFail, tx.send(Res::Seqno(seqno)))?;
             }
         }
 
-        Self::write_journal(&self, &mut reqs, true /*fsync*/)?;
+        Self::write_journals(&self, &mut reqs, true /*fsync*/)?;
 
-        Ok(self.seqno.load(SeqCst).saturating_sub(1))
+        Ok(self.logs.iter().map(|log| log.to_last_seqno()).max().unwrap_or(0))
     }
 }
 
@@ -179,10 +277,11 @@ where
         let journal = {
             let num = journals.journal.to_journal_number().saturating_add(1);
             let state = journals.journal.as_state().clone();
-            Journal::start(&journals.config.dir, &journals.config.name, num, state)?
+            Journal::start(&journals.config, num, state)?
         };
         // replace with current journal
         let journal = mem::replace(&mut journals.journal, journal);
+        journal.truncate()?;
         let (journal, entries, _) = journal.into_archive();
         if !entries.is_empty() {
             err_at!(Fatal, msg: "unflushed entries {}", entries.len())?
@@ -191,36 +290,73 @@ where
         Ok(())
     }
 
-    fn write_journal(
+    fn write_journals(
         ml: &MainLoop<S>,
         reqs: &mut Vec<(Req, Option<mpsc::Sender<Res>>)>,
         fsync: bool,
     ) -> Result<bool> {
+        let mut batches: Vec<Vec<(Req, Option<mpsc::Sender<Res>>)>> =
+            ml.logs.iter().map(|_| vec![]).collect();
+        for req in reqs.drain(..) {
+            match batches.get_mut(req.0.to_log()) {
+                Some(batch) => batch.push(req),
+                None => err_at!(Fatal, msg: "invalid log {}", req.0.to_log())?,
+            }
+        }
+
+        for (log, mut batch) in ml.logs.iter().zip(batches.into_iter()) {
+            Self::write_journal(ml, log, &mut batch, fsync)?;
+        }
+
+        Ok(fsync)
+    }
+
+    fn write_journal(
+        ml: &MainLoop<S>,
+        log: &Log<S>,
+        reqs: &mut Vec<(Req, Option<mpsc::Sender<Res>>)>,
+        fsync: bool,
+    ) -> Result<()> {
         // and then start processing it in batch.
-        let mut journals = err_at!(Fatal, ml.journals.write())?;
+        let mut journals = err_at!(Fatal, log.journals.write())?;
 
         let mut items = vec![];
         for req in reqs.drain(..) {
             match req {
-                (Req::AddEntry { op }, tx) => {
-                    let seqno = ml.seqno.fetch_add(1, SeqCst);
+                (Req::AddEntry { op, .. }, tx) => {
+                    let seqno = log.seqno.fetch_add(1, SeqCst);
                     journals.journal.add_entry(wral::Entry::new(seqno, op))?;
-                    items.push((seqno, tx))
+                    items.push((seqno, false, tx))
+                }
+                (Req::AppendEntry { op, .. }, tx) => {
+                    let seqno = log.seqno.fetch_add(1, SeqCst);
+                    journals.journal.add_entry(wral::Entry::new(seqno, op))?;
+                    items.push((seqno, true, tx))
                 }
                 _ => unreachable!(),
             }
         }
 
-        let res = if fsync {
-            journals.journal.flush()?;
-            true
+        let locate = items.iter().any(|(_, located, _)| *located);
+        let offsets = if fsync {
+            let offsets = journals.journal.flush(locate)?;
+            log.durable.advance(log.to_last_seqno())?;
+            offsets
         } else {
-            false
+            vec![]
         };
 
-        for (seqno, tx) in items.into_iter() {
+        let num = journals.journal.to_journal_number();
+        for (seqno, located, tx) in items.into_iter() {
+            let resp = match located {
+                true => match offsets.binary_search_by_key(&seqno, |(s, _)| *s) {
+                    Ok(off) => Res::Location(seqno, num, offsets[off].1),
+                    Err(_) => err_at!(Fatal, msg: "no location for seqno {}", seqno)?,
+                },
+                false => Res::Seqno(seqno),
+            };
             if let Some(tx) = tx {
-                err_at!(IPCFail, tx.send(Res::Seqno(seqno)))?;
+                err_at!(IPCFail, tx.send(resp))?;
             }
         }
 
@@ -228,6 +364,6 @@ where
             Self::rotate(journals.borrow_mut())?;
         }
 
-        Ok(res)
+        Ok(())
     }
 }
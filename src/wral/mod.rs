@@ -38,18 +38,29 @@
 //! log journal (typically iterating over its entries). Remember that read
 //! operations shall block concurrent writes and vice-versa. But concurrent
 //! reads shall be allowed.
+//!
+//! Group of logs
+//! -------------
+//!
+//! Each [Wal] instance spawns its own flush thread. Applications managing
+//! several small logs can instead use [WalGroup], where named logs under the
+//! same directory share a single flush thread and fsync is batched across
+//! the logs. Each log in the group keeps its own seqno space.
 
 use std::ffi;
 
 mod batch;
 mod entry;
 mod files;
+mod group;
 mod journal;
 mod journals;
 mod state;
+pub mod testutil;
 mod wal;
 
 pub use crate::wral::entry::Entry;
+pub use crate::wral::group::WalGroup;
 pub use crate::wral::state::{NoState, State};
 pub use crate::wral::wal::Wal;
 
@@ -70,6 +81,11 @@ pub struct Config {
     pub journal_limit: usize,
     /// Enable fsync for every flush.
     pub fsync: bool,
+    /// Preallocate journal files upto `journal_limit`, using `fallocate` on
+    /// Unix. Reduces file-system metadata churn and fragmentation when
+    /// appending, journal files are truncated to their real size on rotation
+    /// and close.
+    pub preallocate: bool,
 }
 
 impl<'a> arbitrary::Arbitrary<'a> for Config {
@@ -81,8 +97,9 @@ impl<'a> arbitrary::Arbitrary<'a> for Config {
 
         let journal_limit = *u.choose(&[100, 1000, 10_000, 1_000_000])?;
         let fsync: bool = u.arbitrary()?;
+        let preallocate: bool = u.arbitrary()?;
 
-        let config = Config { name, dir, journal_limit, fsync };
+        let config = Config { name, dir, journal_limit, fsync, preallocate };
         Ok(config)
     }
 }
@@ -94,6 +111,7 @@ impl Config {
             dir: dir.to_os_string(),
             journal_limit: JOURNAL_LIMIT,
             fsync: true,
+            preallocate: false,
         }
     }
 
@@ -106,4 +124,9 @@ impl Config {
         self.fsync = fsync;
         self
     }
+
+    pub fn set_preallocate(mut self, preallocate: bool) -> Self {
+        self.preallocate = preallocate;
+        self
+    }
 }
@@ -0,0 +1,67 @@
+//! Module implement helpers to verify [Wal] durability, useful as test and
+//! fuzz-target body.
+
+use arbitrary::Arbitrary;
+
+use crate::{
+    wral::{self, Config, Wal},
+    Error, Result,
+};
+
+/// Operations applied on [Wal] by [roundtrip].
+#[derive(Clone, Debug, Arbitrary)]
+pub enum Op {
+    /// Refer to [Wal::add_op].
+    Add(Vec<u8>),
+    /// Refer to [Wal::commit].
+    Commit,
+}
+
+/// Create a fresh Wal using `config`, apply `ops` in order, close the Wal,
+/// load it back from disk and verify that replayed operations exactly match
+/// the added operations, in the same order. Journals are purged before
+/// returning.
+pub fn roundtrip(config: Config, ops: &[Op]) -> Result<()> {
+    let wal = Wal::create(config.clone(), wral::NoState)?;
+
+    let mut items = vec![];
+    for op in ops.iter() {
+        match op {
+            Op::Add(op) => items.push((wal.add_op(op)?, op.clone())),
+            Op::Commit => {
+                wal.commit()?;
+            }
+        }
+    }
+    if wal.close()?.is_none() {
+        err_at!(Fatal, msg: "fail closing wal {:?}", config.name)?
+    }
+
+    let wal = Wal::<wral::NoState>::load(config)?;
+    let res = verify(&wal, items);
+    wal.purge()?;
+
+    res
+}
+
+fn verify(wal: &Wal<wral::NoState>, items: Vec<(u64, Vec<u8>)>) -> Result<()> {
+    let mut entries = vec![];
+    for entry in wal.iter()? {
+        entries.push(entry?.unwrap());
+    }
+
+    if entries.len() != items.len() {
+        err_at!(Fatal, msg: "replayed {} ops, added {}", entries.len(), items.len())?
+    }
+    for (i, (entry, item)) in entries.into_iter().zip(items.into_iter()).enumerate() {
+        if entry != item {
+            err_at!(Fatal, msg: "op {} mismatch seqno {}/{}", i, entry.0, item.0)?
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "testutil_test.rs"]
+mod testutil_test;
@@ -0,0 +1,21 @@
+use std::{env, fs, path};
+
+use super::*;
+
+#[test]
+fn test_wral_roundtrip() {
+    let name = "test-wral-roundtrip";
+    let dir: path::PathBuf = vec![env::temp_dir(), name.into()].into_iter().collect();
+    fs::remove_dir_all(&dir).ok();
+
+    // small journal limit, so that commit in the middle shall rotate the journal.
+    let config = Config::new(dir.as_os_str(), name).set_journal_limit(100);
+
+    let mut ops: Vec<Op> = (0..50_u8).map(|i| Op::Add(vec![i; 10])).collect();
+    ops.insert(20, Op::Commit);
+    ops.push(Op::Add(vec![]));
+    ops.extend((0..50_u8).map(|i| Op::Add(vec![i; i as usize])));
+
+    roundtrip(config.clone(), &ops).unwrap();
+    roundtrip(config, &[]).unwrap();
+}
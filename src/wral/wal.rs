@@ -9,7 +9,7 @@ use std::{
     ffi, fs,
     marker::PhantomData,
     mem, ops, path,
-    sync::{Arc, RwLock},
+    sync::{atomic::Ordering::SeqCst, Arc, RwLock},
     vec,
 };
 
@@ -24,9 +24,14 @@ use crate::{
     Error, Result,
 };
 
+// next seqno, archived journals and the active journal for a log.
+pub(crate) type StartLog<S> = (u64, Vec<Journal<S>>, Journal<S>);
+
 /// Write ahead logging.
 pub struct Wal<S = wral::NoState> {
     config: Config,
+    // offset of this log within its group, refer to [wral::WalGroup].
+    log: usize,
 
     w: Arc<RwLock<Journals<S>>>,
     th: Arc<util::thread::Thread<Req, Res, Result<u64>>>,
@@ -37,6 +42,7 @@ impl<S> Clone for Wal<S> {
     fn clone(&self) -> Wal<S> {
         Wal {
             config: self.config.clone(),
+            log: self.log,
 
             w: Arc::clone(&self.w),
             th: Arc::clone(&self.th),
@@ -49,6 +55,46 @@ impl<S> Wal<S> {
     /// Create a new Write-Ahead-Log instance, while create a new journal, older
     /// journals matching the `name` shall be purged.
     pub fn create(config: Config, state: S) -> Result<Wal<S>>
+    where
+        S: wral::State,
+    {
+        let (seqno, journals, journal) = Self::create_journals(&config, state)?;
+        let (w, th, tx) = Journals::start(config.clone(), seqno, journals, journal)?;
+
+        let val = Wal { config, log: 0, w, th: Arc::new(th), tx };
+
+        Ok(val)
+    }
+
+    /// Load an existing journal under `dir`, matching `name`. Files that
+    /// don't match the journal file-name structure or journals with
+    /// corrupted batch or corrupted state shall be ignored.
+    ///
+    /// Application state shall be loaded from the last batch of the
+    /// last journal.
+    pub fn load(config: Config) -> Result<Wal<S>>
+    where
+        S: wral::State,
+    {
+        let (seqno, journals, journal) = Self::load_journals(&config)?;
+        let (w, th, tx) = Journals::start(config.clone(), seqno, journals, journal)?;
+
+        let val = Wal { config, log: 0, w, th: Arc::new(th), tx };
+
+        Ok(val)
+    }
+
+    pub(crate) fn from_group(
+        config: Config,
+        log: usize,
+        w: Arc<RwLock<Journals<S>>>,
+        th: Arc<util::thread::Thread<Req, Res, Result<u64>>>,
+        tx: util::thread::Tx<Req, Res>,
+    ) -> Wal<S> {
+        Wal { config, log, w, th, tx }
+    }
+
+    pub(crate) fn create_journals(config: &Config, state: S) -> Result<StartLog<S>>
     where
         S: wral::State,
     {
@@ -69,23 +115,13 @@ impl<S> Wal<S> {
         }
 
         let num = 0;
-        let journal = Journal::start(&config.dir, &config.name, num, state)?;
+        let journal = Journal::start(config, num, state)?;
 
         let seqno = 1;
-        let (w, th, tx) = Journals::start(config.clone(), seqno, vec![], journal);
-
-        let val = Wal { config, w, th: Arc::new(th), tx };
-
-        Ok(val)
+        Ok((seqno, vec![], journal))
     }
 
-    /// Load an existing journal under `dir`, matching `name`. Files that
-    /// don't match the journal file-name structure or journals with
-    /// corrupted batch or corrupted state shall be ignored.
-    ///
-    /// Application state shall be loaded from the last batch of the
-    /// last journal.
-    pub fn load(config: Config) -> Result<Wal<S>>
+    pub(crate) fn load_journals(config: &Config) -> Result<StartLog<S>>
     where
         S: wral::State,
     {
@@ -111,14 +147,10 @@ impl<S> Wal<S> {
         };
         seqno += 1;
         let num = num.saturating_add(1);
-        let journal = Journal::start(&config.dir, &config.name, num, state)?;
+        let journal = Journal::start(config, num, state)?;
 
         let journals: Vec<Journal<S>> = journals.into_iter().map(|(j, _, _)| j).collect();
-        let (w, th, tx) = Journals::start(config.clone(), seqno, journals, journal);
-
-        let val = Wal { config, w, th: Arc::new(th), tx };
-
-        Ok(val)
+        Ok((seqno, journals, journal))
     }
 
     /// Close the [Wal] instance.
@@ -158,16 +190,81 @@ impl<S> Wal<S> {
     /// Add a operation to WAL, operations are pre-serialized and opaque to
     /// Wal instances. Return the sequence-number for this operation.
     pub fn add_op(&self, op: &[u8]) -> Result<u64> {
-        let req = Req::AddEntry { op: op.to_vec() };
-        let Res::Seqno(seqno) = self.tx.request(req)?;
-        Ok(seqno)
+        let req = Req::AddEntry { log: self.log, op: op.to_vec() };
+        match self.tx.request(req)? {
+            Res::Seqno(seqno) => Ok(seqno),
+            res => err_at!(Fatal, msg: "unexpected response {:?}", res),
+        }
+    }
+
+    /// Same as [Wal::add_op], but block until the operation is flushed into
+    /// journal and return its location as (seqno, journal-number, fpos).
+    /// Applications can index the location and later read the operation back
+    /// using [Wal::read_at].
+    pub fn append_op(&self, op: &[u8]) -> Result<(u64, usize, u64)> {
+        let req = Req::AppendEntry { log: self.log, op: op.to_vec() };
+        match self.tx.request(req)? {
+            Res::Location(seqno, num, fpos) => Ok((seqno, num, fpos)),
+            res => err_at!(Fatal, msg: "unexpected response {:?}", res),
+        }
     }
 
     /// Commit outstanding operations into disc and return the latest seqno.
     pub fn commit(&self) -> Result<u64> {
-        let req = Req::Commit;
-        let Res::Seqno(seqno) = self.tx.request(req)?;
-        Ok(seqno)
+        let req = Req::Commit { log: self.log };
+        match self.tx.request(req)? {
+            Res::Seqno(seqno) => Ok(seqno),
+            res => err_at!(Fatal, msg: "unexpected response {:?}", res),
+        }
+    }
+
+    /// Return the highest seqno that is flushed and fsynced into journal. All
+    /// operations upto and including this seqno are durable.
+    pub fn durable_seqno(&self) -> Result<u64> {
+        let durable = Arc::clone(&err_at!(Fatal, self.w.read())?.durable);
+        durable.to_seqno()
+    }
+
+    /// Block until the operation at `seqno` is flushed and fsynced into journal.
+    /// Operations are flushed when [Config::fsync] is enabled, on [Wal::commit],
+    /// when journals are rotated, or periodically by the flush thread.
+    pub fn wait_durable(&self, seqno: u64) -> Result<()> {
+        let (durable, next_seqno) = {
+            let rd = err_at!(Fatal, self.w.read())?;
+            (Arc::clone(&rd.durable), rd.seqno.load(SeqCst))
+        };
+        if seqno >= next_seqno {
+            err_at!(InvalidInput, msg: "seqno {} not yet allocated", seqno)?
+        }
+        durable.wait(seqno)
+    }
+
+    /// Reclaim the log prefix, once downstream consumers have processed all
+    /// operations before `seqno`. Archived journals whose entries are all
+    /// below `seqno` are purged, rest of the journals are left untouched,
+    /// and the active journal is never purged. `seqno` cannot go past the
+    /// durable seqno, refer to [Wal::durable_seqno]. Return the number of
+    /// journals purged.
+    ///
+    /// Iterators created before compaction may fail on purged journals.
+    pub fn compact(&self, seqno: u64) -> Result<usize> {
+        let mut w = err_at!(Fatal, self.w.write())?;
+
+        let durable = w.durable.to_seqno()?;
+        if seqno > durable.saturating_add(1) {
+            err_at!(InvalidInput, msg: "seqno {} past durable seqno {}", seqno, durable)?
+        }
+
+        let n = w
+            .journals
+            .iter()
+            .take_while(|j| j.to_last_seqno().map_or(true, |last| last < seqno))
+            .count();
+        for journal in w.journals.drain(..n) {
+            journal.purge()?;
+        }
+
+        Ok(n)
     }
 }
 
@@ -214,6 +311,17 @@ impl<S> Wal<S> {
         })
     }
 
+    /// Read the entry located at file-position `fpos` in journal numbered
+    /// `num`, refer to [Wal::append_op].
+    pub fn read_at(&self, num: usize, fpos: u64) -> Result<wral::Entry> {
+        let rd = err_at!(Fatal, self.w.read())?;
+        let journal = rd.journals.iter().chain(Some(&rd.journal));
+        match journal.filter(|j| j.is_open()).find(|j| j.to_journal_number() == num) {
+            Some(journal) => journal.read_at(fpos),
+            None => err_at!(InvalidInput, msg: "journal {} not found", num),
+        }
+    }
+
     fn range_bound_to_range_inclusive<R>(range: R) -> Option<ops::RangeInclusive<u64>>
     where
         R: ops::RangeBounds<u64>,
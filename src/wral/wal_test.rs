@@ -116,3 +116,210 @@ fn reader(_id: usize, wal: Wal, ops: usize, seed: u64, entries: Vec<wral::Entry>
 
     wal.close().unwrap();
 }
+
+#[test]
+fn test_wral_wait_durable() {
+    use crate::wral::state;
+    use std::env;
+
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_wral_wait_durable {}", seed);
+
+    let name = "test-wral-wait-durable";
+    let dir: path::PathBuf = vec![env::temp_dir(), name.into()].into_iter().collect();
+    let config =
+        Config::new(dir.as_os_str(), name).set_journal_limit(1000).set_fsync(false);
+    let wal = Wal::create(config, state::NoState).unwrap();
+    assert_eq!(wal.durable_seqno().unwrap(), 0);
+
+    let mut last = 0;
+    for _i in 0..1000 {
+        let op: Vec<u8> = (0..(rng.gen::<usize>() % 100)).map(|_| rng.gen()).collect();
+        last = wal.add_op(&op).unwrap();
+    }
+    // journals are rotated, hence flushed, as they exceed the limit.
+    assert!(wal.durable_seqno().unwrap() <= last);
+    assert!(wal.wait_durable(last + 1).is_err());
+
+    // waiter unblocks once the tail is flushed, periodically or on commit.
+    let waiter = {
+        let wal = wal.clone();
+        std::thread::spawn(move || wal.wait_durable(last))
+    };
+    if rng.gen::<bool>() {
+        wal.commit().unwrap();
+    }
+    waiter.join().unwrap().unwrap();
+    assert_eq!(wal.durable_seqno().unwrap(), last);
+
+    let seqno = wal.add_op(b"after-commit").unwrap();
+    assert_eq!(wal.commit().unwrap(), seqno);
+    assert_eq!(wal.durable_seqno().unwrap(), seqno);
+
+    wal.purge().unwrap();
+}
+
+#[test]
+fn test_wral_append_op() {
+    use crate::wral::state;
+    use std::env;
+
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_wral_append_op {}", seed);
+
+    let name = "test-wral-append-op";
+    let dir: path::PathBuf = vec![env::temp_dir(), name.into()].into_iter().collect();
+    let config =
+        Config::new(dir.as_os_str(), name).set_journal_limit(1000).set_fsync(false);
+    let wal = Wal::create(config, state::NoState).unwrap();
+
+    let mut locations = vec![];
+    for _i in 0..1000 {
+        let op: Vec<u8> = (0..(rng.gen::<usize>() % 100)).map(|_| rng.gen()).collect();
+        match rng.gen::<u8>() % 4 {
+            0 => {
+                wal.add_op(&op).unwrap();
+            }
+            _ => {
+                let (seqno, num, fpos) = wal.append_op(&op).unwrap();
+                assert_eq!(wal.durable_seqno().unwrap(), seqno);
+                locations.push((seqno, num, fpos, op));
+            }
+        }
+    }
+    // journals are rotated as they exceed the limit.
+    assert!(locations.last().unwrap().1 > 0);
+
+    for (seqno, num, fpos, op) in locations.into_iter() {
+        let entry = wal.read_at(num, fpos).unwrap();
+        assert_eq!(entry.to_seqno(), seqno);
+        assert_eq!(entry.unwrap().1, op);
+    }
+    assert!(wal.read_at(usize::MAX, 0).is_err());
+
+    wal.purge().unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_wral_preallocate() {
+    use crate::wral::{files, state};
+    use std::env;
+
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_wral_preallocate {}", seed);
+
+    let name = "test-wral-preallocate";
+    let dir: path::PathBuf = vec![env::temp_dir(), name.into()].into_iter().collect();
+    let journal_limit = 10_000;
+    let config = Config::new(dir.as_os_str(), name)
+        .set_journal_limit(journal_limit)
+        .set_fsync(false)
+        .set_preallocate(true);
+    let file_len = |num: usize| -> u64 {
+        let loc = dir.join(files::make_filename(name, num));
+        fs::metadata(&loc).unwrap().len()
+    };
+
+    let wal = Wal::create(config.clone(), state::NoState).unwrap();
+
+    let mut ops = vec![];
+    let (_, num, _) = wal.append_op(&[1, 2, 3]).unwrap();
+    ops.push(vec![1, 2, 3]);
+    // journal file is preallocated upto journal_limit.
+    assert_eq!(file_len(num), journal_limit as u64);
+
+    for _i in 0..1000 {
+        let op: Vec<u8> = (0..(rng.gen::<usize>() % 100)).map(|_| rng.gen()).collect();
+        let (seqno, num, fpos) = wal.append_op(&op).unwrap();
+        assert_eq!(wal.read_at(num, fpos).unwrap().to_seqno(), seqno);
+        ops.push(op);
+    }
+    let (_, last_num, _) = wal.append_op(&[4, 5, 6]).unwrap();
+    ops.push(vec![4, 5, 6]);
+    assert!(last_num > 0);
+
+    let items: Vec<Vec<u8>> =
+        wal.iter().unwrap().map(|e| e.unwrap().unwrap().1).collect();
+    assert_eq!(items, ops);
+    assert_eq!(wal.close().unwrap(), Some(ops.len() as u64));
+    // working journal is truncated to its real size on close.
+    assert!(file_len(last_num) < journal_limit as u64);
+
+    // simulate a crash by padding the journal, replay reads only real entries.
+    {
+        let loc = dir.join(files::make_filename(name, last_num));
+        let fd = fs::OpenOptions::new().write(true).open(&loc).unwrap();
+        fd.set_len(journal_limit as u64).unwrap();
+    }
+    let wal = Wal::<state::NoState>::load(config).unwrap();
+    let items: Vec<Vec<u8>> =
+        wal.iter().unwrap().map(|e| e.unwrap().unwrap().1).collect();
+    assert_eq!(items, ops);
+
+    wal.purge().unwrap();
+}
+
+#[test]
+fn test_wral_compact() {
+    use crate::wral::state;
+    use std::env;
+
+    let seed: u64 = random();
+    let mut rng = StdRng::seed_from_u64(seed);
+    println!("test_wral_compact {}", seed);
+
+    let name = "test-wral-compact";
+    let dir: path::PathBuf = vec![env::temp_dir(), name.into()].into_iter().collect();
+    let config =
+        Config::new(dir.as_os_str(), name).set_journal_limit(1000).set_fsync(false);
+    let wal = Wal::create(config.clone(), state::NoState).unwrap();
+
+    let mut locations = vec![];
+    for _i in 0..1000 {
+        let op: Vec<u8> = (0..(rng.gen::<usize>() % 100)).map(|_| rng.gen()).collect();
+        let (seqno, num, _) = wal.append_op(&op).unwrap();
+        locations.push((seqno, num));
+    }
+    let last_num = locations.last().unwrap().1;
+    assert!(last_num > 2, "{}", last_num);
+
+    // cannot compact past the durable seqno.
+    let durable = wal.durable_seqno().unwrap();
+    assert!(wal.compact(durable + 2).is_err());
+    // nothing to compact.
+    assert_eq!(wal.compact(0).unwrap(), 0);
+
+    // compact upto a seqno in the middle of a journal.
+    let (seqno, num) = locations[locations.len() / 2];
+    let n = wal.compact(seqno).unwrap();
+    assert_eq!(n, num, "{} {}", seqno, num);
+    let first = locations.iter().find(|(_, n)| *n == num).unwrap().0;
+    let seqnos: Vec<u64> =
+        wal.range(0..).unwrap().map(|e| e.unwrap().to_seqno()).collect();
+    assert_eq!(seqnos.first(), Some(&first));
+    assert_eq!(seqnos.last(), Some(&locations.last().unwrap().0));
+    assert_eq!(seqnos.len(), locations.iter().filter(|(_, n)| *n >= num).count());
+
+    // active journal is never compacted, entries from purged journals are
+    // not iterated.
+    let n = num + wal.compact(durable + 1).unwrap();
+    assert!(n >= last_num && n <= last_num + 1, "{} {}", n, last_num);
+    let (seqno, _, _) = wal.append_op(&[1, 2, 3]).unwrap();
+    let mut refs: Vec<u64> =
+        locations.iter().filter(|(_, num)| *num >= n).map(|(s, _)| *s).collect();
+    refs.push(seqno);
+    let seqnos: Vec<u64> = wal.iter().unwrap().map(|e| e.unwrap().to_seqno()).collect();
+    assert_eq!(seqnos, refs);
+
+    // compacted journals are not reloaded.
+    wal.close().unwrap();
+    let wal = Wal::<state::NoState>::load(config).unwrap();
+    let seqnos: Vec<u64> = wal.iter().unwrap().map(|e| e.unwrap().to_seqno()).collect();
+    assert_eq!(seqnos, refs);
+
+    wal.purge().unwrap();
+}
@@ -3,4 +3,4 @@
 mod workers;
 mod zim;
 
-pub use zim::{Cluster, Compression, Entry, Header, Namespace, Zimf};
+pub use zim::{Cluster, Compression, CompressionStats, Entry, Header, Namespace, Zimf};
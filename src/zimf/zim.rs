@@ -7,7 +7,7 @@ use std::{
     io::{self, Read, Seek},
     path, result,
     str::FromStr,
-    sync::{mpsc, Arc},
+    sync::{mpsc, Arc, Mutex},
 };
 
 use crate::{util, zimf::workers, Error, Result};
@@ -18,7 +18,7 @@ const MAX_ENTRY_SIZE: usize = 1024;
 const MAX_CLUSTER_SIZE: usize = 10 * 1024 * 1024;
 
 /// Compression types allowed in zim archive.
-#[derive(Clone)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Compression {
     /// Legacy compression
     Uncompress1 = 0,
@@ -199,6 +199,24 @@ impl Zimf {
     pub fn get_blobs(&self, cluster_num: usize) -> Result<Vec<Vec<u8>>> {
         self.inner.get_blobs(cluster_num)
     }
+
+    /// Return count of clusters and blobs for each compression type, along with
+    /// total decompressed bytes. Computing this requires decompressing every
+    /// cluster in the archive, hence it is computed on first call and cached.
+    pub fn compression_stats(&self) -> Result<CompressionStats> {
+        let mut cache = err_at!(Fatal, self.inner.stats.lock())?;
+        if let Some(stats) = cache.as_ref() {
+            return Ok(stats.clone());
+        }
+
+        let mut stats = CompressionStats::default();
+        for (num, cluster) in self.inner.clusters.iter().enumerate() {
+            stats.add_cluster(cluster, &self.inner.get_blobs(num)?);
+        }
+        *cache = Some(stats.clone());
+
+        Ok(stats)
+    }
 }
 
 impl Zimf {
@@ -240,6 +258,7 @@ struct Inner {
     pub clusters: Vec<Cluster>,
 
     index_cluster: BTreeMap<u32, Vec<Arc<Entry>>>,
+    stats: Mutex<Option<CompressionStats>>,
     pool: Option<util::thread::Pool<workers::Req, workers::Res, Result<()>>>,
 }
 
@@ -359,6 +378,7 @@ impl Inner {
             clusters,
 
             index_cluster: BTreeMap::new(),
+            stats: Mutex::new(None),
             pool: Some(pool),
         };
 
@@ -670,6 +690,25 @@ impl Entry {
     }
 }
 
+/// Summary of clusters in zim archive, refer [Zimf::compression_stats].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CompressionStats {
+    /// Number of clusters, for each compression type.
+    pub clusters: BTreeMap<Compression, usize>,
+    /// Number of blobs, for each compression type.
+    pub blobs: BTreeMap<Compression, usize>,
+    /// Total size of all blobs, after decompression.
+    pub decompressed_bytes: usize,
+}
+
+impl CompressionStats {
+    fn add_cluster(&mut self, cluster: &Cluster, blobs: &[Vec<u8>]) {
+        *self.clusters.entry(cluster.compression).or_insert(0) += 1;
+        *self.blobs.entry(cluster.compression).or_insert(0) += blobs.len();
+        self.decompressed_bytes += blobs.iter().map(|b| b.len()).sum::<usize>();
+    }
+}
+
 /// Cluster location in file, its compression and other details.
 /// Refer [here](https://openzim.org/wiki/ZIM_file_format#Clusters) for details.
 #[derive(Clone, Debug)]
@@ -798,3 +837,7 @@ impl Cluster {
             .collect())
     }
 }
+
+#[cfg(test)]
+#[path = "zim_test.rs"]
+mod zim_test;
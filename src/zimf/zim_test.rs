@@ -0,0 +1,62 @@
+use std::{fs, io::Write};
+
+use super::*;
+
+// encode blobs as cluster payload, offset-table of 4-byte offsets followed by
+// blobs.
+fn encode_cluster(blobs: &[&[u8]]) -> Vec<u8> {
+    let mut off = (blobs.len() + 1) * 4;
+    let mut data = vec![];
+    for blob in blobs.iter() {
+        data.extend_from_slice(&(off as u32).to_le_bytes());
+        off += blob.len();
+    }
+    data.extend_from_slice(&(off as u32).to_le_bytes());
+    blobs.iter().for_each(|blob| data.extend_from_slice(blob));
+    data
+}
+
+#[test]
+fn test_zimf_compression_stats() {
+    let dir = std::env::temp_dir().join("test_zimf_compression_stats");
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap();
+    let loc = dir.join("clusters.data");
+
+    let plain: Vec<&[u8]> = vec![b"hello", b"world", b"!"];
+    let packed: Vec<&[u8]> = vec![b"zstd", b"compressed"];
+
+    // uncompressed cluster followed by a zstd compressed cluster.
+    let mut data = vec![0x01];
+    data.extend_from_slice(&encode_cluster(&plain));
+    let zoff = data.len() as u64;
+    data.push(0x05);
+    let payload = encode_cluster(&packed);
+    data.extend_from_slice(&zstd::stream::encode_all(payload.as_slice(), 0).unwrap());
+    fs::File::create(&loc).unwrap().write_all(&data).unwrap();
+
+    let mut fd = fs::File::open(&loc).unwrap();
+    let mut clusters = vec![
+        Cluster::from_offset(0, &mut fd).unwrap(),
+        Cluster::from_offset(zoff, &mut fd).unwrap(),
+    ];
+    clusters[0].size = Some(zoff as usize);
+    assert_eq!(clusters[0].compression, Compression::Uncompress1);
+    assert_eq!(clusters[1].compression, Compression::Zstd);
+
+    let mut stats = CompressionStats::default();
+    for cluster in clusters.iter() {
+        let blobs = cluster.to_blobs(&mut fd).unwrap();
+        stats.add_cluster(cluster, &blobs);
+    }
+
+    let mut refs = CompressionStats::default();
+    refs.clusters.insert(Compression::Uncompress1, 1);
+    refs.clusters.insert(Compression::Zstd, 1);
+    refs.blobs.insert(Compression::Uncompress1, 3);
+    refs.blobs.insert(Compression::Zstd, 2);
+    refs.decompressed_bytes = 5 + 5 + 1 + 4 + 10;
+    assert_eq!(stats, refs);
+
+    fs::remove_dir_all(&dir).ok();
+}